@@ -0,0 +1,273 @@
+/**
+ * Helpers for storing payloads too large to be held in a single entry.
+ *
+ * Content is split into fixed-size chunk entries, addressed by their content hash.
+ * A manifest entry lists the chunks in order along with the total size of the content;
+ * records store the manifest's `EntryHash` in place of the content itself.
+ *
+ * Zomes storing blobs must register both entry types via `blob_chunk_entry_def()` and
+ * `blob_manifest_entry_def()` in their `entry_defs()` extern.
+ *
+ * Record fields which may hold inline `data:` URIs (eg. images) store their content as a blob via
+ * `store_inline_data`, holding the `RecordUri` of its manifest in place of the URI.
+ * `read_inline_data` reassembles the original URI.
+ *
+ * @see     ../README.md
+ * @package HDK Graph Helpers
+ * @since   2021-10-29
+ */
+use hdk::prelude::*;
+use hdk_type_serialization_macros::RecordUri;
+
+use crate::{
+    RecordAPIResult, DataIntegrityError, MaybeUndefined,
+    entry_helpers::{create_entry, get_entry_by_address},
+};
+
+/// Entry def ID for blob chunks
+pub const BLOB_CHUNK_ENTRY_DEF_ID: &str = "blob_chunk";
+/// Entry def ID for blob manifests
+pub const BLOB_MANIFEST_ENTRY_DEF_ID: &str = "blob_manifest";
+
+/// Maximum number of content bytes stored in each chunk entry
+pub const BLOB_CHUNK_SIZE: usize = 1024 * 1024;
+
+const DATA_URI_SCHEME: &str = "data:";
+
+/// A single slice of a blob's content
+///
+#[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
+pub struct BlobChunk {
+    #[serde(with = "serde_bytes")]
+    pub content: Vec<u8>,
+}
+
+app_entry!(BlobChunk);
+
+/// Index of the chunks comprising a blob, in order
+///
+#[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
+pub struct BlobManifest {
+    pub size: usize,
+    pub media_type: Option<String>,
+    pub chunks: Vec<EntryHash>,
+}
+
+app_entry!(BlobManifest);
+
+/// Definition for the blob chunk entry type
+///
+pub fn blob_chunk_entry_def() -> EntryDef {
+    EntryDef {
+        id: BLOB_CHUNK_ENTRY_DEF_ID.into(),
+        visibility: EntryVisibility::Public,
+        crdt_type: CrdtType,
+        required_validations: 1.into(),
+        required_validation_type: RequiredValidationType::default(),
+    }
+}
+
+/// Definition for the blob manifest entry type
+///
+pub fn blob_manifest_entry_def() -> EntryDef {
+    EntryDef {
+        id: BLOB_MANIFEST_ENTRY_DEF_ID.into(),
+        visibility: EntryVisibility::Public,
+        crdt_type: CrdtType,
+        required_validations: 1.into(),
+        required_validation_type: RequiredValidationType::default(),
+    }
+}
+
+/// Store some content as a blob, returning the address of its manifest.
+///
+/// Chunks are content-addressed, so identical content shares storage across blobs.
+///
+pub fn store_blob(content: &[u8], media_type: Option<String>) -> RecordAPIResult<EntryHash> {
+    let chunks = split_chunks(content).into_iter()
+        .map(|chunk| {
+            let (_, chunk_hash) = create_entry(BLOB_CHUNK_ENTRY_DEF_ID, chunk)?;
+            Ok(chunk_hash)
+        })
+        .collect::<RecordAPIResult<Vec<EntryHash>>>()?;
+
+    let (_, manifest_hash) = create_entry(BLOB_MANIFEST_ENTRY_DEF_ID, BlobManifest {
+        size: content.len(),
+        media_type,
+        chunks,
+    })?;
+
+    Ok(manifest_hash)
+}
+
+/// Read the manifest of a stored blob without retrieving its content
+///
+pub fn read_blob_manifest(manifest_address: &EntryHash) -> RecordAPIResult<BlobManifest> {
+    get_entry_by_address(manifest_address)
+}
+
+/// Read back the full content of a stored blob
+///
+pub fn read_blob(manifest_address: &EntryHash) -> RecordAPIResult<(BlobManifest, Vec<u8>)> {
+    let manifest = read_blob_manifest(manifest_address)?;
+
+    let chunks = manifest.chunks.iter()
+        .map(|chunk_address| get_entry_by_address(chunk_address))
+        .collect::<RecordAPIResult<Vec<BlobChunk>>>()?;
+    let content = join_chunks(manifest_address, &manifest, chunks)?;
+
+    Ok((manifest, content))
+}
+
+/// Store the content of an inline `data:` URI as a blob, returning the `RecordUri` of its manifest to use in its place.
+/// Any other value is returned unchanged.
+///
+pub fn store_inline_data(value: &str) -> RecordAPIResult<String> {
+    match split_data_uri(value) {
+        Some((media_type, data)) => {
+            let manifest_address = store_blob(data.as_bytes(), Some(media_type.to_string()))?;
+            Ok(RecordUri::new(zome_info()?.dna_hash, BLOB_MANIFEST_ENTRY_DEF_ID, manifest_address).to_string())
+        },
+        None => Ok(value.to_string()),
+    }
+}
+
+/// Reassemble the `data:` URI referenced by a value returned from `store_inline_data`.
+/// Any other value, including the URIs of blobs held by other DNAs, is returned unchanged.
+///
+pub fn read_inline_data(value: &str) -> RecordAPIResult<String> {
+    let manifest_uri = match value.parse::<RecordUri>() {
+        Ok(uri) if is_local_manifest_uri(&uri, &zome_info()?.dna_hash) => uri,
+        _ => return Ok(value.to_string()),
+    };
+    let (manifest, content) = read_blob(&manifest_uri.entry)?;
+    let data = String::from_utf8(content)
+        .map_err(|_| DataIntegrityError::EntryWrongType)?;
+
+    Ok(format!("{}{},{}", DATA_URI_SCHEME, manifest.media_type.unwrap_or_default(), data))
+}
+
+/// Apply `store_inline_data` to an optional record field
+pub fn store_inline_field<T>(value: &Option<T>) -> RecordAPIResult<Option<T>>
+    where T: AsRef<String> + From<String>,
+{
+    value.as_ref()
+        .map(|v| Ok(store_inline_data(v.as_ref())?.into()))
+        .transpose()
+}
+
+/// Apply `store_inline_data` to a record field in an update request
+pub fn store_inline_update<T>(value: &MaybeUndefined<T>) -> RecordAPIResult<MaybeUndefined<T>>
+    where T: AsRef<String> + From<String>,
+{
+    Ok(match value {
+        MaybeUndefined::Some(v) => MaybeUndefined::Some(store_inline_data(v.as_ref())?.into()),
+        MaybeUndefined::None => MaybeUndefined::None,
+        MaybeUndefined::Undefined => MaybeUndefined::Undefined,
+    })
+}
+
+/// Apply `read_inline_data` to an optional record field
+pub fn read_inline_field<T>(value: &Option<T>) -> RecordAPIResult<Option<T>>
+    where T: AsRef<String> + From<String>,
+{
+    value.as_ref()
+        .map(|v| Ok(read_inline_data(v.as_ref())?.into()))
+        .transpose()
+}
+
+/// Whether a URI references a blob manifest which can be read from the local DNA
+fn is_local_manifest_uri(uri: &RecordUri, local_dna: &DnaHash) -> bool {
+    uri.dna == *local_dna && uri.entry_type == BLOB_MANIFEST_ENTRY_DEF_ID
+}
+
+fn split_chunks(content: &[u8]) -> Vec<BlobChunk> {
+    content.chunks(BLOB_CHUNK_SIZE)
+        .map(|slice| BlobChunk { content: slice.to_vec() })
+        .collect()
+}
+
+fn join_chunks(manifest_address: &EntryHash, manifest: &BlobManifest, chunks: Vec<BlobChunk>) -> RecordAPIResult<Vec<u8>> {
+    let mut content = Vec::with_capacity(manifest.size);
+    for chunk in chunks {
+        content.extend(chunk.content);
+    }
+
+    if content.len() != manifest.size {
+        return Err(DataIntegrityError::BlobSizeMismatch(manifest_address.to_owned(), manifest.size, content.len()));
+    }
+    Ok(content)
+}
+
+/// Split a `data:` URI into its media type (including any `;base64` marker) and data
+fn split_data_uri(value: &str) -> Option<(&str, &str)> {
+    let mut parts = value.strip_prefix(DATA_URI_SCHEME)?.splitn(2, ',');
+    Some((parts.next()?, parts.next()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_address() -> EntryHash {
+        EntryHash::from_raw_36(vec![0xe1; 36])
+    }
+
+    fn manifest_for(content: &[u8]) -> BlobManifest {
+        BlobManifest { size: content.len(), media_type: None, chunks: vec![] }
+    }
+
+    #[test]
+    fn test_chunk_roundtrip() {
+        let content: Vec<u8> = (0..(BLOB_CHUNK_SIZE * 2 + 10)).map(|i| (i % 251) as u8).collect();
+        let chunks = split_chunks(&content);
+
+        assert_eq!(chunks.len(), 3, "content is split into chunks of at most BLOB_CHUNK_SIZE bytes");
+        assert_eq!(chunks[0].content.len(), BLOB_CHUNK_SIZE);
+        assert_eq!(chunks[2].content.len(), 10, "the last chunk holds the remainder");
+        assert_eq!(join_chunks(&test_address(), &manifest_for(&content), chunks).unwrap(), content, "chunks rejoin in order");
+    }
+
+    #[test]
+    fn test_chunk_boundaries() {
+        assert!(split_chunks(&[]).is_empty(), "empty content has no chunks");
+        assert_eq!(join_chunks(&test_address(), &manifest_for(&[]), vec![]).unwrap(), Vec::<u8>::new());
+
+        let exact = vec![7; BLOB_CHUNK_SIZE];
+        assert_eq!(split_chunks(&exact).len(), 1, "content of exactly one chunk is not followed by an empty chunk");
+    }
+
+    #[test]
+    fn test_size_mismatch() {
+        let content = vec![1, 2, 3, 4];
+        let mut chunks = split_chunks(&content);
+        chunks[0].content.pop();
+
+        match join_chunks(&test_address(), &manifest_for(&content), chunks) {
+            Err(DataIntegrityError::BlobSizeMismatch(address, expected, actual)) => {
+                assert_eq!(address, test_address());
+                assert_eq!((expected, actual), (4, 3));
+            },
+            other => panic!("expected size mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_only_local_manifests_are_read() {
+        let local = DnaHash::from_raw_36(vec![0xdb; 36]);
+        let remote = DnaHash::from_raw_36(vec![0xdc; 36]);
+
+        assert!(is_local_manifest_uri(&RecordUri::new(local.clone(), BLOB_MANIFEST_ENTRY_DEF_ID, test_address()), &local));
+        assert!(!is_local_manifest_uri(&RecordUri::new(remote, BLOB_MANIFEST_ENTRY_DEF_ID, test_address()), &local), "blobs in other DNAs are not read");
+        assert!(!is_local_manifest_uri(&RecordUri::new(local.clone(), BLOB_CHUNK_ENTRY_DEF_ID, test_address()), &local), "only manifests are read");
+        assert!(!is_local_manifest_uri(&RecordUri::new(local.clone(), "vf_economic_resource", test_address()), &local));
+    }
+
+    #[test]
+    fn test_data_uri_parsing() {
+        assert_eq!(split_data_uri("data:image/png;base64,iVBORw0KGgo="), Some(("image/png;base64", "iVBORw0KGgo=")));
+        assert_eq!(split_data_uri("data:,a,b"), Some(("", "a,b")), "data may contain commas");
+        assert_eq!(split_data_uri("https://example.com/image.png"), None);
+        assert_eq!(split_data_uri("data:image/png"), None, "URIs without data are not stored as blobs");
+    }
+}
@@ -0,0 +1,282 @@
+/**
+ * Retry & circuit-breaker policy for cross-cell zome calls.
+ *
+ * The policy is read from the `call_policy` block of the DNA properties, falling back to
+ * defaults for any missing values:
+ *
+ *     call_policy:
+ *       max_attempts: 3
+ *       timeout_ms: 10000
+ *       retryable: [network_error]
+ *       breaker_threshold: 5
+ *       breaker_open_ms: 30000
+ *       breaker_max_open_ms: 600000
+ *
+ * Only calls marked as idempotent (eg. reads, index writes) are retried. Retries are made immediately,
+ * since zome calls cannot sleep. Calls are retried until either `max_attempts` calls have been made or
+ * `timeout_ms` has elapsed since the first. Backoff between retries of writes to other cells is left to
+ * the outbox, which reschedules failed deliveries for a later flush (@see outbox_helpers.rs).
+ *
+ * After `breaker_threshold` consecutive network errors to the same DNA, calls to it fail fast for
+ * `breaker_open_ms`. Once this period has passed a single further network error trips the breaker
+ * again, doubling the open period up to `breaker_max_open_ms`, until a call succeeds.
+ *
+ * :NOTE: breaker state is held in the memory of the wasm instance and is not persisted, since any
+ *        entries written by a zome call which fails are discarded along with its other writes. The
+ *        breaker therefore stops a single zome call (eg. an outbox flush, or an index update fanning
+ *        out to many records) from repeatedly waiting on a DNA which is unreachable.
+ *
+ * @see     ../README.md
+ * @package HDK Graph Helpers
+ * @since   2021-10-27
+ */
+use std::cell::RefCell;
+use std::collections::HashMap;
+use hdk::prelude::*;
+use holo_hash::DnaHash;
+
+use crate::{
+    OtherCellResult,
+    CrossCellError,
+    config_helpers::read_dna_properties,
+};
+
+/// Kinds of cross-cell call failure which may be configured as retryable
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryableError {
+    NetworkError,
+    CellAuthFailed,
+    Unauthorized,
+    Internal,
+}
+
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct CallPolicy {
+    pub max_attempts: u32,
+    pub timeout_ms: i64,
+    pub retryable: Vec<RetryableError>,
+    pub breaker_threshold: u32,
+    pub breaker_open_ms: i64,
+    pub breaker_max_open_ms: i64,
+}
+
+impl Default for CallPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            timeout_ms: 10_000,
+            retryable: vec![RetryableError::NetworkError],
+            breaker_threshold: 5,
+            breaker_open_ms: 30_000,
+            breaker_max_open_ms: 600_000,
+        }
+    }
+}
+
+impl CallPolicy {
+    fn is_retryable(&self, e: &CrossCellError) -> bool {
+        match error_kind(e) {
+            Some(kind) => self.retryable.contains(&kind),
+            None => false,
+        }
+    }
+}
+
+/// Read the call policy from DNA properties, using defaults if none is configured
+pub fn read_call_policy() -> OtherCellResult<CallPolicy> {
+    Ok(read_dna_properties()?.call_policy.unwrap_or_default())
+}
+
+/// Circuit breaker state for calls to a single DNA
+#[derive(Clone, PartialEq, Debug)]
+struct BreakerState {
+    dna: DnaHash,
+    consecutive_failures: u32,
+    /// times the breaker has tripped since the last successful call
+    trips: u32,
+    open_until_ms: Option<i64>,
+}
+
+impl BreakerState {
+    fn new(dna: &DnaHash) -> Self {
+        Self { dna: dna.to_owned(), consecutive_failures: 0, trips: 0, open_until_ms: None }
+    }
+
+    fn is_open(&self, now_ms: i64) -> bool {
+        match self.open_until_ms {
+            Some(open_until) => now_ms < open_until,
+            None => false,
+        }
+    }
+
+    fn after_success(&self) -> Self {
+        Self::new(&self.dna)
+    }
+
+    fn after_network_error(&self, policy: &CallPolicy, now_ms: i64) -> Self {
+        let consecutive_failures = self.consecutive_failures + 1;
+        if self.trips == 0 && consecutive_failures < policy.breaker_threshold {
+            return Self { consecutive_failures, ..self.to_owned() };
+        }
+
+        let open_for = policy.breaker_open_ms
+            .saturating_mul(1 << self.trips.min(16))
+            .min(policy.breaker_max_open_ms);
+        Self {
+            dna: self.dna.to_owned(),
+            consecutive_failures: 0,
+            trips: self.trips.saturating_add(1),
+            open_until_ms: Some(now_ms.saturating_add(open_for)),
+        }
+    }
+}
+
+thread_local! {
+    static BREAKERS: RefCell<HashMap<DnaHash, BreakerState>> = RefCell::new(HashMap::new());
+}
+
+/// Make a call to another cell under the DNA's call policy, failing fast while the circuit breaker
+/// for the target DNA is open. Retryable failures are retried only if the call is `idempotent`.
+///
+pub fn with_call_policy<R, F>(to_dna: &DnaHash, idempotent: bool, mut make_call: F) -> OtherCellResult<R>
+    where F: FnMut() -> OtherCellResult<R>,
+{
+    let policy = read_call_policy()?;
+    let mut breaker = read_breaker(to_dna);
+    let started = now_ms()?;
+
+    if breaker.is_open(started) {
+        return Err(CrossCellError::CircuitOpen(to_dna.to_owned()));
+    }
+
+    let max_attempts = if idempotent { policy.max_attempts } else { 1 };
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let result = make_call();
+        let now = now_ms()?;
+
+        let next = match &result {
+            Err(CrossCellError::NetworkError(_)) => breaker.after_network_error(&policy, now),
+            Ok(_) => breaker.after_success(),
+            _ => breaker.to_owned(),
+        };
+        if next != breaker {
+            write_breaker(&next);
+            breaker = next;
+        }
+
+        match result {
+            Err(e) if policy.is_retryable(&e) => {
+                if attempts >= max_attempts || now - started >= policy.timeout_ms || breaker.is_open(now) {
+                    return Err(e);
+                }
+            },
+            other => return other,
+        }
+    }
+}
+
+fn error_kind(e: &CrossCellError) -> Option<RetryableError> {
+    match e {
+        CrossCellError::NetworkError(_) => Some(RetryableError::NetworkError),
+        CrossCellError::CellAuthFailed(_, _) => Some(RetryableError::CellAuthFailed),
+        CrossCellError::Unauthorized(_, _, _, _) => Some(RetryableError::Unauthorized),
+        CrossCellError::Internal(_) => Some(RetryableError::Internal),
+        _ => None,
+    }
+}
+
+/// Latest breaker state recorded for calls to `dna`
+fn read_breaker(dna: &DnaHash) -> BreakerState {
+    BREAKERS.with(|b| b.borrow().get(dna).cloned())
+        .unwrap_or_else(|| BreakerState::new(dna))
+}
+
+fn write_breaker(state: &BreakerState) {
+    BREAKERS.with(|b| { b.borrow_mut().insert(state.dna.to_owned(), state.to_owned()); });
+}
+
+fn now_ms() -> OtherCellResult<i64> {
+    let now = sys_time()?;
+    Ok(now.0 * 1000 + (now.1 / 1_000_000) as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_policy() -> CallPolicy {
+        CallPolicy {
+            breaker_threshold: 3,
+            breaker_open_ms: 1000,
+            breaker_max_open_ms: 5000,
+            ..CallPolicy::default()
+        }
+    }
+
+    fn test_breaker() -> BreakerState {
+        BreakerState::new(&DnaHash::from_raw_36(vec![0xdb; 36]))
+    }
+
+    #[test]
+    fn test_breaker_trips_at_threshold() {
+        let policy = test_policy();
+        let mut breaker = test_breaker();
+        for _ in 0..2 {
+            breaker = breaker.after_network_error(&policy, 100);
+            assert!(!breaker.is_open(100), "breaker stays closed below the failure threshold");
+        }
+
+        breaker = breaker.after_network_error(&policy, 100);
+        assert!(breaker.is_open(100), "breaker opens on reaching the failure threshold");
+        assert!(breaker.is_open(1099), "breaker remains open for the open period");
+        assert!(!breaker.is_open(1100), "breaker admits calls once the open period has passed");
+    }
+
+    #[test]
+    fn test_breaker_open_period_doubles_until_success() {
+        let policy = test_policy();
+        let mut breaker = test_breaker();
+        for _ in 0..3 {
+            breaker = breaker.after_network_error(&policy, 0);
+        }
+        assert_eq!(breaker.open_until_ms, Some(1000));
+
+        breaker = breaker.after_network_error(&policy, 2000);
+        assert_eq!(breaker.open_until_ms, Some(4000), "a single failure after reopening trips the breaker for double the period");
+        breaker = breaker.after_network_error(&policy, 4000);
+        assert_eq!(breaker.open_until_ms, Some(8000));
+        breaker = breaker.after_network_error(&policy, 8000);
+        assert_eq!(breaker.open_until_ms, Some(13000), "open period is capped");
+
+        breaker = breaker.after_success();
+        assert_eq!(breaker, test_breaker(), "success resets the breaker");
+        breaker = breaker.after_network_error(&policy, 20000);
+        assert!(!breaker.is_open(20000), "threshold applies again after a success");
+    }
+
+    #[test]
+    fn test_breaker_open_period_does_not_overflow() {
+        let policy = CallPolicy { breaker_max_open_ms: i64::MAX, ..test_policy() };
+        let breaker = BreakerState { trips: u32::MAX - 1, ..test_breaker() };
+
+        let tripped = breaker.after_network_error(&policy, i64::MAX - 10);
+        assert_eq!(tripped.open_until_ms, Some(i64::MAX));
+    }
+
+    #[test]
+    fn test_only_configured_errors_are_retryable() {
+        let policy = CallPolicy::default();
+
+        assert!(policy.is_retryable(&CrossCellError::NetworkError("timeout".to_string())));
+        assert!(!policy.is_retryable(&CrossCellError::Internal("failed".to_string())));
+        assert!(!policy.is_retryable(&CrossCellError::EntryTooLarge(1)));
+
+        let policy = CallPolicy { retryable: vec![RetryableError::Internal], ..CallPolicy::default() };
+        assert!(policy.is_retryable(&CrossCellError::Internal("failed".to_string())));
+        assert!(!policy.is_retryable(&CrossCellError::NetworkError("timeout".to_string())));
+    }
+}
@@ -0,0 +1,272 @@
+/**
+ * Typed loading and validation of DNA configuration.
+ *
+ * The DNA properties (the `properties` block of a DNA's `dna.yaml`) hold one section per zome,
+ * keyed by zome name, alongside DNA-wide settings such as `remote_auth` and `call_policy`.
+ * Each zome reads its own section as a struct implementing `ZomeConfig`.
+ *
+ * Zomes must be declared by name in the DNA-wide `zome_names` list. Any other key in the
+ * properties which is not a DNA-wide setting is rejected, so that misspelled settings fail validation.
+ *
+ * Zomes should validate the whole properties tree from their `init` callback via
+ * `init_with_config()`, so that misconfigured DNAs fail on installation rather than on first use.
+ *
+ * By convention, any zome section field ending in `_zome` names another zome in the same DNA.
+ * Referenced zomes must be declared in `zome_names` for the reference to be valid.
+ *
+ * @see     ../README.md
+ * @package HDK Graph Helpers
+ * @since   2021-10-30
+ */
+use std::collections::BTreeMap;
+use hdk::prelude::*;
+use holochain_serialized_bytes::decode;
+use vf_attributes_hdk::Action;
+
+use crate::{
+    RecordAPIResult, DataIntegrityError,
+    ErrorEnvelope,
+    call_policy_helpers::CallPolicy,
+};
+
+/// Suffix identifying fields of zome sections which name another zome in the DNA
+pub const ZOME_REFERENCE_SUFFIX: &str = "_zome";
+
+/// Configuration section read by a zome from the DNA properties.
+///
+/// Implementors should be declared with `#[serde(deny_unknown_fields)]` so that misspelled
+/// fields are rejected rather than silently ignored.
+///
+pub trait ZomeConfig: serde::de::DeserializeOwned + std::fmt::Debug {}
+
+/// Zome methods exposed to other DNAs via the auth resolver
+///
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct RemoteAuthConfig {
+    pub permissions: Vec<RemoteAuthPermission>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RemoteAuthPermission {
+    pub extern_id: String,
+    /// (zome name, function name)
+    pub allowed_method: (String, String),
+}
+
+/// The complete DNA properties tree
+///
+#[derive(Deserialize, Debug, Default)]
+pub struct DnaProperties {
+    /// names of all zomes in the DNA; only these may have configuration sections
+    #[serde(default)]
+    pub zome_names: Vec<String>,
+    #[serde(default)]
+    pub remote_auth: Option<RemoteAuthConfig>,
+    #[serde(default)]
+    pub call_policy: Option<CallPolicy>,
+    /// custom action definitions, resolved by `vf_actions`
+    #[serde(default)]
+    pub custom_actions: Option<Vec<Action>>,
+    /// per-zome configuration sections, keyed by zome name
+    #[serde(flatten)]
+    pub zomes: BTreeMap<String, serde_json::Value>,
+}
+
+impl DnaProperties {
+    /// Read the configuration section for the named zome
+    ///
+    pub fn zome_section<C: ZomeConfig>(&self, zome_name: &str) -> RecordAPIResult<C> {
+        let section = self.zomes.get(zome_name)
+            .ok_or_else(|| DataIntegrityError::InvalidConfig(format!("no `{}` section is configured for this zome", zome_name)))?;
+
+        serde_json::from_value(section.to_owned())
+            .map_err(|e| DataIntegrityError::InvalidConfig(format!("`{}`: {}", zome_name, e)))
+    }
+
+    /// Check that every key other than DNA-wide settings is the section of a declared zome
+    ///
+    pub fn validate_zome_sections(&self) -> RecordAPIResult<()> {
+        match self.zomes.keys().find(|zome_name| !self.is_declared(zome_name)) {
+            Some(key) => Err(DataIntegrityError::InvalidConfig(format!("`{}` is neither a DNA setting nor a zome listed in `zome_names`", key))),
+            None => Ok(()),
+        }
+    }
+
+    /// Check that every `*_zome` field of every zome section names a declared zome
+    ///
+    pub fn validate_zome_references(&self) -> RecordAPIResult<()> {
+        for (zome_name, section) in self.zomes.iter() {
+            let fields = section.as_object()
+                .ok_or_else(|| DataIntegrityError::InvalidConfig(format!("`{}` is not a zome configuration section", zome_name)))?;
+
+            for (field, value) in fields.iter().filter(|(f, _)| f.ends_with(ZOME_REFERENCE_SUFFIX)) {
+                match value {
+                    serde_json::Value::Null => (),
+                    serde_json::Value::String(target) if self.is_declared(target) => (),
+                    _ => return Err(DataIntegrityError::InvalidConfig(format!("`{}.{}` does not name a zome listed in `zome_names`: {}", zome_name, field, value))),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn is_declared(&self, zome_name: &str) -> bool {
+        self.zome_names.iter().any(|name| name == zome_name)
+    }
+}
+
+/// Read and parse the full DNA properties tree. DNAs without properties yield an empty tree.
+///
+pub fn read_dna_properties() -> RecordAPIResult<DnaProperties> {
+    let props = zome_info()?.properties;
+    let tree: serde_json::Value = decode(props.bytes())?;
+
+    if tree.is_null() {
+        return Ok(DnaProperties::default());
+    }
+    serde_json::from_value(tree)
+        .map_err(|e| DataIntegrityError::InvalidConfig(e.to_string()))
+}
+
+/// Read the configuration section of the calling zome
+///
+pub fn zome_config<C: ZomeConfig>() -> RecordAPIResult<C> {
+    read_dna_properties()?.zome_section(&zome_info()?.zome_name.0)
+}
+
+/// Validate the full DNA properties tree, and the calling zome's own section against `C`
+///
+pub fn validate_dna_config<C: ZomeConfig>() -> RecordAPIResult<C> {
+    let props = read_dna_properties()?;
+    props.validate_zome_sections()?;
+    props.validate_zome_references()?;
+    props.zome_section(&zome_info()?.zome_name.0)
+}
+
+/// Result for a zome's `init` callback, failing initialisation if the DNA is misconfigured
+///
+pub fn init_with_config<C: ZomeConfig>() -> InitCallbackResult {
+    match validate_dna_config::<C>() {
+        Ok(_) => InitCallbackResult::Pass,
+        Err(e) => InitCallbackResult::Fail(ErrorEnvelope::from(&e).to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Deserialize, Debug)]
+    #[serde(deny_unknown_fields)]
+    struct TestZomeConfig {
+        index_zome: Option<String>,
+    }
+
+    impl ZomeConfig for TestZomeConfig {}
+
+    fn properties(tree: serde_json::Value) -> DnaProperties {
+        serde_json::from_value(tree).unwrap()
+    }
+
+    #[test]
+    fn test_zome_references_must_name_declared_zomes() {
+        let props = properties(json!({
+            "zome_names": ["intent", "intent_index"],
+            "intent": { "index_zome": "intent_index" },
+            "intent_index": { "record_zome": "intent" },
+        }));
+        assert!(props.validate_zome_references().is_ok());
+
+        let props = properties(json!({
+            "zome_names": ["intent", "intent_index"],
+            "intent": { "index_zome": "intent_idx" },
+            "intent_index": { "record_zome": "intent" },
+        }));
+        assert!(matches!(props.validate_zome_references(), Err(DataIntegrityError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_zome_references_need_not_have_sections() {
+        let props = properties(json!({
+            "zome_names": ["resource_specification", "resource_specification_index"],
+            "resource_specification_index": { "record_storage_zome": "resource_specification" },
+        }));
+        assert!(props.validate_zome_references().is_ok());
+    }
+
+    #[test]
+    fn test_zome_references_may_be_unset() {
+        let props = properties(json!({
+            "zome_names": ["intent"],
+            "intent": { "index_zome": null, "note": "not a reference" },
+        }));
+        assert!(props.validate_zome_references().is_ok());
+    }
+
+    #[test]
+    fn test_zome_references_reject_non_object_sections() {
+        let props = properties(json!({ "zome_names": ["intent"], "intent": "intent_index" }));
+        assert!(matches!(props.validate_zome_references(), Err(DataIntegrityError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_undeclared_sections_are_rejected() {
+        let props = properties(json!({
+            "zome_names": ["intent", "remote_auth"],
+            "intent": { "index_zome": null },
+            "remote_auth": { "permissions": [] },
+        }));
+        assert!(props.validate_zome_sections().is_ok());
+
+        let props = properties(json!({
+            "zome_names": ["intent", "remote_auth"],
+            "intent": { "index_zome": null },
+            "remot_auth": { "permissions": [] },
+        }));
+        assert!(matches!(props.validate_zome_sections(), Err(DataIntegrityError::InvalidConfig(_))), "misspelled DNA settings are rejected");
+
+        let props = properties(json!({ "intent": { "index_zome": null } }));
+        assert!(matches!(props.validate_zome_sections(), Err(DataIntegrityError::InvalidConfig(_))), "zome sections must be declared");
+    }
+
+    #[test]
+    fn test_zome_sections_reject_unknown_fields() {
+        let props = properties(json!({
+            "intent": { "index_zome": "intent" },
+            "commitment": { "indx_zome": "intent" },
+        }));
+        assert!(props.zome_section::<TestZomeConfig>("intent").is_ok());
+        assert!(matches!(props.zome_section::<TestZomeConfig>("commitment"), Err(DataIntegrityError::InvalidConfig(_))));
+        assert!(matches!(props.zome_section::<TestZomeConfig>("plan"), Err(DataIntegrityError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_dna_settings_reject_unknown_fields() {
+        let tree = json!({
+            "remote_auth": { "permissions": [], "permission": [] },
+        });
+        assert!(serde_json::from_value::<DnaProperties>(tree).is_err());
+    }
+
+    #[test]
+    fn test_custom_actions_are_parsed() {
+        let props = properties(json!({
+            "custom_actions": [{
+                "id": "repair",
+                "label": "repair",
+                "resourceEffect": "noEffect",
+                "inputOutput": "input",
+                "pairsWith": "notApplicable",
+            }],
+        }));
+        assert_eq!(props.custom_actions.unwrap()[0].id, "repair");
+
+        let tree = json!({
+            "custom_actions": [{ "id": "repair", "label": "repair", "resourceEffect": "fix" }],
+        });
+        assert!(serde_json::from_value::<DnaProperties>(tree).is_err());
+    }
+}
@@ -20,6 +20,26 @@ use hdk::info::zome_info;
 
 use crate::{RevisionHash, RecordAPIResult, DataIntegrityError};
 
+/// Largest serialized entry the conductor will accept. Writes exceeding this are rejected by
+/// `create_entry` and `update_entry` before reaching the conductor.
+///
+/// Values which may grow beyond this (eg. embedded images) should be stored via `hdk_records::blobs`.
+///
+pub use hdk::prelude::ENTRY_SIZE_LIMIT as MAX_ENTRY_SIZE;
+
+/// Ensure an entry's serialized content is within `MAX_ENTRY_SIZE`
+///
+pub fn check_entry_size(entry: &Entry) -> RecordAPIResult<()> {
+    let size = match entry {
+        Entry::App(content) => content.to_owned().into_sb().bytes().len(),
+        _ => SerializedBytes::try_from(entry.to_owned())?.bytes().len(),
+    };
+    if size > MAX_ENTRY_SIZE {
+        return Err(DataIntegrityError::EntryTooLarge(size));
+    }
+    Ok(())
+}
+
 /// Helper to handle retrieving linked element entry from an element
 ///
 pub fn try_entry_from_element<'a>(element: Option<&'a Element>) -> RecordAPIResult<&'a Entry> {
@@ -92,6 +112,7 @@ pub fn create_entry<I: Clone, E, S: AsRef<str>>(
     let entry_data: Result<Entry, E> = entry_struct.try_into();
     match entry_data {
         Ok(entry) => {
+            check_entry_size(&entry)?;
             let header_hash = hdk_create(CreateInput::new(EntryDefId::App(entry_def_id.as_ref().to_string()), entry, ChainTopOrdering::default()))?;
             Ok((RevisionHash(zome_info()?.dna_hash, header_hash), entry_hash))
         },
@@ -126,6 +147,7 @@ pub fn update_entry<'a, I: Clone, E, A, S: AsRef<str>>(
     let entry_data: Result<Entry, E> = new_entry.try_into();
     match entry_data {
         Ok(entry) => {
+            check_entry_size(&entry)?;
             let updated_header = hdk_update(address.as_ref().clone(), CreateInput::new(EntryDefId::App(entry_def_id.as_ref().to_string()), entry, ChainTopOrdering::default()))?;
 
             Ok((RevisionHash(zome_info()?.dna_hash, updated_header), entry_address))
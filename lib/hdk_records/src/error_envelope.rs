@@ -0,0 +1,301 @@
+/**
+ * Serialisable error envelope for reporting failures to clients and other cells.
+ *
+ * All errors leaving a zome are encoded as JSON of the form:
+ *
+ *     { "code": "INDEX_NOT_FOUND", "message": "No index found at address ...", "details": { "address": "..." } }
+ *
+ * Codes are stable and intended to be matched on; messages are for humans. Envelopes returned by
+ * other zomes are decoded once, as they are received, into `CrossCellError::Reported`. Where such an
+ * error is passed on, the original envelope is attached as `details.cause` and its code is carried
+ * over, so that the original failure survives being passed between cells.
+ *
+ * @see     ../README.md
+ * @package HDK Graph Helpers
+ * @since   2021-10-28
+ */
+use hdk::prelude::*;
+
+use crate::{ DataIntegrityError, CrossCellError };
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    EntryNotFound,
+    IndexNotFound,
+    Unauthorized,
+    Conflict,
+    ValidationFailed,
+    NotConfigured,
+    /// the target cell could not be reached, and the request may be retried later
+    Unavailable,
+    SerializationError,
+    RemoteError,
+    Internal,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorDetails {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dna: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zome: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    /// error reported by another cell which caused this one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cause: Option<Box<ErrorEnvelope>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ErrorEnvelope {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(default)]
+    pub details: ErrorDetails,
+}
+
+impl ErrorEnvelope {
+    pub fn new(code: ErrorCode, message: String) -> Self {
+        Self { code, message, details: ErrorDetails::default() }
+    }
+
+    pub fn with_details(self, details: ErrorDetails) -> Self {
+        Self { details, ..self }
+    }
+
+    /// Recover an envelope embedded in the message of an error received from another zome
+    pub(crate) fn decode_from(text: &str) -> Option<ErrorEnvelope> {
+        // the envelope may have been string-escaped when wrapped by the host
+        let unescaped = text.replace("\\\"", "\"").replace("\\\\", "\\");
+        for candidate in vec![text, unescaped.as_str()] {
+            for (i, _) in candidate.match_indices('{') {
+                let parsed = serde_json::Deserializer::from_str(&candidate[i..]).into_iter::<ErrorEnvelope>().next();
+                if let Some(Ok(envelope)) = parsed {
+                    return Some(envelope);
+                }
+            }
+        }
+        None
+    }
+
+    /// Build an envelope for an error caused by a failure reported by another zome, inheriting its code
+    fn caused_by(message: String, cause: &ErrorEnvelope) -> Self {
+        Self {
+            code: cause.code.to_owned(),
+            message,
+            details: ErrorDetails { cause: Some(Box::new(cause.to_owned())), ..ErrorDetails::default() },
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorEnvelope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match serde_json::to_string(self) {
+            Ok(json) => write!(f, "{}", json),
+            Err(_) => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl From<ErrorEnvelope> for WasmError {
+    fn from(e: ErrorEnvelope) -> WasmError {
+        WasmError::Guest(e.to_string())
+    }
+}
+
+impl From<&DataIntegrityError> for ErrorEnvelope {
+    fn from(e: &DataIntegrityError) -> ErrorEnvelope {
+        let message = e.to_string();
+        match e {
+            DataIntegrityError::Serialization(_) |
+            DataIntegrityError::RemoteResponseFormatError(_) =>
+                Self::new(ErrorCode::SerializationError, message),
+            DataIntegrityError::Infallible(_) =>
+                Self::new(ErrorCode::Internal, message),
+            DataIntegrityError::Wasm(_) =>
+                Self::new(ErrorCode::Internal, message),
+            DataIntegrityError::EntryNotFound =>
+                Self::new(ErrorCode::EntryNotFound, message),
+            DataIntegrityError::IndexNotFound(address) =>
+                Self::new(ErrorCode::IndexNotFound, message).with_details(ErrorDetails {
+                    address: Some(address.to_string()), ..ErrorDetails::default()
+                }),
+            DataIntegrityError::CorruptIndexError(address, _) |
+            DataIntegrityError::BlobSizeMismatch(address, _, _) =>
+                Self::new(ErrorCode::Internal, message).with_details(ErrorDetails {
+                    address: Some(address.to_string()), ..ErrorDetails::default()
+                }),
+            DataIntegrityError::CrossCell(e) =>
+                Self::from(e),
+            DataIntegrityError::RemoteIndexingError(_) =>
+                Self::new(ErrorCode::RemoteError, message),
+            DataIntegrityError::ReferencedByIndexes(_) |
+            DataIntegrityError::RecordFinished |
+            DataIntegrityError::Conflict(_) =>
+                Self::new(ErrorCode::Conflict, message),
+            DataIntegrityError::IncompatibleFlow(field) =>
+                Self::new(ErrorCode::ValidationFailed, message).with_details(ErrorDetails {
+                    field: Some(field.to_owned()), ..ErrorDetails::default()
+                }),
+            DataIntegrityError::EntryError(_) |
+            DataIntegrityError::EntryWrongType |
+            DataIntegrityError::EmptyQuery |
+            DataIntegrityError::MaxDepthExceeded(_) |
+            DataIntegrityError::IncompatibleUnits |
+            DataIntegrityError::InsufficientQuantity(_, _) |
+            DataIntegrityError::QuantityExceeded(_, _) |
+            DataIntegrityError::ValidationFailed(_) |
+            DataIntegrityError::EntryTooLarge(_) =>
+                Self::new(ErrorCode::ValidationFailed, message),
+            DataIntegrityError::InvalidConfig(_) =>
+                Self::new(ErrorCode::NotConfigured, message),
+            DataIntegrityError::RollbackFailed(cause, _) =>
+                Self { message, ..Self::from(cause.as_ref()) },
+            DataIntegrityError::UnresolvableUri(uri) =>
+                Self::new(ErrorCode::NotConfigured, message).with_details(ErrorDetails {
+                    address: Some(uri.to_owned()), ..ErrorDetails::default()
+                }),
+        }
+    }
+}
+
+impl From<&CrossCellError> for ErrorEnvelope {
+    fn from(e: &CrossCellError) -> ErrorEnvelope {
+        let message = e.to_string();
+        match e {
+            CrossCellError::Serialization(_) =>
+                Self::new(ErrorCode::SerializationError, message),
+            CrossCellError::Wasm(_) =>
+                Self::new(ErrorCode::RemoteError, message),
+            CrossCellError::Reported(cause) =>
+                Self::caused_by(message, cause),
+            CrossCellError::EntryTooLarge(_) =>
+                Self::new(ErrorCode::ValidationFailed, message),
+            CrossCellError::IndexNotFound(address) =>
+                Self::new(ErrorCode::IndexNotFound, message).with_details(ErrorDetails {
+                    address: Some(address.to_string()), ..ErrorDetails::default()
+                }),
+            CrossCellError::NetworkError(_) =>
+                Self::new(ErrorCode::Unavailable, message),
+            CrossCellError::Unauthorized(cell, zome, function, agent) =>
+                Self::new(ErrorCode::Unauthorized, message).with_details(ErrorDetails {
+                    dna: Some(cell.dna_hash().to_string()),
+                    zome: Some(zome.0.to_owned()),
+                    function: Some(function.0.to_owned()),
+                    agent: Some(agent.to_string()),
+                    ..ErrorDetails::default()
+                }),
+            CrossCellError::CellAuthFailed(dna, _) =>
+                Self::new(ErrorCode::Unauthorized, message).with_details(ErrorDetails {
+                    dna: Some(dna.to_string()), ..ErrorDetails::default()
+                }),
+            CrossCellError::Internal(_) =>
+                Self::new(ErrorCode::RemoteError, message),
+            CrossCellError::NotConfigured(zome, function) =>
+                Self::new(ErrorCode::NotConfigured, message).with_details(ErrorDetails {
+                    zome: Some(zome.0.to_owned()),
+                    function: Some(function.0.to_owned()),
+                    ..ErrorDetails::default()
+                }),
+            CrossCellError::CircuitOpen(dna) =>
+                Self::new(ErrorCode::Unavailable, message).with_details(ErrorDetails {
+                    dna: Some(dna.to_string()), ..ErrorDetails::default()
+                }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Simulate an error being returned from one zome and received by the zome which called it
+    fn pass_to_caller(e: DataIntegrityError) -> CrossCellError {
+        let returned: WasmError = e.into();
+        let json = match returned {
+            WasmError::Guest(json) => json,
+            other => panic!("zome errors are returned as guest errors, got {:?}", other),
+        };
+        // the host wraps the guest error, escaping it as a debug string
+        CrossCellError::from(WasmError::Host(format!("{:?}", json)))
+    }
+
+    #[test]
+    fn test_codes_survive_passing_between_zomes() {
+        let cases = vec![
+            (DataIntegrityError::EntryNotFound, ErrorCode::EntryNotFound),
+            (DataIntegrityError::Conflict("locked".to_string()), ErrorCode::Conflict),
+            (DataIntegrityError::IncompatibleFlow("action".to_string()), ErrorCode::ValidationFailed),
+            (DataIntegrityError::InvalidConfig("missing".to_string()), ErrorCode::NotConfigured),
+            (DataIntegrityError::RecordFinished, ErrorCode::Conflict),
+        ];
+
+        for (error, code) in cases {
+            let message = error.to_string();
+            match pass_to_caller(error) {
+                CrossCellError::Reported(envelope) => {
+                    assert_eq!(envelope.code, code, "code of {} is preserved", message);
+                    assert_eq!(envelope.message, message, "message is preserved");
+                },
+                other => panic!("expected a reported error, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_reported_errors_are_attached_as_cause() {
+        let received = pass_to_caller(DataIntegrityError::EntryNotFound);
+
+        // passed on again by the calling zome
+        let envelope = ErrorEnvelope::from(&DataIntegrityError::from(received));
+        assert_eq!(envelope.code, ErrorCode::EntryNotFound, "code of the original failure is inherited");
+        let cause = envelope.details.cause.expect("original envelope is attached");
+        assert_eq!(cause.code, ErrorCode::EntryNotFound);
+        assert_eq!(cause.message, DataIntegrityError::EntryNotFound.to_string());
+
+        // and received by a third zome
+        match pass_to_caller(DataIntegrityError::from(pass_to_caller(DataIntegrityError::EntryNotFound))) {
+            CrossCellError::Reported(envelope) => {
+                assert_eq!(envelope.code, ErrorCode::EntryNotFound, "code survives multiple hops");
+                assert!(envelope.details.cause.is_some());
+            },
+            other => panic!("expected a reported error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_details_roundtrip() {
+        let address = EntryHash::from_raw_36(vec![0xe1; 36]);
+        match pass_to_caller(DataIntegrityError::IndexNotFound(address.to_owned())) {
+            CrossCellError::Reported(envelope) => {
+                assert_eq!(envelope.code, ErrorCode::IndexNotFound);
+                assert_eq!(envelope.details.address, Some(address.to_string()), "details are preserved");
+            },
+            other => panic!("expected a reported error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_host_errors_are_not_reported_errors() {
+        match CrossCellError::from(WasmError::Host("connection reset".to_string())) {
+            CrossCellError::Wasm(_) => (),
+            other => panic!("expected a host error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_cross_cell_codes() {
+        assert_eq!(ErrorEnvelope::from(&CrossCellError::NetworkError("timeout".to_string())).code, ErrorCode::Unavailable);
+        assert_eq!(ErrorEnvelope::from(&CrossCellError::CircuitOpen(DnaHash::from_raw_36(vec![0xdb; 36]))).code, ErrorCode::Unavailable);
+        assert_eq!(ErrorEnvelope::from(&CrossCellError::EntryTooLarge(1)).code, ErrorCode::ValidationFailed);
+        assert_eq!(ErrorEnvelope::from(&CrossCellError::Internal("failed".to_string())).code, ErrorCode::RemoteError);
+    }
+}
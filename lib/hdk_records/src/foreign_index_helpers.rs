@@ -37,6 +37,7 @@ use crate::{
     rpc::{
         call_local_zome_method,
     },
+    config_helpers::ZomeConfig,
 };
 
 //-------------------------------[ CREATE ]-------------------------------------
@@ -52,8 +53,7 @@ pub fn create_foreign_index<C, F, G, A, B, S>(
     dest: &B,
 ) -> RecordAPIResult<Vec<RecordAPIResult<HeaderHash>>>
     where S: AsRef<str>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: FnOnce(C) -> Option<String>,
         G: FnOnce(C) -> Option<String>,
         A: DnaAddressable<EntryHash>,
@@ -70,14 +70,14 @@ pub fn create_foreign_index<C, F, G, A, B, S>(
             Ok(drr) => drr.indexes_created
                 .first().ok_or(CrossCellError::Internal("cross-zome index creation failed".to_string()))?
                 .clone()
-                .map_err(|e| { DataIntegrityError::RemoteRequestError(e.to_string()) }),
+                .map_err(DataIntegrityError::from),
             Err(e) => Err(e.into()),
         },
         match or {
             Ok(orr) => orr.indexes_created
                 .first().ok_or(CrossCellError::Internal("cross-zome index creation failed".to_string()))?
                 .clone()
-                .map_err(|e| { DataIntegrityError::RemoteRequestError(e.to_string()) }),
+                .map_err(DataIntegrityError::from),
             Err(e) => Err(e.into()),
         },
     ];
@@ -99,8 +99,7 @@ pub fn read_foreign_index<'a, O, A, S, F, C>(
     base_address: &A,
 ) -> RecordAPIResult<Vec<O>>
     where S: AsRef<str>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: FnOnce(C) -> Option<String>,
         A: DnaAddressable<EntryHash>,
         O: serde::de::DeserializeOwned + DnaAddressable<EntryHash>,
@@ -120,8 +119,7 @@ pub fn update_foreign_index<C, F, G, A, B, S>(
     remove_addresses: &[B],
 ) -> RecordAPIResult<RemoteEntryLinkResponse>
     where S: AsRef<str>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: Clone + FnOnce(C) -> Option<String>,
         G: FnOnce(C) -> Option<String>,
         A: DnaAddressable<EntryHash>,
@@ -189,8 +187,7 @@ pub (crate) fn request_sync_foreign_index_destination<C, F, A, B, S>(
     removed_addresses: &[B],
 ) -> OtherCellResult<RemoteEntryLinkResponse>
     where S: AsRef<str>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: FnOnce(C) -> Option<String>,
         A: DnaAddressable<EntryHash>,
         B: DnaAddressable<EntryHash>,
@@ -210,8 +207,7 @@ fn request_read_foreign_index<O, C, F, A, S>(
     source: &A,
 ) -> OtherCellResult<Vec<O>>
     where S: AsRef<str>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: FnOnce(C) -> Option<String>,
         A: DnaAddressable<EntryHash>,
         O: serde::de::DeserializeOwned + DnaAddressable<EntryHash>,
@@ -4,6 +4,7 @@ use crate::{
     RecordAPIResult,
     identity_helpers::read_entry_identity_full,
     rpc_helpers::call_local_zome_method,
+    config_helpers::ZomeConfig,
 };
 
 //--------------- API I/O STRUCTS ----------------
@@ -18,11 +19,14 @@ pub struct ByAddress<T> {
 
 /// Configuration attributes from indexing zomes which link to records in other zomes
 #[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct IndexingZomeConfig {
     // Index zome will call to the specified zome to retrieve records by identity hash.
     pub record_storage_zome: String,
 }
 
+impl ZomeConfig for IndexingZomeConfig {}
+
 /// Fetches all referenced record entries found corresponding to the input
 /// identity addresses.
 ///
@@ -36,8 +40,7 @@ pub (crate) fn retrieve_foreign_records<'a, T, B, C, F, S>(
     where S: AsRef<str>,
         T: serde::de::DeserializeOwned + std::fmt::Debug,
         B: DnaAddressable<EntryHash>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: Fn(C) -> Option<String>,
 {
     let read_single_record = retrieve_foreign_record::<T, B, _,_,_>(zome_name_from_config, &method_name);
@@ -54,8 +57,7 @@ fn retrieve_foreign_record<'a, T, B, C, F, S>(
     where S: AsRef<str>,
         T: serde::de::DeserializeOwned + std::fmt::Debug,
         B: DnaAddressable<EntryHash>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: Fn(C) -> Option<String>,
 {
     move |addr| {
@@ -0,0 +1,137 @@
+/**
+ * Helpers related to referential integrity between records.
+ *
+ * A record is considered safe to delete only when no inbound indexes reference it.
+ * Zomes declare which of a record's index link tags represent *inbound* references
+ * (ie. other records pointing at this one) and check these before deleting.
+ *
+ * @see     ../README.md
+ * @package HDK Graph Helpers
+ * @since   2021-10-18
+ */
+use hdk::prelude::*;
+use hdk_type_serialization_macros::AnyRecordAddress;
+
+use crate::{
+    RecordAPIResult, DataIntegrityError,
+    DnaAddressable,
+    internals::*,
+    identity_helpers::{
+        calculate_identity_address,
+        read_entry_identity_full,
+    },
+    links::get_linked_addresses,
+};
+
+/// Describes the set of records referencing some other record via a single index
+///
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockingReference {
+    pub link_tag: String,
+    pub referenced_by: Vec<AnyRecordAddress>,
+}
+
+/// Reads all records referencing `base_entry_type.base_address` via any of the given
+/// inbound index `link_tags`. Tags with no referencing records are omitted from the result.
+///
+pub fn read_blocking_references<A, S, I>(
+    base_entry_type: &I,
+    base_address: &A,
+    link_tags: &[S],
+) -> RecordAPIResult<Vec<BlockingReference>>
+    where S: AsRef<str>,
+        I: AsRef<str>,
+        A: DnaAddressable<EntryHash>,
+{
+    let index_address = calculate_identity_address(base_entry_type, base_address)?;
+    let mut blocking = vec![];
+
+    for link_tag in link_tags.iter() {
+        let refd_index_addresses = get_linked_addresses(&index_address, LinkTag::new(link_tag.as_ref()))?;
+        if refd_index_addresses.is_empty() { continue; }
+
+        let (existing_link_results, read_errors): (Vec<RecordAPIResult<AnyRecordAddress>>, Vec<RecordAPIResult<AnyRecordAddress>>) = refd_index_addresses.iter()
+            .map(read_entry_identity_full)
+            .partition(Result::is_ok);
+        throw_any_error(read_errors)?;
+
+        blocking.push(BlockingReference {
+            link_tag: link_tag.as_ref().to_string(),
+            referenced_by: existing_link_results.into_iter().map(Result::unwrap).collect(),
+        });
+    }
+
+    Ok(blocking)
+}
+
+/// Determine whether a record may be deleted without leaving other records referencing it.
+///
+pub fn is_deletable<A, S, I>(
+    base_entry_type: &I,
+    base_address: &A,
+    link_tags: &[S],
+) -> RecordAPIResult<bool>
+    where S: AsRef<str>,
+        I: AsRef<str>,
+        A: DnaAddressable<EntryHash>,
+{
+    Ok(read_blocking_references(base_entry_type, base_address, link_tags)?.is_empty())
+}
+
+/// Guard for `handle_delete_*` API methods. Errors with `DataIntegrityError::ReferencedByIndexes`
+/// if any records still reference the one being deleted, unless `force` is set.
+///
+pub fn ensure_deletable<A, S, I>(
+    base_entry_type: &I,
+    base_address: &A,
+    link_tags: &[S],
+    force: bool,
+) -> RecordAPIResult<()>
+    where S: AsRef<str>,
+        I: AsRef<str>,
+        A: DnaAddressable<EntryHash>,
+{
+    if force { return Ok(()); }
+
+    ensure_unreferenced(read_blocking_references(base_entry_type, base_address, link_tags)?)
+}
+
+fn ensure_unreferenced(blocking: Vec<BlockingReference>) -> RecordAPIResult<()> {
+    if blocking.is_empty() {
+        Ok(())
+    } else {
+        Err(DataIntegrityError::ReferencedByIndexes(blocking))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(byte: u8) -> AnyRecordAddress {
+        AnyRecordAddress(DnaHash::from_raw_36(vec![0xdb; 36]), EntryHash::from_raw_36(vec![byte; 36]))
+    }
+
+    #[test]
+    fn test_unreferenced_record_is_deletable() {
+        assert!(ensure_unreferenced(vec![]).is_ok());
+    }
+
+    #[test]
+    fn test_referenced_record_is_not_deletable() {
+        let blocking = vec![BlockingReference { link_tag: "inputs".to_string(), referenced_by: vec![address(1), address(2)] }];
+
+        match ensure_unreferenced(blocking.clone()) {
+            Err(DataIntegrityError::ReferencedByIndexes(refs)) => assert_eq!(refs, blocking),
+            other => panic!("expected ReferencedByIndexes error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_force_skips_reference_checks() {
+        // no references are read when forcing, so this does not require a Holochain host
+        let tags: &[&str] = &["inputs"];
+        assert!(ensure_deletable(&"process", &address(1), tags, true).is_ok());
+    }
+}
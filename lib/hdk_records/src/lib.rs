@@ -30,15 +30,23 @@ mod record_helpers;
 mod anchored_record_helpers;
 mod local_index_helpers;
 mod rpc_helpers;
+mod call_policy_helpers;
 mod remote_index_helpers;
 // :TODO: finalise this per https://github.com/holochain/holochain/issues/743
 //        and https://github.com/holochain/holochain/issues/563
 mod foreign_index_helpers;
 mod index_retrieval_helpers;
+mod integrity_helpers;
+mod outbox_helpers;
+mod uri_helpers;
+mod blob_helpers;
+mod config_helpers;
 
 // API interfaces
 
 pub mod record_interface;
+pub mod error_envelope;
+pub use error_envelope::{ ErrorEnvelope, ErrorCode, ErrorDetails };
 
 // helper functions API
 
@@ -47,10 +55,18 @@ pub mod links { pub use crate::link_helpers::*; }
 pub mod records { pub use crate::record_helpers::*; }
 pub mod records_anchored { pub use crate::anchored_record_helpers::*; }
 pub mod local_indexes { pub use crate::local_index_helpers::*; }
-pub mod rpc { pub use crate::rpc_helpers::*; }
+pub mod rpc {
+    pub use crate::rpc_helpers::*;
+    pub use crate::call_policy_helpers::{ CallPolicy, RetryableError, read_call_policy };
+}
 pub mod remote_indexes { pub use crate::remote_index_helpers::*; }
 pub mod foreign_indexes { pub use crate::foreign_index_helpers::*; }
 pub mod index_retrieval { pub use crate::index_retrieval_helpers::*; }
+pub mod integrity { pub use crate::integrity_helpers::*; }
+pub mod outbox { pub use crate::outbox_helpers::*; }
+pub mod uris { pub use crate::uri_helpers::*; }
+pub mod blobs { pub use crate::blob_helpers::*; }
+pub mod config { pub use crate::config_helpers::*; }
 
 // :TODO: these error types may just be duplicating enums from the HDK,
 // revisit this once result handling & serialisation have stabilised.
@@ -78,12 +94,50 @@ pub enum DataIntegrityError {
     EmptyQuery,
     #[error("Index at address {0} with malformed bytes {1:?}")]
     CorruptIndexError(EntryHash, Option<Vec<u8>>),
-    #[error("Error in remote call {0}")]
-    RemoteRequestError(String),
+    #[error("Blob at address {0} is corrupt: expected {1} bytes but its chunks hold {2}")]
+    BlobSizeMismatch(EntryHash, usize, usize),
+    #[error(transparent)]
+    CrossCell(#[from] CrossCellError),
     #[error("Bad zome RPC response format from {0}")]
     RemoteResponseFormatError(String),
     #[error("Indexing error in remote call {0}")]
     RemoteIndexingError(String),
+    #[error("Record is still referenced by other records and cannot be deleted: {0:?}")]
+    ReferencedByIndexes(Vec<integrity::BlockingReference>),
+    #[error("Exceeded maximum nesting depth of {0}; the structure may contain a cycle")]
+    MaxDepthExceeded(usize),
+    #[error("Cannot combine quantities measured in different units")]
+    IncompatibleUnits,
+    #[error("Requested quantity of {0} exceeds the {1} available")]
+    InsufficientQuantity(f64, f64),
+    #[error("Record has been marked as finished")]
+    RecordFinished,
+    #[error("Record is incompatible with the record it fulfills or satisfies: {0} does not match")]
+    IncompatibleFlow(String),
+    #[error("Cumulative quantity of {0} would exceed the {1} expected")]
+    QuantityExceeded(f64, f64),
+    #[error("No zome is known to store the record at {0}")]
+    UnresolvableUri(String),
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    ValidationFailed(String),
+    #[error("Entry size of {0} bytes exceeded maximum allowable")]
+    EntryTooLarge(usize),
+    #[error("Invalid DNA configuration: {0}")]
+    InvalidConfig(String),
+    #[error("{0} (undoing partial changes also failed: {1:?})")]
+    RollbackFailed(Box<DataIntegrityError>, Vec<String>),
+}
+
+impl DataIntegrityError {
+    /// Attach the failures of any writes made to undo an incomplete operation to the error which caused it
+    pub fn with_rollback_failures(self, failures: Vec<DataIntegrityError>) -> Self {
+        if failures.is_empty() {
+            return self;
+        }
+        DataIntegrityError::RollbackFailed(Box::new(self), failures.iter().map(|e| e.to_string()).collect())
+    }
 }
 
 pub type RecordAPIResult<T> = Result<T, DataIntegrityError>;
@@ -95,7 +149,10 @@ pub enum CrossCellError {
     #[error(transparent)]
     Serialization(#[from] SerializedBytesError),
     #[error(transparent)]
-    Wasm(#[from] WasmError),
+    Wasm(WasmError),
+    /// error returned by the zome called, as decoded from its `ErrorEnvelope`
+    #[error("Error in remote call: {}", .0.message)]
+    Reported(ErrorEnvelope),
 
     #[error("Entry size of {0} exceeded maximum allowable")]
     EntryTooLarge(usize),
@@ -111,6 +168,8 @@ pub enum CrossCellError {
     Internal(String),
     #[error("Local zome call failed: {0} zome is not configured for target {1}")]
     NotConfigured(ZomeName, FunctionName),
+    #[error("Calls to DNA {0} are suspended after repeated network errors")]
+    CircuitOpen(DnaHash),
 }
 
 pub type OtherCellResult<T> = Result<T, CrossCellError>;
@@ -121,28 +180,35 @@ impl From<DataIntegrityError> for CrossCellError {
     fn from(e: DataIntegrityError) -> CrossCellError {
         match e {
             DataIntegrityError::IndexNotFound(entry) => CrossCellError::IndexNotFound(entry),
-            _ => CrossCellError::Internal(e.to_string()),
+            DataIntegrityError::EntryTooLarge(size) => CrossCellError::EntryTooLarge(size),
+            DataIntegrityError::CrossCell(e) => e,
+            _ => CrossCellError::Reported(ErrorEnvelope::from(&e)),
         }
     }
 }
 
-// coerce error types to HDK errors for output
+// recover errors returned by other zomes, which arrive as JSON-encoded `ErrorEnvelope`s within host errors
 
-impl From<DataIntegrityError> for WasmError {
-    fn from(e: DataIntegrityError) -> WasmError {
-        WasmError::Guest(e.to_string())
+impl From<WasmError> for CrossCellError {
+    fn from(e: WasmError) -> CrossCellError {
+        match ErrorEnvelope::decode_from(&format!("{:?}", e)) {
+            Some(envelope) => CrossCellError::Reported(envelope),
+            None => CrossCellError::Wasm(e),
+        }
     }
 }
 
-impl From<CrossCellError> for DataIntegrityError {
-    fn from(e: CrossCellError) -> DataIntegrityError {
-        DataIntegrityError::RemoteRequestError(e.to_string())
+// coerce error types to HDK errors for output, as JSON-encoded `ErrorEnvelope`s
+
+impl From<DataIntegrityError> for WasmError {
+    fn from(e: DataIntegrityError) -> WasmError {
+        ErrorEnvelope::from(&e).into()
     }
 }
 
 impl From<CrossCellError> for WasmError {
     fn from(e: CrossCellError) -> WasmError {
-        WasmError::CallError(e.to_string())
+        ErrorEnvelope::from(&e).into()
     }
 }
 
@@ -43,6 +43,27 @@ pub (crate) fn get_linked_headers(
     pull_links_data(base_address, link_tag, get_link_target_header)
 }
 
+/// Create a link from `base_address` to `target_address` with the given `link_tag`, unless such a
+/// link already exists. Returns the `HeaderHash` of the new or existing link.
+///
+/// Makes index writes safe to repeat, such as when a call to build an index is retried.
+///
+pub (crate) fn ensure_link(
+    base_address: &EntryHash,
+    target_address: &EntryHash,
+    link_tag: LinkTag,
+) -> RecordAPIResult<HeaderHash> {
+    let existing = get_links((*base_address).clone(), Some(link_tag.to_owned()))?
+        .into_inner()
+        .into_iter()
+        .find(|l| l.target == *target_address);
+
+    match existing {
+        Some(link) => Ok(link.create_link_hash),
+        None => Ok(create_link(base_address.clone(), target_address.clone(), link_tag)?),
+    }
+}
+
 //-----------------------------------------------------
 
 // :TODO: ensure ordering is latest-first
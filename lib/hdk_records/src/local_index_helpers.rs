@@ -24,11 +24,13 @@ use crate::{
     links::{
         get_linked_headers,
         get_linked_addresses,
+        ensure_link,
     },
     records::{
         read_record_entry_by_identity,
     },
     index_retrieval_helpers::retrieve_foreign_records,
+    config_helpers::ZomeConfig,
 };
 
 //--------------------------------[ READ ]--------------------------------------
@@ -83,8 +85,7 @@ pub fn query_index<'a, T, O, C, F, A, S, I, J>(
         A: DnaAddressable<EntryHash>,
         O: DnaAddressable<EntryHash>,
         T: serde::de::DeserializeOwned + std::fmt::Debug,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: Fn(C) -> Option<String>,
 {
     let index_address = calculate_identity_address(base_entry_type, base_address)?;
@@ -123,6 +124,7 @@ pub fn query_root_index<'a, T, R, O, I: AsRef<str>>(
 
 /// Creates a bidirectional link between two entry addresses, and returns a vector
 /// of the `HeaderHash`es of the (respectively) forward & reciprocal links created.
+/// Links which already exist are left in place, so the operation is safe to repeat.
 pub fn create_index<A, B, S, I>(
     source_entry_type: &I,
     source: &A,
@@ -140,9 +142,8 @@ pub fn create_index<A, B, S, I>(
     let dest_hash = calculate_identity_address(dest_entry_type, dest)?;
 
     Ok(vec! [
-        // :TODO: prevent duplicates- is there an efficient way to ensure a link of a given tag exists?
-        Ok(create_link(source_hash.clone(), dest_hash.clone(), LinkTag::new(link_tag.as_ref()))?),
-        Ok(create_link(dest_hash, source_hash, LinkTag::new(link_tag_reciprocal.as_ref()))?),
+        ensure_link(&source_hash, &dest_hash, LinkTag::new(link_tag.as_ref())),
+        ensure_link(&dest_hash, &source_hash, LinkTag::new(link_tag_reciprocal.as_ref())),
     ])
 }
 
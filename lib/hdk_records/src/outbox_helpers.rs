@@ -0,0 +1,389 @@
+/**
+ * Helpers for durable delivery of cross-DNA replication calls.
+ *
+ * Operations which must be mirrored into another DNA are attempted directly where possible.
+ * Any which fail are stored as private entries on the agent's source chain (the "outbox"),
+ * to be retried with exponential backoff when the zome's outbox is next flushed.
+ *
+ * Zomes using the outbox declare their own entry type for outbox items, registered via
+ * `outbox_entry_def()` in their `entry_defs()` extern. The zome's `Outbox` is located within the
+ * same entry definitions returned by that extern. Replication calls are described by a
+ * zome-specific operation type implementing `ReplicationCall`.
+ *
+ * Updates and deletions are addressed to the revision of the replica written by the previous call,
+ * never to the origin record's own revision, which does not exist in the remote DNA. They are held
+ * in the outbox until the replica has been created.
+ *
+ * @see     ../README.md
+ * @package HDK Graph Helpers
+ * @since   2021-10-25
+ */
+use std::collections::HashSet;
+use std::fmt::Debug;
+use hdk::prelude::*;
+use holochain_serialized_bytes::{
+    SerializedBytes, UnsafeBytes,
+    decode, encode,
+};
+use serde::de::DeserializeOwned;
+use hdk_type_serialization_macros::{ RevisionHash, DnaAddressable };
+use vf_attributes_hdk::{ ReplicationStatus, OutboxFlushReport };
+
+use crate::{
+    RecordAPIResult, OtherCellResult, DataIntegrityError,
+    entry_helpers::{ check_entry_size, try_entry_from_element },
+    record_helpers::{ read_record_entry, update_record },
+    record_interface::{ Identifiable, Identified, Updateable },
+};
+
+/// Delay before the first retry of a failed delivery, doubling with each subsequent attempt
+pub const OUTBOX_INITIAL_RETRY_SECONDS: i64 = 30;
+/// Upper bound on the delay between delivery attempts
+pub const OUTBOX_MAX_RETRY_SECONDS: i64 = 6 * 60 * 60;
+
+/// An operation on a local record which is mirrored into the DNA of the remote record `T`
+pub trait ReplicationCall<T> {
+    /// Whether the operation modifies an existing replica, and so cannot be delivered until one has been written
+    fn requires_replica(&self) -> bool;
+
+    /// Deliver the operation to the replica at `replica_revision_id`, returning the revision of the
+    /// replica it leaves behind (if any remains)
+    fn deliver(&self, target: &T, replica_revision_id: Option<&RevisionHash>) -> OtherCellResult<Option<RevisionHash>>;
+}
+
+/// Update payload marking all replication calls on a record as delivered
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Acknowledgement {
+    /// revision of the replica written by the most recent call, where it wrote one
+    pub replica_revision_id: Option<RevisionHash>,
+}
+
+/// Records which track the delivery state of their most recent replication call, and the
+/// revision of the replica it was delivered to
+pub trait Replicated: Updateable<Acknowledgement> {
+    fn replication(&self) -> Option<&ReplicationStatus>;
+    fn replica_revision_id(&self) -> Option<&RevisionHash>;
+}
+
+/// Undelivered replication call, held privately on the agent's source chain until acknowledged
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OutboxItem<R, T, O> {
+    pub record: R,
+    /// remote record determining the DNA the operation is delivered to
+    pub target: T,
+    pub operation: O,
+    pub queued_at: Timestamp,
+    pub attempts: u32,
+    pub retry_after: Timestamp,
+    pub last_error: Option<String>,
+    /// latest revision of the replica as of the operation, once known
+    #[serde(default)]
+    pub replica_revision_id: Option<RevisionHash>,
+}
+
+/// Definition for a zome's outbox entry type. Outbox items are private to the agent queueing them.
+///
+pub fn outbox_entry_def<S: AsRef<str>>(entry_def_id: S) -> EntryDef {
+    EntryDef {
+        id: entry_def_id.as_ref().into(),
+        visibility: EntryVisibility::Private,
+        crdt_type: CrdtType,
+        required_validations: 1.into(),
+        required_validation_type: RequiredValidationType::default(),
+    }
+}
+
+/// The outbox entry type of the calling zome
+///
+#[derive(Debug, Clone)]
+pub struct Outbox {
+    entry_def_id: String,
+    entry_def_index: EntryDefIndex,
+}
+
+impl Outbox {
+    /// Locate the outbox entry type `entry_def_id` within `entry_defs`, which must be the definitions
+    /// returned by the zome's `entry_defs()` extern since headers reference entry types by index.
+    ///
+    pub fn new<S: AsRef<str>>(entry_def_id: S, entry_defs: &EntryDefs) -> RecordAPIResult<Self> {
+        let entry_def_index = entry_defs.entry_def_index_from_id(EntryDefId::App(entry_def_id.as_ref().to_string()))
+            .ok_or_else(|| DataIntegrityError::InvalidConfig(format!("zome does not define the entry type {}", entry_def_id.as_ref())))?;
+        Ok(Outbox { entry_def_id: entry_def_id.as_ref().to_string(), entry_def_index })
+    }
+}
+
+/// Reads all undelivered items from the calling zome's outbox, in the order they were written.
+///
+/// Only entries of the outbox entry type declared by the calling zome are read, so other private
+/// entries held by the zome are never mistaken for outbox items.
+///
+pub fn read_outbox<R, T, O>(outbox: &Outbox) -> RecordAPIResult<Vec<(HeaderHash, OutboxItem<R, T, O>)>>
+    where R: DeserializeOwned,
+        T: DeserializeOwned,
+        O: DeserializeOwned,
+{
+    let entry_type = EntryType::App(AppEntryType::new(
+        outbox.entry_def_index,
+        zome_info()?.zome_id,
+        EntryVisibility::Private,
+    ));
+    let queued = query(ChainQueryFilter::new().entry_type(entry_type).include_entries(true))?;
+
+    let removed: HashSet<HeaderHash> = query(ChainQueryFilter::new().header_type(HeaderType::Delete))?
+        .iter()
+        .filter_map(|el| match el.header() {
+            Header::Delete(d) => Some(d.deletes_address.to_owned()),
+            _ => None,
+        })
+        .collect();
+
+    queued.iter()
+        .filter(|el| !removed.contains(el.header_address()))
+        .map(|el| {
+            let item = match try_entry_from_element(Some(el))? {
+                Entry::App(content) => decode(content.to_owned().into_sb().bytes())?,
+                _ => return Err(DataIntegrityError::EntryWrongType),
+            };
+            Ok((el.header_address().to_owned(), item))
+        })
+        .collect()
+}
+
+/// Store a replication call in the outbox. Calls which have already failed once are held back for retry.
+///
+pub fn enqueue<R, T, O>(
+    outbox: &Outbox,
+    record: &R,
+    target: &T,
+    operation: O,
+    replica_revision_id: Option<&RevisionHash>,
+    failure: Option<String>,
+) -> RecordAPIResult<()>
+    where R: Clone + Serialize + Debug,
+        T: Clone + Serialize + Debug,
+        O: Serialize + Debug,
+{
+    let attempts = if failure.is_some() { 1 } else { 0 };
+    let queued_at = sys_time()?;
+    write_outbox_item(outbox, &OutboxItem {
+        record: record.to_owned(),
+        target: target.to_owned(),
+        operation,
+        queued_at: queued_at.to_owned(),
+        attempts,
+        retry_after: if attempts > 0 { next_retry_time(&queued_at, attempts) } else { queued_at },
+        last_error: failure,
+        replica_revision_id: replica_revision_id.cloned(),
+    })
+}
+
+/// Deliver a replication call, queueing it in the outbox if delivery fails or earlier calls for
+/// the same record are still awaiting delivery.
+///
+/// `replica_revision_id` is the replica revision acknowledged for the record before this change.
+/// Calls requiring a replica are queued until one has been acknowledged.
+///
+/// Returns the acknowledgement of the call if it was delivered immediately, or `None` where it was queued.
+///
+pub fn replicate<R, T, O>(
+    outbox: &Outbox,
+    record: &R,
+    target: &T,
+    operation: O,
+    replica_revision_id: Option<&RevisionHash>,
+) -> RecordAPIResult<Option<Acknowledgement>>
+    where R: Clone + PartialEq + Serialize + DeserializeOwned + Debug,
+        T: Clone + Serialize + DeserializeOwned + Debug,
+        O: ReplicationCall<T> + Serialize + DeserializeOwned + Debug,
+{
+    let waiting = read_outbox::<R, T, O>(outbox)?.iter().any(|(_, item)| item.record == *record);
+    if waiting || !is_deliverable::<T, O>(&operation, replica_revision_id) {
+        enqueue(outbox, record, target, operation, replica_revision_id, None)?;
+        return Ok(None);
+    }
+
+    match operation.deliver(target, replica_revision_id) {
+        Ok(written) => Ok(Some(Acknowledgement { replica_revision_id: written })),
+        Err(e) => {
+            enqueue(outbox, record, target, operation, replica_revision_id, Some(e.to_string()))?;
+            Ok(None)
+        },
+    }
+}
+
+/// Retry delivery of all replication calls in the outbox which are due, in the order they were queued.
+///
+/// Calls for a record are delivered strictly in order, so any failure defers the remaining calls for
+/// the same record until the next flush. Each call is addressed to the replica revision written by the
+/// call before it, which is stored with the first call left queued. Calls requiring a replica which
+/// has not yet been written are held.
+///
+/// Records with no calls left undelivered are passed to `acknowledge`, along with the replica revision
+/// written by the last of their calls to write one.
+///
+pub fn flush_outbox<R, T, O, A>(outbox: &Outbox, acknowledge: A) -> RecordAPIResult<OutboxFlushReport>
+    where R: Clone + PartialEq + Serialize + DeserializeOwned + Debug,
+        T: Clone + Serialize + DeserializeOwned + Debug,
+        O: ReplicationCall<T> + Clone + Serialize + DeserializeOwned + Debug,
+        A: Fn(&R, &Acknowledgement) -> RecordAPIResult<()>,
+{
+    let mut queued: Vec<(HeaderHash, OutboxItem<R, T, O>)> = read_outbox(outbox)?;
+    queued.sort_by(|a, b| a.1.queued_at.cmp(&b.1.queued_at));
+
+    let now = sys_time()?;
+    let mut report = OutboxFlushReport::default();
+    let mut blocked: Vec<R> = vec![];
+    let mut delivered: Vec<(R, Acknowledgement)> = vec![];
+
+    for (header, item) in queued.into_iter() {
+        // replica revision written by calls delivered earlier in this flush, if any
+        let written = delivered.iter().find(|(r, _)| *r == item.record).and_then(|(_, ack)| ack.replica_revision_id.to_owned());
+        let replica_revision_id = written.to_owned().or_else(|| item.replica_revision_id.to_owned());
+
+        if blocked.contains(&item.record) || item.retry_after > now || !is_deliverable::<T, O>(&item.operation, replica_revision_id.as_ref()) {
+            report.deferred += 1;
+            // keep the replica revision for the next flush, once the calls which wrote it have been removed
+            if !blocked.contains(&item.record) && written.is_some() && written != item.replica_revision_id {
+                remove_outbox_item(&header)?;
+                write_outbox_item(outbox, &OutboxItem { replica_revision_id, ..item.to_owned() })?;
+            }
+            blocked.push(item.record);
+            continue;
+        }
+
+        match item.operation.deliver(&item.target, replica_revision_id.as_ref()) {
+            Ok(written) => {
+                remove_outbox_item(&header)?;
+                report.delivered += 1;
+                match delivered.iter_mut().find(|(r, _)| *r == item.record) {
+                    Some((_, ack)) => if written.is_some() { ack.replica_revision_id = written },
+                    None => delivered.push((item.record, Acknowledgement { replica_revision_id: written })),
+                }
+            },
+            Err(e) => {
+                let attempts = item.attempts + 1;
+                remove_outbox_item(&header)?;
+                write_outbox_item(outbox, &OutboxItem {
+                    attempts,
+                    retry_after: next_retry_time(&now, attempts),
+                    last_error: Some(e.to_string()),
+                    replica_revision_id,
+                    ..item.to_owned()
+                })?;
+                report.failed += 1;
+                report.errors.push(e.to_string());
+                blocked.push(item.record);
+            },
+        }
+    }
+
+    for (record, ack) in delivered.iter().filter(|(r, _)| !blocked.contains(r)) {
+        acknowledge(record, ack)?;
+    }
+
+    Ok(report)
+}
+
+/// Record the acknowledgement of all outstanding replication calls on a record, if it still exists.
+/// Any replica revision previously recorded is kept where the acknowledged calls wrote none.
+///
+pub fn acknowledge<I, R, B, E, S>(entry_def_id: &S, record: &B, ack: &Acknowledgement) -> RecordAPIResult<()>
+    where S: AsRef<str>,
+        B: DnaAddressable<EntryHash>,
+        I: Debug + Identifiable<R> + Replicated,
+        WasmError: From<E>,
+        Entry: TryFrom<R, Error = E>,
+        R: Clone + Debug + Identified<I, B>,
+        SerializedBytes: TryInto<R, Error = SerializedBytesError>,
+{
+    let (revision_id, _, entry): (RevisionHash, B, I) = match read_record_entry::<I, R, B, S>(entry_def_id, record.as_ref()) {
+        Ok(found) => found,
+        Err(DataIntegrityError::EntryNotFound) => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    let unchanged = ack.replica_revision_id.is_none() || ack.replica_revision_id.as_ref() == entry.replica_revision_id();
+    if entry.replication() != Some(&ReplicationStatus::Acknowledged) || !unchanged {
+        let _updated: (RevisionHash, B, I, I) = update_record(entry_def_id, &revision_id, ack.to_owned())?;
+    }
+    Ok(())
+}
+
+/// Determine whether a call can be delivered, given the latest known revision of its replica
+///
+pub fn is_deliverable<T, O: ReplicationCall<T>>(operation: &O, replica_revision_id: Option<&RevisionHash>) -> bool {
+    replica_revision_id.is_some() || !operation.requires_replica()
+}
+
+/// Determine when an item which has failed delivery `attempts` times as of `now` should next be retried
+///
+pub fn next_retry_time(now: &Timestamp, attempts: u32) -> Timestamp {
+    let exponent = attempts.saturating_sub(1).min(16);
+    let delay = (OUTBOX_INITIAL_RETRY_SECONDS << exponent).min(OUTBOX_MAX_RETRY_SECONDS);
+    Timestamp(now.0 + delay, now.1)
+}
+
+/// Remove a delivered (or superseded) item from the outbox
+///
+pub fn remove_outbox_item(header: &HeaderHash) -> RecordAPIResult<()> {
+    delete_entry(header.to_owned())?;
+    Ok(())
+}
+
+fn write_outbox_item<R, T, O>(outbox: &Outbox, item: &OutboxItem<R, T, O>) -> RecordAPIResult<()>
+    where R: Serialize + Debug,
+        T: Serialize + Debug,
+        O: Serialize + Debug,
+{
+    let entry = Entry::app(SerializedBytes::from(UnsafeBytes::from(encode(item)?)))?;
+    check_entry_size(&entry)?;
+    create(CreateInput::new(EntryDefId::App(outbox.entry_def_id.to_owned()), entry, ChainTopOrdering::default()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outbox_located_in_entry_defs() {
+        let defs = EntryDefs::from(vec![Path::entry_def(), outbox_entry_def("test_outbox")]);
+        assert_eq!(Outbox::new("test_outbox", &defs).unwrap().entry_def_index, EntryDefIndex(1));
+        assert!(matches!(Outbox::new("other_outbox", &defs), Err(DataIntegrityError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_retry_delay_doubles_with_each_attempt() {
+        let now = Timestamp(1000, 5);
+        assert_eq!(next_retry_time(&now, 1), Timestamp(1000 + OUTBOX_INITIAL_RETRY_SECONDS, 5));
+        assert_eq!(next_retry_time(&now, 2), Timestamp(1000 + OUTBOX_INITIAL_RETRY_SECONDS * 2, 5));
+        assert_eq!(next_retry_time(&now, 4), Timestamp(1000 + OUTBOX_INITIAL_RETRY_SECONDS * 8, 5));
+    }
+
+    #[test]
+    fn test_retry_delay_is_capped() {
+        let now = Timestamp(1000, 0);
+        assert_eq!(next_retry_time(&now, 12), Timestamp(1000 + OUTBOX_MAX_RETRY_SECONDS, 0));
+        assert_eq!(next_retry_time(&now, u32::MAX), Timestamp(1000 + OUTBOX_MAX_RETRY_SECONDS, 0), "large attempt counts do not overflow");
+    }
+
+    struct TestCall(bool);
+
+    impl ReplicationCall<()> for TestCall {
+        fn requires_replica(&self) -> bool { self.0 }
+        fn deliver(&self, _target: &(), _replica_revision_id: Option<&RevisionHash>) -> OtherCellResult<Option<RevisionHash>> { Ok(None) }
+    }
+
+    #[test]
+    fn test_calls_on_replicas_are_held_until_one_is_written() {
+        let revision = RevisionHash(DnaHash::from_raw_36(vec![0xdb; 36]), HeaderHash::from_raw_36(vec![0xdb; 36]));
+        assert!(is_deliverable::<(), _>(&TestCall(false), None), "creation needs no existing replica");
+        assert!(!is_deliverable::<(), _>(&TestCall(true), None), "updates and deletions wait for the replica");
+        assert!(is_deliverable::<(), _>(&TestCall(true), Some(&revision)));
+    }
+
+    #[test]
+    fn test_unattempted_items_retry_as_first_attempt() {
+        let now = Timestamp(1000, 0);
+        assert_eq!(next_retry_time(&now, 0), next_retry_time(&now, 1));
+    }
+}
@@ -16,6 +16,7 @@ use crate::{
     RecordAPIResult, DataIntegrityError,
     record_interface::{Identifiable, Identified, Updateable},
     entries::{
+        try_decode_entry,
         get_entry_by_header,
         create_entry,
         update_entry,
@@ -62,6 +63,35 @@ pub fn get_latest_header_hash(entry_hash: EntryHash) -> RecordAPIResult<Revision
     })?))
 }
 
+/// Retrieve the latest `RevisionHash` for a given EntryHash which was written at or before
+/// `as_of_micros` (microseconds since the UNIX epoch), or `None` if no revision existed by then.
+///
+pub fn get_header_hash_as_of(entry_hash: EntryHash, as_of_micros: i64) -> RecordAPIResult<Option<RevisionHash>> {
+    let details = match get_details(entry_hash, GetOptions { strategy: GetStrategy::Latest })? {
+        Some(Details::Entry(details)) => details,
+        _ => return Err(DataIntegrityError::EntryNotFound),
+    };
+
+    let latest = details.headers.iter().chain(details.updates.iter())
+        .filter(|header| header.header().timestamp().as_micros() <= as_of_micros)
+        .max_by_key(|header| header.header().timestamp().as_micros());
+
+    Ok(match latest {
+        Some(header) => Some(RevisionHash(zome_info()?.dna_hash, get_header_hash(header.to_owned()))),
+        None => None,
+    })
+}
+
+/// Retrieve the time at which the given revision was written, in microseconds since the UNIX epoch
+///
+pub fn get_revision_time(revision: &RevisionHash) -> RecordAPIResult<i64> {
+    let header_hash: &HeaderHash = revision.as_ref();
+    match get(header_hash.clone(), GetOptions { strategy: GetStrategy::Latest })? {
+        Some(element) => Ok(element.header().timestamp().as_micros()),
+        None => Err(DataIntegrityError::EntryNotFound),
+    }
+}
+
 /// Retrive the specific version of an entry specified by the given `RevisionHash`
 ///
 pub fn read_record_entry_by_header<T, R, B>(
@@ -125,6 +155,39 @@ pub fn read_record_entry<T, R, B, S>(
     read_record_entry_by_identity::<T, R, B>(&identity_address)
 }
 
+/// Read the first revision of a record, exactly as it was originally created.
+///
+/// The identity of a record is the `EntryHash` of its initial entry, so this
+/// is a direct lookup rather than a traversal of the update history. Records
+/// which have since been deleted can still be read.
+///
+pub fn read_record_initial_entry<T, R, B, S>(
+    entry_type_root_path: &S,
+    address: &EntryHash,
+) -> RecordAPIResult<(RevisionHash, B, T)>
+    where S: AsRef<str>,
+        T: std::fmt::Debug,
+        B: DnaAddressable<EntryHash>,
+        SerializedBytes: TryInto<R, Error = SerializedBytesError>,
+        Entry: TryFrom<R>,
+        R: std::fmt::Debug + Identified<T, B>,
+{
+    let dna_hash = zome_info()?.dna_hash;
+    // ensure the record is of the expected type before reading its original entry
+    let identity_address = calculate_identity_address(entry_type_root_path, &B::new(dna_hash.clone(), address.clone()))?;
+    let initial_entry_hash = read_entry_identity(&identity_address)?;
+
+    // details are returned for deleted entries as well, so that the original content of removed records can be recovered
+    let details = match get_details(initial_entry_hash, GetOptions::default())? {
+        Some(Details::Entry(details)) => details,
+        _ => return Err(DataIntegrityError::EntryNotFound),
+    };
+    let initial_header = RevisionHash(dna_hash, get_header_hash(details.headers.first().ok_or(DataIntegrityError::EntryNotFound)?.to_owned()));
+    let storage_entry: R = try_decode_entry(details.entry)?;
+
+    Ok((initial_header, storage_entry.identity()?, storage_entry.entry()))
+}
+
 //-------------------------------[ CREATE ]-------------------------------------
 
 /// Creates a new record in the DHT, assigns it an identity index (@see identity_helpers.rs)
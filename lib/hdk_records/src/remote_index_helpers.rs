@@ -25,7 +25,8 @@ use crate::{
         request_sync_foreign_index_destination,
         merge_indexing_results,
     },
-    rpc_helpers::call_zome_method,
+    rpc_helpers::call_idempotent_zome_method,
+    config_helpers::ZomeConfig,
 };
 
 /// Common request format (zome trait) for linking remote entries in cooperating DNAs
@@ -110,8 +111,7 @@ pub fn create_remote_index<C, F, A, B, S>(
     where S: AsRef<str>,
         A: DnaAddressable<EntryHash>,
         B: DnaAddressable<EntryHash>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: Clone + FnOnce(C) -> Option<String>,
 {
     let sources = vec![source.clone()];
@@ -194,8 +194,7 @@ pub fn update_remote_index<C, F, A, B, S>(
     where S: AsRef<str>,
         A: DnaAddressable<EntryHash>,
         B: DnaAddressable<EntryHash>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: Clone + FnOnce(C) -> Option<String>,
 {
     // handle local 'origin' index first
@@ -262,7 +261,8 @@ fn request_sync_remote_index_destination<A, B, I>(
 
     // Call into remote DNA to enable target entries to setup data structures
     // for querying the associated remote entry records back out.
-    Ok(call_zome_method(
+    // Link creation and removal in `sync_remote_index` are safe to repeat, so failed calls are retried.
+    Ok(call_idempotent_zome_method(
         &context_dna, remote_permission_id,
         RemoteEntryLinkRequest::new(
             source,
@@ -15,10 +15,20 @@ use hc_zome_dna_auth_resolver_lib::{DNAConnectionAuth, ensure_authed};
 use crate::{
     OtherCellResult,
     CrossCellError,
+    call_policy_helpers::with_call_policy,
+    config_helpers::{ZomeConfig, RemoteAuthConfig, zome_config, read_dna_properties},
 };
 
 /**
  * Wrapper for `hdk::call` which handles decoding of the response and coercion of error types.
+ *
+ * Calls to other DNAs are authenticated via the auth resolver. Where the target is in the calling
+ * cell's own DNA, the zome call is made directly to the zome & method which the permission ID is
+ * registered against in the DNA's own `remote_auth` properties.
+ *
+ * Calls to other DNAs are made under the DNA's `call_policy`, but are never retried since the call
+ * may have taken effect before failing. Use `call_idempotent_zome_method` for calls which are safe to
+ * repeat (@see call_policy_helpers.rs).
  */
 pub fn call_zome_method<H, R, I, S>(
     to_registered_dna: &H,
@@ -30,18 +40,81 @@ pub fn call_zome_method<H, R, I, S>(
         I: serde::Serialize + std::fmt::Debug,
         R: serde::de::DeserializeOwned + std::fmt::Debug,
 {
-    let to_dna: &DnaHash = to_registered_dna.as_ref();
-    let auth_data = ensure_authed(to_dna, remote_permission_id)?;
+    call_registered_method(to_registered_dna.as_ref(), remote_permission_id, payload, false)
+}
+
+/**
+ * As for `call_zome_method`, but calls to other DNAs which fail with a retryable error are retried
+ * according to the DNA's `call_policy`. Only use for methods which are safe to repeat, such as reads.
+ */
+pub fn call_idempotent_zome_method<H, R, I, S>(
+    to_registered_dna: &H,
+    remote_permission_id: &S,
+    payload: I,
+) -> OtherCellResult<R>
+    where S: AsRef<str>,
+        H: AsRef<DnaHash>,
+        I: serde::Serialize + std::fmt::Debug,
+        R: serde::de::DeserializeOwned + std::fmt::Debug,
+{
+    call_registered_method(to_registered_dna.as_ref(), remote_permission_id, payload, true)
+}
+
+fn call_registered_method<R, I, S>(
+    to_dna: &DnaHash,
+    remote_permission_id: &S,
+    payload: I,
+    idempotent: bool,
+) -> OtherCellResult<R>
+    where S: AsRef<str>,
+        I: serde::Serialize + std::fmt::Debug,
+        R: serde::de::DeserializeOwned + std::fmt::Debug,
+{
+    if *to_dna == zome_info()?.dna_hash {
+        return call_same_dna_method(remote_permission_id, payload);
+    }
+
+    with_call_policy(to_dna, idempotent, || {
+        let auth_data = ensure_authed(to_dna, remote_permission_id)?;
+
+        let DNAConnectionAuth { claim, method } = auth_data;
+
+        let to_cell = Some(CellId::new(to_dna.clone(), claim.grantor().to_owned()));
+        let resp = call(to_cell, method.0, method.1, Some(claim.secret().to_owned()), &payload)
+            .map_err(CrossCellError::from)?;
+
+        handle_resp(resp)
+    })
+}
+
+/// Call the zome method registered against a remote permission ID in the calling cell's own DNA
+fn call_same_dna_method<R, I, S>(
+    permission_id: &S,
+    payload: I,
+) -> OtherCellResult<R>
+    where S: AsRef<str>,
+        I: serde::Serialize + std::fmt::Debug,
+        R: serde::de::DeserializeOwned + std::fmt::Debug,
+{
+    let zome_meta = zome_info()?;
+    let not_configured = || CrossCellError::NotConfigured(zome_meta.zome_name.to_owned(), FunctionName(permission_id.as_ref().to_string()));
 
-    let DNAConnectionAuth { claim, method } = auth_data;
+    let (zome, method) = registered_method(read_dna_properties()?.remote_auth, permission_id.as_ref())
+        .ok_or_else(not_configured)?;
 
-    let to_cell = Some(CellId::new(to_dna.clone(), claim.grantor().to_owned()));
-    let resp = call(to_cell, method.0, method.1, Some(claim.secret().to_owned()), payload)
+    let resp = call(None, ZomeName(zome), FunctionName(method), None, payload)
         .map_err(CrossCellError::from)?;
 
     handle_resp(resp)
 }
 
+/// The (zome, function) pair registered against a remote permission ID, if any
+fn registered_method(remote_auth: Option<RemoteAuthConfig>, permission_id: &str) -> Option<(String, String)> {
+    remote_auth?.permissions.into_iter()
+        .find(|p| p.extern_id == permission_id)
+        .map(|p| p.allowed_method)
+}
+
 /**
  * Helper for making local-zome calls, which implicitly require no authentication and operate under a different security model.
  *
@@ -54,8 +127,7 @@ pub fn call_local_zome_method<C, F, R, I, S>(
     payload: I,
 ) -> OtherCellResult<R>
     where S: AsRef<str>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: FnOnce(C) -> Option<String>,
         I: serde::Serialize + std::fmt::Debug,
         R: serde::de::DeserializeOwned + std::fmt::Debug,
@@ -64,8 +136,7 @@ pub fn call_local_zome_method<C, F, R, I, S>(
     let this_zome = zome_meta.zome_name;
     let remote_local_zome_method = FunctionName(method_name.as_ref().to_string());
 
-    let zome_props: C = zome_meta.properties
-        .try_into()
+    let zome_props: C = zome_config()
         .map_err(|_| { CrossCellError::NotConfigured(this_zome, remote_local_zome_method.to_owned()) })?;
 
     match zome_name_from_config(zome_props) {
@@ -80,7 +151,7 @@ pub fn call_local_zome_method<C, F, R, I, S>(
 
 }
 
-fn handle_resp<R>(
+pub(crate) fn handle_resp<R>(
     resp: ZomeCallResponse,
 ) -> OtherCellResult<R>
     // :TODO: data.decode() requires Debug to be implemented. Is this expected behaviour?
@@ -97,3 +168,27 @@ fn handle_resp<R>(
             Err(CrossCellError::NetworkError(msg)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_helpers::RemoteAuthPermission;
+
+    fn remote_auth() -> RemoteAuthConfig {
+        RemoteAuthConfig { permissions: vec![
+            RemoteAuthPermission { extern_id: "index_process_inputs".to_string(), allowed_method: ("process_index".to_string(), "index_process_inputs".to_string()) },
+            RemoteAuthPermission { extern_id: "read_process".to_string(), allowed_method: ("process".to_string(), "get_process".to_string()) },
+        ] }
+    }
+
+    #[test]
+    fn test_registered_method() {
+        assert_eq!(registered_method(Some(remote_auth()), "read_process"), Some(("process".to_string(), "get_process".to_string())));
+    }
+
+    #[test]
+    fn test_unregistered_method() {
+        assert_eq!(registered_method(Some(remote_auth()), "get_process"), None, "permissions are looked up by extern ID, not method name");
+        assert_eq!(registered_method(None, "read_process"), None);
+    }
+}
@@ -0,0 +1,111 @@
+/**
+ * Helpers for resolving records referenced by `RecordUri` to the cell storing them.
+ *
+ * Each zome declares a route for each entry type it references, naming the permission ID under
+ * which DNAs storing those records expose the zome method to call. Routing to the cell holding the
+ * record is then handled by `call_idempotent_zome_method`.
+ *
+ * Indexes of referenced records are similarly routed by entry type, to the zome in the calling DNA
+ * which manages them.
+ *
+ * @see     ../README.md
+ * @package HDK Graph Helpers
+ * @since   2021-10-26
+ */
+use hdk::prelude::*;
+use hdk_type_serialization_macros::{RecordUri, DnaAddressable, AnyRecordAddress};
+
+use crate::{
+    RecordAPIResult, OtherCellResult, DataIntegrityError,
+    rpc_helpers::call_idempotent_zome_method,
+    foreign_index_helpers::update_foreign_index,
+    config_helpers::ZomeConfig,
+};
+
+/// How to reach the zome storing records of some entry type
+#[derive(Clone, Debug)]
+pub struct UriRoute {
+    pub entry_type: &'static str,
+    /// permission ID registered in the `remote_auth` properties of DNAs storing these records
+    pub permission_id: &'static str,
+}
+
+/// How to index records of some entry type stored in the calling DNA
+pub struct UriIndexRoute<C> {
+    pub entry_type: &'static str,
+    /// accessor for the name of the zome managing indexes of these records
+    pub index_zome: fn(C) -> Option<String>,
+    /// method of the index zome which links these records to the referencing record
+    pub index_fn_name: &'static str,
+}
+
+/// Determine whether an identifier references a record stored in the calling cell's DNA
+pub fn is_local_dna<A>(address: &A) -> RecordAPIResult<bool>
+    where A: AsRef<DnaHash>,
+{
+    Ok(*address.as_ref() == zome_info()?.dna_hash)
+}
+
+/// Find the route for the entry type referenced by a `RecordUri`
+pub fn resolve_route<'r>(uri: &RecordUri, routes: &'r [UriRoute]) -> RecordAPIResult<&'r UriRoute> {
+    routes.iter()
+        .find(|r| r.entry_type == uri.entry_type)
+        .ok_or_else(|| DataIntegrityError::UnresolvableUri(uri.to_string()))
+}
+
+/// Call the zome storing the record referenced by a `RecordUri`, in whichever cell it resides.
+/// Failed calls may be retried, so the method called must be safe to repeat (eg. a read).
+pub fn call_uri_method<R, I>(
+    uri: &RecordUri,
+    routes: &[UriRoute],
+    payload: I,
+) -> OtherCellResult<R>
+    where I: serde::Serialize + std::fmt::Debug,
+        R: serde::de::DeserializeOwned + std::fmt::Debug,
+{
+    let route = resolve_route(uri, routes)?;
+    call_idempotent_zome_method(uri, &route.permission_id, payload)
+}
+
+/// Link a record to those referenced by `add_uris` and unlink it from those referenced by `remove_uris`,
+/// calling the index zome routed to for each referenced record's entry type.
+///
+/// References to records in other DNAs, or of entry types with no route, are skipped.
+/// Such records are indexed in their own DNA by replication.
+///
+pub fn update_uri_index<C, F, A>(
+    origin_zome_name_from_config: F,
+    origin_fn_name: &str,
+    source: &A,
+    routes: &[UriIndexRoute<C>],
+    add_uris: &[&RecordUri],
+    remove_uris: &[&RecordUri],
+) -> RecordAPIResult<()>
+    where C: ZomeConfig,
+        F: Clone + FnOnce(C) -> Option<String>,
+        A: DnaAddressable<EntryHash>,
+{
+    let dna_hash = zome_info()?.dna_hash;
+
+    for route in routes {
+        let routed = |uris: &[&RecordUri]| -> Vec<AnyRecordAddress> {
+            uris.iter()
+                .filter(|uri| uri.entry_type == route.entry_type && uri.dna == dna_hash)
+                .map(|uri| uri.to_address())
+                .collect()
+        };
+        let (add, remove) = (routed(add_uris), routed(remove_uris));
+        if add.is_empty() && remove.is_empty() {
+            continue;
+        }
+
+        let _results = update_foreign_index(
+            origin_zome_name_from_config.to_owned(), &origin_fn_name,
+            source,
+            route.index_zome, &route.index_fn_name,
+            add.as_slice(), remove.as_slice(),
+        )?;
+    }
+
+    Ok(())
+}
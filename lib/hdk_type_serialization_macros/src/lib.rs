@@ -49,6 +49,16 @@ pub trait DnaAddressable<B>
 
 #[macro_export]
 macro_rules! addressable_identifier {
+    // identifier for records of a known entry type, which can also be expressed as a `RecordUri`
+    ($r:ident => $base:ty, $entry_type:expr) => {
+        $crate::addressable_identifier!($r => $base);
+
+        impl $crate::UriAddressable for $r {
+            fn entry_type() -> &'static str {
+                $entry_type
+            }
+        }
+    };
     ($r:ident => $base:ty) => {
         // externally facing type, with DnaHash of cell for context
         #[derive(Serialize, Deserialize, SerializedBytes, Debug, Clone, PartialEq, Eq, Hash)]
@@ -84,8 +94,145 @@ macro_rules! addressable_identifier {
     }
 }
 
+/// Canonical, human-readable form of a record identifier, locating the record by the `DnaHash` of the
+/// cell storing it, its entry type and the `EntryHash` of its initial entry:
+///
+/// `hrea://<DnaHash>/<entry type>/<EntryHash>`
+///
+/// Hashes are encoded as for Holochain's conductor APIs, ie. a `u`-prefixed URL-safe base64 string.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RecordUri {
+    pub dna: DnaHash,
+    pub entry_type: String,
+    pub entry: EntryHash,
+}
+
+pub const RECORD_URI_SCHEME: &str = "hrea://";
+
+// holo_hash type prefixes for the hash types embedded in URIs
+const DNA_HASH_PREFIX: [u8; 3] = [0x84, 0x2d, 0x24];
+const ENTRY_HASH_PREFIX: [u8; 3] = [0x84, 0x21, 0x24];
+
+impl RecordUri {
+    pub fn new(dna: DnaHash, entry_type: &str, entry: EntryHash) -> Self {
+        Self { dna, entry_type: entry_type.to_string(), entry }
+    }
+
+    /// Convert to an identifier for the referenced record, without checking its entry type.
+    /// Use `UriAddressable::from_uri` where the expected entry type is known.
+    ///
+    pub fn to_address<A>(&self) -> A
+        where A: DnaAddressable<EntryHash>,
+    {
+        A::new(self.dna.to_owned(), self.entry.to_owned())
+    }
+}
+
+impl std::fmt::Display for RecordUri {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}/{}/{}",
+            RECORD_URI_SCHEME,
+            encode_hash(&DNA_HASH_PREFIX, self.dna.get_raw_36()),
+            self.entry_type,
+            encode_hash(&ENTRY_HASH_PREFIX, self.entry.get_raw_36()),
+        )
+    }
+}
+
+impl std::str::FromStr for RecordUri {
+    type Err = SerializedBytesError;
+
+    fn from_str(uri: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = uri.strip_prefix(RECORD_URI_SCHEME)
+            .ok_or_else(|| SerializedBytesError::Deserialize(format!("Not a record URI: {}", uri)))?
+            .split('/')
+            .collect();
+        if parts.len() != 3 || parts[1].is_empty() {
+            return Err(SerializedBytesError::Deserialize(format!("Malformed record URI: {}", uri)));
+        }
+
+        Ok(Self {
+            dna: DnaHash::from_raw_36(decode_hash(&DNA_HASH_PREFIX, parts[0])?),
+            entry_type: parts[1].to_string(),
+            entry: EntryHash::from_raw_36(decode_hash(&ENTRY_HASH_PREFIX, parts[2])?),
+        })
+    }
+}
+
+impl serde::Serialize for RecordUri {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for RecordUri {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: serde::Deserializer<'de>,
+    {
+        let uri = String::deserialize(deserializer)?;
+        uri.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl AsRef<DnaHash> for RecordUri {
+    fn as_ref(&self) -> &DnaHash {
+        &self.dna
+    }
+}
+
+impl AsRef<EntryHash> for RecordUri {
+    fn as_ref(&self) -> &EntryHash {
+        &self.entry
+    }
+}
+
+fn encode_hash(prefix: &[u8], raw_36: &[u8]) -> String {
+    format!("u{}", base64::encode_config([prefix, raw_36].concat(), base64::URL_SAFE_NO_PAD))
+}
+
+fn decode_hash(prefix: &[u8], encoded: &str) -> Result<Vec<u8>, SerializedBytesError> {
+    let bytes = encoded.strip_prefix('u')
+        .and_then(|b64| base64::decode_config(b64, base64::URL_SAFE_NO_PAD).ok())
+        .ok_or_else(|| SerializedBytesError::Deserialize(format!("Invalid hash encoding: {}", encoded)))?;
+    if bytes.len() != HOLO_HASH_UNTYPED_LEN + prefix.len() || &bytes[0..prefix.len()] != prefix {
+        return Err(SerializedBytesError::Deserialize(format!("Wrong hash type or length: {}", encoded)));
+    }
+    Ok(bytes[prefix.len()..].to_vec())
+}
+
+/// Identifiers for records of a single entry type, which can be converted to and from a `RecordUri`.
+///
+/// Implemented by passing the entry type to `addressable_identifier!`.
+///
+pub trait UriAddressable
+    where Self: DnaAddressable<EntryHash>,
+{
+    fn entry_type() -> &'static str;
+
+    fn to_uri(&self) -> RecordUri {
+        RecordUri::new(
+            AsRef::<DnaHash>::as_ref(self).to_owned(),
+            Self::entry_type(),
+            AsRef::<EntryHash>::as_ref(self).to_owned(),
+        )
+    }
+
+    fn from_uri(uri: &RecordUri) -> Result<Self, SerializedBytesError> {
+        if uri.entry_type != Self::entry_type() {
+            return Err(SerializedBytesError::Deserialize(format!("Expected {} record URI, got {}", Self::entry_type(), uri)));
+        }
+        Ok(uri.to_address())
+    }
+}
+
 addressable_identifier!(RevisionHash => HeaderHash);
 
+// untyped record identifier, for use where the type of a referenced record is not known
+addressable_identifier!(AnyRecordAddress => EntryHash);
+
 /// Supertrait for things which can be identified by some string label in a particular DNA
 ///
 pub trait DnaIdentifiable<B>
@@ -179,4 +326,45 @@ mod tests {
 
         assert_eq!(base, extracted, "Original data matches wrapped, externalised, extracted roundtrip data");
     }
+
+    fn test_uri() -> RecordUri {
+        RecordUri::new(
+            DnaHash::from_raw_36(vec![0xdb; HOLO_HASH_UNTYPED_LEN]),
+            "vf_commitment",
+            EntryHash::from_raw_36(vec![0xe1; HOLO_HASH_UNTYPED_LEN]),
+        )
+    }
+
+    #[test]
+    fn test_record_uri_roundtrip() {
+        let uri = test_uri();
+        let encoded = uri.to_string();
+
+        assert!(encoded.starts_with("hrea://uhC0k"), "DnaHash is encoded as for conductor APIs");
+        assert!(encoded.contains("/vf_commitment/uhCEk"), "entry type precedes EntryHash");
+        assert_eq!(encoded.parse::<RecordUri>().unwrap(), uri, "URI parses to the original identifier");
+    }
+
+    #[test]
+    fn test_record_uri_malformed() {
+        let encoded = test_uri().to_string();
+        let parts: Vec<&str> = encoded.trim_start_matches(RECORD_URI_SCHEME).split('/').collect();
+        let (dna, entry) = (parts[0], parts[2]);
+
+        let malformed = vec![
+            ("missing scheme", format!("{}/vf_commitment/{}", dna, entry)),
+            ("wrong scheme", format!("http://{}/vf_commitment/{}", dna, entry)),
+            ("missing part", format!("hrea://{}/{}", dna, entry)),
+            ("extra part", format!("hrea://{}/vf_commitment/{}/extra", dna, entry)),
+            ("empty entry type", format!("hrea://{}//{}", dna, entry)),
+            ("missing multibase prefix", format!("hrea://{}/vf_commitment/{}", &dna[1..], entry)),
+            ("invalid base64", format!("hrea://{}/vf_commitment/u!!{}", dna, &entry[3..])),
+            ("hashes swapped", format!("hrea://{}/vf_commitment/{}", entry, dna)),
+            ("truncated hash", format!("hrea://{}/vf_commitment/{}", dna, &entry[..entry.len() - 4])),
+        ];
+
+        for (case, uri) in malformed {
+            assert!(uri.parse::<RecordUri>().is_err(), "{} is rejected: {}", case, uri);
+        }
+    }
 }
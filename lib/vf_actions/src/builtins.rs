@@ -52,7 +52,9 @@ pub fn get_builtin_action(key: &str) -> Option<Action> {
         transfer => ActionEffect::DecrementIncrement, ProcessType::NotApplicable, notApplicable;
         move => ActionEffect::DecrementIncrement, ProcessType::NotApplicable, notApplicable;
         raise => ActionEffect::Increment, ProcessType::NotApplicable, notApplicable;
-        lower => ActionEffect::Decrement, ProcessType::NotApplicable, notApplicable
+        lower => ActionEffect::Decrement, ProcessType::NotApplicable, notApplicable;
+        combine => ActionEffect::NoEffect, ProcessType::Input, notApplicable;
+        separate => ActionEffect::NoEffect, ProcessType::Output, notApplicable
     )
 }
 
@@ -76,6 +78,8 @@ pub fn get_all_builtin_actions() -> Vec<Action> {
         get_builtin_action("move").unwrap(),
         get_builtin_action("raise").unwrap(),
         get_builtin_action("lower").unwrap(),
+        get_builtin_action("combine").unwrap(),
+        get_builtin_action("separate").unwrap(),
     ]
 }
 
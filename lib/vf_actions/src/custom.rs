@@ -0,0 +1,106 @@
+/**
+ * User-defined actions.
+ *
+ * Networks may extend the builtin ValueFlows actions with their own domain-specific
+ * actions (eg. "repair", "inspect", "harvest"). These may be declared in the `custom_actions`
+ * DNA property, or registered as records via the `action` zome bundled in the same DNA.
+ *
+ * :NOTE: entry validation only resolves builtin and declared actions, since these are fixed for
+ * the lifetime of the DNA. Registered action records depend on what a validator has seen of the
+ * DHT, so are only resolved by zome API handlers when records are written. DNAs which write flows
+ * using registered actions must bundle the `action` zome themselves.
+ */
+use hdk::prelude::*;
+use hdk_records::{
+    RecordAPIResult, DataIntegrityError,
+    generate_record_entry,
+    config::read_dna_properties,
+    records_anchored::read_anchored_record_entry,
+};
+use vf_attributes_hdk::{ ActionInternalAddress, ActionRecordId };
+
+use super::{
+    Action,
+    ActionEffect,
+    ProcessType,
+    get_builtin_action,
+};
+
+/// Entry type (and anchor root) of custom action records managed by the `action` zome
+/// :DUPE: hc_zome_rea_action_storage_consts::ACTION_ENTRY_TYPE
+pub const CUSTOM_ACTION_ENTRY_TYPE: &str = "vf_action";
+
+/// Storage format of custom action records managed by the `action` zome
+///
+#[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
+pub struct CustomActionEntry {
+    pub id: String,
+    pub label: String,
+    pub resource_effect: ActionEffect,
+    pub input_output: ProcessType,
+    pub pairs_with: String,
+}
+
+generate_record_entry!(CustomActionEntry, ActionInternalAddress, CustomActionStorage);
+
+/// Convert stored records into the same format used for builtin actions
+impl From<CustomActionEntry> for Action {
+    fn from(e: CustomActionEntry) -> Action {
+        Action {
+            id: e.id,
+            label: e.label,
+            resource_effect: e.resource_effect,
+            input_output: e.input_output,
+            pairs_with: e.pairs_with,
+        }
+    }
+}
+
+/// Retrieve all custom actions declared in the current DNA's properties.
+/// DNAs without any `custom_actions` configured yield an empty list.
+///
+pub fn get_all_custom_actions() -> RecordAPIResult<Vec<Action>> {
+    Ok(read_dna_properties()?.custom_actions.unwrap_or_default())
+}
+
+/// Retrieve a custom action declared in the current DNA's properties or registered
+/// with the DNA's `action` zome, if any. Declared properties take precedence.
+///
+pub fn get_custom_action(key: &str) -> RecordAPIResult<Option<Action>> {
+    match get_all_custom_actions()?.into_iter().find(|a| a.id == key) {
+        Some(action) => Ok(Some(action)),
+        None => read_custom_action_record(key),
+    }
+}
+
+/// Read a custom action registered with the `action` zome of the current DNA.
+/// Actions which have not been registered yield `None`.
+///
+fn read_custom_action_record(key: &str) -> RecordAPIResult<Option<Action>> {
+    match read_anchored_record_entry::<CustomActionEntry, CustomActionStorage, ActionInternalAddress, ActionRecordId, _,_>(&CUSTOM_ACTION_ENTRY_TYPE, key) {
+        Ok((_revision, _id, entry)) => Ok(Some(entry.into())),
+        Err(DataIntegrityError::IndexNotFound(_)) | Err(DataIntegrityError::EntryNotFound) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Resolve an action by its ID from the builtin actions and those declared in the DNA properties.
+/// Unlike `get_action`, the result does not depend on DHT state, so this is safe to use in validation.
+///
+pub fn get_declared_action(key: &str) -> RecordAPIResult<Option<Action>> {
+    match get_builtin_action(key) {
+        Some(action) => Ok(Some(action)),
+        None => Ok(get_all_custom_actions()?.into_iter().find(|a| a.id == key)),
+    }
+}
+
+/// Resolve any action by its ID, preferring builtins over custom actions.
+///
+/// :WARNING: reads registered actions from the DHT, so must not be used in validation callbacks.
+///
+pub fn get_action(key: &str) -> RecordAPIResult<Option<Action>> {
+    match get_builtin_action(key) {
+        Some(action) => Ok(Some(action)),
+        None => get_custom_action(key),
+    }
+}
@@ -4,53 +4,14 @@
  * @package Holo-REA
  * @since
  */
-use serde::{Deserializer, Serializer, de::Error};
-
-use hdk::prelude::*;
+use hdk_records::{ RecordAPIResult, DataIntegrityError };
 use vf_attributes_hdk::{ ActionId, ProcessAddress, ResourceAddress };
+pub use vf_attributes_hdk::{ Action, ActionEffect, ProcessType };
 
 pub mod builtins;
 pub use builtins::{ get_builtin_action, get_all_builtin_actions };
-
-#[derive(SerializedBytes, Debug, Clone, Copy, PartialEq)]
-pub enum ActionEffect {
-    // for 'process' events
-    NoEffect,
-    Increment,
-    Decrement,
-    // for 'transfer' events
-    DecrementIncrement,
-}
-
-impl Serialize for ActionEffect {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-        where S: Serializer
-    {
-        serializer.serialize_str(match *self {
-            ActionEffect::NoEffect => "noEffect",
-            ActionEffect::Increment => "increment",
-            ActionEffect::Decrement => "decrement",
-            ActionEffect::DecrementIncrement => "decrementIncrement",
-        })
-    }
-}
-
-impl<'de> Deserialize<'de> for ActionEffect {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-        where D: Deserializer<'de>
-    {
-        let s = String::deserialize(deserializer)?;
-        Ok(match s.as_str() {
-            "noEffect" => ActionEffect::NoEffect,
-            "increment" => ActionEffect::Increment,
-            "decrement" => ActionEffect::Decrement,
-            "decrementIncrement" => ActionEffect::DecrementIncrement,
-            &_ => Err(
-                D::Error::custom(format!("Invalid value for ActionEffect: {}", s.as_str()))
-            )?,
-        })
-    }
-}
+pub mod custom;
+pub use custom::{ get_action, get_declared_action, get_custom_action, get_all_custom_actions };
 
 // actual underlying operations applied to particular resources are a subset of higher-level ActionEffect
 #[derive(Debug)]
@@ -60,69 +21,42 @@ pub enum ActionInventoryEffect {
     Decrement,
 }
 
-#[derive(SerializedBytes, Debug, Clone, Copy, PartialEq)]
-pub enum ProcessType {
-    NotApplicable,
-    Input,
-    Output,
-}
-
-impl Serialize for ProcessType {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-        where S: Serializer
-    {
-        serializer.serialize_str(match *self {
-            ProcessType::NotApplicable => "notApplicable",
-            ProcessType::Input => "input",
-            ProcessType::Output => "output",
-        })
+/**
+ * Validation for EconomicEvent, Commitment and Process to ensure correct use of actions & Processes
+ *
+ * Only builtin and declared actions are checked, so that the outcome does not depend on DHT state.
+ * Flows using actions registered with the `action` zome are checked by `ensure_flow_action` when written.
+ */
+pub fn validate_flow_action(action_id: ActionId, input_process: Option<ProcessAddress>, output_process: Option<ProcessAddress>) -> Result<(), String> {
+    match get_declared_action(action_id.as_ref()).map_err(|e| e.to_string())? {
+        Some(action) => validate_flow_processes(&action, input_process, output_process),
+        None => Ok(()),
     }
 }
 
-impl<'de> Deserialize<'de> for ProcessType {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-        where D: Deserializer<'de>
-    {
-        let s = String::deserialize(deserializer)?;
-        Ok(match s.as_str() {
-            "notApplicable" => ProcessType::NotApplicable,
-            "input" => ProcessType::Input,
-            "output" => ProcessType::Output,
-            &_ => Err(
-                D::Error::custom(format!("Invalid value for ProcessType: {}", s.as_str()))
-            )?,
-        })
+/**
+ * Check the action of a flow being written from a zome API handler, including any actions registered
+ * with the `action` zome. Unknown actions are rejected.
+ */
+pub fn ensure_flow_action(action_id: &ActionId, input_process: Option<ProcessAddress>, output_process: Option<ProcessAddress>) -> RecordAPIResult<()> {
+    match get_action(action_id.as_ref())? {
+        Some(action) => validate_flow_processes(&action, input_process, output_process).map_err(DataIntegrityError::ValidationFailed),
+        None => Err(DataIntegrityError::ValidationFailed(format!("Unknown action '{:}'", action_id.as_ref()))),
     }
 }
 
-#[derive(Serialize, Deserialize, SerializedBytes, Debug, Clone, PartialEq)]
-#[serde(rename_all = "camelCase")]
-pub struct Action {
-    pub id: String,
-    pub label: String,
-    pub resource_effect: ActionEffect,
-    pub input_output: ProcessType,
-    pub pairs_with: String, // any of the action labels, or "notApplicable"
-}
-
-/**
- * Validation for EconomicEvent, Commitment and Process to ensure correct use of actions & Processes
- */
-pub fn validate_flow_action(action_id: ActionId, input_process: Option<ProcessAddress>, output_process: Option<ProcessAddress>) -> Result<(), String> {
-    if let Some(action) = get_builtin_action(action_id.as_ref()) {
-        match action.input_output {
-            ProcessType::NotApplicable => if input_process.is_some() || output_process.is_some() {
-                Err(format!("EconomicEvent of '{:}' action cannot link to processes", action.id).into())
-            } else { Ok(()) },
-            ProcessType::Input => if input_process.is_none() {
-                Err(format!("EconomicEvent input process required for '{:}' action", action.id).into())
-            } else { Ok(()) },
-            ProcessType::Output => if output_process.is_none() {
-                Err(format!("EconomicEvent output process required for '{:}' action", action.id).into())
-            } else { Ok(()) },
-        }
-    } else {
-        Err("Unknown action".to_string())
+/// Check that a flow links the processes required by its action
+fn validate_flow_processes(action: &Action, input_process: Option<ProcessAddress>, output_process: Option<ProcessAddress>) -> Result<(), String> {
+    match action.input_output {
+        ProcessType::NotApplicable => if input_process.is_some() || output_process.is_some() {
+            Err(format!("EconomicEvent of '{:}' action cannot link to processes", action.id))
+        } else { Ok(()) },
+        ProcessType::Input => if input_process.is_none() {
+            Err(format!("EconomicEvent input process required for '{:}' action", action.id))
+        } else { Ok(()) },
+        ProcessType::Output => if output_process.is_none() {
+            Err(format!("EconomicEvent output process required for '{:}' action", action.id))
+        } else { Ok(()) },
     }
 }
 
@@ -138,3 +72,47 @@ pub fn validate_move_inventories(resouce_inventoried_as: Option<ResourceAddress>
         },
     }
 }
+
+/**
+ * Validation for containment events. A `combine` event places `resource_inventoried_as` inside
+ * `to_resource_inventoried_as`; a `separate` event removes `resource_inventoried_as` from its container.
+ */
+pub fn validate_containment_inventories(action_id: ActionId, resouce_inventoried_as: Option<ResourceAddress>, to_resource_inventoried_as: Option<ResourceAddress>) -> Result<(), String> {
+    match action_id.as_ref().as_str() {
+        "combine" => match (resouce_inventoried_as, to_resource_inventoried_as) {
+            (Some(resource), Some(container)) => if resource == container {
+                Err("combine EconomicEvent cannot place a resource inside itself".into())
+            } else { Ok(()) },
+            _ => Err("combine EconomicEvent requires both an inventoried resource and a containing resource".into()),
+        },
+        "separate" => match resouce_inventoried_as {
+            Some(_) => Ok(()),
+            None => Err("separate EconomicEvent requires an inventoried resource".into()),
+        },
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hdk::prelude::{ DnaHash, EntryHash };
+
+    fn process() -> Option<ProcessAddress> {
+        Some(ProcessAddress(DnaHash::from_raw_36(vec![0xdb; 36]), EntryHash::from_raw_36(vec![1; 36])))
+    }
+
+    fn action(id: &str) -> Action {
+        get_builtin_action(id).unwrap()
+    }
+
+    #[test]
+    fn test_flow_processes_match_action() {
+        assert!(validate_flow_processes(&action("consume"), process(), None).is_ok());
+        assert!(validate_flow_processes(&action("consume"), None, process()).is_err(), "input actions require an input process");
+        assert!(validate_flow_processes(&action("produce"), None, process()).is_ok());
+        assert!(validate_flow_processes(&action("produce"), process(), None).is_err(), "output actions require an output process");
+        assert!(validate_flow_processes(&action("transfer"), None, None).is_ok());
+        assert!(validate_flow_processes(&action("transfer"), process(), None).is_err(), "transfers cannot link processes");
+    }
+}
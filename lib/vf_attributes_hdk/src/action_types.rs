@@ -0,0 +1,95 @@
+/**
+ * Type definitions for ValueFlows actions
+ *
+ * These live alongside the other VF attribute types so that DNA configuration
+ * (which may declare custom actions) can be validated without depending on `vf_actions`.
+ *
+ * @see ../../vf_actions
+ */
+use serde::{Deserializer, Serializer, de::Error};
+use hdk_type_serialization_macros::*;
+
+#[derive(SerializedBytes, Debug, Clone, Copy, PartialEq)]
+pub enum ActionEffect {
+    // for 'process' events
+    NoEffect,
+    Increment,
+    Decrement,
+    // for 'transfer' events
+    DecrementIncrement,
+}
+
+impl Serialize for ActionEffect {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        serializer.serialize_str(match *self {
+            ActionEffect::NoEffect => "noEffect",
+            ActionEffect::Increment => "increment",
+            ActionEffect::Decrement => "decrement",
+            ActionEffect::DecrementIncrement => "decrementIncrement",
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for ActionEffect {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: Deserializer<'de>
+    {
+        let s = String::deserialize(deserializer)?;
+        Ok(match s.as_str() {
+            "noEffect" => ActionEffect::NoEffect,
+            "increment" => ActionEffect::Increment,
+            "decrement" => ActionEffect::Decrement,
+            "decrementIncrement" => ActionEffect::DecrementIncrement,
+            &_ => Err(
+                D::Error::custom(format!("Invalid value for ActionEffect: {}", s.as_str()))
+            )?,
+        })
+    }
+}
+
+#[derive(SerializedBytes, Debug, Clone, Copy, PartialEq)]
+pub enum ProcessType {
+    NotApplicable,
+    Input,
+    Output,
+}
+
+impl Serialize for ProcessType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        serializer.serialize_str(match *self {
+            ProcessType::NotApplicable => "notApplicable",
+            ProcessType::Input => "input",
+            ProcessType::Output => "output",
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for ProcessType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: Deserializer<'de>
+    {
+        let s = String::deserialize(deserializer)?;
+        Ok(match s.as_str() {
+            "notApplicable" => ProcessType::NotApplicable,
+            "input" => ProcessType::Input,
+            "output" => ProcessType::Output,
+            &_ => Err(
+                D::Error::custom(format!("Invalid value for ProcessType: {}", s.as_str()))
+            )?,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, SerializedBytes, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Action {
+    pub id: String,
+    pub label: String,
+    pub resource_effect: ActionEffect,
+    pub input_output: ProcessType,
+    pub pairs_with: String, // any of the action labels, or "notApplicable"
+}
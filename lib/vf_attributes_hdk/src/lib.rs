@@ -1,47 +1,160 @@
+use std::convert::TryFrom;
 use hdk_type_serialization_macros::*;
 
+mod action_types;
+pub use action_types::{ Action, ActionEffect, ProcessType };
+mod replication_types;
+pub use replication_types::{ ReplicationStatus, OutboxFlushReport };
+
 // re-exports for convenience
 pub use chrono::{ FixedOffset, Utc, DateTime };
-pub use holo_hash::{ AgentPubKey, EntryHash, HeaderHash };
+pub use holo_hash::{ AgentPubKey, EntryHash, HeaderHash, DnaHash };
 pub use holochain_zome_types::timestamp::Timestamp;
-pub use hdk_type_serialization_macros::{RevisionHash, DnaAddressable};
+pub use hdk_type_serialization_macros::{RevisionHash, DnaAddressable, RecordUri, UriAddressable};
 
 simple_alias!(ActionId => String);
+dna_scoped_string!(ActionRecordId);
+addressable_identifier!(ActionInternalAddress => EntryHash, "vf_action");
 
 simple_alias!(ExternalURL => String);
 
 addressable_identifier!(LocationAddress => EntryHash);
 
 dna_scoped_string!(UnitId);
-addressable_identifier!(UnitInternalAddress => EntryHash);
+addressable_identifier!(UnitInternalAddress => EntryHash, "vf_unit");
 
 addressable_identifier!(AgentAddress => AgentPubKey);
 
-addressable_identifier!(EventAddress => EntryHash);
-addressable_identifier!(ResourceAddress => EntryHash);
+addressable_identifier!(EventAddress => EntryHash, "vf_economic_event");
+addressable_identifier!(ResourceAddress => EntryHash, "vf_economic_resource");
 addressable_identifier!(ProductBatchAddress => EntryHash);
-addressable_identifier!(ProcessAddress => EntryHash);
+addressable_identifier!(ProcessAddress => EntryHash, "vf_process");
+
+addressable_identifier!(CommitmentAddress => EntryHash, "vf_commitment");
+addressable_identifier!(FulfillmentAddress => EntryHash, "vf_fulfillment");
+addressable_identifier!(IntentAddress => EntryHash, "vf_intent");
+addressable_identifier!(SatisfactionAddress => EntryHash, "vf_satisfaction");
+
+addressable_identifier!(PlanAddress => EntryHash, "vf_plan");
+addressable_identifier!(AgreementAddress => EntryHash, "vf_agreement");
+
+addressable_identifier!(ResourceSpecificationAddress => EntryHash, "vf_resource_specification");
+addressable_identifier!(ProcessSpecificationAddress => EntryHash, "vf_process_specification");
+
+addressable_identifier!(RecipeResourceAddress => EntryHash, "vf_recipe_resource");
+addressable_identifier!(RecipeProcessAddress => EntryHash, "vf_recipe_process");
+addressable_identifier!(RecipeFlowAddress => EntryHash, "vf_recipe_flow");
 
-addressable_identifier!(CommitmentAddress => EntryHash);
-addressable_identifier!(FulfillmentAddress => EntryHash);
-addressable_identifier!(IntentAddress => EntryHash);
-addressable_identifier!(SatisfactionAddress => EntryHash);
+addressable_identifier!(ProposedIntentAddress => EntryHash, "vf_proposed_intent");
+addressable_identifier!(ProposalAddress => EntryHash, "vf_proposal");
+addressable_identifier!(ProposedToAddress => EntryHash, "vf_proposed_to");
 
-addressable_identifier!(PlanAddress => EntryHash);
-addressable_identifier!(AgreementAddress => EntryHash);
+/// Identifier for the `EconomicEvent` or `Commitment` satisfying an `Intent`.
+///
+/// Carries the entry type of the referenced record, and so is serialized as a `RecordUri` string.
+///
+/// Identifiers written in the legacy `(DnaHash, EntryHash)` encoding are also accepted. These do not
+/// record which type of record they reference, and must be typed with `resolve_legacy` before use.
+///
+#[derive(Serialize, Deserialize, SerializedBytes, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(try_from = "EventOrCommitmentEncoding", into = "RecordUri")]
+pub struct EventOrCommitmentAddress(RecordUri);
 
-addressable_identifier!(ResourceSpecificationAddress => EntryHash);
-addressable_identifier!(ProcessSpecificationAddress => EntryHash);
+/// Entry type given to identifiers read in the legacy encoding, until they are resolved
+pub const LEGACY_EVENT_OR_COMMITMENT_TYPE: &str = "vf_event_or_commitment";
+
+/// Encodings accepted when reading an `EventOrCommitmentAddress`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EventOrCommitmentEncoding {
+    Uri(RecordUri),
+    Legacy(DnaHash, EntryHash),
+}
+
+impl EventOrCommitmentAddress {
+    pub fn uri(&self) -> &RecordUri {
+        &self.0
+    }
 
-addressable_identifier!(ProposedIntentAddress => EntryHash);
-addressable_identifier!(ProposalAddress => EntryHash);
-addressable_identifier!(ProposedToAddress => EntryHash);
+    /// Whether this identifier was read in the legacy encoding, and has not yet been resolved
+    pub fn is_legacy(&self) -> bool {
+        self.0.entry_type == LEGACY_EVENT_OR_COMMITMENT_TYPE
+    }
+
+    /// Type an identifier read in the legacy encoding. Events and commitments are stored in separate
+    /// DNAs, so it references a `local_entry_type` record if it is in `local_dna` and the other type
+    /// of record otherwise. Identifiers which are already typed are returned unchanged.
+    ///
+    pub fn resolve_legacy(&self, local_dna: &DnaHash, local_entry_type: &str) -> Self {
+        if !self.is_legacy() {
+            return self.to_owned();
+        }
+        let is_local = self.0.dna == *local_dna;
+        let is_commitment = (local_entry_type == CommitmentAddress::entry_type()) == is_local;
+        let entry_type = if is_commitment { CommitmentAddress::entry_type() } else { EventAddress::entry_type() };
+        Self(RecordUri::new(self.0.dna.to_owned(), entry_type, self.0.entry.to_owned()))
+    }
+
+    pub fn as_event(&self) -> Option<EventAddress> {
+        EventAddress::from_uri(&self.0).ok()
+    }
+
+    pub fn as_commitment(&self) -> Option<CommitmentAddress> {
+        CommitmentAddress::from_uri(&self.0).ok()
+    }
+}
 
-addressable_identifier!(EventOrCommitmentAddress => EntryHash);
+impl TryFrom<RecordUri> for EventOrCommitmentAddress {
+    type Error = SerializedBytesError;
 
-impl From<EventOrCommitmentAddress> for CommitmentAddress {
+    fn try_from(uri: RecordUri) -> Result<Self, Self::Error> {
+        if uri.entry_type != EventAddress::entry_type() && uri.entry_type != CommitmentAddress::entry_type()
+            && uri.entry_type != LEGACY_EVENT_OR_COMMITMENT_TYPE
+        {
+            return Err(SerializedBytesError::Deserialize(format!("Expected economic event or commitment URI, got {}", uri)));
+        }
+        Ok(Self(uri))
+    }
+}
+
+impl TryFrom<EventOrCommitmentEncoding> for EventOrCommitmentAddress {
+    type Error = SerializedBytesError;
+
+    fn try_from(encoded: EventOrCommitmentEncoding) -> Result<Self, Self::Error> {
+        match encoded {
+            EventOrCommitmentEncoding::Uri(uri) => Self::try_from(uri),
+            EventOrCommitmentEncoding::Legacy(dna, entry) => Ok(Self(RecordUri::new(dna, LEGACY_EVENT_OR_COMMITMENT_TYPE, entry))),
+        }
+    }
+}
+
+impl From<EventOrCommitmentAddress> for RecordUri {
     fn from(a: EventOrCommitmentAddress) -> Self {
-        Self(a.0, a.1)
+        a.0
+    }
+}
+
+impl From<CommitmentAddress> for EventOrCommitmentAddress {
+    fn from(a: CommitmentAddress) -> Self {
+        Self(a.to_uri())
+    }
+}
+
+impl From<EventAddress> for EventOrCommitmentAddress {
+    fn from(a: EventAddress) -> Self {
+        Self(a.to_uri())
+    }
+}
+
+impl AsRef<DnaHash> for EventOrCommitmentAddress {
+    fn as_ref(&self) -> &DnaHash {
+        self.0.as_ref()
+    }
+}
+
+impl AsRef<EntryHash> for EventOrCommitmentAddress {
+    fn as_ref(&self) -> &EntryHash {
+        self.0.as_ref()
     }
 }
 
@@ -52,7 +165,107 @@ pub struct ByHeader {
     pub address: RevisionHash,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteRequest {
+    pub address: RevisionHash,
+    /// delete even if other records still reference the one being deleted
+    #[serde(default)]
+    pub force: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ByAddress<T> {
     pub address: T,
 }
+
+/// Result of comparing a record against its replica in another DNA
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ReplicaState {
+    InSync,
+    Missing,
+    Divergent,
+    /// the record links only to records in its own DNA, so has no replica
+    NotReplicated,
+    /// the record has been deleted, but its replica remains
+    Orphaned,
+}
+
+/// Describes the replica of a record in another DNA, and any repair made to it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplicaReport<A> {
+    pub id: A,
+    pub revision_id: RevisionHash,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replica_id: Option<A>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replica_revision_id: Option<RevisionHash>,
+    pub state: ReplicaState,
+    pub divergent_fields: Vec<String>,
+    /// revision of the replica written to bring it back in line with the origin record
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repaired_revision_id: Option<RevisionHash>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holochain_serialized_bytes::{ encode, decode };
+
+    fn planning_dna() -> DnaHash {
+        DnaHash::from_raw_36(vec![0xd1; 36])
+    }
+
+    fn observation_dna() -> DnaHash {
+        DnaHash::from_raw_36(vec![0xd2; 36])
+    }
+
+    fn entry() -> EntryHash {
+        EntryHash::from_raw_36(vec![0xe1; 36])
+    }
+
+    #[test]
+    fn test_uri_encoding_round_trips() {
+        let address: EventOrCommitmentAddress = EventAddress(observation_dna(), entry()).into();
+        let decoded: EventOrCommitmentAddress = decode(&encode(&address).unwrap()).unwrap();
+
+        assert_eq!(decoded, address);
+        assert!(!decoded.is_legacy());
+        assert_eq!(decoded.as_event(), Some(EventAddress(observation_dna(), entry())));
+    }
+
+    #[test]
+    fn test_legacy_encoding_is_accepted() {
+        let legacy = encode(&(observation_dna(), entry())).unwrap();
+        let decoded: EventOrCommitmentAddress = decode(&legacy).unwrap();
+
+        assert!(decoded.is_legacy());
+        assert_eq!(decoded.as_event(), None);
+        assert_eq!(decoded.as_commitment(), None);
+
+        let reencoded: EventOrCommitmentAddress = decode(&encode(&decoded).unwrap()).unwrap();
+        assert_eq!(reencoded, decoded, "unresolved identifiers survive being written again");
+    }
+
+    #[test]
+    fn test_legacy_identifiers_resolve_by_dna() {
+        let legacy = |dna: DnaHash| -> EventOrCommitmentAddress { decode(&encode(&(dna, entry())).unwrap()).unwrap() };
+
+        // as seen from the planning DNA, where commitments are stored
+        let local = legacy(planning_dna()).resolve_legacy(&planning_dna(), CommitmentAddress::entry_type());
+        assert_eq!(local.as_commitment(), Some(CommitmentAddress(planning_dna(), entry())));
+        let remote = legacy(observation_dna()).resolve_legacy(&planning_dna(), CommitmentAddress::entry_type());
+        assert_eq!(remote.as_event(), Some(EventAddress(observation_dna(), entry())));
+
+        // as seen from the observation DNA, where events are stored
+        let local = legacy(observation_dna()).resolve_legacy(&observation_dna(), EventAddress::entry_type());
+        assert_eq!(local.as_event(), Some(EventAddress(observation_dna(), entry())));
+    }
+
+    #[test]
+    fn test_typed_identifiers_are_not_resolved() {
+        let address: EventOrCommitmentAddress = CommitmentAddress(observation_dna(), entry()).into();
+        assert_eq!(address.resolve_legacy(&observation_dna(), EventAddress::entry_type()), address);
+    }
+}
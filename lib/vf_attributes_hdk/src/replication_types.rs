@@ -0,0 +1,29 @@
+/**
+ * Type definitions for the delivery state of replication calls into other DNAs
+ *
+ * Produced by the outbox helpers in `hdk_records` and exposed through record RPC structs.
+ *
+ * @see ../../hdk_records/src/outbox_helpers.rs
+ */
+use serde::{Serialize, Deserialize};
+
+/// Delivery state of a record's most recent replication call into another DNA
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ReplicationStatus {
+    /// awaiting delivery from the replication outbox
+    Pending,
+    /// accepted by the remote DNA
+    Acknowledged,
+}
+
+/// Outcome of an attempt to deliver all due entries in a zome's replication outbox
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxFlushReport {
+    pub delivered: usize,
+    pub failed: usize,
+    /// not yet due for retry, or queued behind a failed operation for the same record
+    pub deferred: usize,
+    pub errors: Vec<String>,
+}
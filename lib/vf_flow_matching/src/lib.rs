@@ -0,0 +1,216 @@
+/**
+ * Rules for matching flows against each other
+ *
+ * Used to check that an `EconomicEvent` or `Commitment` is compatible with the
+ * `Commitment` or `Intent` it is recorded as fulfilling or satisfying.
+ * Kept apart from `vf_actions` so that action validation does not depend on record RPC crates.
+ *
+ * @package Holo-REA
+ */
+use hdk_records::{ RecordAPIResult, DataIntegrityError };
+use vf_attributes_hdk::{
+    ActionId,
+    AgentAddress,
+    ResourceSpecificationAddress,
+    ExternalURL,
+};
+use hc_zome_rea_commitment_rpc::Response as CommitmentResponse;
+use hc_zome_rea_intent_rpc::Response as IntentResponse;
+use hc_zome_rea_economic_event_rpc::Response as EventResponse;
+use hc_zome_rea_economic_event_storage::EntryData as EventData;
+
+/// Fields of a flow which must line up with those of any flow it fulfills or satisfies
+#[derive(Debug, Clone, Copy)]
+pub struct FlowSignature<'a> {
+    pub action: &'a ActionId,
+    pub provider: Option<&'a AgentAddress>,
+    pub receiver: Option<&'a AgentAddress>,
+    pub resource_conforms_to: Option<&'a ResourceSpecificationAddress>,
+    pub resource_classified_as: Option<&'a Vec<ExternalURL>>,
+}
+
+impl<'a> From<&'a CommitmentResponse> for FlowSignature<'a> {
+    fn from(c: &'a CommitmentResponse) -> Self {
+        FlowSignature {
+            action: &c.action,
+            provider: Some(&c.provider),
+            receiver: Some(&c.receiver),
+            resource_conforms_to: c.resource_conforms_to.as_ref(),
+            resource_classified_as: c.resource_classified_as.as_ref(),
+        }
+    }
+}
+
+impl<'a> From<&'a IntentResponse> for FlowSignature<'a> {
+    fn from(i: &'a IntentResponse) -> Self {
+        FlowSignature {
+            action: &i.action,
+            provider: i.provider.as_ref(),
+            receiver: i.receiver.as_ref(),
+            resource_conforms_to: i.resource_conforms_to.as_ref(),
+            resource_classified_as: i.resource_classified_as.as_ref(),
+        }
+    }
+}
+
+impl<'a> From<&'a EventResponse> for FlowSignature<'a> {
+    fn from(e: &'a EventResponse) -> Self {
+        FlowSignature {
+            action: &e.action,
+            provider: Some(&e.provider),
+            receiver: Some(&e.receiver),
+            resource_conforms_to: e.resource_conforms_to.as_ref(),
+            resource_classified_as: e.resource_classified_as.as_ref(),
+        }
+    }
+}
+
+impl<'a> From<&'a EventData> for FlowSignature<'a> {
+    fn from(e: &'a EventData) -> Self {
+        FlowSignature {
+            action: &e.action,
+            provider: Some(&e.provider),
+            receiver: Some(&e.receiver),
+            resource_conforms_to: e.resource_conforms_to.as_ref(),
+            resource_classified_as: e.resource_classified_as.as_ref(),
+        }
+    }
+}
+
+/// Compare a flow against the one it fulfills or satisfies, returning the name of the first
+/// incompatible field.
+///
+/// Fields left unspecified by the `expected` flow match anything, since intents in particular may leave
+/// their provider or receiver open. Fields specified by the `expected` flow must be present on the `actual`
+/// one: a `Commitment` for a particular `ResourceSpecification` is not fulfilled by an event which omits it.
+/// Classifications match if every classification of the `expected` flow is also present on the `actual` one.
+///
+pub fn find_flow_mismatch(expected: &FlowSignature, actual: &FlowSignature) -> Option<&'static str> {
+    if expected.action != actual.action {
+        return Some("action");
+    }
+    if !field_matches(expected.provider, actual.provider) {
+        return Some("provider");
+    }
+    if !field_matches(expected.receiver, actual.receiver) {
+        return Some("receiver");
+    }
+    if !field_matches(expected.resource_conforms_to, actual.resource_conforms_to) {
+        return Some("resourceConformsTo");
+    }
+    match (expected.resource_classified_as, actual.resource_classified_as) {
+        (None, _) => None,
+        (Some(wanted), None) if wanted.is_empty() => None,
+        (Some(_), None) => Some("resourceClassifiedAs"),
+        (Some(wanted), Some(given)) if !wanted.iter().all(|c| given.contains(c)) => Some("resourceClassifiedAs"),
+        (Some(_), Some(_)) => None,
+    }
+}
+
+/// Reject a flow which is incompatible with the one it fulfills or satisfies, see `find_flow_mismatch`
+pub fn ensure_flows_match<'a, E, A>(expected: E, actual: A) -> RecordAPIResult<()>
+    where E: Into<FlowSignature<'a>>,
+        A: Into<FlowSignature<'a>>,
+{
+    match find_flow_mismatch(&expected.into(), &actual.into()) {
+        Some(field) => Err(DataIntegrityError::IncompatibleFlow(field.to_string())),
+        None => Ok(()),
+    }
+}
+
+/// An unspecified `expected` field matches any value; a specified one must be present and equal
+fn field_matches<T: PartialEq>(expected: Option<T>, actual: Option<T>) -> bool {
+    match (expected, actual) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(e), Some(a)) => e == a,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hdk::prelude::{ AgentPubKey, DnaHash, EntryHash };
+
+    fn dna() -> DnaHash {
+        DnaHash::from_raw_36(vec![0xdb; 36])
+    }
+
+    fn agent(byte: u8) -> AgentAddress {
+        AgentAddress(dna(), AgentPubKey::from_raw_36(vec![byte; 36]))
+    }
+
+    fn spec(byte: u8) -> ResourceSpecificationAddress {
+        ResourceSpecificationAddress(dna(), EntryHash::from_raw_36(vec![byte; 36]))
+    }
+
+    fn action(id: &str) -> ActionId {
+        ActionId(id.to_string())
+    }
+
+    fn url(u: &str) -> ExternalURL {
+        ExternalURL(u.to_string())
+    }
+
+    fn signature<'a>(action: &'a ActionId, provider: Option<&'a AgentAddress>, receiver: Option<&'a AgentAddress>) -> FlowSignature<'a> {
+        FlowSignature { action, provider, receiver, resource_conforms_to: None, resource_classified_as: None }
+    }
+
+    #[test]
+    fn test_matching_flows() {
+        let (transfer, alice, bob) = (action("transfer"), agent(1), agent(2));
+        let expected = signature(&transfer, Some(&alice), Some(&bob));
+        assert_eq!(find_flow_mismatch(&expected, &signature(&transfer, Some(&alice), Some(&bob))), None);
+    }
+
+    #[test]
+    fn test_mismatched_action_agents_and_specification() {
+        let (transfer, produce, alice, bob) = (action("transfer"), action("produce"), agent(1), agent(2));
+        let expected = signature(&transfer, Some(&alice), Some(&bob));
+
+        assert_eq!(find_flow_mismatch(&expected, &signature(&produce, Some(&alice), Some(&bob))), Some("action"));
+        assert_eq!(find_flow_mismatch(&expected, &signature(&transfer, Some(&bob), Some(&bob))), Some("provider"));
+        assert_eq!(find_flow_mismatch(&expected, &signature(&transfer, Some(&alice), Some(&alice))), Some("receiver"));
+
+        let (wheat, barley) = (spec(1), spec(2));
+        let expected = FlowSignature { resource_conforms_to: Some(&wheat), ..expected };
+        let actual = FlowSignature { resource_conforms_to: Some(&barley), ..signature(&transfer, Some(&alice), Some(&bob)) };
+        assert_eq!(find_flow_mismatch(&expected, &actual), Some("resourceConformsTo"));
+    }
+
+    #[test]
+    fn test_unspecified_fields_are_not_compared() {
+        let (transfer, alice, bob) = (action("transfer"), agent(1), agent(2));
+        let open_intent = signature(&transfer, Some(&alice), None);
+
+        assert_eq!(find_flow_mismatch(&open_intent, &signature(&transfer, Some(&alice), Some(&bob))), None, "an open receiver matches any agent");
+        assert_eq!(find_flow_mismatch(&signature(&transfer, None, None), &signature(&transfer, Some(&bob), Some(&alice))), None);
+    }
+
+    #[test]
+    fn test_specified_fields_must_be_present() {
+        let (transfer, alice, bob) = (action("transfer"), agent(1), agent(2));
+        let wheat = spec(1);
+        let commitment = FlowSignature { resource_conforms_to: Some(&wheat), ..signature(&transfer, Some(&alice), Some(&bob)) };
+
+        assert_eq!(find_flow_mismatch(&commitment, &signature(&transfer, Some(&alice), Some(&bob))), Some("resourceConformsTo"), "an event without a specification does not fulfill a commitment with one");
+        assert_eq!(find_flow_mismatch(&signature(&transfer, Some(&alice), Some(&bob)), &commitment), None, "an intent without a specification is satisfied by a commitment with one");
+        assert_eq!(find_flow_mismatch(&signature(&transfer, Some(&alice), None), &signature(&transfer, None, None)), Some("provider"));
+    }
+
+    #[test]
+    fn test_classifications_must_include_expected() {
+        let transfer = action("transfer");
+        let wanted = vec![url("http://example.com/organic")];
+        let given = vec![url("http://example.com/organic"), url("http://example.com/local")];
+        let other = vec![url("http://example.com/local")];
+        let with_classes = |classes| FlowSignature { resource_classified_as: classes, ..signature(&transfer, None, None) };
+
+        assert_eq!(find_flow_mismatch(&with_classes(Some(&wanted)), &with_classes(Some(&given))), None, "actual flow may be more specific");
+        assert_eq!(find_flow_mismatch(&with_classes(Some(&given)), &with_classes(Some(&wanted))), Some("resourceClassifiedAs"));
+        assert_eq!(find_flow_mismatch(&with_classes(Some(&wanted)), &with_classes(Some(&other))), Some("resourceClassifiedAs"));
+        assert_eq!(find_flow_mismatch(&with_classes(Some(&wanted)), &with_classes(None)), Some("resourceClassifiedAs"), "expected classifications must be present");
+        assert_eq!(find_flow_mismatch(&with_classes(Some(&vec![])), &with_classes(None)), None);
+        assert_eq!(find_flow_mismatch(&with_classes(None), &with_classes(Some(&given))), None);
+    }
+}
@@ -34,6 +34,49 @@ impl<'a> QuantityValue {
     pub fn get_unit(&'a self) -> Option<UnitId> {
         self.has_unit.to_owned()
     }
+
+    pub fn get_numerical_value(&'a self) -> f64 {
+        self.has_numerical_value
+    }
+}
+
+/// Units of time usable in a `Duration`
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum TimeUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Duration {
+    numeric_duration: f64,
+    unit_type: TimeUnit,
+}
+
+impl<'a> Duration {
+    pub fn new(numeric_duration: f64, unit_type: TimeUnit) -> Duration {
+        Duration {
+            numeric_duration,
+            unit_type,
+        }
+    }
+
+    /// Length of this duration in whole seconds, rounded up
+    pub fn as_seconds(&'a self) -> i64 {
+        let multiplier = match self.unit_type {
+            TimeUnit::Second => 1.0,
+            TimeUnit::Minute => 60.0,
+            TimeUnit::Hour => 3600.0,
+            TimeUnit::Day => 86400.0,
+            TimeUnit::Week => 604800.0,
+        };
+        (self.numeric_duration * multiplier).ceil() as i64
+    }
 }
 
 pub fn add(q1: QuantityValue, q2: QuantityValue) -> QuantityValue {
@@ -46,6 +89,13 @@ pub fn add(q1: QuantityValue, q2: QuantityValue) -> QuantityValue {
     }
 }
 
+pub fn multiply(q: QuantityValue, factor: f64) -> QuantityValue {
+    QuantityValue {
+        has_numerical_value: q.has_numerical_value * factor,
+        has_unit: q.has_unit,
+    }
+}
+
 pub fn subtract(q1: QuantityValue, q2: QuantityValue) -> QuantityValue {
     if q1.has_unit != q2.has_unit {
         panic!("Unimplemented! Need to enable unit conversions in QuantityValue math");
@@ -55,3 +105,77 @@ pub fn subtract(q1: QuantityValue, q2: QuantityValue) -> QuantityValue {
         has_unit: q1.has_unit,
     }
 }
+
+/// Add two quantities, yielding `None` if they are measured in different units
+///
+/// :TODO: convert between compatible units rather than rejecting them
+///
+pub fn checked_add(q1: QuantityValue, q2: QuantityValue) -> Option<QuantityValue> {
+    if q1.has_unit != q2.has_unit {
+        return None;
+    }
+    Some(add(q1, q2))
+}
+
+/// Subtract `q2` from `q1`, yielding `None` if they are measured in different units
+pub fn checked_subtract(q1: QuantityValue, q2: QuantityValue) -> Option<QuantityValue> {
+    if q1.has_unit != q2.has_unit {
+        return None;
+    }
+    Some(subtract(q1, q2))
+}
+
+/// Sum `quantities` onto `initial`, yielding `None` if any is measured in a different unit
+pub fn checked_sum<I>(initial: QuantityValue, quantities: I) -> Option<QuantityValue>
+    where I: IntoIterator<Item = QuantityValue>,
+{
+    quantities.into_iter().try_fold(initial, checked_add)
+}
+
+/// Quantity of `total` left once `used` is taken from it, floored at zero.
+/// Yields `None` if the quantities are measured in different units.
+///
+pub fn checked_remaining(total: QuantityValue, used: QuantityValue) -> Option<QuantityValue> {
+    checked_subtract(total, used)
+        .map(|r| QuantityValue::new(r.has_numerical_value.max(0.0), r.has_unit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vf_attributes_hdk::DnaHash;
+
+    fn unit(id: &str) -> Option<UnitId> {
+        Some(UnitId(DnaHash::from_raw_36(vec![0xdb; 36]), id.to_string()))
+    }
+
+    fn kg(qty: f64) -> QuantityValue {
+        QuantityValue::new(qty, unit("kg"))
+    }
+
+    #[test]
+    fn test_checked_math_rejects_unit_mismatch() {
+        let litres = QuantityValue::new(1.0, unit("l"));
+
+        assert_eq!(checked_add(kg(1.0), kg(2.0)), Some(kg(3.0)));
+        assert_eq!(checked_add(kg(1.0), litres.to_owned()), None, "cannot add different units");
+        assert_eq!(checked_subtract(kg(3.0), kg(1.0)), Some(kg(2.0)));
+        assert_eq!(checked_subtract(kg(1.0), litres), None, "cannot subtract different units");
+        assert_eq!(checked_add(kg(1.0), QuantityValue::new(1.0, None)), None, "unitless quantities are distinct from measured ones");
+    }
+
+    #[test]
+    fn test_checked_sum() {
+        assert_eq!(checked_sum(kg(0.0), vec![]), Some(kg(0.0)), "empty sum is the initial value");
+        assert_eq!(checked_sum(kg(0.0), vec![kg(1.5), kg(2.5)]), Some(kg(4.0)));
+        assert_eq!(checked_sum(kg(0.0), vec![kg(1.5), QuantityValue::new(2.5, unit("l"))]), None, "any mismatched unit invalidates the sum");
+    }
+
+    #[test]
+    fn test_checked_remaining() {
+        assert_eq!(checked_remaining(kg(10.0), kg(4.0)), Some(kg(6.0)));
+        assert_eq!(checked_remaining(kg(10.0), kg(10.0)), Some(kg(0.0)));
+        assert_eq!(checked_remaining(kg(10.0), kg(12.0)), Some(kg(0.0)), "remaining quantity is floored at zero");
+        assert_eq!(checked_remaining(kg(10.0), QuantityValue::new(1.0, unit("l"))), None);
+    }
+}
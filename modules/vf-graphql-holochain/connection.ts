@@ -110,6 +110,27 @@ function serializeHash(hash: Uint8Array): string {
   return `u${Base64.fromUint8Array(hash, true)}`
 }
 
+/**
+ * Convert a serialized record ID to a `RecordUri`, as accepted by fields
+ * which may reference records of several entry types.
+ */
+export function recordUri(id: string, entryType: string): string {
+  const [entryHash, dnaHash] = id.split(':')
+  return `hrea://${dnaHash}/${entryType}/${entryHash}`
+}
+
+/**
+ * Split a `RecordUri` into the serialized ID of the record it references and its entry type.
+ */
+export function parseRecordUri(uri: string): { id: string, entryType: string } {
+  const matches = uri.match(/^hrea:\/\/([^/]+)\/([^/]+)\/([^/]+)$/)
+  if (!matches) {
+    throw new Error(`Not a record URI: ${uri}`)
+  }
+  const [, dnaHash, entryType, entryHash] = matches
+  return { id: `${entryHash}:${dnaHash}`, entryType }
+}
+
 function seralizeId(id: RecordId): string {
   return `${serializeHash(id[1])}:${serializeHash(id[0])}`
 }
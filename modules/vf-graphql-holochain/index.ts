@@ -12,7 +12,7 @@ import { makeExecutableSchema } from '@graphql-tools/schema'
 
 import { APIOptions, ResolverOptions, DEFAULT_VF_MODULES } from './types'
 import generateResolvers from './resolvers'
-import { mapZomeFn, openConnection } from './connection'
+import { mapZomeFn, openConnection, recordUri, parseRecordUri } from './connection'
 const { buildSchema, printSchema } = require('@valueflows/vf-graphql')
 
 export {
@@ -21,6 +21,9 @@ export {
   // direct access to Holochain zome method bindings for authoring own custom resolvers bound to non-REA DNAs
   openConnection,
   mapZomeFn,
+  // conversion between record IDs and the URIs taken by fields which may reference several record types (eg. `Satisfaction.satisfiedBy`)
+  recordUri,
+  parseRecordUri,
 }
 
 /**
@@ -6,7 +6,7 @@
  */
 
 import { DNAIdMappings } from '../types'
-import { mapZomeFn } from '../connection'
+import { mapZomeFn, parseRecordUri } from '../connection'
 import { deleteHandler } from './'
 
 import {
@@ -30,12 +30,22 @@ export default (dnaConfig: DNAIdMappings, conductorUri: string) => {
   const runUpdate = mapZomeFn(dnaConfig, conductorUri, 'planning', 'satisfaction', 'update_satisfaction')
   const runDelete = mapZomeFn(dnaConfig, conductorUri, 'planning', 'satisfaction', 'delete_satisfaction')
 
+  // satisfying records are referenced by URI, which carries the type of the record referenced
+  const satisfiedByUri = (uri: string) => {
+    const { entryType } = parseRecordUri(uri)
+    if (entryType !== 'vf_economic_event' && entryType !== 'vf_commitment') {
+      throw new Error(`satisfiedBy must reference an EconomicEvent or Commitment, got ${uri}`)
+    }
+    return uri
+  }
+
   const createSatisfaction: createHandler = async (root, args) => {
-    return runCreate(args)
+    return runCreate({ satisfaction: { ...args.satisfaction, satisfiedBy: satisfiedByUri(args.satisfaction.satisfiedBy) } })
   }
 
   const updateSatisfaction: updateHandler = async (root, args) => {
-    return runUpdate(args)
+    const { satisfiedBy } = args.satisfaction
+    return runUpdate({ satisfaction: satisfiedBy ? { ...args.satisfaction, satisfiedBy: satisfiedByUri(satisfiedBy) } : args.satisfaction })
   }
 
   const deleteSatisfaction: deleteHandler = async (root, args) => {
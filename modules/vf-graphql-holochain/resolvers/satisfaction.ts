@@ -6,7 +6,7 @@
  */
 
 import { DNAIdMappings, addTypename, DEFAULT_VF_MODULES } from '../types'
-import { mapZomeFn } from '../connection'
+import { mapZomeFn, parseRecordUri } from '../connection'
 
 import {
   Satisfaction,
@@ -14,37 +14,28 @@ import {
   Intent,
 } from '@valueflows/vf-graphql'
 
-async function extractRecordsOrFail (query, subfieldId: string): Promise<any> {
-  const val = await query
-  if (!val || !val.length || !val[0][subfieldId]) {
-    throw new Error('Reference not found')
-  }
-  return val[0][subfieldId]
-}
-
 export default (enabledVFModules: string[] = DEFAULT_VF_MODULES, dnaConfig: DNAIdMappings, conductorUri: string) => {
   const hasObservation = -1 !== enabledVFModules.indexOf("observation")
 
-  const readEvents = mapZomeFn(dnaConfig, conductorUri, 'observation', 'economic_event_index', 'query_events')
-  const readCommitments = mapZomeFn(dnaConfig, conductorUri, 'planning', 'commitment_index', 'query_commitments')
+  const readEvent = mapZomeFn(dnaConfig, conductorUri, 'observation', 'economic_event', 'get_event')
+  const readCommitment = mapZomeFn(dnaConfig, conductorUri, 'planning', 'commitment', 'get_commitment')
   const readIntents = mapZomeFn(dnaConfig, conductorUri, 'planning', 'intent_index', 'query_intents')
 
   return {
     satisfiedBy: async (record: Satisfaction): Promise<EventOrCommitment> => {
-      // :NOTE: this presumes a satisfaction will never be erroneously linked to 2 records
-      return (
-        await Promise.all([
-          extractRecordsOrFail(readCommitments({ params: { satisfies: record.id } }), 'commitment')
-            .then(addTypename('Commitment'))
-            .catch((e) => e),
-        ].concat(hasObservation ? [
-          extractRecordsOrFail(readEvents({ params: { satisfies: record.id } }), 'economicEvent')
-            .then(addTypename('EconomicEvent'))
-            .catch((e) => e),
-        ] : []))
-      )
-      .filter(r => !(r instanceof Error))
-      .pop()
+      // the satisfying record is referenced by URI, which carries its type
+      const { id, entryType } = parseRecordUri(record.satisfiedBy as unknown as string)
+      switch (entryType) {
+        case 'vf_commitment':
+          return addTypename<EventOrCommitment>('Commitment')((await readCommitment({ address: id })).commitment)
+        case 'vf_economic_event':
+          if (!hasObservation) {
+            throw new Error('Satisfied by an EconomicEvent, but the observation module is not enabled')
+          }
+          return addTypename<EventOrCommitment>('EconomicEvent')((await readEvent({ address: id })).economicEvent)
+        default:
+          throw new Error(`Unexpected record type for Satisfaction.satisfiedBy: ${entryType}`)
+      }
     },
 
     satisfies: async (record: Satisfaction): Promise<Intent> => {
@@ -3,6 +3,7 @@ const {
   buildConfig,
   buildRunner,
   buildPlayer,
+  graphQLRecordUri,
 } = require('../init')
 
 const runner = buildRunner()
@@ -252,11 +253,11 @@ runner.registerScenario('flow records and relationships', async (s, t) => {
     },
     "inputEventSatisfaction": {
       "satisfies": inputIntentId,
-      "satisfiedBy": inputEventId,
+      "satisfiedBy": graphQLRecordUri(inputEventId, 'vf_economic_event'),
     },
     "inputCommitmentSatisfaction": {
       "satisfies": inputIntentId,
-      "satisfiedBy": inputCommitmentId,
+      "satisfiedBy": graphQLRecordUri(inputCommitmentId, 'vf_commitment'),
     },
   })
   await s.consistency()
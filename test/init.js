@@ -155,6 +155,14 @@ module.exports = {
   bridge: Config.bridge,
   buildConfig: Config.gen,
 
+  // reference a record by `RecordUri`, as for fields which may link records of several entry types
+  recordUri: (id, entryType) => `hrea://${serializeHash(id[0])}/${entryType}/${serializeHash(id[1])}`,
+  // as above, for record IDs in their GraphQL format
+  graphQLRecordUri: (id, entryType) => {
+    const [entryHash, dnaHash] = id.split(':')
+    return `hrea://${dnaHash}/${entryType}/${entryHash}`
+  },
+
   // :TODO: :SHONK: temporary code for mocking, eventually tests will need to populate mock data with referential integrity to pass
   mockAgentId: (asStr = true) => {
     const a = [
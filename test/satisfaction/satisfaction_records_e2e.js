@@ -4,6 +4,7 @@ const {
   buildPlayer,
   mockIdentifier,
   mockAgentId,
+  recordUri,
 } = require('../init')
 
 const runner = buildRunner()
@@ -44,7 +45,7 @@ runner.registerScenario('satisfactions can be written and read between DNAs by a
 
   const satisfaction = {
     satisfies: intentId,
-    satisfiedBy: eventId,
+    satisfiedBy: recordUri(eventId, 'vf_economic_event'),
     note: 'satisfied by an event',
   }
   const satisfactionResp = await planning.call('satisfaction', 'create_satisfaction', { satisfaction })
@@ -54,12 +55,12 @@ runner.registerScenario('satisfactions can be written and read between DNAs by a
 
   // ASSERT: check satisfaction in originating network
   let readResponse = await planning.call('satisfaction', 'get_satisfaction', { address: satisfactionId })
-  t.deepEqual(readResponse.satisfaction.satisfiedBy, eventId, 'Satisfaction.satisfiedBy reference saved')
+  t.deepEqual(readResponse.satisfaction.satisfiedBy, satisfaction.satisfiedBy, 'Satisfaction.satisfiedBy reference saved')
   t.deepEqual(readResponse.satisfaction.satisfies, intentId, 'Satisfaction.satisfies reference saved')
 
   // ASSERT: check satisfaction in target network
   readResponse = await observation.call('satisfaction', 'get_satisfaction', { address: satisfactionId })
-  t.deepEqual(readResponse.satisfaction.satisfiedBy, eventId, 'Satisfaction.satisfiedBy reference saved')
+  t.deepEqual(readResponse.satisfaction.satisfiedBy, satisfaction.satisfiedBy, 'Satisfaction.satisfiedBy reference saved')
   t.deepEqual(readResponse.satisfaction.satisfies, intentId, 'Satisfaction.satisfies reference saved')
 
   // ASSERT: check event field refs
@@ -80,7 +81,7 @@ runner.registerScenario('satisfactions can be written and read between DNAs by a
   t.deepEqual(readResponse.Ok[0].satisfaction.id, satisfactionId, 'Satisfaction.satisfies indexed correctly')
 
   // ASSERT: check event query indexes
-  readResponse = await observation.call('satisfaction_index', 'query_satisfactions', { params: { satisfiedBy: eventId } })
+  readResponse = await observation.call('satisfaction_index', 'query_satisfactions', { params: { satisfiedBy: recordUri(eventId, 'vf_economic_event') } })
   t.equal(readResponse.length, 1, 'read satisfactions by event OK')
   t.deepEqual(readResponse.Ok[0].satisfaction.id, satisfactionId, 'Satisfaction.satisfiedBy indexed correctly')
 
@@ -108,7 +109,7 @@ runner.registerScenario('satisfactions can be written and read between DNAs by a
 
   const satisfaction2 = {
     satisfies: intentId,
-    satisfiedBy: commitmentId,
+    satisfiedBy: recordUri(commitmentId, 'vf_commitment'),
     note: 'satisfied by a commitment',
   }
   const satisfactionResp2 = await planning.call('satisfaction', 'create_satisfaction', { satisfaction: satisfaction2 })
@@ -135,7 +136,7 @@ runner.registerScenario('satisfactions can be written and read between DNAs by a
   t.deepEqual(readResponse.intent.satisfiedBy[1], satisfactionId, 'Intent.satisfiedBy reference 1 OK')
 
   // ASSERT: check commitment query indexes
-  readResponse = await planning.call('satisfaction_index', 'query_satisfactions', { params: { satisfiedBy: commitmentId } })
+  readResponse = await planning.call('satisfaction_index', 'query_satisfactions', { params: { satisfiedBy: recordUri(commitmentId, 'vf_commitment') } })
   t.equal(readResponse.length, 1, 'read satisfactions by commitment OK')
   t.deepEqual(readResponse.Ok[0].satisfaction.id, satisfactionId2, 'Satisfaction.satisfiedBy indexed correctly')
 
@@ -0,0 +1,136 @@
+/**
+ * Holo-REA action zome library API
+ *
+ * Contains helper methods that can be used to manipulate custom `Action` data
+ * structures in either the local Holochain zome, or a separate DNA-local zome.
+ *
+ * Builtin ValueFlows actions are always resolved first and may not be overridden.
+ *
+ * @package Holo-REA
+ */
+use hdk::prelude::*;
+use hdk_records::{
+    RecordAPIResult, DataIntegrityError,
+    records::read_record_entry_by_header,
+    record_interface::Updateable,
+    records_anchored::{
+        create_anchored_record,
+        read_anchored_record_entry,
+        update_anchored_record,
+    },
+    local_indexes::query_root_index,
+};
+
+pub use vf_attributes_hdk::{
+    ActionRecordId,
+};
+use vf_actions::{
+    get_builtin_action,
+    get_all_builtin_actions,
+};
+
+pub use hc_zome_rea_action_storage_consts::*;
+use hc_zome_rea_action_storage::*;
+use hc_zome_rea_action_rpc::*;
+
+pub fn handle_create_action<S>(entry_def_id: S, action: CreateRequest) -> RecordAPIResult<ResponseData>
+    where S: AsRef<str>,
+{
+    if get_builtin_action(action.get_id()).is_some() {
+        return Err(DataIntegrityError::Conflict(format!("Cannot redefine builtin action '{}'", action.get_id())));
+    }
+    if read_custom_action(&entry_def_id, action.get_id()).is_ok() {
+        return Err(DataIntegrityError::Conflict(format!("Action '{}' already exists", action.get_id())));
+    }
+    validate_pairs_with(&entry_def_id, action.get_id(), action.get_pairs_with())?;
+
+    let (revision_id, _entry_id, entry_resp): (_,ActionRecordId,EntryData) = create_anchored_record(&entry_def_id, action.to_owned())?;
+    Ok(construct_response(&revision_id, &entry_resp))
+}
+
+/// Resolve an action by ID, checking builtin actions before any custom actions
+/// registered in this zome.
+///
+pub fn handle_get_action<S>(entry_def_id: S, id: ActionId) -> RecordAPIResult<Action>
+    where S: AsRef<str>,
+{
+    match get_builtin_action(id.as_ref()) {
+        Some(action) => Ok(action),
+        None => {
+            let (_revision_id, entry) = read_custom_action(&entry_def_id, id.as_ref())?;
+            Ok(entry.into())
+        },
+    }
+}
+
+pub fn handle_get_all_actions<S>(entry_def_id: S) -> RecordAPIResult<Vec<Action>>
+    where S: AsRef<str>,
+{
+    let mut actions = get_all_builtin_actions();
+    let custom = query_root_index::<EntryData, EntryStorage, ActionInternalAddress, _>(&entry_def_id)?;
+
+    // :NOTE: anchor paths share the same root as record identities and have no identity index,
+    //        so are skipped here. Any other failure is returned to the caller.
+    for result in custom {
+        match result {
+            Ok((_revision, _addr, entry)) => actions.push(entry.into()),
+            Err(DataIntegrityError::IndexNotFound(_)) => (),
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(actions)
+}
+
+pub fn handle_update_action<S>(entry_def_id: S, action: UpdateRequest) -> RecordAPIResult<ResponseData>
+    where S: AsRef<str>,
+{
+    let revision_id = action.get_revision_id().clone();
+
+    // merge and validate the updated entry before committing anything
+    let (_id, prev_entry): (ActionInternalAddress, EntryData) = read_record_entry_by_header::<EntryData, EntryStorage, ActionInternalAddress>(&revision_id)?;
+    let merged_entry = prev_entry.update_with(action.to_owned());
+    validate_pairs_with(&entry_def_id, &merged_entry.id, &merged_entry.pairs_with)?;
+
+    let (new_revision, _new_id, new_entry, _prev_entry): (_,ActionRecordId,EntryData,_) = update_anchored_record::<EntryData, EntryStorage, ActionInternalAddress, _,_,_,_>(&entry_def_id, &revision_id, action)?;
+
+    Ok(construct_response(&new_revision, &new_entry))
+}
+
+/// Read a custom action record via its anchored ID
+///
+fn read_custom_action<S>(entry_def_id: S, id: &str) -> RecordAPIResult<(RevisionHash, EntryData)>
+    where S: AsRef<str>,
+{
+    let (revision_id, _entry_id, entry): (_,ActionRecordId,_) = read_anchored_record_entry::<EntryData, EntryStorage, ActionInternalAddress, _,_,_>(&entry_def_id.as_ref(), id)?;
+    Ok((revision_id, entry))
+}
+
+/// Ensure the `pairs_with` field of a custom action refers to a resolvable action
+///
+fn validate_pairs_with<S>(entry_def_id: S, action_id: &str, pairs_with: &str) -> RecordAPIResult<()>
+    where S: AsRef<str>,
+{
+    if pairs_with == "notApplicable" || pairs_with == action_id {
+        return Ok(());
+    }
+    if get_builtin_action(pairs_with).is_some() || read_custom_action(&entry_def_id, pairs_with).is_ok() {
+        return Ok(());
+    }
+    Err(DataIntegrityError::ValidationFailed(format!("Action '{}' pairs with unknown action '{}'", action_id, pairs_with)))
+}
+
+fn construct_response<'a>(
+    revision_id: &RevisionHash, e: &EntryData
+) -> ResponseData {
+    ResponseData {
+        action: Response {
+            id: e.id.to_owned().into(),
+            revision_id: revision_id.to_owned(),
+            label: e.label.to_owned(),
+            resource_effect: e.resource_effect,
+            input_output: e.input_output,
+            pairs_with: e.pairs_with.to_owned(),
+        }
+    }
+}
@@ -0,0 +1,147 @@
+/**
+ * Holo-REA action zome I/O data structures
+ *
+ * Required by packages wishing to interact with the zome via its standard RPC interface,
+ * and by the zome API handlers accepting these parameters.
+ *
+ * @package Holo-REA
+ */
+use holochain_serialized_bytes::prelude::*;
+
+use hdk_records::{
+    MaybeUndefined, RecordAPIResult,
+    record_interface::{ UniquelyIdentifiable, UpdateableIdentifier, Updateable },
+};
+
+//---------------- EXTERNAL RECORD STRUCTURE ----------------
+
+// Export external type interface to allow consuming zomes to easily import & define zome API
+pub use vf_attributes_hdk::{
+    RevisionHash,
+    ActionId,
+};
+pub use vf_actions::{
+    Action,
+    ActionEffect,
+    ProcessType,
+    custom::CustomActionEntry,
+};
+
+/// I/O struct to describe the complete record, including all managed link fields
+///
+#[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+    pub id: ActionId,
+    pub revision_id: RevisionHash,
+    pub label: String,
+    pub resource_effect: ActionEffect,
+    pub input_output: ProcessType,
+    pub pairs_with: String,
+}
+
+/// I/O struct to describe what is returned outside the gateway.
+/// Responses are usually returned as named attributes in order to leave space
+/// for future additional return values.
+///
+#[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseData {
+    pub action: Response,
+}
+
+//---------------- CREATE REQUEST ----------------
+
+/// I/O struct to describe the complete input record, including all managed links
+///
+#[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateRequest {
+    pub id: ActionId,
+    pub label: String,
+    pub resource_effect: ActionEffect,
+    pub input_output: ProcessType,
+    pub pairs_with: String, // any of the action IDs, or "notApplicable"
+}
+
+impl<'a> CreateRequest {
+    pub fn get_id(&'a self) -> &str {
+        self.id.as_ref()
+    }
+
+    pub fn get_pairs_with(&'a self) -> &str {
+        &self.pairs_with
+    }
+}
+
+/// Pick relevant fields out of I/O record into underlying DHT entry
+impl From<CreateRequest> for CustomActionEntry {
+    fn from(e: CreateRequest) -> CustomActionEntry {
+        CustomActionEntry {
+            id: e.id.into(),
+            label: e.label.into(),
+            resource_effect: e.resource_effect,
+            input_output: e.input_output,
+            pairs_with: e.pairs_with.into(),
+        }
+    }
+}
+
+impl UniquelyIdentifiable for CreateRequest {
+    fn get_anchor_key(&self) -> RecordAPIResult<String> {
+        Ok(self.get_id().to_string())
+    }
+}
+
+//---------------- UPDATE REQUEST ----------------
+
+/// I/O struct to describe the complete input record, including all managed links
+///
+/// :NOTE: action IDs are immutable once assigned, since events & commitments reference them directly.
+///
+#[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRequest {
+    pub revision_id: RevisionHash,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "MaybeUndefined::is_undefined")]
+    pub label: MaybeUndefined<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "MaybeUndefined::is_undefined")]
+    pub resource_effect: MaybeUndefined<ActionEffect>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "MaybeUndefined::is_undefined")]
+    pub input_output: MaybeUndefined<ProcessType>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "MaybeUndefined::is_undefined")]
+    pub pairs_with: MaybeUndefined<String>,
+}
+
+impl<'a> UpdateRequest {
+    pub fn get_revision_id(&'a self) -> &RevisionHash {
+        &self.revision_id
+    }
+
+    pub fn get_pairs_with(&'a self) -> Option<String> {
+        self.pairs_with.to_owned().to_option()
+    }
+}
+
+impl UpdateableIdentifier for UpdateRequest {
+    fn get_new_anchor_key(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Handles update operations by merging any newly provided fields
+impl Updateable<UpdateRequest> for CustomActionEntry {
+    fn update_with(&self, e: UpdateRequest) -> CustomActionEntry {
+        CustomActionEntry {
+            id: self.id.to_owned(),
+            label: if !e.label.is_some() { self.label.to_owned() } else { e.label.to_owned().unwrap() },
+            resource_effect: if !e.resource_effect.is_some() { self.resource_effect } else { e.resource_effect.to_owned().unwrap() },
+            input_output: if !e.input_output.is_some() { self.input_output } else { e.input_output.to_owned().unwrap() },
+            pairs_with: if !e.pairs_with.is_some() { self.pairs_with.to_owned() } else { e.pairs_with.to_owned().unwrap() },
+        }
+    }
+}
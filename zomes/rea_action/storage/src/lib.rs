@@ -0,0 +1,22 @@
+/**
+ * Holo-REA action zome internal data structures
+ *
+ * Required by the zome itself, and for any DNA-local zomes interacting with its
+ * storage API directly.
+ *
+ * @package Holo-REA
+ */
+use vf_actions::custom::{ CustomActionEntry, CustomActionStorage };
+
+pub use vf_attributes_hdk::{ ActionInternalAddress };
+
+// :SHONK: needed as re-export in zome logic to allow validation logic to parse entries
+pub use hdk_records::record_interface::Identified;
+
+//---------------- RECORD INTERNALS & VALIDATION ----------------
+
+// :NOTE: the entry format is owned by `vf_actions`, so that any zome validating flow actions
+// can resolve custom action records bundled in the same DNA. Conversions from the zome's
+// I/O structs are implemented alongside them in `hc_zome_rea_action_rpc`.
+pub type EntryData = CustomActionEntry;
+pub type EntryStorage = CustomActionStorage;
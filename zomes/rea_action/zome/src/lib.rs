@@ -1,21 +1,41 @@
 /**
  * ValueFlows Actions zome
  *
- * Provides read-only access to built-in action struct metadata.
+ * Provides access to built-in action struct metadata, and management of
+ * user-defined custom actions keyed by their action ID.
  *
  * @package: HoloREA
  * @since:   2019-12-23
  */
 use hdk::prelude::*;
+use hdk_records::{ DataIntegrityError, ErrorEnvelope, ErrorCode };
 
-use vf_attributes_hdk::{
-    ActionId,
-};
-use vf_actions::{
-    Action,
-    get_builtin_action,
-    get_all_builtin_actions,
-};
+use hc_zome_rea_action_rpc::*;
+use hc_zome_rea_action_lib::*;
+
+#[hdk_extern]
+fn entry_defs(_: ()) -> ExternResult<EntryDefsCallbackResult> {
+    Ok(EntryDefsCallbackResult::from(vec![
+        Path::entry_def(),
+        EntryDef {
+            id: ACTION_ENTRY_TYPE.into(),
+            visibility: EntryVisibility::Public,
+            crdt_type: CrdtType,
+            required_validations: 2.into(),
+            required_validation_type: RequiredValidationType::default(),
+        }
+    ]))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CreateParams {
+    pub action: CreateRequest,
+}
+
+#[hdk_extern]
+fn create_action(CreateParams { action }: CreateParams) -> ExternResult<ResponseData> {
+    Ok(handle_create_action(ACTION_ENTRY_TYPE, action)?)
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 struct ById {
@@ -24,13 +44,25 @@ struct ById {
 
 #[hdk_extern]
 fn get_action(ById { id }: ById) -> ExternResult<Action> {
-    match get_builtin_action(id.as_ref()) {
-        Some(action) => Ok(action),
-        None => Err(WasmError::Guest(format!("No action with ID '{}' available", id.as_ref()))),
+    match handle_get_action(ACTION_ENTRY_TYPE, id.to_owned()) {
+        Ok(action) => Ok(action),
+        Err(DataIntegrityError::IndexNotFound(_)) | Err(DataIntegrityError::EntryNotFound) =>
+            Err(ErrorEnvelope::new(ErrorCode::EntryNotFound, format!("No action with ID '{}' available", id.as_ref())).into()),
+        Err(e) => Err(e.into()),
     }
 }
 
 #[hdk_extern]
 fn get_all_actions(_: ()) -> ExternResult<Vec<Action>> {
-    Ok(get_all_builtin_actions())
+    Ok(handle_get_all_actions(ACTION_ENTRY_TYPE)?)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct UpdateParams {
+    pub action: UpdateRequest,
+}
+
+#[hdk_extern]
+fn update_action(UpdateParams { action }: UpdateParams) -> ExternResult<ResponseData> {
+    Ok(handle_update_action(ACTION_ENTRY_TYPE, action)?)
 }
@@ -11,12 +11,14 @@ use hdk_records::{
     records::{
         create_record,
         read_record_entry,
+        read_record_entry_by_header,
         update_record,
         delete_record,
     },
     foreign_indexes::{
         read_foreign_index,
     },
+    integrity::ensure_deletable,
 };
 
 pub use hc_zome_rea_agreement_storage_consts::*;
@@ -45,7 +47,17 @@ pub fn handle_update_agreement<S>(entry_def_id: S, agreement: UpdateRequest) ->
     construct_response(&identity_address, revision_id, &entry, get_link_fields(&identity_address)?)
 }
 
-pub fn handle_delete_agreement(address: RevisionHash) -> RecordAPIResult<bool> {
+/// Indexes of other records referencing an `Agreement`, which prevent its deletion
+const INBOUND_LINK_TAGS: &[&str] = &[
+    AGREEMENT_EVENTS_LINK_TAG,
+    AGREEMENT_COMMITMENTS_LINK_TAG,
+];
+
+pub fn handle_delete_agreement(address: RevisionHash, force: bool) -> RecordAPIResult<bool> {
+    let (base_address, _entry): (AgreementAddress, EntryData) = read_record_entry_by_header::<EntryData, EntryStorage, _>(&address)?;
+
+    ensure_deletable(&AGREEMENT_ENTRY_TYPE, &base_address, INBOUND_LINK_TAGS, force)?;
+
     delete_record::<EntryData, RevisionHash>(&address)
 }
 
@@ -75,8 +87,8 @@ fn construct_response<'a>(
 //---------------- READ ----------------
 
 /// Properties accessor for zome config
-fn read_foreign_index_zome(conf: DnaConfigSlice) -> Option<String> {
-    Some(conf.agreement.index_zome)
+fn read_foreign_index_zome(conf: AgreementZomeConfig) -> Option<String> {
+    Some(conf.index_zome)
 }
 
 // @see construct_response
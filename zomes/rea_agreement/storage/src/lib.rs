@@ -9,6 +9,7 @@
 use hdk::prelude::*;
 
 use hdk_records::{
+    config::ZomeConfig,
     generate_record_entry,
     record_interface::{Updateable},
 };
@@ -27,17 +28,14 @@ pub use hdk_records::record_interface::Identified;
 
 //--------------- ZOME CONFIGURATION ATTRIBUTES ----------------
 
-// :TODO: remove this, replace with reference to appropriate namespacing of zome config
-#[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
-pub struct DnaConfigSlice {
-    pub agreement: AgreementZomeConfig,
-}
-
 #[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct AgreementZomeConfig {
     pub index_zome: String,
 }
 
+impl ZomeConfig for AgreementZomeConfig {}
+
 //---------------- RECORD INTERNALS & VALIDATION ----------------
 
 #[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
@@ -8,9 +8,16 @@
  * @package Holo-REA
  */
 use hdk::prelude::*;
+use hdk_records::config::init_with_config;
 
 use hc_zome_rea_agreement_rpc::*;
 use hc_zome_rea_agreement_lib::*;
+use hc_zome_rea_agreement_storage::AgreementZomeConfig;
+
+#[hdk_extern]
+fn init(_: ()) -> ExternResult<InitCallbackResult> {
+    Ok(init_with_config::<AgreementZomeConfig>())
+}
 
 #[hdk_extern]
 fn entry_defs(_: ()) -> ExternResult<EntryDefsCallbackResult> {
@@ -56,12 +63,7 @@ fn update_agreement(UpdateParams { agreement }: UpdateParams) -> ExternResult<Re
     Ok(handle_update_agreement(AGREEMENT_ENTRY_TYPE, agreement)?)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct DeleteParams {
-    pub address: RevisionHash,
-}
-
 #[hdk_extern]
-fn delete_agreement(DeleteParams { address }: DeleteParams) -> ExternResult<bool> {
-    Ok(handle_delete_agreement(address)?)
+fn delete_agreement(DeleteRequest { address, force }: DeleteRequest) -> ExternResult<bool> {
+    Ok(handle_delete_agreement(address, force)?)
 }
@@ -8,6 +8,7 @@
  */
 use hdk::prelude::*;
 use hdk_records::{
+    config::init_with_config,
     index_retrieval::{ ByAddress, IndexingZomeConfig },
     remote_indexes::{
         RemoteEntryLinkRequest,
@@ -27,15 +28,14 @@ use hc_zome_rea_commitment_storage_consts::{ COMMITMENT_ENTRY_TYPE, COMMITMENT_C
 
 entry_defs![Path::entry_def()];
 
-// :TODO: obviate this with zome-specific configs
-#[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
-pub struct DnaConfigSlice {
-    pub agreement_index: IndexingZomeConfig,
+#[hdk_extern]
+fn init(_: ()) -> ExternResult<InitCallbackResult> {
+    Ok(init_with_config::<IndexingZomeConfig>())
 }
 
 /*
-fn read_index_target_zome(conf: DnaConfigSlice) -> Option<String> {
-    Some(conf.agreement_index.record_storage_zome)
+fn read_index_target_zome(conf: IndexingZomeConfig) -> Option<String> {
+    Some(conf.record_storage_zome)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -10,6 +10,7 @@ use hdk::prelude::*;
 use hdk_records::{
     RecordAPIResult, DataIntegrityError,
     MaybeUndefined,
+    config::{ ZomeConfig, zome_config },
     records::{
         create_record,
         read_record_entry,
@@ -21,16 +22,23 @@ use hdk_records::{
         query_index,
     },
     foreign_indexes::{
+        create_foreign_index,
         read_foreign_index,
+        update_foreign_index,
     },
     remote_indexes::{
         create_remote_index,
         update_remote_index,
     },
+    integrity::ensure_deletable,
+    record_interface::Updateable,
 };
+use vf_measurement::{ QuantityValue, checked_sum };
+use vf_actions::ensure_flow_action;
 
 use vf_attributes_hdk::{
     AgentAddress,
+    PlanAddress,
     FulfillmentAddress,
     SatisfactionAddress,
 };
@@ -39,18 +47,27 @@ use hc_zome_rea_commitment_storage_consts::*;
 use hc_zome_rea_commitment_storage::*;
 use hc_zome_rea_commitment_rpc::*;
 
+use hc_zome_rea_process_lib_planning::reindex_planned_flows;
 use hc_zome_rea_process_storage_consts::{PROCESS_COMMITMENT_INPUTS_LINK_TAG, PROCESS_COMMITMENT_OUTPUTS_LINK_TAG};
-use hc_zome_rea_fulfillment_storage_consts::{FULFILLMENT_FULFILLS_LINK_TAG};
+use hc_zome_rea_fulfillment_storage_consts::{FULFILLMENT_ENTRY_TYPE, FULFILLMENT_FULFILLS_LINK_TAG};
+use hc_zome_rea_fulfillment_storage::{
+    EntryData as FulfillmentData,
+    EntryStorage as FulfillmentStorage,
+};
 use hc_zome_rea_satisfaction_storage_consts::{SATISFACTION_SATISFIEDBY_LINK_TAG};
 use hc_zome_rea_agreement_storage_consts::{AGREEMENT_COMMITMENTS_LINK_TAG};
 
 // :SHONK: needed to re-export for zome `entry_defs()` where macro-assigned defs are overridden
 pub use hdk_records::CAP_STORAGE_ENTRY_DEF_ID;
 
+pub mod promotion;
+pub use promotion::handle_commit_to_intent;
+
 pub fn handle_create_commitment<S>(entry_def_id: S, commitment: CreateRequest) -> RecordAPIResult<ResponseData>
     where S: AsRef<str>
 {
     let (header_addr, base_address, entry_resp): (_,_, EntryData) = create_record(&entry_def_id, commitment.to_owned())?;
+    ensure_flow_action(&entry_resp.action, entry_resp.input_of.to_owned(), entry_resp.output_of.to_owned())?;
 
     // handle link fields
     if let CreateRequest { input_of: MaybeUndefined::Some(input_of), .. } = &commitment {
@@ -80,6 +97,28 @@ pub fn handle_create_commitment<S>(entry_def_id: S, commitment: CreateRequest) -
             vec![clause_of.clone()].as_slice(),
         )?;
     };
+    if let CreateRequest { plan: MaybeUndefined::Some(plan), .. } = &commitment {
+        let _results = create_foreign_index(
+            read_foreign_index_zome,
+            &COMMITMENT_PLAN_INDEXING_API_METHOD,
+            &base_address,
+            read_foreign_plan_index_zome,
+            &PLAN_COMMITMENTS_INDEXING_API_METHOD,
+            plan,
+        )?;
+    };
+    if let CreateRequest { independent_demand_of: MaybeUndefined::Some(plan), .. } = &commitment {
+        let _results = create_foreign_index(
+            read_foreign_index_zome,
+            &COMMITMENT_INDEPENDENT_DEMAND_INDEXING_API_METHOD,
+            &base_address,
+            read_foreign_plan_index_zome,
+            &PLAN_INDEPENDENT_DEMANDS_INDEXING_API_METHOD,
+            plan,
+        )?;
+    };
+
+    reindex_planned_flows(&[&entry_resp.input_of, &entry_resp.output_of])?;
 
     // :TODO: pass results from link creation rather than re-reading
     construct_response(&base_address, &header_addr, &entry_resp, get_link_fields(&base_address)?)
@@ -96,7 +135,8 @@ pub fn handle_update_commitment<S>(entry_def_id: S, commitment: UpdateRequest) -
     where S: AsRef<str>
 {
     let address = commitment.get_revision_id().to_owned();
-    let (revision_id, base_address, new_entry, _prev_entry): (_, CommitmentAddress, EntryData, EntryData) = update_record(&entry_def_id, &address, commitment.to_owned())?;
+    let (revision_id, base_address, new_entry, prev_entry): (_, CommitmentAddress, EntryData, EntryData) = update_record(&entry_def_id, &address, commitment.to_owned())?;
+    ensure_flow_action(&new_entry.action, new_entry.input_of.to_owned(), new_entry.output_of.to_owned())?;
 
     // handle link fields
     // :TODO: revise this logic; it creates dangling pointers. Need to check old record and ignore unchanged value, delete on removal.
@@ -131,34 +171,88 @@ pub fn handle_update_commitment<S>(entry_def_id: S, commitment: UpdateRequest) -
             vec![].as_slice(),
         );
     }
+    if new_entry.plan != prev_entry.plan {
+        let _results = update_foreign_index(
+            read_foreign_index_zome,
+            &COMMITMENT_PLAN_INDEXING_API_METHOD,
+            &base_address,
+            read_foreign_plan_index_zome,
+            &PLAN_COMMITMENTS_INDEXING_API_METHOD,
+            new_entry.plan.iter().cloned().collect::<Vec<PlanAddress>>().as_slice(),
+            prev_entry.plan.iter().cloned().collect::<Vec<PlanAddress>>().as_slice(),
+        )?;
+    }
+    if new_entry.independent_demand_of != prev_entry.independent_demand_of {
+        let _results = update_foreign_index(
+            read_foreign_index_zome,
+            &COMMITMENT_INDEPENDENT_DEMAND_INDEXING_API_METHOD,
+            &base_address,
+            read_foreign_plan_index_zome,
+            &PLAN_INDEPENDENT_DEMANDS_INDEXING_API_METHOD,
+            new_entry.independent_demand_of.iter().cloned().collect::<Vec<PlanAddress>>().as_slice(),
+            prev_entry.independent_demand_of.iter().cloned().collect::<Vec<PlanAddress>>().as_slice(),
+        )?;
+    }
+
+    reindex_planned_flows(&[&new_entry.input_of, &new_entry.output_of, &prev_entry.input_of, &prev_entry.output_of])?;
+
+    construct_response(&base_address, &revision_id, &new_entry, get_link_fields(&base_address)?)
+}
+
+/// Recalculate the fulfillment progress of a `Commitment` after its `Fulfillment`s have changed,
+/// marking it as finished if fully fulfilled and the zome is configured to do so, or reverting
+/// that if it is no longer fully fulfilled.
+///
+pub fn handle_recompute_fulfillment<S>(entry_def_id: S, address: CommitmentAddress) -> RecordAPIResult<ResponseData>
+    where S: AsRef<str>
+{
+    let (revision, base_address, entry) = read_record_entry::<EntryData, EntryStorage, _,_>(&entry_def_id, address.as_ref())?;
+    let fulfillments: Vec<FulfillmentAddress> = read_foreign_index(read_foreign_index_zome, &COMMITMENT_FULFILLEDBY_READ_API_METHOD, &base_address)?;
+
+    let progress = FulfillmentProgress {
+        fulfilled_quantity: sum_fulfillments(&entry, &fulfillments)?,
+        finish_when_fulfilled: should_finish_when_fulfilled()?,
+    };
+    let next = entry.update_with(progress.to_owned());
+    if next.fulfilled_quantity == entry.fulfilled_quantity && next.finished == entry.finished {
+        return construct_response(&base_address, &revision, &entry, get_link_fields(&base_address)?);
+    }
 
+    let (revision_id, base_address, new_entry, _prev_entry): (_, CommitmentAddress, EntryData, EntryData) = update_record(&entry_def_id, &revision, progress)?;
     construct_response(&base_address, &revision_id, &new_entry, get_link_fields(&base_address)?)
 }
 
-pub fn handle_delete_commitment(revision_id: RevisionHash) -> RecordAPIResult<bool>
+/// Indexes of other records referencing a `Commitment`, which prevent its deletion
+const INBOUND_LINK_TAGS: &[&str] = &[
+    COMMITMENT_FULFILLEDBY_LINK_TAG,
+];
+
+pub fn handle_delete_commitment(revision_id: RevisionHash, force: bool) -> RecordAPIResult<bool>
 {
     // load the record to ensure it is of the correct type
-    let (base_address, entry) = read_record_entry_by_header::<EntryData, EntryStorage, _>(&revision_id)?;
+    let (base_address, entry): (CommitmentAddress, EntryData) = read_record_entry_by_header::<EntryData, EntryStorage, _>(&revision_id)?;
+
+    ensure_deletable(&COMMITMENT_ENTRY_TYPE, &base_address, INBOUND_LINK_TAGS, force)?;
 
     // handle link fields
-    if let Some(process_address) = entry.input_of {
+    if let Some(process_address) = &entry.input_of {
         let _results = update_remote_index(
             read_foreign_index_zome,
             &COMMITMENT_INPUT_INDEXING_API_METHOD,
             &base_address,
             &PROCESS_INPUT_INDEXING_API_METHOD,
             vec![].as_slice(),
-            vec![process_address].as_slice(),
+            vec![process_address.to_owned()].as_slice(),
         );
     }
-    if let Some(process_address) = entry.output_of {
+    if let Some(process_address) = &entry.output_of {
         let _results = update_remote_index(
             read_foreign_index_zome,
             &COMMITMENT_OUTPUT_INDEXING_API_METHOD,
             &base_address,
             &PROCESS_OUTPUT_INDEXING_API_METHOD,
             vec![].as_slice(),
-            vec![process_address].as_slice(),
+            vec![process_address.to_owned()].as_slice(),
         );
     }
     if let Some(agreement_address) = entry.clause_of {
@@ -171,9 +265,35 @@ pub fn handle_delete_commitment(revision_id: RevisionHash) -> RecordAPIResult<bo
             vec![agreement_address].as_slice(),
         );
     }
+    if let Some(plan_address) = entry.plan {
+        let _results = update_foreign_index(
+            read_foreign_index_zome,
+            &COMMITMENT_PLAN_INDEXING_API_METHOD,
+            &base_address,
+            read_foreign_plan_index_zome,
+            &PLAN_COMMITMENTS_INDEXING_API_METHOD,
+            vec![].as_slice(),
+            vec![plan_address].as_slice(),
+        );
+    }
+    if let Some(plan_address) = entry.independent_demand_of {
+        let _results = update_foreign_index(
+            read_foreign_index_zome,
+            &COMMITMENT_INDEPENDENT_DEMAND_INDEXING_API_METHOD,
+            &base_address,
+            read_foreign_plan_index_zome,
+            &PLAN_INDEPENDENT_DEMANDS_INDEXING_API_METHOD,
+            vec![].as_slice(),
+            vec![plan_address].as_slice(),
+        );
+    }
 
     // delete entry last, as it must be present in order for links to be removed
-    delete_record::<EntryStorage, _>(&revision_id)
+    let deleted = delete_record::<EntryStorage, _>(&revision_id)?;
+
+    reindex_planned_flows(&[&entry.input_of, &entry.output_of])?;
+
+    Ok(deleted)
 }
 
 const READ_FN_NAME: &str = "get_commitment";
@@ -186,8 +306,7 @@ pub fn generate_query_handler<S, C, F>(
     agreement_entry_def_id: S,
 ) -> impl FnOnce(&QueryParams) -> RecordAPIResult<Vec<ResponseData>>
     where S: AsRef<str>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: Fn(C) -> Option<String>,
 {
     move |params| {
@@ -291,6 +410,8 @@ fn construct_response<'a>(
             independent_demand_of: e.independent_demand_of.to_owned(),
             finished: e.finished.to_owned(),
             in_scope_of: e.in_scope_of.to_owned(),
+            fulfilled_quantity: e.fulfilled_quantity.to_owned(),
+            remaining_quantity: e.remaining_quantity(),
             fulfilled_by: fulfillments.to_owned(),
             satisfies: satisfactions.to_owned(),
             involved_agents: involved_agents.to_owned(),
@@ -301,8 +422,13 @@ fn construct_response<'a>(
 //---------------- READ ----------------
 
 /// Properties accessor for zome config
-fn read_foreign_index_zome(conf: DnaConfigSlice) -> Option<String> {
-    Some(conf.commitment.index_zome)
+fn read_foreign_index_zome(conf: CommitmentZomeConfig) -> Option<String> {
+    Some(conf.index_zome)
+}
+
+/// Properties accessor for zome config
+fn read_foreign_plan_index_zome(conf: CommitmentZomeConfig) -> Option<String> {
+    conf.plan_index_zome
 }
 
 // @see construct_response
@@ -317,3 +443,31 @@ fn get_link_fields(commitment: &CommitmentAddress) -> RecordAPIResult<(
         vec![],   // :TODO:
     ))
 }
+
+/// Sum the quantities of all `Fulfillment`s of a commitment, measured by `resource_quantity` where
+/// the commitment has one, otherwise `effort_quantity`.
+///
+/// `None` if the commitment has no quantity, or any fulfillment is measured in units incompatible with it.
+///
+fn sum_fulfillments(e: &EntryData, fulfillments: &[FulfillmentAddress]) -> RecordAPIResult<Option<QuantityValue>> {
+    let measure_effort = e.resource_quantity.is_none();
+    let committed = match e.committed_quantity() {
+        Some(q) => q,
+        None => return Ok(None),
+    };
+
+    let mut delivered = vec![];
+    for address in fulfillments.iter() {
+        let (_, _, fulfillment): (_, FulfillmentAddress, FulfillmentData) = read_record_entry::<FulfillmentData, FulfillmentStorage, _,_>(&FULFILLMENT_ENTRY_TYPE, address.as_ref())?;
+        if let Some(q) = if measure_effort { fulfillment.effort_quantity } else { fulfillment.resource_quantity } {
+            delivered.push(q);
+        }
+    }
+
+    Ok(checked_sum(QuantityValue::new(0.0, committed.get_unit()), delivered))
+}
+
+/// Read the `finish_when_fulfilled` zome config flag
+fn should_finish_when_fulfilled() -> RecordAPIResult<bool> {
+    Ok(zome_config::<CommitmentZomeConfig>()?.finish_when_fulfilled)
+}
@@ -0,0 +1,235 @@
+/**
+ * Intent promotion
+ *
+ * Converts an accepted `Intent` into a `Commitment` between the intent's agent and a
+ * counterparty, linked back to the intent via a `Satisfaction`.
+ *
+ * @package Holo-REA
+ */
+use hdk::prelude::*;
+use hdk_records::{
+    RecordAPIResult, DataIntegrityError,
+    MaybeUndefined,
+    records::read_record_entry,
+    rpc::call_local_zome_method,
+};
+use vf_measurement::{ QuantityValue, checked_subtract };
+
+use hc_zome_rea_commitment_storage_consts::*;
+use hc_zome_rea_commitment_storage::*;
+use hc_zome_rea_commitment_rpc::*;
+use crate::{ handle_create_commitment, handle_get_commitment, handle_delete_commitment };
+
+use hc_zome_rea_intent_storage_consts::INTENT_ENTRY_TYPE;
+use hc_zome_rea_intent_storage::{
+    EntryData as IntentData,
+    EntryStorage as IntentStorage,
+};
+use hc_zome_rea_satisfaction_rpc::{
+    CreateParams as SatisfactionCreateParams,
+    CreateRequest as SatisfactionCreateRequest,
+    ByHeader as SatisfactionDeleteRequest,
+    ResponseData as SatisfactionResponse,
+};
+
+/// Properties accessor for zome config
+fn read_satisfaction_zome(conf: CommitmentZomeConfig) -> Option<String> {
+    conf.satisfaction_zome
+}
+
+/// Create a `Commitment` from the fields of an `Intent`, with `counterparty` taking up whichever
+/// of provider or receiver the intent leaves open, and record it as satisfying the intent.
+///
+/// The intent's availability is checked before anything is written. If any later step fails, the
+/// records already written are removed again, returning any quantity reserved from the intent.
+///
+pub fn handle_commit_to_intent<S>(entry_def_id: S, request: CommitToIntentRequest) -> RecordAPIResult<ResponseData>
+    where S: AsRef<str>
+{
+    let (_, intent_address, intent): (_, IntentAddress, IntentData) = read_record_entry::<IntentData, IntentStorage, _,_>(&INTENT_ENTRY_TYPE, request.intent.as_ref())?;
+
+    if intent.finished {
+        return Err(DataIntegrityError::RecordFinished);
+    }
+    if intent.provider.is_some() && intent.receiver.is_some() {
+        return Err(DataIntegrityError::ValidationFailed("Intent already has both a provider and a receiver, leaving no side for a counterparty to take up".to_string()));
+    }
+
+    let resource_quantity = request.resource_quantity.to_owned()
+        .or_else(|| intent.available_quantity.to_owned())
+        .or_else(|| intent.resource_quantity.to_owned());
+    ensure_available(&intent, &resource_quantity)?;
+
+    let effort_quantity = request.effort_quantity.to_owned().or_else(|| intent.effort_quantity.to_owned());
+
+    let created = handle_create_commitment(&entry_def_id, CreateRequest {
+        action: intent.action.to_owned(),
+        note: request.note.to_owned().or_else(|| intent.note.to_owned()).into(),
+        input_of: intent.input_of.to_owned().into(),
+        output_of: intent.output_of.to_owned().into(),
+        provider: intent.provider.to_owned().unwrap_or_else(|| request.counterparty.to_owned()),
+        receiver: intent.receiver.to_owned().unwrap_or_else(|| request.counterparty.to_owned()),
+        resource_inventoried_as: intent.resource_inventoried_as.to_owned().into(),
+        resource_classified_as: intent.resource_classified_as.to_owned().into(),
+        resource_conforms_to: intent.resource_conforms_to.to_owned().into(),
+        resource_quantity: resource_quantity.to_owned().into(),
+        effort_quantity: effort_quantity.to_owned().into(),
+        has_beginning: intent.has_beginning.to_owned().into(),
+        has_end: intent.has_end.to_owned().into(),
+        has_point_in_time: intent.has_point_in_time.to_owned().into(),
+        due: intent.due.to_owned().into(),
+        at_location: intent.at_location.to_owned().into(),
+        plan: request.plan.to_owned().into(),
+        agreed_in: intent.agreed_in.to_owned().into(),
+        clause_of: MaybeUndefined::Undefined,
+        independent_demand_of: MaybeUndefined::Undefined,
+        finished: MaybeUndefined::Some(false),
+        in_scope_of: intent.in_scope_of.to_owned().into(),
+    })?.commitment;
+
+    let (commitment_id, commitment_revision) = (created.id, created.revision_id);
+    complete_promotion(
+        // satisfaction creation also reserves quantity from the intent, and releases it again if it fails
+        || {
+            let satisfied: SatisfactionResponse = call_local_zome_method(
+                read_satisfaction_zome,
+                &SATISFACTION_CREATE_API_METHOD,
+                SatisfactionCreateParams { satisfaction: SatisfactionCreateRequest {
+                    satisfied_by: commitment_id.to_owned().into(),
+                    satisfies: intent_address,
+                    resource_quantity: resource_quantity.into(),
+                    effort_quantity: effort_quantity.into(),
+                    note: MaybeUndefined::Undefined,
+                } },
+            )?;
+            Ok(satisfied.satisfaction.revision_id)
+        },
+        || handle_get_commitment(&entry_def_id, commitment_id.to_owned()),
+        delete_satisfaction,
+        || handle_delete_commitment(commitment_revision, true),
+    )
+}
+
+/// Record the newly created commitment as satisfying the intent and read back the result. If either
+/// step fails, the satisfaction (if written) and then the commitment are deleted again.
+///
+fn complete_promotion<T, S, R, DS, DC>(
+    satisfy: S,
+    read: R,
+    delete_satisfaction: DS,
+    delete_commitment: DC,
+) -> RecordAPIResult<T>
+    where S: FnOnce() -> RecordAPIResult<RevisionHash>,
+        R: FnOnce() -> RecordAPIResult<T>,
+        DS: FnOnce(RevisionHash) -> RecordAPIResult<bool>,
+        DC: FnOnce() -> RecordAPIResult<bool>,
+{
+    let satisfaction = match satisfy() {
+        Ok(revision_id) => revision_id,
+        Err(e) => return Err(e.with_rollback_failures(delete_commitment().err().into_iter().collect())),
+    };
+
+    read().map_err(|e| {
+        // deleting the satisfaction returns its reservation to the intent
+        let mut failures: Vec<DataIntegrityError> = delete_satisfaction(satisfaction).err().into_iter().collect();
+        failures.extend(delete_commitment().err());
+        e.with_rollback_failures(failures)
+    })
+}
+
+/// Remove a `Satisfaction` created while promoting an intent
+fn delete_satisfaction(revision_id: RevisionHash) -> RecordAPIResult<bool> {
+    Ok(call_local_zome_method(
+        read_satisfaction_zome,
+        &SATISFACTION_DELETE_API_METHOD,
+        SatisfactionDeleteRequest { address: revision_id },
+    )?)
+}
+
+/// Reject quantities exceeding what remains available from an `Intent`
+fn ensure_available(intent: &IntentData, requested: &Option<QuantityValue>) -> RecordAPIResult<()> {
+    match (&intent.available_quantity, requested) {
+        (Some(available), Some(requested)) => {
+            let remaining = checked_subtract(available.to_owned(), requested.to_owned()).ok_or(DataIntegrityError::IncompatibleUnits)?;
+            if remaining.get_numerical_value() < 0.0 {
+                return Err(DataIntegrityError::InsufficientQuantity(requested.get_numerical_value(), available.get_numerical_value()));
+            }
+            Ok(())
+        },
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn revision(byte: u8) -> RevisionHash {
+        RevisionHash(DnaHash::from_raw_36(vec![0xdb; 36]), HeaderHash::from_raw_36(vec![byte; 36]))
+    }
+
+    fn failed<T>() -> RecordAPIResult<T> {
+        Err(DataIntegrityError::EmptyQuery)
+    }
+
+    #[test]
+    fn test_promotion_success_keeps_records() {
+        let deleted = RefCell::new(vec![]);
+        let result = complete_promotion(
+            || Ok(revision(2)),
+            || Ok("commitment"),
+            |_| { deleted.borrow_mut().push("satisfaction"); Ok(true) },
+            || { deleted.borrow_mut().push("commitment"); Ok(true) },
+        );
+
+        assert_eq!(result.unwrap(), "commitment");
+        assert!(deleted.into_inner().is_empty());
+    }
+
+    #[test]
+    fn test_failed_satisfaction_removes_commitment() {
+        let deleted = RefCell::new(vec![]);
+        let result: RecordAPIResult<()> = complete_promotion(
+            failed,
+            || Ok(()),
+            |_| { deleted.borrow_mut().push("satisfaction"); Ok(true) },
+            || { deleted.borrow_mut().push("commitment"); Ok(true) },
+        );
+
+        assert!(matches!(result, Err(DataIntegrityError::EmptyQuery)), "original error is returned when rollback succeeds");
+        assert_eq!(deleted.into_inner(), vec!["commitment"]);
+    }
+
+    #[test]
+    fn test_failed_read_removes_satisfaction_then_commitment() {
+        let deleted = RefCell::new(vec![]);
+        let result: RecordAPIResult<()> = complete_promotion(
+            || Ok(revision(2)),
+            failed,
+            |revision_id| { assert_eq!(revision_id, revision(2)); deleted.borrow_mut().push("satisfaction"); Ok(true) },
+            || { deleted.borrow_mut().push("commitment"); Ok(true) },
+        );
+
+        assert!(matches!(result, Err(DataIntegrityError::EmptyQuery)));
+        assert_eq!(deleted.into_inner(), vec!["satisfaction", "commitment"]);
+    }
+
+    #[test]
+    fn test_rollback_failures_are_reported() {
+        let result: RecordAPIResult<()> = complete_promotion(
+            || Ok(revision(2)),
+            failed,
+            |_| Err(DataIntegrityError::RecordFinished),
+            || Ok(true),
+        );
+
+        match result {
+            Err(DataIntegrityError::RollbackFailed(cause, failures)) => {
+                assert!(matches!(*cause, DataIntegrityError::EmptyQuery));
+                assert_eq!(failures.len(), 1);
+            },
+            other => panic!("expected RollbackFailed, got {:?}", other),
+        }
+    }
+}
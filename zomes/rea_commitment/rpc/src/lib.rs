@@ -11,7 +11,7 @@ use holochain_serialized_bytes::prelude::*;
 use serde_maybe_undefined::{MaybeUndefined, default_false};
 use vf_measurement::QuantityValue;
 pub use vf_attributes_hdk::{
-    RevisionHash,
+    RevisionHash, DeleteRequest,
     ActionId,
     DateTime, FixedOffset,
     ExternalURL,
@@ -24,6 +24,7 @@ pub use vf_attributes_hdk::{
     AgreementAddress,
     FulfillmentAddress,
     SatisfactionAddress,
+    IntentAddress,
 };
 
 //---------------- EXTERNAL RECORD STRUCTURE ----------------
@@ -80,6 +81,14 @@ pub struct Response {
 
     pub finished: bool,
 
+    // DERIVED FIELDS
+    /// Total quantity delivered by all `Fulfillment`s, measured against `resource_quantity`
+    /// if present, otherwise `effort_quantity`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fulfilled_quantity: Option<QuantityValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining_quantity: Option<QuantityValue>,
+
     // LINK FIELDS
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
@@ -218,6 +227,28 @@ impl<'a> UpdateRequest {
     // :TODO: accessors for other field data
 }
 
+//---------------- INTENT PROMOTION REQUEST ----------------
+
+/// I/O struct for creating a `Commitment` which satisfies an `Intent`
+///
+#[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitToIntentRequest {
+    pub intent: IntentAddress,
+    /// agent taking up the side of the exchange left open by the intent
+    pub counterparty: AgentAddress,
+    /// defaults to the intent's `available_quantity`, or `resource_quantity` if not tracked
+    #[serde(default)]
+    pub resource_quantity: Option<QuantityValue>,
+    /// defaults to the intent's `effort_quantity`
+    #[serde(default)]
+    pub effort_quantity: Option<QuantityValue>,
+    #[serde(default)]
+    pub plan: Option<PlanAddress>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
 //---------------- QUERY FILTER REQUEST ----------------
 
 #[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
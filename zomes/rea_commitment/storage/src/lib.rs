@@ -9,11 +9,12 @@
 use hdk::prelude::*;
 
 use hdk_records::{
+    config::ZomeConfig,
     MaybeUndefined,
     record_interface::Updateable,
     generate_record_entry,
 };
-use vf_measurement::QuantityValue;
+use vf_measurement::{ QuantityValue, checked_remaining };
 
 pub use vf_attributes_hdk::{
     RevisionHash,
@@ -39,17 +40,19 @@ pub use hdk_records::record_interface::Identified;
 
 //--------------- ZOME CONFIGURATION ATTRIBUTES ----------------
 
-// :TODO: remove this, replace with reference to appropriate namespacing of zome config
-#[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
-pub struct DnaConfigSlice {
-    pub commitment: CommitmentZomeConfig,
-}
-
 #[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct CommitmentZomeConfig {
     pub index_zome: String,
+    pub plan_index_zome: Option<String>,
+    pub satisfaction_zome: Option<String>,
+    /// Mark commitments as finished once their fulfillments meet the committed quantity
+    #[serde(default)]
+    pub finish_when_fulfilled: bool,
 }
 
+impl ZomeConfig for CommitmentZomeConfig {}
+
 //---------------- RECORD INTERNALS & VALIDATION ----------------
 
 #[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
@@ -76,9 +79,31 @@ pub struct EntryData {
     pub finished: bool,
     pub in_scope_of: Option<Vec<String>>,
     pub note: Option<String>,
+    /// Total quantity delivered by `Fulfillment`s, maintained by recomputing whenever they change.
+    /// `None` where fulfillments are measured in units incompatible with the commitment.
+    /// Commitments written before this was tracked report no progress until their next recompute.
+    #[serde(default)]
+    pub fulfilled_quantity: Option<QuantityValue>,
+    /// Set when `finished` was set upon becoming fully fulfilled, so that it can be reverted
+    /// if fulfillments are later removed
+    #[serde(default)]
+    pub finished_by_fulfillment: bool,
 }
 
 impl EntryData {
+    /// Quantity committed to, measured by `resource_quantity` where present, otherwise `effort_quantity`
+    pub fn committed_quantity(&self) -> Option<&QuantityValue> {
+        self.resource_quantity.as_ref().or(self.effort_quantity.as_ref())
+    }
+
+    /// Quantity yet to be fulfilled, if known
+    pub fn remaining_quantity(&self) -> Option<QuantityValue> {
+        match (self.committed_quantity(), &self.fulfilled_quantity) {
+            (Some(committed), Some(fulfilled)) => checked_remaining(committed.to_owned(), fulfilled.to_owned()),
+            _ => None,
+        }
+    }
+
     pub fn validate_action(&self) -> Result<(), String> {
         validate_flow_action(self.action.to_owned(), self.input_of.to_owned(), self.output_of.to_owned())
     }
@@ -104,6 +129,10 @@ generate_record_entry!(EntryData, CommitmentAddress, EntryStorage);
 /// Pick relevant fields out of I/O record into underlying DHT entry
 impl From<CreateRequest> for EntryData {
     fn from(e: CreateRequest) -> EntryData {
+        // progress starts from zero, in the units of the committed quantity
+        let fulfilled_quantity = e.resource_quantity.to_owned().to_option()
+            .or(e.effort_quantity.to_owned().to_option())
+            .map(|q| QuantityValue::new(0.0, q.get_unit()));
         EntryData {
             action: e.action.to_owned(),
             note: e.note.into(),
@@ -127,6 +156,8 @@ impl From<CreateRequest> for EntryData {
             independent_demand_of: e.independent_demand_of.into(),
             finished: e.finished.to_option().unwrap(),  // :NOTE: unsafe, would crash if not for "default_false" binding via Serde
             in_scope_of: e.in_scope_of.into(),
+            fulfilled_quantity,
+            finished_by_fulfillment: false,
         }
     }
 }
@@ -159,6 +190,70 @@ impl Updateable<UpdateRequest> for EntryData {
             finished: if e.finished == MaybeUndefined::Undefined { self.finished.clone() } else { e.finished.clone().to_option().unwrap() },
             in_scope_of: if e.in_scope_of== MaybeUndefined::Undefined { self.in_scope_of.clone() } else { e.in_scope_of.clone().into() },
             note: if e.note== MaybeUndefined::Undefined { self.note.clone() } else { e.note.clone().into() },
+            fulfilled_quantity: self.fulfilled_quantity.to_owned(),
+            finished_by_fulfillment: if e.finished == MaybeUndefined::Undefined { self.finished_by_fulfillment } else { false },
         }
     }
 }
+
+/// Fulfillment totals recomputed from the `Fulfillment`s of a `Commitment`
+#[derive(Clone, Debug)]
+pub struct FulfillmentProgress {
+    pub fulfilled_quantity: Option<QuantityValue>,
+    /// whether the zome is configured to finish commitments once fulfilled
+    pub finish_when_fulfilled: bool,
+}
+
+/// Handles recomputed fulfillment totals, finishing the commitment once fully fulfilled if configured
+/// to do so, and reverting that if it is no longer fully fulfilled
+impl Updateable<FulfillmentProgress> for EntryData {
+    fn update_with(&self, e: FulfillmentProgress) -> EntryData {
+        let next = EntryData {
+            fulfilled_quantity: e.fulfilled_quantity,
+            ..self.to_owned()
+        };
+        let fully_fulfilled = next.remaining_quantity().map(|q| q.get_numerical_value() <= 0.0).unwrap_or(false);
+        let (finished, finished_by_fulfillment) = next_finished_state(
+            self.finished, self.finished_by_fulfillment, fully_fulfilled, e.finish_when_fulfilled,
+        );
+        EntryData { finished, finished_by_fulfillment, ..next }
+    }
+}
+
+/// Determine the `finished` & `finished_by_fulfillment` flags of a commitment after its fulfillment changes.
+/// Commitments finished by other means are left finished.
+///
+fn next_finished_state(finished: bool, finished_by_fulfillment: bool, fully_fulfilled: bool, finish_when_fulfilled: bool) -> (bool, bool) {
+    if !finished && fully_fulfilled && finish_when_fulfilled {
+        (true, true)
+    } else if finished && finished_by_fulfillment && !fully_fulfilled {
+        (false, false)
+    } else {
+        (finished, finished_by_fulfillment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finishes_when_fulfilled_if_configured() {
+        assert_eq!(next_finished_state(false, false, true, true), (true, true));
+        assert_eq!(next_finished_state(false, false, true, false), (false, false), "not finished unless configured");
+        assert_eq!(next_finished_state(false, false, false, true), (false, false), "not finished while partially fulfilled");
+    }
+
+    #[test]
+    fn test_reverts_finish_when_no_longer_fulfilled() {
+        assert_eq!(next_finished_state(true, true, false, true), (false, false));
+        assert_eq!(next_finished_state(true, true, false, false), (false, false), "reverted even if no longer configured");
+        assert_eq!(next_finished_state(true, true, true, true), (true, true), "remains finished while fulfilled");
+    }
+
+    #[test]
+    fn test_manually_finished_commitments_stay_finished() {
+        assert_eq!(next_finished_state(true, false, false, true), (true, false));
+        assert_eq!(next_finished_state(true, false, true, true), (true, false));
+    }
+}
@@ -11,6 +11,8 @@ pub const COMMITMENT_SATISFIES_LINK_TAG: &str = "satisfies";
 pub const COMMITMENT_INPUT_OF_LINK_TAG: &str = "input_of";
 pub const COMMITMENT_OUTPUT_OF_LINK_TAG: &str = "output_of";
 pub const COMMITMENT_CLAUSE_OF_LINK_TAG: &str = "clause_of";
+pub const COMMITMENT_PLAN_LINK_TAG: &str = "plan";
+pub const COMMITMENT_INDEPENDENT_DEMAND_OF_LINK_TAG: &str = "independent_demand_of";
 
 pub const COMMITMENT_FULFILLEDBY_READ_API_METHOD: &str = "_internal_read_commitment_fulfillments";
 pub const COMMITMENT_SATISFIES_READ_API_METHOD: &str = "_internal_read_commitment_satisfactions";
@@ -26,3 +28,11 @@ pub const PROCESS_OUTPUT_INDEXING_API_METHOD: &str = "index_process_output_commi
 pub const COMMITMENT_CLAUSEOF_READ_API_METHOD: &str = "_internal_read_commitment_agreements";
 pub const COMMITMENT_CLAUSEOF_INDEXING_API_METHOD: &str = "_internal_reindex_agreement_clauses";
 pub const AGREEMENT_CLAUSE_INDEXING_API_METHOD: &str = "index_agreement_clauses";
+
+pub const COMMITMENT_PLAN_INDEXING_API_METHOD: &str = "_internal_reindex_plans";
+pub const PLAN_COMMITMENTS_INDEXING_API_METHOD: &str = "_internal_reindex_commitments";
+pub const COMMITMENT_INDEPENDENT_DEMAND_INDEXING_API_METHOD: &str = "_internal_reindex_independent_demand_plans";
+pub const PLAN_INDEPENDENT_DEMANDS_INDEXING_API_METHOD: &str = "_internal_reindex_independent_demands";
+
+pub const SATISFACTION_CREATE_API_METHOD: &str = "create_satisfaction";
+pub const SATISFACTION_DELETE_API_METHOD: &str = "delete_satisfaction";
@@ -12,12 +12,18 @@
  * @since:   2019-02-06
  */
 use hdk::prelude::*;
+use hdk_records::{ ErrorEnvelope, ErrorCode, config::init_with_config };
 
 use hc_zome_rea_commitment_rpc::*;
 use hc_zome_rea_commitment_lib::*;
 use hc_zome_rea_commitment_storage::*;
 use hc_zome_rea_commitment_storage_consts::*;
 
+#[hdk_extern]
+fn init(_: ()) -> ExternResult<InitCallbackResult> {
+    Ok(init_with_config::<CommitmentZomeConfig>())
+}
+
 #[hdk_extern]
 fn validate(validation_data: ValidateData) -> ExternResult<ValidateCallbackResult> {
     let element = validation_data.element;
@@ -33,7 +39,7 @@ fn validate(validation_data: ValidateData) -> ExternResult<ValidateCallbackResul
             record.validate_or_fields()
                 .and_then(|()| { record.validate_action() })
                 .and_then(|()| { Ok(ValidateCallbackResult::Valid) })
-                .or_else(|e| { Ok(ValidateCallbackResult::Invalid(e)) })
+                .or_else(|e| { Ok(ValidateCallbackResult::Invalid(ErrorEnvelope::new(ErrorCode::ValidationFailed, e).to_string())) })
         },
         _ => Ok(ValidateCallbackResult::Valid),
     }
@@ -90,12 +96,17 @@ fn update_commitment(UpdateParams { commitment }: UpdateParams) -> ExternResult<
     Ok(handle_update_commitment(COMMITMENT_ENTRY_TYPE, commitment)?)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct ByHeader {
-    pub address: RevisionHash,
+#[hdk_extern]
+fn commit_to_intent(request: CommitToIntentRequest) -> ExternResult<ResponseData> {
+    Ok(handle_commit_to_intent(COMMITMENT_ENTRY_TYPE, request)?)
+}
+
+#[hdk_extern]
+fn _internal_recompute_fulfillment(ByAddress { address }: ByAddress) -> ExternResult<ResponseData> {
+    Ok(handle_recompute_fulfillment(COMMITMENT_ENTRY_TYPE, address)?)
 }
 
 #[hdk_extern]
-fn delete_commitment(ByHeader { address }: ByHeader) -> ExternResult<bool> {
-    Ok(handle_delete_commitment(address)?)
+fn delete_commitment(DeleteRequest { address, force }: DeleteRequest) -> ExternResult<bool> {
+    Ok(handle_delete_commitment(address, force)?)
 }
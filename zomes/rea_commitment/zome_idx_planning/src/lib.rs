@@ -6,6 +6,7 @@
  */
 use hdk::prelude::*;
 use hdk_records::{
+    config::init_with_config,
     index_retrieval::{ ByAddress, IndexingZomeConfig },
     remote_indexes::{
         RemoteEntryLinkRequest,
@@ -23,18 +24,19 @@ use hc_zome_rea_commitment_storage_consts::*;
 use hc_zome_rea_fulfillment_storage_consts::{FULFILLMENT_ENTRY_TYPE, FULFILLMENT_FULFILLS_LINK_TAG};
 use hc_zome_rea_satisfaction_storage_consts::{SATISFACTION_ENTRY_TYPE, SATISFACTION_SATISFIEDBY_LINK_TAG};
 use hc_zome_rea_process_storage_consts::{ PROCESS_ENTRY_TYPE, PROCESS_COMMITMENT_OUTPUTS_LINK_TAG, PROCESS_COMMITMENT_INPUTS_LINK_TAG };
+use hc_zome_rea_process_rpc::AdjacentProcessesRequest;
 use hc_zome_rea_agreement_storage_consts::{ AGREEMENT_ENTRY_TYPE, AGREEMENT_COMMITMENTS_LINK_TAG };
+use hc_zome_rea_plan_storage_consts::{ PLAN_ENTRY_TYPE, PLAN_COMMITMENTS_LINK_TAG, PLAN_INDEPENDENT_DEMANDS_LINK_TAG };
 
 entry_defs![Path::entry_def()];
 
-// :TODO: obviate this with zome-specific configs
-#[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
-pub struct DnaConfigSlice {
-    pub commitment_index: IndexingZomeConfig,
+#[hdk_extern]
+fn init(_: ()) -> ExternResult<InitCallbackResult> {
+    Ok(init_with_config::<IndexingZomeConfig>())
 }
 
-fn read_index_target_zome(conf: DnaConfigSlice) -> Option<String> {
-    Some(conf.commitment_index.record_storage_zome)
+fn read_index_target_zome(conf: IndexingZomeConfig) -> Option<String> {
+    Some(conf.record_storage_zome)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -145,3 +147,36 @@ fn _internal_reindex_agreement_clauses(indexes: RemoteEntryLinkRequest<Agreement
         AGREEMENT_COMMITMENTS_LINK_TAG, COMMITMENT_CLAUSE_OF_LINK_TAG,
     )?)
 }
+
+#[hdk_extern]
+fn _internal_reindex_plans(indexes: RemoteEntryLinkRequest<PlanAddress, CommitmentAddress>) -> ExternResult<RemoteEntryLinkResponse> {
+    let RemoteEntryLinkRequest { remote_entry, target_entries, removed_entries } = indexes;
+
+    Ok(sync_remote_index(
+        &PLAN_ENTRY_TYPE, &remote_entry,
+        &COMMITMENT_ENTRY_TYPE,
+        target_entries.as_slice(),
+        removed_entries.as_slice(),
+        PLAN_COMMITMENTS_LINK_TAG, COMMITMENT_PLAN_LINK_TAG,
+    )?)
+}
+
+#[hdk_extern]
+fn _internal_reindex_independent_demand_plans(indexes: RemoteEntryLinkRequest<PlanAddress, CommitmentAddress>) -> ExternResult<RemoteEntryLinkResponse> {
+    let RemoteEntryLinkRequest { remote_entry, target_entries, removed_entries } = indexes;
+
+    Ok(sync_remote_index(
+        &PLAN_ENTRY_TYPE, &remote_entry,
+        &COMMITMENT_ENTRY_TYPE,
+        target_entries.as_slice(),
+        removed_entries.as_slice(),
+        PLAN_INDEPENDENT_DEMANDS_LINK_TAG, COMMITMENT_INDEPENDENT_DEMAND_OF_LINK_TAG,
+    )?)
+}
+
+/// Processes adjacent to a `Process` via the planned flows of its `Commitment`s and `Intent`s.
+/// Called from the observation DNA to serve `Process.nextProcesses` & `Process.previousProcesses`.
+#[hdk_extern]
+fn read_adjacent_planned_processes(AdjacentProcessesRequest { address, direction }: AdjacentProcessesRequest) -> ExternResult<Vec<ProcessAddress>> {
+    Ok(hc_zome_rea_process_lib_planning::read_adjacent_planned_processes(&address, direction)?)
+}
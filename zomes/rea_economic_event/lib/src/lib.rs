@@ -9,7 +9,9 @@
 use hdk::prelude::*;
 use hdk_records::{
     RecordAPIResult, OtherCellResult, DataIntegrityError, MaybeUndefined,
+    config::ZomeConfig,
     local_indexes::{
+        read_index,
         query_index,
         query_root_index,
     },
@@ -25,6 +27,7 @@ use hdk_records::{
     rpc::{
         call_local_zome_method,
     },
+    integrity::ensure_deletable,
     records::{
         create_record,
         read_record_entry,
@@ -34,6 +37,11 @@ use hdk_records::{
     },
 };
 
+use vf_actions::{ Action, ProcessType, get_action, ensure_flow_action };
+
+pub mod trace;
+pub use trace::{ trace, track, DEFAULT_TRAVERSAL_DEPTH };
+
 pub use hc_zome_rea_economic_event_storage_consts::*;
 pub use hc_zome_rea_economic_resource_storage_consts::{RESOURCE_ENTRY_TYPE};
 pub use hc_zome_rea_process_storage_consts::{PROCESS_ENTRY_TYPE};
@@ -66,8 +74,8 @@ use hc_zome_rea_process_storage_consts::{ PROCESS_EVENT_INPUTS_LINK_TAG, PROCESS
 use hc_zome_rea_agreement_storage_consts::{ AGREEMENT_EVENTS_LINK_TAG };
 
 /// Properties accessor for zome config.
-fn read_foreign_resource_index_zome(conf: DnaConfigSlice) -> Option<String> {
-    conf.economic_event.economic_resource_index_zome
+fn read_foreign_resource_index_zome(conf: EconomicEventZomeConfig) -> Option<String> {
+    conf.economic_resource_index_zome
 }
 
 // API gateway entrypoints. All methods must accept parameters by value.
@@ -81,6 +89,17 @@ pub fn handle_create_economic_event<S>(
     let mut resources_affected: Vec<(RevisionHash, ResourceAddress, EconomicResourceData, EconomicResourceData)> = vec![];
     let mut resource_created: Option<(RevisionHash, ResourceAddress, EconomicResourceData)> = None;
 
+    // ensure paired actions (eg. accept / modify, pickup / dropoff) are balanced before writing anything
+    validate_paired_action(
+        &entry_def_id, &process_entry_def_id,
+        event.get_action(), event.output_of.to_owned().to_option().as_ref(),
+        PairedResource::identify(
+            event.resource_inventoried_as.to_owned().to_option().as_ref(),
+            event.resource_conforms_to.to_owned().to_option().as_ref(),
+        ),
+        None,
+    )?;
+
     // if the event observes a new resource, create that resource & return it in the response
     if let Some(economic_resource) = new_inventoried_resource {
         let new_resource = handle_create_inventory_from_event(
@@ -121,9 +140,7 @@ pub fn handle_create_economic_event<S>(
         Some((resource_revision_id, resource_addr, resource_entry)) => {
             construct_response_with_resource(
                 &event_address, &revision_id, &event_entry, get_link_fields(&event_address)?,
-                Some(resource_addr.clone()), &resource_revision_id, resource_entry, get_resource_link_fields(
-                    &entry_def_id, &process_entry_def_id, &resource_addr
-                )?
+                Some(resource_addr.clone()), &resource_revision_id, resource_entry, get_resource_link_fields(&resource_addr)?
             )
         },
         None => {
@@ -140,20 +157,41 @@ pub fn handle_get_economic_event<S>(entry_def_id: S, address: EventAddress) -> R
     construct_response(&base_address, &revision, &entry, get_link_fields(&address)?)
 }
 
-pub fn handle_update_economic_event<S>(entry_def_id: S, event: EconomicEventUpdateRequest) -> RecordAPIResult<ResponseData>
+pub fn handle_update_economic_event<S>(entry_def_id: S, process_entry_def_id: S, event: EconomicEventUpdateRequest) -> RecordAPIResult<ResponseData>
     where S: AsRef<str>
 {
     let address = event.get_revision_id().to_owned();
     let (revision_id, identity_address, new_entry, _prev_entry): (_, EventAddress, EntryData, EntryData) = update_record(&entry_def_id, &address, event)?;
 
+    // ensure the updated event still pairs with an input; a failure here rolls back the update
+    validate_paired_action(
+        &entry_def_id, &process_entry_def_id,
+        new_entry.action.as_ref(), new_entry.output_of.as_ref(), PairedResource::of_entry(&new_entry),
+        Some(&identity_address),
+    )?;
+
     // :TODO: optimise this- should pass results from `replace_direct_index` instead of retrieving from `get_link_fields` where updates
     construct_response(&identity_address, &revision_id, &new_entry, get_link_fields(&identity_address)?)
 }
 
-pub fn handle_delete_economic_event(revision_id: RevisionHash) -> RecordAPIResult<bool>
+/// Indexes of other records referencing an `EconomicEvent`, which prevent its deletion
+const INBOUND_LINK_TAGS: &[&str] = &[
+    EVENT_FULFILLS_LINK_TAG,
+    EVENT_SATISFIES_LINK_TAG,
+];
+
+pub fn handle_delete_economic_event<S>(entry_def_id: S, process_entry_def_id: S, revision_id: RevisionHash, force: bool) -> RecordAPIResult<bool>
+    where S: AsRef<str>
 {
     // read any referencing indexes
-    let (base_address, entry) = read_record_entry_by_header::<EntryData, EntryStorage, _>(&revision_id)?;
+    let (base_address, entry): (EventAddress, EntryData) = read_record_entry_by_header::<EntryData, EntryStorage, _>(&revision_id)?;
+
+    ensure_deletable(&EVENT_ENTRY_TYPE, &base_address, INBOUND_LINK_TAGS, force)?;
+
+    // paired output events (eg. `modify` after `accept`) must not be left without their input
+    if !force {
+        validate_paired_input_removal(&entry_def_id, &process_entry_def_id, &base_address, &entry)?;
+    }
 
     // handle link fields
     if let Some(process_address) = entry.input_of {
@@ -207,15 +245,15 @@ pub fn handle_get_all_economic_events<S>(entry_def_id: S) -> RecordAPIResult<Vec
 // API logic handlers
 
 /// Properties accessor for zome config.
-fn read_foreign_index_zome(conf: DnaConfigSlice) -> Option<String> {
-    Some(conf.economic_event.index_zome)
+fn read_foreign_index_zome(conf: EconomicEventZomeConfig) -> Option<String> {
+    Some(conf.index_zome)
 }
 /// Properties accessor for zome config.
 ///
 /// :TODO: should this be configurable as an array, to allow shared process planning spaces to be driven by multiple event logs?
 ///
-fn read_foreign_process_index_zome(conf: DnaConfigSlice) -> Option<String> {
-    conf.economic_event.process_index_zome
+fn read_foreign_process_index_zome(conf: EconomicEventZomeConfig) -> Option<String> {
+    conf.process_index_zome
 }
 
 fn handle_create_economic_event_record<S>(entry_def_id: S, event: &EconomicEventCreateRequest, resource_address: Option<ResourceAddress>,
@@ -229,6 +267,7 @@ fn handle_create_economic_event_record<S>(entry_def_id: S, event: &EconomicEvent
             None => event.to_owned(),
         }
     )?;
+    ensure_flow_action(&entry_resp.action, entry_resp.input_of.to_owned(), entry_resp.output_of.to_owned())?;
 
     // handle link fields
     // :TODO: propagate errors
@@ -265,12 +304,197 @@ fn handle_create_economic_event_record<S>(entry_def_id: S, event: &EconomicEvent
     Ok((revision_id, base_address, entry_resp))
 }
 
+/// Identity of the resource an event acts upon, used to pair input & output events.
+/// Inventoried resources are identified by address; otherwise by `ResourceSpecification`.
+///
+#[derive(Debug, Clone, PartialEq)]
+enum PairedResource {
+    Inventoried(ResourceAddress),
+    Conforming(ResourceSpecificationAddress),
+}
+
+impl PairedResource {
+    fn identify(inventoried_as: Option<&ResourceAddress>, conforms_to: Option<&ResourceSpecificationAddress>) -> Option<Self> {
+        match (inventoried_as, conforms_to) {
+            (Some(resource), _) => Some(PairedResource::Inventoried(resource.to_owned())),
+            (None, Some(spec)) => Some(PairedResource::Conforming(spec.to_owned())),
+            (None, None) => None,
+        }
+    }
+
+    fn of_entry(e: &EntryData) -> Option<Self> {
+        Self::identify(e.resource_inventoried_as.as_ref(), e.resource_conforms_to.as_ref())
+    }
+}
+
+/// Minimal view of an event already linked to a `Process`, as needed to check action pairing
+///
+#[derive(Debug, Clone)]
+struct PairedEvent {
+    action_id: String,
+    pairs_with: String,
+    resource: Option<PairedResource>,
+}
+
+impl PairedEvent {
+    fn is_action_for(&self, action_id: &str, resource: &PairedResource) -> bool {
+        self.action_id == action_id && self.resource.as_ref() == Some(resource)
+    }
+}
+
+/// Ensure that an output event with a paired action (eg. `modify`, `dropoff`, `pass`, `fail`)
+/// corresponds to a prior input event of the paired action (`accept`, `pickup`) for the same resource
+/// in the same `Process`. Each input may be matched by at most one output of each paired action,
+/// so `accept` followed by `pass` and then `modify` is permitted but a second `modify` is not.
+///
+/// `exclude` omits the event being validated from the stored `Process` events, when re-checking
+/// an event that has already been written.
+///
+/// Input events and actions without a pairing are always permitted.
+///
+fn validate_paired_action<S>(
+    entry_def_id: S, process_entry_def_id: S,
+    action_id: &str, output_of: Option<&ProcessAddress>, resource: Option<PairedResource>,
+    exclude: Option<&EventAddress>,
+) -> RecordAPIResult<()>
+    where S: AsRef<str>
+{
+    let action = match get_action(action_id)? {
+        Some(action) => action,
+        None => return Ok(()), // unknown actions are rejected by entry validation
+    };
+    if action.input_output != ProcessType::Output || action.pairs_with == "notApplicable" {
+        return Ok(());
+    }
+    let process = match output_of {
+        Some(process) => process,
+        None => return Ok(()), // missing process is rejected by entry validation
+    };
+
+    let inputs = read_process_events(&entry_def_id, &process_entry_def_id, process, &PROCESS_EVENT_INPUTS_LINK_TAG, exclude)?;
+    let outputs = read_process_events(&entry_def_id, &process_entry_def_id, process, &PROCESS_EVENT_OUTPUTS_LINK_TAG, exclude)?;
+
+    check_paired_output(&action, resource.as_ref(), &inputs, &outputs)
+        .map_err(|reason| DataIntegrityError::ValidationFailed(format!("{} in Process {:?}", reason, process)))
+}
+
+/// Ensure that removing an input event with a paired action (eg. `accept`, `pickup`) from its `Process`
+/// leaves an input for every output event which was matched against it.
+///
+fn validate_paired_input_removal<S>(
+    entry_def_id: S, process_entry_def_id: S,
+    address: &EventAddress, entry: &EntryData,
+) -> RecordAPIResult<()>
+    where S: AsRef<str>
+{
+    let action = match get_action(entry.action.as_ref())? {
+        Some(action) => action,
+        None => return Ok(()),
+    };
+    if action.input_output != ProcessType::Input || action.pairs_with == "notApplicable" {
+        return Ok(());
+    }
+    let (process, resource) = match (&entry.input_of, PairedResource::of_entry(entry)) {
+        (Some(process), Some(resource)) => (process, resource),
+        _ => return Ok(()), // events without a process or resource identity are never paired
+    };
+
+    let inputs = read_process_events(&entry_def_id, &process_entry_def_id, process, &PROCESS_EVENT_INPUTS_LINK_TAG, Some(address))?;
+    let outputs = read_process_events(&entry_def_id, &process_entry_def_id, process, &PROCESS_EVENT_OUTPUTS_LINK_TAG, None)?;
+
+    check_paired_input_removal(&action, &resource, &inputs, &outputs)
+        .map_err(|reason| DataIntegrityError::ValidationFailed(format!("{} in Process {:?}", reason, process)))
+}
+
+/// Read the events linked to a `Process` under `link_tag`, omitting `exclude` if provided
+///
+fn read_process_events<S>(
+    entry_def_id: S, process_entry_def_id: S,
+    process: &ProcessAddress, link_tag: &str,
+    exclude: Option<&EventAddress>,
+) -> RecordAPIResult<Vec<PairedEvent>>
+    where S: AsRef<str>
+{
+    let addresses: Vec<EventAddress> = read_index(&process_entry_def_id, process, link_tag)?;
+
+    let mut events = vec![];
+    for addr in addresses.iter().filter(|a| Some(*a) != exclude) {
+        let (_, _, e) = read_record_entry::<EntryData, EntryStorage, _,_>(&entry_def_id, addr.as_ref())?;
+        if let Some(e_action) = get_action(e.action.as_ref())? {
+            events.push(PairedEvent {
+                action_id: e_action.id,
+                pairs_with: e_action.pairs_with,
+                resource: PairedResource::of_entry(&e),
+            });
+        }
+    }
+    Ok(events)
+}
+
+/// Pairing rule for a new output event of a paired `action` against the other events of its `Process`.
+/// Inputs of the paired action are matched against prior outputs of the *same* action only.
+///
+fn check_paired_output(
+    action: &Action, resource: Option<&PairedResource>,
+    inputs: &[PairedEvent], outputs: &[PairedEvent],
+) -> Result<(), String> {
+    let resource = resource.ok_or_else(|| format!(
+        "EconomicEvent of '{}' action must identify its resource via resourceInventoriedAs or resourceConformsTo to be paired with a '{}' input event",
+        action.id, action.pairs_with,
+    ))?;
+
+    let paired_inputs = inputs.iter().filter(|e| e.is_action_for(&action.pairs_with, resource)).count();
+    let matched_outputs = outputs.iter().filter(|e| e.is_action_for(&action.id, resource)).count();
+
+    if paired_inputs == 0 {
+        return Err(format!(
+            "EconomicEvent of '{}' action requires a prior '{}' input event for the same resource",
+            action.id, action.pairs_with,
+        ));
+    }
+    if matched_outputs >= paired_inputs {
+        return Err(format!(
+            "EconomicEvent of '{}' action has no unmatched '{}' input event for the same resource ({} inputs already matched)",
+            action.id, action.pairs_with, matched_outputs,
+        ));
+    }
+    Ok(())
+}
+
+/// Pairing rule for removing an input event of a paired `action` from its `Process`.
+/// `remaining_inputs` must not include the event being removed.
+///
+fn check_paired_input_removal(
+    action: &Action, resource: &PairedResource,
+    remaining_inputs: &[PairedEvent], outputs: &[PairedEvent],
+) -> Result<(), String> {
+    let remaining = remaining_inputs.iter().filter(|e| e.is_action_for(&action.id, resource)).count();
+
+    let mut output_actions: Vec<&str> = outputs.iter()
+        .filter(|e| e.pairs_with == action.id && e.resource.as_ref() == Some(resource))
+        .map(|e| e.action_id.as_str())
+        .collect();
+    output_actions.sort();
+    output_actions.dedup();
+
+    for output_action in output_actions {
+        let matched = outputs.iter().filter(|e| e.is_action_for(output_action, resource)).count();
+        if matched > remaining {
+            return Err(format!(
+                "'{}' input event cannot be removed while {} '{}' output events for the same resource depend on it",
+                action.id, matched, output_action,
+            ));
+        }
+    }
+    Ok(())
+}
+
 /// Properties accessor for zome config.
 ///
 /// :TODO: should this be configurable as an array, to allow multiple inventories to be driven by the same event log?
 ///
-fn read_foreign_resource_zome(conf: DnaConfigSlice) -> Option<String> {
-    conf.economic_event.economic_resource_zome
+fn read_foreign_resource_zome(conf: EconomicEventZomeConfig) -> Option<String> {
+    conf.economic_resource_zome
 }
 
 /// Handle creation of new resources via events + resource metadata
@@ -316,8 +540,7 @@ pub fn generate_query_handler<S, C, F>(
     agreement_entry_def_id: S,
 ) -> impl FnOnce(&QueryParams) -> RecordAPIResult<Vec<ResponseData>>
     where S: AsRef<str>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: Fn(C) -> Option<String>,
 {
     move |params| {
@@ -392,13 +615,9 @@ pub fn construct_response_with_resource<'a>(
     resource_revision_id: &RevisionHash,
     resource: EconomicResourceData, (
         contained_in,
-        stage,
-        state,
         contains,
      ): (
         Option<ResourceAddress>,
-        Option<ProcessSpecificationAddress>,
-        Option<ActionId>,
         Vec<ResourceAddress>,
     ),
 ) -> RecordAPIResult<ResponseData> {
@@ -430,7 +649,7 @@ pub fn construct_response_with_resource<'a>(
             satisfies: satisfactions.to_owned(),
         },
         economic_resource: match resource_address {
-            Some(addr) => Some(construct_resource_response(&addr, &resource_revision_id, &resource, (contained_in, stage, state, contains))?),
+            Some(addr) => Some(construct_resource_response(&addr, &resource_revision_id, &resource, (contained_in, contains))?),
             None => None,
         },
     })
@@ -488,15 +707,112 @@ pub fn get_link_fields(event: &EventAddress) -> RecordAPIResult<(
     ))
 }
 
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vf_actions::get_builtin_action;
+
+    fn dna() -> DnaHash {
+        DnaHash::from_raw_36(vec![0xdb; 36])
+    }
+
+    fn inventoried(byte: u8) -> PairedResource {
+        PairedResource::Inventoried(ResourceAddress(dna(), EntryHash::from_raw_36(vec![byte; 36])))
+    }
+
+    fn conforming(byte: u8) -> PairedResource {
+        PairedResource::Conforming(ResourceSpecificationAddress(dna(), EntryHash::from_raw_36(vec![byte; 36])))
+    }
+
+    fn action(id: &str) -> Action {
+        get_builtin_action(id).unwrap()
+    }
+
+    fn event(id: &str, resource: Option<PairedResource>) -> PairedEvent {
+        let a = action(id);
+        PairedEvent { action_id: a.id, pairs_with: a.pairs_with, resource }
+    }
+
+    #[test]
+    fn test_output_requires_paired_input() {
+        let r = inventoried(1);
+        assert!(check_paired_output(&action("modify"), Some(&r), &[], &[]).is_err());
+        assert!(check_paired_output(&action("modify"), Some(&r), &[event("accept", Some(r.clone()))], &[]).is_ok());
+        assert!(check_paired_output(&action("dropoff"), Some(&r), &[event("accept", Some(r.clone()))], &[]).is_err(), "pickup is required for dropoff");
+    }
+
+    #[test]
+    fn test_outputs_counted_per_action() {
+        let r = inventoried(1);
+        let inputs = [event("accept", Some(r.clone()))];
+
+        // accept -> pass -> modify
+        let outputs = [event("pass", Some(r.clone()))];
+        assert!(check_paired_output(&action("modify"), Some(&r), &inputs, &outputs).is_ok());
+
+        // accept -> pass -> modify -> modify
+        let outputs = [event("pass", Some(r.clone())), event("modify", Some(r.clone()))];
+        assert!(check_paired_output(&action("modify"), Some(&r), &inputs, &outputs).is_err());
+
+        // a second accept permits a second modify
+        let inputs = [event("accept", Some(r.clone())), event("accept", Some(r.clone()))];
+        assert!(check_paired_output(&action("modify"), Some(&r), &inputs, &outputs).is_ok());
+    }
+
+    #[test]
+    fn test_output_requires_resource_identity() {
+        let inputs = [event("accept", None)];
+        assert!(check_paired_output(&action("modify"), None, &inputs, &[]).is_err());
+
+        let r = inventoried(1);
+        assert!(check_paired_output(&action("modify"), Some(&r), &inputs, &[]).is_err(), "input without a resource does not pair");
+    }
+
+    #[test]
+    fn test_output_requires_same_resource() {
+        let inputs = [event("accept", Some(inventoried(1)))];
+        assert!(check_paired_output(&action("modify"), Some(&inventoried(2)), &inputs, &[]).is_err());
+        assert!(check_paired_output(&action("modify"), Some(&conforming(1)), &inputs, &[]).is_err());
+
+        // outputs for other resources do not consume the input
+        let outputs = [event("modify", Some(inventoried(2)))];
+        assert!(check_paired_output(&action("modify"), Some(&inventoried(1)), &inputs, &outputs).is_ok());
+    }
+
+    #[test]
+    fn test_output_paired_by_specification() {
+        let spec = conforming(3);
+        let inputs = [event("pickup", Some(spec.clone()))];
+        assert!(check_paired_output(&action("dropoff"), Some(&spec), &inputs, &[]).is_ok());
+        assert!(check_paired_output(&action("dropoff"), Some(&conforming(4)), &inputs, &[]).is_err());
+    }
 
-    // #[test]
-    // fn test_derived_fields() {
-    //     let e = Entry { note: Some("a note".into()), ..Entry::default() };
-    //     assert_eq!(e.note, Some("a note".into()))
-    // }
+    #[test]
+    fn test_resource_identity() {
+        let resource = ResourceAddress(dna(), EntryHash::from_raw_36(vec![1; 36]));
+        let spec = ResourceSpecificationAddress(dna(), EntryHash::from_raw_36(vec![3; 36]));
+        assert_eq!(PairedResource::identify(None, None), None);
+        assert_eq!(PairedResource::identify(None, Some(&spec)), Some(conforming(3)));
+        assert_eq!(PairedResource::identify(Some(&resource), Some(&spec)), Some(inventoried(1)));
+    }
 
-    // :TODO: unit tests for type conversions... though maybe these should be macro tests, not tests for every single record type
-// }
+    #[test]
+    fn test_input_removal() {
+        let r = inventoried(1);
+        let accept = action("accept");
+
+        // unmatched input
+        assert!(check_paired_input_removal(&accept, &r, &[], &[]).is_ok());
+        // matched input
+        let outputs = [event("modify", Some(r.clone()))];
+        assert!(check_paired_input_removal(&accept, &r, &[], &outputs).is_err());
+        // another input remains for the output
+        assert!(check_paired_input_removal(&accept, &r, &[event("accept", Some(r.clone()))], &outputs).is_ok());
+        // pass & modify each need the remaining input only once
+        let outputs = [event("pass", Some(r.clone())), event("modify", Some(r.clone()))];
+        assert!(check_paired_input_removal(&accept, &r, &[event("accept", Some(r.clone()))], &outputs).is_ok());
+        // outputs for other resources are unaffected
+        let outputs = [event("modify", Some(inventoried(2)))];
+        assert!(check_paired_input_removal(&accept, &r, &[], &outputs).is_ok());
+    }
+}
@@ -0,0 +1,286 @@
+/**
+ * Trace & track algorithms for following the flows of resources through economic networks
+ *
+ * `trace` follows flows backwards (from outputs to the inputs which produced them), whilst
+ * `track` follows them forwards. Both walk the same DNA-local link indexes connecting
+ * `EconomicResource`s, `EconomicEvent`s and `Process`es, guarding against cycles by
+ * never revisiting a node and stopping once `max_depth` hops have been taken or `limit`
+ * nodes have been found.
+ *
+ * Traversals are potentially expensive, and are only run on request via the `trace` & `track`
+ * zome APIs rather than as part of record reads.
+ *
+ * @package Holo-REA
+ */
+use hdk_records::{
+    RecordAPIResult,
+    local_indexes::read_index,
+    records::read_record_entry,
+};
+
+use hc_zome_rea_economic_event_storage_consts::EVENT_ENTRY_TYPE;
+use hc_zome_rea_economic_event_storage::{EntryData, EntryStorage};
+use hc_zome_rea_economic_event_rpc::{
+    EventAddress, ResourceAddress,
+    ProductionFlowItem,
+};
+use hc_zome_rea_economic_resource_storage_consts::{ RESOURCE_ENTRY_TYPE, RESOURCE_AFFECTED_BY_EVENT_LINK_TAG };
+use hc_zome_rea_process_storage_consts::{ PROCESS_ENTRY_TYPE, PROCESS_EVENT_INPUTS_LINK_TAG, PROCESS_EVENT_OUTPUTS_LINK_TAG };
+
+/// Number of hops followed when the caller does not specify a depth limit
+pub const DEFAULT_TRAVERSAL_DEPTH: usize = 32;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Direction {
+    Backward,
+    Forward,
+}
+
+/// Follow flows backwards from `start`, returning upstream nodes in breadth-first order
+pub fn trace(start: ProductionFlowItem, max_depth: Option<usize>, limit: Option<usize>) -> RecordAPIResult<Vec<ProductionFlowItem>> {
+    traverse(start, Direction::Backward, max_depth.unwrap_or(DEFAULT_TRAVERSAL_DEPTH), limit)
+}
+
+/// Follow flows forwards from `start`, returning downstream nodes in breadth-first order
+pub fn track(start: ProductionFlowItem, max_depth: Option<usize>, limit: Option<usize>) -> RecordAPIResult<Vec<ProductionFlowItem>> {
+    traverse(start, Direction::Forward, max_depth.unwrap_or(DEFAULT_TRAVERSAL_DEPTH), limit)
+}
+
+fn traverse(start: ProductionFlowItem, direction: Direction, max_depth: usize, limit: Option<usize>) -> RecordAPIResult<Vec<ProductionFlowItem>> {
+    traverse_with(start, max_depth, limit, |node| adjacent_nodes(node, direction))
+}
+
+/// Breadth-first walk from `start`, loading the neighbours of each node via `adjacent`
+fn traverse_with<F>(start: ProductionFlowItem, max_depth: usize, limit: Option<usize>, adjacent: F) -> RecordAPIResult<Vec<ProductionFlowItem>>
+    where F: Fn(&ProductionFlowItem) -> RecordAPIResult<Vec<ProductionFlowItem>>
+{
+    let mut visited = vec![start.to_owned()];
+    let mut frontier = vec![start];
+    let mut depth = 0;
+    // includes the starting node
+    let max_visited = limit.map(|l| l.saturating_add(1)).unwrap_or(usize::MAX);
+
+    'levels: while !frontier.is_empty() && depth < max_depth {
+        let mut next_frontier = vec![];
+        for node in frontier.iter() {
+            for neighbour in adjacent(node)? {
+                if !visited.contains(&neighbour) {
+                    if visited.len() >= max_visited {
+                        break 'levels;
+                    }
+                    visited.push(neighbour.to_owned());
+                    next_frontier.push(neighbour);
+                }
+            }
+        }
+        frontier = next_frontier;
+        depth += 1;
+    }
+
+    // first element is the starting node
+    Ok(visited.split_off(1))
+}
+
+fn adjacent_nodes(node: &ProductionFlowItem, direction: Direction) -> RecordAPIResult<Vec<ProductionFlowItem>> {
+    match node {
+        ProductionFlowItem::EconomicResource(resource) => {
+            let event_ids: Vec<EventAddress> = read_index(&RESOURCE_ENTRY_TYPE, resource, &RESOURCE_AFFECTED_BY_EVENT_LINK_TAG)?;
+            let mut events = vec![];
+            for event_id in event_ids {
+                let event = read_event(&event_id)?;
+                let matches = match direction {
+                    Direction::Backward => is_incoming_to(&event, resource),
+                    Direction::Forward => is_outgoing_from(&event, resource),
+                };
+                if matches {
+                    events.push(ProductionFlowItem::EconomicEvent(event_id));
+                }
+            }
+            Ok(events)
+        },
+        ProductionFlowItem::EconomicEvent(event_id) => Ok(event_flows(&read_event(event_id)?, direction)),
+        ProductionFlowItem::Process(process) => {
+            let link_tag = match direction {
+                Direction::Backward => PROCESS_EVENT_INPUTS_LINK_TAG,
+                Direction::Forward => PROCESS_EVENT_OUTPUTS_LINK_TAG,
+            };
+            let event_ids: Vec<EventAddress> = read_index(&PROCESS_ENTRY_TYPE, process, &link_tag)?;
+            Ok(event_ids.into_iter().map(ProductionFlowItem::EconomicEvent).collect())
+        },
+    }
+}
+
+/// Nodes reached by following an event's flow in the given direction
+fn event_flows(event: &EntryData, direction: Direction) -> Vec<ProductionFlowItem> {
+    match direction {
+        // outputs were produced by their process; inputs & transfers were drawn from their source resource
+        Direction::Backward => match (&event.output_of, &event.resource_inventoried_as) {
+            (Some(process), _) => vec![ProductionFlowItem::Process(process.to_owned())],
+            (None, Some(resource)) if event.input_of.is_some() || is_transfer_from(event, resource) =>
+                vec![ProductionFlowItem::EconomicResource(resource.to_owned())],
+            _ => vec![],
+        },
+        // inputs are consumed by their process; outputs & transfers arrive in their destination resource
+        Direction::Forward => match (&event.input_of, &event.output_of, &event.to_resource_inventoried_as) {
+            (Some(process), _, _) => vec![ProductionFlowItem::Process(process.to_owned())],
+            (None, Some(_), _) => event.resource_inventoried_as.iter()
+                .map(|r| ProductionFlowItem::EconomicResource(r.to_owned()))
+                .collect(),
+            (None, None, Some(resource)) if event.resource_inventoried_as.iter().any(|r| is_transfer_from(event, r)) =>
+                vec![ProductionFlowItem::EconomicResource(resource.to_owned())],
+            _ => vec![],
+        },
+    }
+}
+
+fn read_event(event_id: &EventAddress) -> RecordAPIResult<EntryData> {
+    let (_, _, entry) = read_record_entry::<EntryData, EntryStorage, _,_>(&EVENT_ENTRY_TYPE, event_id.as_ref())?;
+    Ok(entry)
+}
+
+/// An event flows into a resource if it is the output of a process producing that resource,
+/// or if it moves some quantity into the resource from elsewhere.
+fn is_incoming_to(event: &EntryData, resource: &ResourceAddress) -> bool {
+    (event.output_of.is_some() && event.resource_inventoried_as.as_ref() == Some(resource))
+        || (event.to_resource_inventoried_as.as_ref() == Some(resource) && event.resource_inventoried_as.as_ref() != Some(resource))
+}
+
+/// Whether an event moves some quantity out of `resource` into a different resource
+fn is_transfer_from(event: &EntryData, resource: &ResourceAddress) -> bool {
+    event.to_resource_inventoried_as.is_some() && event.to_resource_inventoried_as.as_ref() != Some(resource)
+}
+
+/// An event flows out of a resource if it inputs the resource into a process,
+/// or if it moves some quantity of the resource elsewhere.
+fn is_outgoing_from(event: &EntryData, resource: &ResourceAddress) -> bool {
+    event.resource_inventoried_as.as_ref() == Some(resource) && (event.input_of.is_some() || is_transfer_from(event, resource))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hdk::prelude::*;
+    use hc_zome_rea_economic_event_rpc::{ ActionId, AgentAddress, ProcessAddress };
+
+    fn dna() -> DnaHash {
+        DnaHash::from_raw_36(vec![0xdb; 36])
+    }
+
+    fn resource(byte: u8) -> ResourceAddress {
+        ResourceAddress(dna(), EntryHash::from_raw_36(vec![byte; 36]))
+    }
+
+    fn process(byte: u8) -> ProcessAddress {
+        ProcessAddress(dna(), EntryHash::from_raw_36(vec![byte; 36]))
+    }
+
+    fn node(byte: u8) -> ProductionFlowItem {
+        ProductionFlowItem::EconomicEvent(EventAddress(dna(), EntryHash::from_raw_36(vec![byte; 36])))
+    }
+
+    fn event(input_of: Option<ProcessAddress>, output_of: Option<ProcessAddress>, from: Option<ResourceAddress>, to: Option<ResourceAddress>) -> EntryData {
+        let agent = AgentAddress(dna(), AgentPubKey::from_raw_36(vec![0xaa; 36]));
+        EntryData {
+            action: ActionId("transfer".to_string()),
+            provider: agent.to_owned(),
+            receiver: agent,
+            input_of,
+            output_of,
+            resource_inventoried_as: from,
+            to_resource_inventoried_as: to,
+            resource_classified_as: None,
+            resource_conforms_to: None,
+            resource_quantity: None,
+            effort_quantity: None,
+            has_beginning: None,
+            has_end: None,
+            has_point_in_time: None,
+            at_location: None,
+            agreed_in: None,
+            realization_of: None,
+            triggered_by: None,
+            in_scope_of: None,
+            note: None,
+        }
+    }
+
+    /// Adjacency over a fixed set of directed edges between nodes
+    fn graph(edges: Vec<(u8, u8)>) -> impl Fn(&ProductionFlowItem) -> RecordAPIResult<Vec<ProductionFlowItem>> {
+        move |n| Ok(edges.iter().filter(|(from, _)| node(*from) == *n).map(|(_, to)| node(*to)).collect())
+    }
+
+    fn chain(length: u8) -> Vec<(u8, u8)> {
+        (0..length).map(|i| (i, i + 1)).collect()
+    }
+
+    #[test]
+    fn test_traversal_is_breadth_first() {
+        let found = traverse_with(node(0), 10, None, graph(vec![(0, 1), (1, 3), (0, 2), (2, 4)])).unwrap();
+        assert_eq!(found, vec![node(1), node(2), node(3), node(4)]);
+    }
+
+    #[test]
+    fn test_traversal_depth_limit() {
+        let found = traverse_with(node(0), 2, None, graph(chain(5))).unwrap();
+        assert_eq!(found, vec![node(1), node(2)]);
+
+        assert!(traverse_with(node(0), 0, None, graph(chain(5))).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_traversal_result_limit() {
+        let found = traverse_with(node(0), 10, Some(3), graph(vec![(0, 1), (0, 2), (0, 3), (0, 4), (1, 5)])).unwrap();
+        assert_eq!(found, vec![node(1), node(2), node(3)], "limit does not count the starting node");
+
+        assert!(traverse_with(node(0), 10, Some(0), graph(chain(5))).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_traversal_does_not_revisit_nodes() {
+        let found = traverse_with(node(0), 100, None, graph(vec![(0, 1), (1, 2), (2, 0), (2, 1)])).unwrap();
+        assert_eq!(found, vec![node(1), node(2)], "cycles back to visited nodes, including the start, are ignored");
+    }
+
+    #[test]
+    fn test_process_output_flows() {
+        let produced = event(None, Some(process(1)), Some(resource(2)), None);
+
+        assert_eq!(event_flows(&produced, Direction::Backward), vec![ProductionFlowItem::Process(process(1))]);
+        assert_eq!(event_flows(&produced, Direction::Forward), vec![ProductionFlowItem::EconomicResource(resource(2))]);
+        assert!(is_incoming_to(&produced, &resource(2)));
+        assert!(!is_outgoing_from(&produced, &resource(2)));
+    }
+
+    #[test]
+    fn test_process_input_flows() {
+        let consumed = event(Some(process(1)), None, Some(resource(2)), None);
+
+        assert_eq!(event_flows(&consumed, Direction::Backward), vec![ProductionFlowItem::EconomicResource(resource(2))]);
+        assert_eq!(event_flows(&consumed, Direction::Forward), vec![ProductionFlowItem::Process(process(1))]);
+        assert!(is_outgoing_from(&consumed, &resource(2)));
+        assert!(!is_incoming_to(&consumed, &resource(2)));
+    }
+
+    #[test]
+    fn test_transfer_flows() {
+        let transfer = event(None, None, Some(resource(1)), Some(resource(2)));
+
+        assert_eq!(event_flows(&transfer, Direction::Backward), vec![ProductionFlowItem::EconomicResource(resource(1))]);
+        assert_eq!(event_flows(&transfer, Direction::Forward), vec![ProductionFlowItem::EconomicResource(resource(2))]);
+        assert!(is_outgoing_from(&transfer, &resource(1)));
+        assert!(is_incoming_to(&transfer, &resource(2)));
+    }
+
+    #[test]
+    fn test_events_within_a_resource_do_not_flow() {
+        let moved = event(None, None, Some(resource(1)), Some(resource(1)));
+        assert!(event_flows(&moved, Direction::Backward).is_empty());
+        assert!(event_flows(&moved, Direction::Forward).is_empty());
+        assert!(!is_incoming_to(&moved, &resource(1)));
+        assert!(!is_outgoing_from(&moved, &resource(1)));
+
+        let raised = event(None, None, Some(resource(1)), None);
+        assert!(event_flows(&raised, Direction::Backward).is_empty());
+        assert!(event_flows(&raised, Direction::Forward).is_empty());
+    }
+}
@@ -11,7 +11,7 @@ use holochain_serialized_bytes::prelude::*;
 use serde_maybe_undefined::MaybeUndefined;
 use vf_measurement::QuantityValue;
 pub use vf_attributes_hdk::{
-    RevisionHash,
+    RevisionHash, DeleteRequest,
     EventAddress,
     ResourceAddress,
     ActionId,
@@ -270,3 +270,14 @@ pub struct QueryParams {
     pub fulfills: Option<CommitmentAddress>,
     pub realization_of: Option<AgreementAddress>,
 }
+
+//---------------- TRACE / TRACK ----------------
+
+/// A single node visited when tracing or tracking the flows of resources through the network
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "type", content = "id")]
+pub enum ProductionFlowItem {
+    EconomicEvent(EventAddress),
+    EconomicResource(ResourceAddress),
+    Process(ProcessAddress),
+}
@@ -9,6 +9,7 @@
 use hdk::prelude::*;
 
 use hdk_records::{
+    config::ZomeConfig,
     generate_record_entry,
     MaybeUndefined,
     record_interface::Updateable,
@@ -27,7 +28,7 @@ use vf_attributes_hdk::{
     AgreementAddress,
     DateTime, FixedOffset,
 };
-use vf_actions::{ validate_flow_action, validate_move_inventories };
+use vf_actions::{ validate_flow_action, validate_move_inventories, validate_containment_inventories };
 use hc_zome_rea_economic_event_rpc::*;
 
 // :SHONK: needed as re-export in zome logic to allow validation logic to parse entries
@@ -35,13 +36,8 @@ pub use hdk_records::record_interface::Identified;
 
 //--------------- ZOME CONFIGURATION ATTRIBUTES ----------------
 
-// :TODO: remove this, replace with reference to appropriate namespacing of zome config
-#[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
-pub struct DnaConfigSlice {
-    pub economic_event: EconomicEventZomeConfig,
-}
-
 #[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct EconomicEventZomeConfig {
     pub index_zome: String,
     // zome ID (defined in `dna.yaml`) of a ValueFlows `EconomicResource`-compatible zome where inventory state for these `EconomicEvents` can be managed.
@@ -50,6 +46,8 @@ pub struct EconomicEventZomeConfig {
     pub process_index_zome: Option<String>,
 }
 
+impl ZomeConfig for EconomicEventZomeConfig {}
+
 //---------------- RECORD INTERNALS & VALIDATION ----------------
 
 #[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
@@ -82,9 +80,19 @@ impl EntryData {
         if result.is_ok() && self.action.as_ref() == "move" {
             return validate_move_inventories(self.resource_inventoried_as.to_owned(), self.to_resource_inventoried_as.to_owned());
         }
+        if result.is_ok() {
+            return validate_containment_inventories(self.action.to_owned(), self.resource_inventoried_as.to_owned(), self.to_resource_inventoried_as.to_owned());
+        }
         return result;
     }
 
+    /// The time at which the event is considered to have taken effect, used when ordering events
+    pub fn get_event_time(&self) -> Option<DateTime<FixedOffset>> {
+        self.has_point_in_time.to_owned()
+            .or(self.has_end.to_owned())
+            .or(self.has_beginning.to_owned())
+    }
+
     pub fn validate_or_fields(&self) -> Result<(), String> {
         if !(self.resource_inventoried_as.is_some() || self.resource_classified_as.is_some() || self.resource_conforms_to.is_some()) {
             return Err("EconomicEvent must reference an inventoried resource, resource specification or resource classification".into());
@@ -133,6 +141,35 @@ impl From<CreateRequest> for EntryData {
     }
 }
 
+/// Reconstruct the creation payload of a stored event, for replaying its effects against inventories
+impl From<EntryData> for CreateRequest {
+    fn from(e: EntryData) -> CreateRequest {
+        CreateRequest {
+            action: e.action,
+            note: e.note.into(),
+            provider: e.provider,
+            receiver: e.receiver,
+            input_of: e.input_of.into(),
+            output_of: e.output_of.into(),
+            resource_inventoried_as: e.resource_inventoried_as.into(),
+            to_resource_inventoried_as: e.to_resource_inventoried_as.into(),
+            resource_classified_as: e.resource_classified_as.into(),
+            resource_conforms_to: e.resource_conforms_to.into(),
+            resource_quantity: e.resource_quantity.into(),
+            effort_quantity: e.effort_quantity.into(),
+            has_beginning: e.has_beginning.into(),
+            has_end: e.has_end.into(),
+            has_point_in_time: e.has_point_in_time.into(),
+            agreed_in: e.agreed_in.into(),
+            realization_of: e.realization_of.into(),
+            triggered_by: e.triggered_by.into(),
+            at_location: e.at_location.into(),
+            in_scope_of: e.in_scope_of.into(),
+            target_inventory_type: None,
+        }
+    }
+}
+
 //---------------- UPDATE ----------------
 
 /// Handles update operations by merging any newly provided fields into
@@ -8,12 +8,18 @@
  * @package Holo-REA
  */
 use hdk::prelude::*;
+use hdk_records::{ ErrorEnvelope, ErrorCode, config::init_with_config };
 
 use hc_zome_rea_economic_event_lib::*;
 use hc_zome_rea_economic_event_rpc::*;
 use hc_zome_rea_economic_event_storage::*;
 use hc_zome_rea_economic_resource_rpc::CreateRequest as EconomicResourceCreateRequest;
 
+#[hdk_extern]
+fn init(_: ()) -> ExternResult<InitCallbackResult> {
+    Ok(init_with_config::<EconomicEventZomeConfig>())
+}
+
 #[hdk_extern]
 fn entry_defs(_: ()) -> ExternResult<EntryDefsCallbackResult> {
     Ok(EntryDefsCallbackResult::from(vec![
@@ -43,7 +49,7 @@ fn validate(validation_data: ValidateData) -> ExternResult<ValidateCallbackResul
             record.validate_or_fields()
                 .and_then(|()| { record.validate_action() })
                 .and_then(|()| { Ok(ValidateCallbackResult::Valid) })
-                .or_else(|e| { Ok(ValidateCallbackResult::Invalid(e)) })
+                .or_else(|e| { Ok(ValidateCallbackResult::Invalid(ErrorEnvelope::new(ErrorCode::ValidationFailed, e).to_string())) })
         },
         _ => Ok(ValidateCallbackResult::Valid),
     }
@@ -80,20 +86,33 @@ struct UpdateParams {
 
 #[hdk_extern]
 fn update_event(UpdateParams { event }: UpdateParams) -> ExternResult<ResponseData> {
-    Ok(handle_update_economic_event(EVENT_ENTRY_TYPE, event)?)
+    Ok(handle_update_economic_event(EVENT_ENTRY_TYPE, PROCESS_ENTRY_TYPE, event)?)
+}
+
+#[hdk_extern]
+fn delete_event(DeleteRequest { address, force }: DeleteRequest) -> ExternResult<bool> {
+    Ok(handle_delete_economic_event(EVENT_ENTRY_TYPE, PROCESS_ENTRY_TYPE, address, force)?)
+}
+
+#[hdk_extern]
+fn get_all_events(_: ()) -> ExternResult<Vec<ResponseData>> {
+    Ok(handle_get_all_economic_events(EVENT_ENTRY_TYPE)?)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-struct ByHeader {
-    pub address: RevisionHash,
+struct TraversalParams {
+    pub address: EventAddress,
+    pub max_depth: Option<usize>,
+    /// maximum number of results to return
+    pub limit: Option<usize>,
 }
 
 #[hdk_extern]
-fn delete_event(ByHeader { address }: ByHeader) -> ExternResult<bool> {
-    Ok(handle_delete_economic_event(address)?)
+fn trace(TraversalParams { address, max_depth, limit }: TraversalParams) -> ExternResult<Vec<ProductionFlowItem>> {
+    Ok(hc_zome_rea_economic_event_lib::trace(ProductionFlowItem::EconomicEvent(address), max_depth, limit)?)
 }
 
 #[hdk_extern]
-fn get_all_events(_: ()) -> ExternResult<Vec<ResponseData>> {
-    Ok(handle_get_all_economic_events(EVENT_ENTRY_TYPE)?)
+fn track(TraversalParams { address, max_depth, limit }: TraversalParams) -> ExternResult<Vec<ProductionFlowItem>> {
+    Ok(hc_zome_rea_economic_event_lib::track(ProductionFlowItem::EconomicEvent(address), max_depth, limit)?)
 }
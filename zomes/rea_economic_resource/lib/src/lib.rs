@@ -6,10 +6,13 @@
  *
  * @package Holo-REA
  */
+use std::convert::TryInto;
 use hdk::prelude::*;
 use hdk_records::{
     DataIntegrityError, RecordAPIResult, MaybeUndefined,
+    config::ZomeConfig,
     local_indexes::{
+        read_index,
         query_root_index,
     },
     foreign_indexes::{
@@ -24,17 +27,26 @@ use hdk_records::{
         get_latest_header_hash,
         create_record,
         read_record_entry,
+        read_record_entry_by_header,
+        read_record_initial_entry,
+        get_header_hash_as_of,
+        get_revision_time,
         update_record,
     },
+    blobs::{ store_inline_field, store_inline_update, read_inline_field },
     EntryHash,
 };
 
 use vf_attributes_hdk::{
     ResourceAddress,
+    ResourceSpecificationAddress,
     EventAddress,
-    ActionId,
+    ProcessAddress,
     ProcessSpecificationAddress,
+    ExternalURL,
+    DateTime, FixedOffset,
 };
+use vf_measurement::QuantityValue;
 
 pub use hc_zome_rea_economic_resource_storage_consts::*;
 pub use hc_zome_rea_economic_event_storage_consts::{EVENT_ENTRY_TYPE};
@@ -43,6 +55,7 @@ pub use hc_zome_rea_resource_specification_storage_consts::{
     ECONOMIC_RESOURCE_SPECIFICATION_ENTRY_TYPE,
     RESOURCE_SPECIFICATION_CONFORMING_RESOURCE_LINK_TAG,
 };
+pub use hdk_records::blobs::{ blob_chunk_entry_def, blob_manifest_entry_def };
 
 use hc_zome_rea_economic_resource_storage::*;
 use hc_zome_rea_economic_resource_rpc::*;
@@ -65,21 +78,29 @@ use hc_zome_rea_economic_event_rpc::{
 ///
 /// :TODO: assess whether this should use the same standardised API format as external endpoints
 ///
-pub fn handle_create_inventory_from_event<S>(resource_entry_def_id: S, params: CreationPayload) -> RecordAPIResult<(RevisionHash, ResourceAddress, EntryData)>
+pub fn handle_create_inventory_from_event<S>(resource_entry_def_id: S, process_entry_def_id: S, params: CreationPayload) -> RecordAPIResult<(RevisionHash, ResourceAddress, EntryData)>
     where S: AsRef<str>
 {
     // :TODO: move this assertion to validation callback
     if let MaybeUndefined::Some(_sent_inventory_id) = &params.get_event_params().resource_inventoried_as {
-        return Err(DataIntegrityError::RemoteRequestError("cannot create a new EconomicResource and specify an inventoried resource ID in the same event".to_string()));
+        return Err(DataIntegrityError::ValidationFailed("cannot create a new EconomicResource and specify an inventoried resource ID in the same event".to_string()));
     }
 
     let resource_params = params.get_resource_params().clone();
     let resource_spec = params.get_resource_specification_id();
+    let stage = match &params.get_event_params().output_of {
+        MaybeUndefined::Some(process) => get_process_stage(&process_entry_def_id, process)?,
+        _ => None,
+    };
 
-    let (revision_id, base_address, entry_resp): (_, ResourceAddress, EntryData) = create_record(
-        &resource_entry_def_id,
-        params.with_inventory_type(ResourceInventoryType::ProvidingInventory),  // inventories can only be inited by their owners initially
-    )?;
+    // inventories can only be inited by their owners initially
+    let new_entry: EntryData = params.with_inventory_type(ResourceInventoryType::ProvidingInventory).try_into()?;
+    let new_entry = EntryData {
+        stage,
+        image: store_inline_field(&new_entry.image)?,
+        ..new_entry
+    };
+    let (revision_id, base_address, entry_resp): (_, ResourceAddress, EntryData) = create_record(&resource_entry_def_id, new_entry)?;
 
     // :NOTE: this will always run- resource without a specification ID would fail entry validation (implicit in the above)
     if let Some(conforms_to) = resource_spec {
@@ -106,30 +127,123 @@ pub fn handle_create_inventory_from_event<S>(resource_entry_def_id: S, params: C
     Ok((revision_id, base_address, entry_resp))
 }
 
-pub fn handle_get_economic_resource<S>(entry_def_id: S, event_entry_def_id: S, process_entry_def_id: S, address: ResourceAddress) -> RecordAPIResult<ResponseData>
+pub fn handle_get_economic_resource<S>(entry_def_id: S, address: ResourceAddress) -> RecordAPIResult<ResponseData>
     where S: AsRef<str>
 {
     let (revision, base_address, entry) = read_record_entry::<EntryData, EntryStorage, _,_>(&entry_def_id, address.as_ref())?;
-    construct_response(&base_address, &revision, &entry, get_link_fields(&event_entry_def_id, &process_entry_def_id, &address)?)
+    construct_response(&base_address, &revision, &entry, get_link_fields(&address)?)
+}
+
+/// Read the state of a resource as it was at the given time, by replaying all events
+/// which affected it up until that instant.
+///
+/// The returned `revision_id` is the latest revision written by that time (or the initial revision,
+/// if the resource was recorded after events dated at that time). `contains` lists the resources
+/// combined into this one by that time which had not since been separated.
+///
+pub fn handle_get_economic_resource_as_of<S>(
+    entry_def_id: S, event_entry_def_id: S, process_entry_def_id: S,
+    address: ResourceAddress, as_of: DateTime<FixedOffset>,
+) -> RecordAPIResult<ResponseData>
+    where S: AsRef<str>
+{
+    read_economic_resource_as_of(&entry_def_id, &event_entry_def_id, &process_entry_def_id, &address, &as_of)?
+        .ok_or(DataIntegrityError::EntryNotFound)
+}
+
+/// Read the state of all resources conforming to a `ResourceSpecification` as they were at the given time.
+/// Resources which did not yet exist at that time are omitted.
+///
+pub fn handle_get_economic_resources_by_specification_as_of<S>(
+    entry_def_id: S, event_entry_def_id: S, process_entry_def_id: S,
+    conforms_to: ResourceSpecificationAddress, as_of: DateTime<FixedOffset>,
+) -> RecordAPIResult<Vec<ResponseData>>
+    where S: AsRef<str>
+{
+    let resources: Vec<ResourceAddress> = read_index(&ECONOMIC_RESOURCE_SPECIFICATION_ENTRY_TYPE, &conforms_to, &RESOURCE_SPECIFICATION_CONFORMING_RESOURCE_LINK_TAG)?;
+
+    let mut results = vec![];
+    for address in resources.iter() {
+        if let Some(resource) = read_economic_resource_as_of(&entry_def_id, &event_entry_def_id, &process_entry_def_id, address, &as_of)? {
+            results.push(resource);
+        }
+    }
+    Ok(results)
+}
+
+/// Read the state of a resource at the given time, or `None` if it did not yet exist
+///
+fn read_economic_resource_as_of<S>(
+    entry_def_id: S, event_entry_def_id: S, process_entry_def_id: S,
+    address: &ResourceAddress, as_of: &DateTime<FixedOffset>,
+) -> RecordAPIResult<Option<ResponseData>>
+    where S: AsRef<str>
+{
+    let until = to_micros(as_of);
+    let replayed = replay_resource_events(&entry_def_id, &event_entry_def_id, &process_entry_def_id, address, Some(until))?;
+
+    // the resource is always created by an event, so no events means it did not yet exist
+    if replayed.events_replayed == 0 {
+        return Ok(None);
+    }
+
+    let revision = match get_header_hash_as_of(address.as_ref().clone(), until)? {
+        Some(revision) => revision,
+        None => replayed.initial_revision.to_owned(),
+    };
+
+    let mut contains = vec![];
+    for candidate in replayed.combined.iter() {
+        let contained = replay_resource_events(&entry_def_id, &event_entry_def_id, &process_entry_def_id, candidate, Some(until))?;
+        if contained.entry.contained_in.as_ref() == Some(address) {
+            contains.push(candidate.to_owned());
+        }
+    }
+
+    Ok(Some(construct_response(address, &revision, &replayed.entry, (
+        replayed.entry.contained_in.to_owned(),
+        contains,
+    ))?))
 }
 
 /// Handle update of resources by iterative reduction of event records over time.
 ///
+/// Resource `state` and `stage` are materialised here as well, so that reads need not
+/// consult the resource's event history.
+///
 pub fn handle_update_inventory_from_event<S>(
     resource_entry_def_id: S,
+    process_entry_def_id: S,
     event: EventCreateRequest,
 ) -> RecordAPIResult<Vec<(RevisionHash, ResourceAddress, EntryData, EntryData)>>
     where S: AsRef<str>
 {
     let mut resources_affected: Vec<(RevisionHash, ResourceAddress, EntryData, EntryData)> = vec![];
 
+    // a missing or unreadable process is an integrity error, rather than grounds for leaving the stage unchanged.
+    // Only the resource output by the process (`resource_inventoried_as`) moves to its stage.
+    let stage = match &event.output_of {
+        MaybeUndefined::Some(process) => get_process_stage(&process_entry_def_id, process)?,
+        _ => None,
+    };
+
+    // containment changes must never allow a resource to contain itself
+    if event.get_action() == "combine" {
+        if let (MaybeUndefined::Some(resource), MaybeUndefined::Some(container)) = (&event.resource_inventoried_as, &event.to_resource_inventoried_as) {
+            ensure_no_containment_cycle(&resource_entry_def_id, resource, container)?;
+        }
+    }
+
     // if the event is a transfer-like event, run the receiver's update first
     if let MaybeUndefined::Some(receiver_inventory) = &event.to_resource_inventoried_as {
         let inv_entry_hash: &EntryHash = receiver_inventory.as_ref();
         resources_affected.push(handle_update_inventory_resource(
             &resource_entry_def_id,
             &get_latest_header_hash(inv_entry_hash.clone())?,   // :TODO: temporal reduction here! Should error on mismatch and return latest valid ID
-            event.with_inventory_type(ResourceInventoryType::ReceivingInventory),
+            InventoryUpdatePayload {
+                event: event.with_inventory_type(ResourceInventoryType::ReceivingInventory),
+                stage: None,
+            },
         )?);
     }
     // after receiver, run provider. This entry data will be returned in the response.
@@ -138,62 +252,310 @@ pub fn handle_update_inventory_from_event<S>(
         resources_affected.push(handle_update_inventory_resource(
             &resource_entry_def_id,
             &get_latest_header_hash(inv_entry_hash.clone())?,   // :TODO: temporal reduction here! Should error on mismatch and return latest valid ID
-            event.with_inventory_type(ResourceInventoryType::ProvidingInventory),
+            InventoryUpdatePayload {
+                event: event.with_inventory_type(ResourceInventoryType::ProvidingInventory),
+                stage: stage.to_owned(),
+            },
         )?);
     }
 
+    // update containment indexes for any resource combined into or separated from another
+    for (_, identity_address, entry, prev_entry) in resources_affected.iter() {
+        if entry.contained_in != prev_entry.contained_in {
+            update_containment_index(identity_address, entry, prev_entry)?;
+        }
+    }
+
     Ok(resources_affected)
 }
 
-pub fn handle_update_economic_resource<S>(entry_def_id: S, event_entry_def_id: S, process_entry_def_id: S, resource: UpdateRequest) -> RecordAPIResult<ResponseData>
+pub fn handle_update_economic_resource<S>(entry_def_id: S, resource: UpdateRequest) -> RecordAPIResult<ResponseData>
     where S: AsRef<str>
 {
     let address = resource.get_revision_id().clone();
-    let (revision_id, identity_address, entry, prev_entry): (_,_, EntryData, EntryData) = update_record(&entry_def_id, &address, resource)?;
+    let resource = UpdateRequest { image: store_inline_update(&resource.image)?, ..resource };
 
     // :TODO: this may eventually be moved to an EconomicEvent update, see https://lab.allmende.io/valueflows/valueflows/-/issues/637
-    let now_contained = if let Some(contained) = &entry.contained_in { vec![contained.clone()] } else { vec![] };
-    let prev_contained = if let Some(contained) = &prev_entry.contained_in { vec![contained.clone()] } else { vec![] };
-    update_foreign_index(
-        read_foreign_index_zome,
-        &RESOURCE_CONTAINEDIN_INDEXING_API_METHOD,
-        &identity_address,
-        no_index_target,
-        &RESOURCE_CONTAINS_INDEXING_API_METHOD, // :NOTE: ignored :TODO: special-case methods for managing foreign indexes
-        now_contained.as_slice(), prev_contained.as_slice(),
-    )?;
+    if let MaybeUndefined::Some(container) = &resource.contained_in {
+        let (resource_address, _): (ResourceAddress, EntryData) = read_record_entry_by_header::<EntryData, EntryStorage, _>(&address)?;
+        ensure_no_containment_cycle(&entry_def_id, &resource_address, container)?;
+    }
+
+    let (revision_id, identity_address, entry, prev_entry): (_,_, EntryData, EntryData) = update_record(&entry_def_id, &address, resource)?;
+
+    update_containment_index(&identity_address, &entry, &prev_entry)?;
 
     // :TODO: optimise this- should pass results from `replace_direct_index` instead of retrieving from `get_link_fields` where updates
-    construct_response(&identity_address, &revision_id, &entry, get_link_fields(&event_entry_def_id, &process_entry_def_id, &identity_address)?)
+    construct_response(&identity_address, &revision_id, &entry, get_link_fields(&identity_address)?)
 }
 
-pub fn handle_get_all_economic_resources<S>(entry_def_id: S, event_entry_def_id: S, process_entry_def_id: S) -> RecordAPIResult<Vec<ResponseData>>
+pub fn handle_get_all_economic_resources<S>(entry_def_id: S) -> RecordAPIResult<Vec<ResponseData>>
     where S: AsRef<str>
 {
     let entries_result = query_root_index::<EntryData, EntryStorage, _,_>(&entry_def_id)?;
 
-    Ok(handle_list_output(event_entry_def_id, process_entry_def_id, entries_result)?.iter().cloned()
+    Ok(handle_list_output(entries_result)?.iter().cloned()
         .filter_map(Result::ok)
         .collect()
     )
 }
 
+/// Recompute the event-derived fields of a resource by replaying every event which has affected it,
+/// reporting any drift from the stored values. If `write_correction` is set and drift is detected,
+/// a new revision of the resource is written with the recomputed values.
+///
+pub fn handle_reconcile_economic_resource<S>(
+    entry_def_id: S, event_entry_def_id: S, process_entry_def_id: S,
+    address: ResourceAddress, write_correction: bool,
+) -> RecordAPIResult<ReconciliationReport>
+    where S: AsRef<str>
+{
+    let (revision_id, _, stored_entry) = read_record_entry::<EntryData, EntryStorage, _,_>(&entry_def_id, address.as_ref())?;
+    let replayed = replay_resource_events(&entry_def_id, &event_entry_def_id, &process_entry_def_id, &address, None)?;
+    let events_replayed = replayed.events_replayed;
+
+    let stored = stored_entry.get_inventory_state();
+    let recomputed = replayed.entry.get_inventory_state();
+
+    let drifted_fields = find_drifted_fields(&stored, &recomputed);
+
+    let corrected_revision_id = if write_correction && drifted_fields.len() > 0 {
+        let (new_revision, _, _, _): (_, ResourceAddress, EntryData, EntryData) = update_record(&entry_def_id, &revision_id, recomputed.to_owned())?;
+        Some(new_revision)
+    } else {
+        None
+    };
+
+    Ok(ReconciliationReport {
+        resource: address,
+        revision_id,
+        events_replayed,
+        stored,
+        recomputed,
+        drifted_fields,
+        corrected_revision_id,
+    })
+}
+
+/// List the (GraphQL-named) inventory fields which differ between stored and recomputed resource state
+fn find_drifted_fields(stored: &InventoryState, recomputed: &InventoryState) -> Vec<String> {
+    let mut drifted_fields = vec![];
+    if stored.accounting_quantity != recomputed.accounting_quantity { drifted_fields.push("accountingQuantity".to_string()); }
+    if stored.onhand_quantity != recomputed.onhand_quantity { drifted_fields.push("onhandQuantity".to_string()); }
+    if stored.current_location != recomputed.current_location { drifted_fields.push("currentLocation".to_string()); }
+    if !same_classifications(&stored.classified_as, &recomputed.classified_as) { drifted_fields.push("classifiedAs".to_string()); }
+    if stored.state != recomputed.state { drifted_fields.push("state".to_string()); }
+    if stored.stage != recomputed.stage { drifted_fields.push("stage".to_string()); }
+    drifted_fields
+}
+
+/// Compare classification lists irrespective of ordering, since event updates de-duplicate & sort them
+fn same_classifications(a: &Option<Vec<ExternalURL>>, b: &Option<Vec<ExternalURL>>) -> bool {
+    let normalise = |list: &Option<Vec<ExternalURL>>| -> Vec<String> {
+        let mut strings: Vec<String> = list.to_owned().unwrap_or(vec![]).into_iter().map(|url| url.into()).collect();
+        strings.sort_unstable();
+        strings.dedup();
+        strings
+    };
+    normalise(a) == normalise(b)
+}
+
+/// Resource state rebuilt from its event history
+///
+struct ReplayedResource {
+    entry: EntryData,
+    initial_revision: RevisionHash,
+    events_replayed: usize,
+    /// resources combined into this one by the replayed events, which may since have been separated
+    combined: Vec<ResourceAddress>,
+}
+
+/// Rebuild a resource's state by replaying all affecting events in time order,
+/// starting from the resource as originally created. If `until` is provided (in microseconds
+/// since the UNIX epoch), only events which took effect at or before that time are applied.
+///
+/// Events without a recorded time are taken to have occurred when they were written.
+///
+fn replay_resource_events<S>(
+    entry_def_id: S, event_entry_def_id: S, process_entry_def_id: S,
+    address: &ResourceAddress,
+    until: Option<i64>,
+) -> RecordAPIResult<ReplayedResource>
+    where S: AsRef<str>
+{
+    let (initial_revision, _, initial_entry) = read_record_initial_entry::<EntryData, EntryStorage, ResourceAddress, _>(&entry_def_id, address.as_ref())?;
+
+    // quantities, state & stage in the initial revision already include the effects of the creating event, so start from zero
+    let mut entry = EntryData {
+        accounting_quantity: initial_entry.accounting_quantity.as_ref().map(|q| QuantityValue::new(0.0, q.get_unit())),
+        onhand_quantity: initial_entry.onhand_quantity.as_ref().map(|q| QuantityValue::new(0.0, q.get_unit())),
+        state: None,
+        stage: None,
+        ..initial_entry
+    };
+
+    let mut events = get_affecting_events(address)?.iter()
+        .map(|event_address| {
+            let (_, _, event) = read_record_entry::<EventData, EventStorage, _,_>(&event_entry_def_id, event_address.as_ref())?;
+            let time = match event.get_event_time() {
+                Some(time) => to_micros(&time),
+                None => {
+                    let (created, _, _) = read_record_initial_entry::<EventData, EventStorage, EventAddress, _>(&event_entry_def_id, event_address.as_ref())?;
+                    get_revision_time(&created)?
+                },
+            };
+            Ok((time, event))
+        })
+        .collect::<RecordAPIResult<Vec<(i64, EventData)>>>()?;
+    events.sort_by_key(|(time, _)| *time);
+
+    let mut events_replayed = 0;
+    let mut combined = vec![];
+    for (time, event) in events.into_iter() {
+        if !is_before(time, until) {
+            continue;
+        }
+
+        if let Some(resource) = combined_into(address, &event) {
+            if !combined.contains(&resource) {
+                combined.push(resource);
+            }
+        }
+
+        let stage = match &event.output_of {
+            Some(process) => get_process_stage(&process_entry_def_id, process)?,
+            None => None,
+        };
+        entry = apply_replayed_event(entry, address, event, stage)?;
+        events_replayed += 1;
+    }
+
+    Ok(ReplayedResource { entry, initial_revision, events_replayed, combined })
+}
+
+/// Apply a single replayed event to the resource at `address`, on whichever side(s) of the event it appears.
+/// `stage` is the specification of the process the event is an output of, if any.
+///
+fn apply_replayed_event(
+    entry: EntryData,
+    address: &ResourceAddress,
+    event: EventData,
+    stage: Option<ProcessSpecificationAddress>,
+) -> RecordAPIResult<EntryData> {
+    // apply receiving side first, as in `handle_update_inventory_from_event`
+    let receives = event.to_resource_inventoried_as.as_ref() == Some(address);
+    let provides = event.resource_inventoried_as.as_ref() == Some(address);
+    let request: EventCreateRequest = event.into();
+
+    let mut entry = entry;
+    if receives {
+        entry = entry.apply_inventory_update(InventoryUpdatePayload {
+            event: request.with_inventory_type(ResourceInventoryType::ReceivingInventory),
+            stage: None,
+        })?;
+    }
+    if provides {
+        entry = entry.apply_inventory_update(InventoryUpdatePayload {
+            event: request.with_inventory_type(ResourceInventoryType::ProvidingInventory),
+            stage,
+        })?;
+    }
+    Ok(entry)
+}
+
+/// The resource placed inside `container` by an event, if it is a `combine` event targeting that container
+fn combined_into(container: &ResourceAddress, event: &EventData) -> Option<ResourceAddress> {
+    if event.action.as_ref() == "combine" && event.to_resource_inventoried_as.as_ref() == Some(container) {
+        event.resource_inventoried_as.to_owned()
+    } else {
+        None
+    }
+}
+
 /// Properties accessor for zome config
-fn read_foreign_index_zome(conf: DnaConfigSlice) -> Option<String> {
-    Some(conf.economic_resource.index_zome)
+fn read_foreign_index_zome(conf: EconomicResourceZomeConfig) -> Option<String> {
+    Some(conf.index_zome)
+}
+
+/// Maximum depth of nested containers traversed when checking for containment cycles
+const MAX_CONTAINMENT_DEPTH: usize = 256;
+
+/// Ensure that placing `resource` inside `container` would not cause a resource to
+/// (transitively) contain itself, by walking up the chain of the container's parents.
+///
+fn ensure_no_containment_cycle<S>(
+    entry_def_id: S,
+    resource: &ResourceAddress,
+    container: &ResourceAddress,
+) -> RecordAPIResult<()>
+    where S: AsRef<str>
+{
+    check_containment_chain(resource, container, |current| {
+        let (_, _, entry) = read_record_entry::<EntryData, EntryStorage, _,_>(&entry_def_id, current.as_ref())?;
+        Ok(entry.contained_in)
+    })
+}
+
+/// Walk up the chain of containers starting at `container`, reading the parent of each via `container_of`,
+/// and fail if `resource` is reached or the chain is itself cyclic or deeper than `MAX_CONTAINMENT_DEPTH`.
+///
+fn check_containment_chain<F>(
+    resource: &ResourceAddress,
+    container: &ResourceAddress,
+    container_of: F,
+) -> RecordAPIResult<()>
+    where F: Fn(&ResourceAddress) -> RecordAPIResult<Option<ResourceAddress>>
+{
+    let mut visited: Vec<ResourceAddress> = vec![];
+    let mut next = Some(container.to_owned());
+
+    while let Some(current) = next {
+        if current == *resource {
+            return Err(DataIntegrityError::ValidationFailed(format!(
+                "EconomicResource {:?} cannot be contained in {:?}: containment cycle detected", resource, container,
+            )));
+        }
+        if visited.contains(&current) || visited.len() >= MAX_CONTAINMENT_DEPTH {
+            return Err(DataIntegrityError::ValidationFailed(format!(
+                "EconomicResource {:?} has invalid container hierarchy at {:?}", container, current,
+            )));
+        }
+        next = container_of(&current)?;
+        visited.push(current);
+    }
+
+    Ok(())
+}
+
+/// Sync the contains / containedIn index pair after a resource's container has changed
+///
+fn update_containment_index(identity_address: &ResourceAddress, entry: &EntryData, prev_entry: &EntryData) -> RecordAPIResult<()> {
+    let now_contained = if let Some(contained) = &entry.contained_in { vec![contained.clone()] } else { vec![] };
+    let prev_contained = if let Some(contained) = &prev_entry.contained_in { vec![contained.clone()] } else { vec![] };
+    update_foreign_index(
+        read_foreign_index_zome,
+        &RESOURCE_CONTAINEDIN_INDEXING_API_METHOD,
+        identity_address,
+        no_index_target,
+        &RESOURCE_CONTAINS_INDEXING_API_METHOD, // :NOTE: ignored :TODO: special-case methods for managing foreign indexes
+        now_contained.as_slice(), prev_contained.as_slice(),
+    )?;
+    Ok(())
 }
 
 /// Null zome target for contains / containedIn index, since (unlike most indexes) both sides of the index exist within the same zome
-fn no_index_target(_conf: DnaConfigSlice) -> Option<String> { None }
+fn no_index_target(_conf: EconomicResourceZomeConfig) -> Option<String> { None }
 
 fn handle_update_inventory_resource<S>(
     resource_entry_def_id: S,
     resource_addr: &RevisionHash,
-    event: EventCreateRequest,
+    update: InventoryUpdatePayload,
 ) -> RecordAPIResult<(RevisionHash, ResourceAddress, EntryData, EntryData)>
     where S: AsRef<str>,
 {
-    Ok(update_record(&resource_entry_def_id, resource_addr, event)?)
+    let (_, current_entry): (ResourceAddress, EntryData) = read_record_entry_by_header::<EntryData, EntryStorage, _>(resource_addr)?;
+    let next = current_entry.apply_inventory_update(update)?;
+    Ok(update_record(&resource_entry_def_id, resource_addr, next)?)
 }
 
 // const READ_FN_NAME: &str = "get_resource";
@@ -204,8 +566,7 @@ pub fn generate_query_handler<S, C, F>(
     _process_entry_def_id: S,
 ) -> impl FnOnce(&QueryParams) -> RecordAPIResult<Vec<ResponseData>>
     where S: AsRef<str>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: Fn(C) -> Option<String>,
 {
     move |_params| {
@@ -246,8 +607,7 @@ pub fn generate_query_handler<S, C, F>(
     }
 }
 
-fn handle_list_output<S>(event_entry_def_id: S, process_entry_def_id: S, entries_result: Vec<RecordAPIResult<(RevisionHash, ResourceAddress, EntryData)>>) -> RecordAPIResult<Vec<RecordAPIResult<ResponseData>>>
-    where S: AsRef<str>
+fn handle_list_output(entries_result: Vec<RecordAPIResult<(RevisionHash, ResourceAddress, EntryData)>>) -> RecordAPIResult<Vec<RecordAPIResult<ResponseData>>>
 {
     Ok(entries_result.iter()
         .cloned()
@@ -255,7 +615,7 @@ fn handle_list_output<S>(event_entry_def_id: S, process_entry_def_id: S, entries
         .map(|(revision_id, entry_base_address, entry)| {
             construct_response(
                 &entry_base_address, &revision_id, &entry,
-                get_link_fields(&event_entry_def_id, &process_entry_def_id, &entry_base_address)?
+                get_link_fields(&entry_base_address)?
             )
         })
         .collect()
@@ -266,18 +626,14 @@ fn handle_list_output<S>(event_entry_def_id: S, process_entry_def_id: S, entries
 fn construct_response<'a>(
     address: &ResourceAddress, revision_id: &RevisionHash, e: &EntryData, (
         contained_in,
-        stage,
-        state,
         contains,
      ): (
         Option<ResourceAddress>,
-        Option<ProcessSpecificationAddress>,
-        Option<ActionId>,
         Vec<ResourceAddress>,
     ),
 ) -> RecordAPIResult<ResponseData> {
     Ok(ResponseData {
-        economic_resource: construct_response_record(address, revision_id, e, (contained_in, stage, state, contains))?
+        economic_resource: construct_response_record(address, revision_id, e, (contained_in, contains))?
     })
 }
 
@@ -285,13 +641,9 @@ fn construct_response<'a>(
 pub fn construct_response_record<'a>(
     address: &ResourceAddress, revision_id: &RevisionHash, e: &EntryData, (
         contained_in,
-        stage,
-        state,
         contains,
      ): (
         Option<ResourceAddress>,
-        Option<ProcessSpecificationAddress>,
-        Option<ActionId>,
         Vec<ResourceAddress>,
     ),
 ) -> RecordAPIResult<Response> {
@@ -303,12 +655,12 @@ pub fn construct_response_record<'a>(
         classified_as: e.classified_as.to_owned(),
         tracking_identifier: e.tracking_identifier.to_owned(),
         lot: e.lot.to_owned(),
-        image: e.image.to_owned(),
+        image: read_inline_field(&e.image)?,
         accounting_quantity: e.accounting_quantity.to_owned(),
         onhand_quantity: e.onhand_quantity.to_owned(),
         unit_of_effort: e.unit_of_effort.to_owned(),
-        stage: stage.to_owned(),
-        state: state.to_owned(),
+        stage: e.stage.to_owned(),
+        state: e.state.to_owned(),
         current_location: e.current_location.to_owned(),
         note: e.note.to_owned(),
 
@@ -320,89 +672,37 @@ pub fn construct_response_record<'a>(
 
 // field list retrieval internals
 // @see construct_response
-pub fn get_link_fields<'a, S>(event_entry_def_id: S, process_entry_def_id: S, resource: &ResourceAddress) -> RecordAPIResult<(
+pub fn get_link_fields(resource: &ResourceAddress) -> RecordAPIResult<(
     Option<ResourceAddress>,
-    Option<ProcessSpecificationAddress>,
-    Option<ActionId>,
     Vec<ResourceAddress>,
-)>
-    where S: AsRef<str>
-{
+)> {
     Ok((
         read_foreign_index(read_foreign_index_zome, &RESOURCE_CONTAINEDIN_READ_API_METHOD, resource)?.pop(),
-        get_resource_stage(&event_entry_def_id, &process_entry_def_id, resource)?,
-        get_resource_state(&event_entry_def_id, resource)?,
         read_foreign_index(read_foreign_index_zome, &RESOURCE_CONTAINS_READ_API_METHOD, resource)?,
     ))
 }
 
-fn get_resource_state<S>(event_entry_def_id: S, resource: &ResourceAddress) -> RecordAPIResult<Option<ActionId>>
+/// Determine the stage entered by resources output from a `Process`, being the
+/// `ProcessSpecification` the process is based on (if any)
+///
+fn get_process_stage<S>(process_entry_def_id: S, process: &ProcessAddress) -> RecordAPIResult<Option<ProcessSpecificationAddress>>
     where S: AsRef<str>
 {
-    let events: Vec<EventAddress> = get_affecting_events(resource)?;
-
-    // grab the most recent "pass" or "fail" action
-    Ok(events.iter()
-        .rev()
-        .fold(None, move |result, event| {
-            // already found it, just fall through
-            // :TODO: figure out the Rust STL method to abort on first Some() value
-            if let Some(_) = result {
-                return result;
-            }
-
-            let evt = read_record_entry::<EventData, EventStorage, _,_>(&event_entry_def_id, event.as_ref());
-            match evt {
-                Err(_) => result, // :TODO: this indicates some data integrity error
-                Ok((_, _, entry)) => {
-                    match &*String::from(entry.action.clone()) {
-                        "pass" | "fail" => Some(entry.action),  // found it! Return this as the current resource state.
-                        _ => result,    // still not located, keep looking...
-                    }
-                },
-            }
-        })
-    )
+    let (_, _, entry) = read_record_entry::<ProcessData, ProcessStorage, _,_>(&process_entry_def_id, process.as_ref())?;
+    Ok(entry.based_on)
 }
 
-fn get_resource_stage<S>(event_entry_def_id: S, process_entry_def_id: S, resource: &ResourceAddress) -> RecordAPIResult<Option<ProcessSpecificationAddress>>
-    where S: AsRef<str>
-{
-    let events: Vec<EventAddress> = get_affecting_events(resource)?;
-
-    // grab the most recent event with a process output association
-    Ok(events.iter()
-        .rev()
-        .fold(None, move |result, event| {
-            // already found it, just fall through
-            // :TODO: figure out the Rust STL method to abort on first Some() value
-            if let Some(_) = result {
-                return result;
-            }
+/// Determine whether an event took effect at or before the given time (if any)
+fn is_before(event_time: i64, as_of: Option<i64>) -> bool {
+    match as_of {
+        Some(as_of) => event_time <= as_of,
+        None => true,
+    }
+}
 
-            let evt = read_record_entry::<EventData, EventStorage, _,_>(&event_entry_def_id, event.as_ref());
-            match evt {
-                Err(_) => result, // :TODO: this indicates some data integrity error
-                Ok((_, _, entry)) => {
-                    match &entry.output_of {
-                        Some(output_of) => {
-                            // get the associated process
-                            let maybe_process_entry = read_record_entry::<ProcessData, ProcessStorage, _,_>(&process_entry_def_id, output_of.as_ref());
-                            // check to see if it has an associated specification
-                            match &maybe_process_entry {
-                                Ok((_,_, process_entry)) => match &process_entry.based_on {
-                                    Some(based_on) => Some(based_on.to_owned()),   // found it!
-                                    None => result, // still not located, keep looking...
-                                },
-                                Err(_) => result, // :TODO: this indicates some data integrity error
-                            }
-                        },
-                        None => result,    // still not located, keep looking...
-                    }
-                },
-            }
-        })
-    )
+/// Convert a time to microseconds since the UNIX epoch, for comparison with header timestamps
+fn to_micros(time: &DateTime<FixedOffset>) -> i64 {
+    time.timestamp() * 1_000_000 + time.timestamp_subsec_micros() as i64
 }
 
 /// Read all the EconomicEvents affecting a given EconomicResource
@@ -410,3 +710,291 @@ fn get_affecting_events(resource: &ResourceAddress) -> RecordAPIResult<Vec<Event
 {
     read_foreign_index(read_foreign_index_zome, &RESOURCE_AFFECTED_BY_READ_API_METHOD, resource)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vf_attributes_hdk::{ ActionId, AgentAddress, LocationAddress };
+
+    fn dna() -> DnaHash {
+        DnaHash::from_raw_36(vec![0xdb; 36])
+    }
+
+    fn resource(byte: u8) -> ResourceAddress {
+        ResourceAddress(dna(), EntryHash::from_raw_36(vec![byte; 36]))
+    }
+
+    fn agent() -> AgentAddress {
+        AgentAddress(dna(), AgentPubKey::from_raw_36(vec![0xaa; 36]))
+    }
+
+    fn event(action: &str, resource_inventoried_as: Option<ResourceAddress>, to_resource_inventoried_as: Option<ResourceAddress>) -> EventData {
+        EventData {
+            action: ActionId(action.to_string()),
+            provider: agent(),
+            receiver: agent(),
+            input_of: None,
+            output_of: None,
+            resource_inventoried_as,
+            to_resource_inventoried_as,
+            resource_classified_as: None,
+            resource_conforms_to: None,
+            resource_quantity: None,
+            effort_quantity: None,
+            has_beginning: None,
+            has_end: None,
+            has_point_in_time: None,
+            at_location: None,
+            agreed_in: None,
+            realization_of: None,
+            triggered_by: None,
+            in_scope_of: None,
+            note: None,
+        }
+    }
+
+    #[test]
+    fn test_is_before() {
+        assert!(is_before(100, Some(100)));
+        assert!(is_before(99, Some(100)));
+        assert!(!is_before(101, Some(100)));
+        assert!(is_before(i64::MAX, None), "all events apply when replaying to the present");
+    }
+
+    #[test]
+    fn test_to_micros() {
+        let time = DateTime::parse_from_rfc3339("1970-01-01T00:00:01.000002+00:00").unwrap();
+        assert_eq!(to_micros(&time), 1_000_002);
+
+        let offset = DateTime::parse_from_rfc3339("1970-01-01T10:00:01.000002+10:00").unwrap();
+        assert_eq!(to_micros(&offset), 1_000_002, "offsets do not affect the instant");
+    }
+
+    #[test]
+    fn test_combined_into() {
+        let (container, item, other) = (resource(1), resource(2), resource(3));
+
+        assert_eq!(combined_into(&container, &event("combine", Some(item.clone()), Some(container.clone()))), Some(item.clone()));
+        assert_eq!(combined_into(&container, &event("combine", Some(item.clone()), Some(other.clone()))), None, "combined into another container");
+        assert_eq!(combined_into(&container, &event("separate", Some(item.clone()), Some(container.clone()))), None);
+        assert_eq!(combined_into(&container, &event("transfer", Some(item), Some(container.clone()))), None);
+    }
+
+    /// Containment lookup over a fixed set of (resource, container) pairs
+    fn containers(pairs: Vec<(ResourceAddress, ResourceAddress)>) -> impl Fn(&ResourceAddress) -> RecordAPIResult<Option<ResourceAddress>> {
+        move |r| Ok(pairs.iter().find(|(contained, _)| contained == r).map(|(_, container)| container.to_owned()))
+    }
+
+    fn is_validation_error(result: RecordAPIResult<()>) -> bool {
+        matches!(result, Err(DataIntegrityError::ValidationFailed(_)))
+    }
+
+    #[test]
+    fn test_containment_cycle_to_self() {
+        assert!(is_validation_error(check_containment_chain(&resource(1), &resource(1), containers(vec![]))));
+    }
+
+    #[test]
+    fn test_containment_cycle_two_hops() {
+        // 2 is inside 1, so 1 cannot go inside 2
+        let lookup = containers(vec![(resource(2), resource(1))]);
+        assert!(is_validation_error(check_containment_chain(&resource(1), &resource(2), lookup)));
+
+        // but 3 may go inside 2
+        let lookup = containers(vec![(resource(2), resource(1))]);
+        assert!(check_containment_chain(&resource(3), &resource(2), lookup).is_ok());
+    }
+
+    #[test]
+    fn test_containment_cycle_deep() {
+        // chain of 100 nested containers: 100 inside 99 ... inside 1
+        let chain: Vec<(ResourceAddress, ResourceAddress)> = (2..=100).map(|i| (resource(i), resource(i - 1))).collect();
+
+        assert!(is_validation_error(check_containment_chain(&resource(1), &resource(100), containers(chain.clone()))), "outermost container cannot go inside the innermost");
+        assert!(is_validation_error(check_containment_chain(&resource(50), &resource(100), containers(chain.clone()))));
+        assert!(check_containment_chain(&resource(101), &resource(100), containers(chain)).is_ok(), "a new resource may go inside the innermost container");
+    }
+
+    #[test]
+    fn test_containment_existing_cycle_is_rejected() {
+        // 2 and 3 already contain each other; placing 1 inside either must not loop forever
+        let lookup = containers(vec![(resource(2), resource(3)), (resource(3), resource(2))]);
+        assert!(is_validation_error(check_containment_chain(&resource(1), &resource(2), lookup)));
+    }
+
+    #[test]
+    fn test_containment_depth_limit() {
+        let depth = MAX_CONTAINMENT_DEPTH + 1;
+        let address = |i: usize| ResourceAddress(dna(), EntryHash::from_raw_36([(i % 256) as u8, (i / 256) as u8].iter().cloned().cycle().take(36).collect()));
+        let chain: Vec<(ResourceAddress, ResourceAddress)> = (1..=depth).map(|i| (address(i), address(i + 1))).collect();
+
+        assert!(is_validation_error(check_containment_chain(&resource(0xff), &address(1), containers(chain))));
+    }
+
+    fn qty(value: f64) -> QuantityValue {
+        QuantityValue::new(value, None)
+    }
+
+    fn stored_resource(quantity: f64) -> EntryData {
+        EntryData {
+            conforms_to: None,
+            classified_as: Some(vec!["http://example.com/apples".to_string().into()]),
+            tracking_identifier: None,
+            lot: None,
+            image: None,
+            accounting_quantity: Some(qty(quantity)),
+            onhand_quantity: Some(qty(quantity)),
+            unit_of_effort: None,
+            current_location: None,
+            contained_in: None,
+            note: None,
+            state: None,
+            stage: None,
+        }
+    }
+
+    fn with_quantity(event: EventData, quantity: f64) -> EventData {
+        EventData { resource_quantity: Some(qty(quantity)), ..event }
+    }
+
+    fn stage() -> ProcessSpecificationAddress {
+        ProcessSpecificationAddress(dna(), EntryHash::from_raw_36(vec![0x5e; 36]))
+    }
+
+    fn location() -> LocationAddress {
+        LocationAddress(dna(), EntryHash::from_raw_36(vec![0x10; 36]))
+    }
+
+    fn quantities(entry: &EntryData) -> (Option<QuantityValue>, Option<QuantityValue>) {
+        (entry.accounting_quantity.to_owned(), entry.onhand_quantity.to_owned())
+    }
+
+    #[test]
+    fn test_replay_produce_and_consume() {
+        let produced = apply_replayed_event(stored_resource(10.0), &resource(1), with_quantity(event("produce", Some(resource(1)), None), 5.0), None).unwrap();
+        assert_eq!(quantities(&produced), (Some(qty(15.0)), Some(qty(15.0))));
+
+        let consumed = apply_replayed_event(produced, &resource(1), with_quantity(event("consume", Some(resource(1)), None), 3.0), None).unwrap();
+        assert_eq!(quantities(&consumed), (Some(qty(12.0)), Some(qty(12.0))));
+    }
+
+    #[test]
+    fn test_replay_raise_and_lower() {
+        let raised = apply_replayed_event(stored_resource(10.0), &resource(1), with_quantity(event("raise", Some(resource(1)), None), 2.0), None).unwrap();
+        assert_eq!(quantities(&raised), (Some(qty(12.0)), Some(qty(12.0))));
+
+        let lowered = apply_replayed_event(raised, &resource(1), with_quantity(event("lower", Some(resource(1)), None), 4.0), None).unwrap();
+        assert_eq!(quantities(&lowered), (Some(qty(8.0)), Some(qty(8.0))));
+    }
+
+    #[test]
+    fn test_replay_transfer_affects_each_side() {
+        let transfer = with_quantity(event("transfer", Some(resource(1)), Some(resource(2))), 4.0);
+
+        let provider = apply_replayed_event(stored_resource(10.0), &resource(1), transfer.clone(), None).unwrap();
+        assert_eq!(quantities(&provider), (Some(qty(6.0)), Some(qty(6.0))));
+
+        let receiver = apply_replayed_event(stored_resource(1.0), &resource(2), transfer, None).unwrap();
+        assert_eq!(quantities(&receiver), (Some(qty(5.0)), Some(qty(5.0))));
+    }
+
+    #[test]
+    fn test_replay_event_unrelated_to_resource() {
+        let replayed = apply_replayed_event(stored_resource(10.0), &resource(3), with_quantity(event("transfer", Some(resource(1)), Some(resource(2))), 4.0), None).unwrap();
+        assert_eq!(quantities(&replayed), (Some(qty(10.0)), Some(qty(10.0))));
+    }
+
+    #[test]
+    fn test_replay_move_sets_location() {
+        let moved = EventData { at_location: Some(location()), ..with_quantity(event("move", Some(resource(1)), Some(resource(1))), 10.0) };
+        let replayed = apply_replayed_event(stored_resource(10.0), &resource(1), moved, None).unwrap();
+
+        assert_eq!(replayed.current_location, Some(location()));
+        assert_eq!(quantities(&replayed), (Some(qty(10.0)), Some(qty(10.0))), "moving a resource onto itself does not change its quantity");
+    }
+
+    #[test]
+    fn test_replay_pass_and_fail_set_state() {
+        let passed = apply_replayed_event(stored_resource(1.0), &resource(1), event("pass", Some(resource(1)), None), None).unwrap();
+        assert_eq!(passed.state, Some(ActionId("pass".to_string())));
+
+        let consumed = apply_replayed_event(passed, &resource(1), with_quantity(event("consume", Some(resource(1)), None), 1.0), None).unwrap();
+        assert_eq!(consumed.state, Some(ActionId("pass".to_string())), "other actions leave the state unchanged");
+
+        let failed = apply_replayed_event(consumed, &resource(1), event("fail", Some(resource(1)), None), None).unwrap();
+        assert_eq!(failed.state, Some(ActionId("fail".to_string())));
+    }
+
+    #[test]
+    fn test_replay_stage_applies_to_provider_only() {
+        let produced = apply_replayed_event(stored_resource(0.0), &resource(1), with_quantity(event("produce", Some(resource(1)), None), 1.0), Some(stage())).unwrap();
+        assert_eq!(produced.stage, Some(stage()));
+
+        let received = apply_replayed_event(stored_resource(0.0), &resource(2), with_quantity(event("transfer", Some(resource(1)), Some(resource(2))), 1.0), Some(stage())).unwrap();
+        assert_eq!(received.stage, None);
+
+        let unstaged = apply_replayed_event(produced, &resource(1), with_quantity(event("consume", Some(resource(1)), None), 1.0), None).unwrap();
+        assert_eq!(unstaged.stage, Some(stage()), "events outside of a process leave the stage unchanged");
+    }
+
+    #[test]
+    fn test_replay_combine_and_separate() {
+        let combined = apply_replayed_event(stored_resource(1.0), &resource(2), event("combine", Some(resource(2)), Some(resource(1))), None).unwrap();
+        assert_eq!(combined.contained_in, Some(resource(1)));
+
+        let container = apply_replayed_event(stored_resource(1.0), &resource(1), event("combine", Some(resource(2)), Some(resource(1))), None).unwrap();
+        assert_eq!(container.contained_in, None, "the container itself is not contained");
+
+        let separated = apply_replayed_event(combined, &resource(2), event("separate", Some(resource(2)), Some(resource(1))), None).unwrap();
+        assert_eq!(separated.contained_in, None);
+    }
+
+    #[test]
+    fn test_replay_merges_classifications() {
+        let classified = EventData {
+            resource_classified_as: Some(vec!["http://example.com/fruit".to_string().into(), "http://example.com/apples".to_string().into()]),
+            ..event("raise", Some(resource(1)), None)
+        };
+        let replayed = apply_replayed_event(stored_resource(1.0), &resource(1), classified, None).unwrap();
+
+        assert!(same_classifications(&replayed.classified_as, &Some(vec!["http://example.com/apples".to_string().into(), "http://example.com/fruit".to_string().into()])));
+        assert_eq!(replayed.classified_as.unwrap().len(), 2, "duplicate classifications are removed");
+    }
+
+    #[test]
+    fn test_find_drifted_fields() {
+        let stored = stored_resource(10.0).get_inventory_state();
+        assert!(find_drifted_fields(&stored, &stored.clone()).is_empty());
+
+        let recomputed = InventoryState {
+            accounting_quantity: Some(qty(9.0)),
+            current_location: Some(location()),
+            state: Some(ActionId("pass".to_string())),
+            stage: Some(stage()),
+            ..stored.clone()
+        };
+        assert_eq!(find_drifted_fields(&stored, &recomputed), vec!["accountingQuantity", "currentLocation", "state", "stage"]);
+
+        let onhand = InventoryState { onhand_quantity: Some(qty(11.0)), ..stored.clone() };
+        assert_eq!(find_drifted_fields(&stored, &onhand), vec!["onhandQuantity"]);
+    }
+
+    #[test]
+    fn test_find_drifted_fields_ignores_classification_order() {
+        let stored = InventoryState {
+            classified_as: Some(vec!["http://example.com/b".to_string().into(), "http://example.com/a".to_string().into()]),
+            ..stored_resource(1.0).get_inventory_state()
+        };
+        let reordered = InventoryState {
+            classified_as: Some(vec!["http://example.com/a".to_string().into(), "http://example.com/b".to_string().into()]),
+            ..stored.clone()
+        };
+        assert!(find_drifted_fields(&stored, &reordered).is_empty());
+
+        let extra = InventoryState {
+            classified_as: Some(vec!["http://example.com/a".to_string().into()]),
+            ..stored.clone()
+        };
+        assert_eq!(find_drifted_fields(&stored, &extra), vec!["classifiedAs"]);
+    }
+}
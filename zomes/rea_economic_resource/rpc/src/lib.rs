@@ -9,6 +9,7 @@
 use holochain_serialized_bytes::prelude::*;
 
 use serde_maybe_undefined::MaybeUndefined;
+use vf_measurement::QuantityValue;
 pub use vf_attributes_hdk::{
     RevisionHash,
     ResourceAddress,
@@ -17,6 +18,9 @@ pub use vf_attributes_hdk::{
     ResourceSpecificationAddress,
     UnitId,
     ProductBatchAddress,
+    ActionId,
+    ProcessSpecificationAddress,
+    DateTime, FixedOffset,
 };
 
 use hc_zome_rea_economic_event_rpc::{
@@ -107,6 +111,61 @@ impl<'a> UpdateRequest {
     }
 }
 
+//---------------- POINT-IN-TIME QUERIES ----------------
+
+/// I/O struct for reading the state of a resource as it was at some past time
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AsOfRequest {
+    pub address: ResourceAddress,
+    pub as_of: DateTime<FixedOffset>,
+}
+
+/// I/O struct for reading the state of all resources of some specification as they were at some past time
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecificationAsOfRequest {
+    pub conforms_to: ResourceSpecificationAddress,
+    pub as_of: DateTime<FixedOffset>,
+}
+
+//---------------- RECONCILIATION ----------------
+
+/// I/O struct for requesting recomputation of a resource's inventory from its event history
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconcileRequest {
+    pub address: ResourceAddress,
+    #[serde(default)]
+    pub write_correction: bool,
+}
+
+/// The subset of EconomicResource fields which are derived from EconomicEvents
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryState {
+    pub accounting_quantity: Option<QuantityValue>,
+    pub onhand_quantity: Option<QuantityValue>,
+    pub current_location: Option<LocationAddress>,
+    pub classified_as: Option<Vec<ExternalURL>>,
+    pub state: Option<ActionId>,
+    pub stage: Option<ProcessSpecificationAddress>,
+}
+
+/// Result of replaying all events affecting a resource and comparing against its stored state
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconciliationReport {
+    pub resource: ResourceAddress,
+    pub revision_id: RevisionHash,
+    pub events_replayed: usize,
+    pub stored: InventoryState,
+    pub recomputed: InventoryState,
+    pub drifted_fields: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub corrected_revision_id: Option<RevisionHash>,
+}
+
 //---------------- QUERY FILTER REQUEST ----------------
 
 #[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
@@ -8,11 +8,14 @@
  */
 use hdk::prelude::*;
 
+use std::convert::TryFrom;
 use hdk_records::{
+    config::ZomeConfig,
     MaybeUndefined, OtherCellResult,
+    RecordAPIResult, DataIntegrityError,
     generate_record_entry,
     record_interface::Updateable,
-    rpc::call_zome_method,
+    rpc::call_idempotent_zome_method,
 };
 
 use vf_measurement::*;
@@ -24,8 +27,9 @@ use vf_attributes_hdk::{
     UnitId,
     ProductBatchAddress,
     ActionId,
+    ProcessSpecificationAddress,
 };
-use vf_actions::{ ActionEffect, ActionInventoryEffect, get_builtin_action };
+use vf_actions::{ ActionEffect, ActionInventoryEffect, get_action };
 use hc_zome_rea_resource_specification_rpc::{ResponseData as ResourceSpecificationResponse};
 
 use hc_zome_rea_economic_resource_rpc::*;
@@ -39,17 +43,14 @@ pub use hdk_records::record_interface::Identified;
 
 //--------------- ZOME CONFIGURATION ATTRIBUTES ----------------
 
-// :TODO: remove this, replace with reference to appropriate namespacing of zome config
-#[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
-pub struct DnaConfigSlice {
-    pub economic_resource: EconomicResourceZomeConfig,
-}
-
 #[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct EconomicResourceZomeConfig {
     pub index_zome: String,
 }
 
+impl ZomeConfig for EconomicResourceZomeConfig {}
+
 //---------------- RECORD INTERNALS & VALIDATION ----------------
 
 #[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
@@ -65,6 +66,19 @@ pub struct EntryData {
     pub current_location: Option<LocationAddress>,
     pub contained_in: Option<ResourceAddress>,
     pub note: Option<String>,
+    /// most recent "pass" or "fail" action applied to the resource
+    ///
+    /// :NOTE: entries written before this was stored deserialize with `None`, and are not migrated.
+    /// Their state is assigned by the next "pass" or "fail" event affecting them.
+    ///
+    #[serde(default)]
+    pub state: Option<ActionId>,
+    /// specification of the most recent process to output the resource
+    ///
+    /// :NOTE: as with `state`, this is `None` for older entries until the resource is next output by a process.
+    ///
+    #[serde(default)]
+    pub stage: Option<ProcessSpecificationAddress>,
 }
 
 impl EntryData {
@@ -74,6 +88,18 @@ impl EntryData {
         }
         Ok(())
     }
+
+    /// Pick out the fields of the resource which are derived from EconomicEvents
+    pub fn get_inventory_state(&self) -> InventoryState {
+        InventoryState {
+            accounting_quantity: self.accounting_quantity.to_owned(),
+            onhand_quantity: self.onhand_quantity.to_owned(),
+            current_location: self.current_location.to_owned(),
+            classified_as: self.classified_as.to_owned(),
+            state: self.state.to_owned(),
+            stage: self.stage.to_owned(),
+        }
+    }
 }
 
 generate_record_entry!(EntryData, ResourceAddress, EntryStorage);
@@ -82,13 +108,15 @@ generate_record_entry!(EntryData, ResourceAddress, EntryStorage);
 
 /// Handles create operations via observed event resource inspection parameter
 /// @see https://github.com/holo-rea/holo-rea/issues/65
-impl From<CreationPayload> for EntryData
+impl TryFrom<CreationPayload> for EntryData
 {
-    fn from(t: CreationPayload) -> EntryData {
+    type Error = DataIntegrityError;
+
+    fn try_from(t: CreationPayload) -> RecordAPIResult<EntryData> {
         let conforming = t.get_resource_specification_id();
         let r = t.resource;
         let e = t.event;
-        EntryData {
+        Ok(EntryData {
             conforms_to: conforming.clone(),
             classified_as: if e.resource_classified_as == MaybeUndefined::Undefined { None } else { e.resource_classified_as.to_owned().to_option() },
             tracking_identifier: if r.tracking_identifier == MaybeUndefined::Undefined { None } else { r.tracking_identifier.to_owned().to_option() },
@@ -104,7 +132,7 @@ impl From<CreationPayload> for EntryData
                         Some(inventory_type) => inventory_type.to_owned(),
                         None => panic!("Developer error: EconomicEvent inventory type must be provided when creating EconomicResource!"),
                     },
-                ),
+                )?,
                 _ => None,
             },
             onhand_quantity: match e.resource_quantity.to_owned() {
@@ -117,7 +145,7 @@ impl From<CreationPayload> for EntryData
                         Some(inventory_type) => inventory_type.to_owned(),
                         None => panic!("Developer error: EconomicEvent inventory type must be provided when updating EconomicResource!"),
                     },
-                ),
+                )?,
                 _ => None,
             },
             unit_of_effort: match conforming {
@@ -127,7 +155,9 @@ impl From<CreationPayload> for EntryData
             current_location: if r.current_location == MaybeUndefined::Undefined { None } else { r.current_location.to_owned().to_option() },
             contained_in: if r.contained_in == MaybeUndefined::Undefined { None } else { r.contained_in.to_owned().to_option() },
             note: if r.note == MaybeUndefined::Undefined { None } else { r.note.clone().into() },
-        }
+            state: get_event_state(&e, &None),
+            stage: None,    // :NOTE: assigned by zome API, since it requires a lookup of the related Process
+        })
     }
 }
 
@@ -139,7 +169,7 @@ pub struct GetSpecificationRequest {
 }
 
 fn get_default_unit_for_specification(specification_id: ResourceSpecificationAddress) -> Option<UnitId> {
-    let spec_data: OtherCellResult<ResourceSpecificationResponse> = call_zome_method(
+    let spec_data: OtherCellResult<ResourceSpecificationResponse> = call_idempotent_zome_method(
         &specification_id,
         &String::from("read_resource_specification"),
         GetSpecificationRequest { address: specification_id.to_owned() },
@@ -168,21 +198,45 @@ impl Updateable<UpdateRequest> for EntryData {
             current_location: self.current_location.to_owned(),
             contained_in: if e.contained_in == MaybeUndefined::Undefined { self.contained_in.to_owned() } else { e.contained_in.to_owned().to_option() },
             note: if e.note == MaybeUndefined::Undefined { self.note.to_owned() } else { e.note.to_owned().to_option() },
+            state: self.state.to_owned(),
+            stage: self.stage.to_owned(),
         }
     }
 }
 
-/// Handle update operations by observed events
-///
-/// :WARNING: we presume the event has already been determined to relate to the
-/// resource, and this method will panic if that is not the case.
-///
-/// Currently it is only called within `hdk_records::record_helpers::update_record`,
-/// where this check is already implicitly performed.
-///
-impl Updateable<EventCreateRequest> for EntryData {
-    fn update_with(&self, e: EventCreateRequest) -> EntryData {
+/// Handles corrective updates, overwriting event-derived fields with values recomputed from event history
+impl Updateable<InventoryState> for EntryData {
+    fn update_with(&self, e: InventoryState) -> EntryData {
         EntryData {
+            accounting_quantity: e.accounting_quantity,
+            onhand_quantity: e.onhand_quantity,
+            current_location: e.current_location,
+            classified_as: e.classified_as,
+            state: e.state,
+            stage: e.stage,
+            ..self.to_owned()
+        }
+    }
+}
+
+/// Replaces the entry with a revision computed by the zome API, for updates which
+/// may fail and so cannot be expressed as an `Updateable` payload.
+impl Updateable<EntryData> for EntryData {
+    fn update_with(&self, e: EntryData) -> EntryData {
+        e
+    }
+}
+
+impl EntryData {
+    /// Handle update operations by observed events
+    ///
+    /// :WARNING: we presume the event has already been determined to relate to the
+    /// resource, and this method will panic if that is not the case.
+    ///
+    /// Errors if the action of the event cannot be resolved.
+    ///
+    pub fn apply_event(&self, e: EventCreateRequest) -> RecordAPIResult<EntryData> {
+        Ok(EntryData {
             conforms_to: self.conforms_to.to_owned(),
             classified_as: {
                 if let MaybeUndefined::Some(classified_as) = e.resource_classified_as.to_owned() {
@@ -213,14 +267,14 @@ impl Updateable<EventCreateRequest> for EntryData {
                     Some(inventory_type) => inventory_type.to_owned(),
                     None => panic!("Developer error: EconomicEvent inventory type must be provided when updating EconomicResource!"),
                 },
-            ),
+            )?,
             onhand_quantity: update_quantity(
                 self.onhand_quantity.to_owned(), e.resource_quantity.to_owned(),
                 &e.action, ResourceValueType::OnhandValue, match &e.target_inventory_type {
                     Some(inventory_type) => inventory_type.to_owned(),
                     None => panic!("Developer error: EconomicEvent inventory type must be provided when updating EconomicResource!"),
                 },
-            ),
+            )?,
             unit_of_effort: self.unit_of_effort.to_owned(), // :TODO: pull from e.resource_conforms_to.unit_of_effort
             current_location: if e.get_action() == "move" {
                 if let MaybeUndefined::Some(at_location) = e.get_location() {
@@ -229,9 +283,49 @@ impl Updateable<EventCreateRequest> for EntryData {
                     self.current_location.to_owned()
                 }
             } else { self.current_location.to_owned() },
-            contained_in: self.contained_in.to_owned(),
+            contained_in: match &e.target_inventory_type {
+                // only the combined / separated resource changes its container, never the container itself
+                Some(ResourceInventoryType::ProvidingInventory) => match e.get_action() {
+                    "combine" => e.to_resource_inventoried_as.to_owned().to_option(),
+                    "separate" => None,
+                    _ => self.contained_in.to_owned(),
+                },
+                _ => self.contained_in.to_owned(),
+            },
             note: self.note.to_owned(),
-        }
+            state: get_event_state(&e, &self.state),
+            stage: self.stage.to_owned(),
+        })
+    }
+
+    /// Handle update operations by observed events, including any change of stage
+    pub fn apply_inventory_update(&self, e: InventoryUpdatePayload) -> RecordAPIResult<EntryData> {
+        let updated = self.apply_event(e.event)?;
+        Ok(EntryData {
+            stage: match e.stage {
+                Some(stage) => Some(stage),
+                None => updated.stage.to_owned(),
+            },
+            ..updated
+        })
+    }
+}
+
+/// Event update payload, paired with the `ProcessSpecification` of the process the event is an
+/// output of (if any). The zome API resolves this before updating, since it requires a lookup of
+/// the related `Process` record.
+///
+#[derive(Clone, Debug)]
+pub struct InventoryUpdatePayload {
+    pub event: EventCreateRequest,
+    pub stage: Option<ProcessSpecificationAddress>,
+}
+
+/// "pass" and "fail" actions set the state of the resource they affect, others leave it unchanged
+fn get_event_state(e: &EventCreateRequest, current_state: &Option<ActionId>) -> Option<ActionId> {
+    match e.get_action() {
+        "pass" | "fail" => Some(e.action.to_owned()),
+        _ => current_state.to_owned(),
     }
 }
 
@@ -242,23 +336,23 @@ fn update_quantity(
     action: &ActionId,
     which_qty_type: ResourceValueType,
     which_inventory_type: ResourceInventoryType,
-) -> Option<QuantityValue> {
+) -> RecordAPIResult<Option<QuantityValue>> {
     if None == current_val {
-        return None;
+        return Ok(None);
     }
     if MaybeUndefined::None == event_val || MaybeUndefined::Undefined == event_val {
-        return current_val;
+        return Ok(current_val);
     }
     let current = current_val.unwrap();
     let event_qty = event_val.unwrap();
 
-    let action_to_perform = get_event_action(action, which_qty_type, which_inventory_type);
+    let action_to_perform = get_event_action(action, which_qty_type, which_inventory_type)?;
 
-    match action_to_perform {
+    Ok(match action_to_perform {
         ActionInventoryEffect::NoEffect => Some(current),
         ActionInventoryEffect::Increment => Some(add(current, event_qty)),
         ActionInventoryEffect::Decrement => Some(subtract(current, event_qty)),
-    }
+    })
 }
 
 enum ResourceValueType {
@@ -273,11 +367,11 @@ fn get_event_action(
     action: &ActionId,
     which_qty_type: ResourceValueType,
     which_inventory_type: ResourceInventoryType,
-) -> ActionInventoryEffect {
+) -> RecordAPIResult<ActionInventoryEffect> {
     let action_str: &str = (*action).as_ref();
 
-    match get_builtin_action(action_str) {
-        Some(action_obj) => match &action_str[..] {
+    match get_action(action_str)? {
+        Some(action_obj) => Ok(match &action_str[..] {
             // 'transfer-custody' updates onHand but not Accounting
             "transfer-custody" => match which_qty_type {
                 ResourceValueType::AccountingValue => ActionInventoryEffect::NoEffect,
@@ -316,11 +410,112 @@ fn get_event_action(
                     },
                 }
             }
-        },
-        None => {
-            let mut err_string: String = "unknown EconomicEvent action type: ".to_string();
-            err_string.push_str(action.as_ref());
-            panic!("{:?}", err_string);
+        }),
+        None => Err(DataIntegrityError::ValidationFailed(format!("unknown EconomicEvent action type: {}", action_str))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vf_attributes_hdk::AgentAddress;
+
+    fn dna() -> DnaHash {
+        DnaHash::from_raw_36(vec![0xdb; 36])
+    }
+
+    fn stage(byte: u8) -> ProcessSpecificationAddress {
+        ProcessSpecificationAddress(dna(), EntryHash::from_raw_36(vec![byte; 36]))
+    }
+
+    fn action(id: &str) -> ActionId {
+        ActionId(id.to_string())
+    }
+
+    fn resource() -> EntryData {
+        EntryData {
+            conforms_to: None,
+            classified_as: Some(vec!["http://example.com/apples".to_string().into()]),
+            tracking_identifier: None,
+            lot: None,
+            image: None,
+            accounting_quantity: Some(QuantityValue::new(1.0, None)),
+            onhand_quantity: Some(QuantityValue::new(1.0, None)),
+            unit_of_effort: None,
+            current_location: None,
+            contained_in: None,
+            note: Some("apples".to_string()),
+            state: None,
+            stage: None,
         }
     }
+
+    fn event(action_id: &str) -> EventCreateRequest {
+        let agent = AgentAddress(dna(), AgentPubKey::from_raw_36(vec![0xaa; 36]));
+        EventCreateRequest {
+            action: action(action_id),
+            note: MaybeUndefined::Undefined,
+            input_of: MaybeUndefined::Undefined,
+            output_of: MaybeUndefined::Undefined,
+            provider: agent.to_owned(),
+            receiver: agent,
+            resource_inventoried_as: MaybeUndefined::Undefined,
+            to_resource_inventoried_as: MaybeUndefined::Undefined,
+            resource_classified_as: MaybeUndefined::Undefined,
+            resource_conforms_to: MaybeUndefined::Undefined,
+            resource_quantity: MaybeUndefined::Undefined,
+            effort_quantity: MaybeUndefined::Undefined,
+            has_beginning: MaybeUndefined::Undefined,
+            has_end: MaybeUndefined::Undefined,
+            has_point_in_time: MaybeUndefined::Undefined,
+            at_location: MaybeUndefined::Undefined,
+            agreed_in: MaybeUndefined::Undefined,
+            realization_of: MaybeUndefined::Undefined,
+            triggered_by: MaybeUndefined::Undefined,
+            in_scope_of: MaybeUndefined::Undefined,
+            target_inventory_type: Some(ResourceInventoryType::ProvidingInventory),
+        }
+    }
+
+    fn update(entry: &EntryData, action_id: &str, stage: Option<ProcessSpecificationAddress>) -> EntryData {
+        entry.apply_inventory_update(InventoryUpdatePayload { event: event(action_id), stage }).unwrap()
+    }
+
+    #[test]
+    fn test_pass_and_fail_set_state() {
+        let passed = update(&resource(), "pass", None);
+        assert_eq!(passed.state, Some(action("pass")));
+
+        let modified = update(&passed, "modify", None);
+        assert_eq!(modified.state, Some(action("pass")), "other actions keep the last inspection result");
+
+        let failed = update(&modified, "fail", None);
+        assert_eq!(failed.state, Some(action("fail")));
+    }
+
+    #[test]
+    fn test_process_output_sets_stage() {
+        let staged = update(&resource(), "produce", Some(stage(1)));
+        assert_eq!(staged.stage, Some(stage(1)));
+
+        let restaged = update(&staged, "modify", Some(stage(2)));
+        assert_eq!(restaged.stage, Some(stage(2)));
+
+        let unstaged = update(&restaged, "raise", None);
+        assert_eq!(unstaged.stage, Some(stage(2)), "events outside of a staged process keep the last stage");
+    }
+
+    #[test]
+    fn test_corrections_overwrite_state_and_stage() {
+        let stored = EntryData { state: Some(action("fail")), stage: Some(stage(1)), ..resource() };
+        let corrected = stored.update_with(InventoryState {
+            state: Some(action("pass")),
+            stage: None,
+            ..stored.get_inventory_state()
+        });
+
+        assert_eq!(corrected.state, Some(action("pass")));
+        assert_eq!(corrected.stage, None);
+        assert_eq!(corrected.note, stored.note, "fields not derived from events are preserved");
+    }
 }
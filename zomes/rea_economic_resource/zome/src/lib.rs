@@ -8,6 +8,7 @@
  * @package Holo-REA
  */
 use hdk::prelude::*;
+use hdk_records::{ ErrorEnvelope, ErrorCode, config::init_with_config };
 
 use hc_zome_rea_economic_resource_lib::*;
 use hc_zome_rea_economic_resource_rpc::*;
@@ -15,8 +16,14 @@ use hc_zome_rea_economic_resource_storage::*;
 use hc_zome_rea_economic_event_rpc::{
     ResourceResponseData as ResponseData,
     CreateRequest as EventCreateRequest,
+    ProductionFlowItem,
 };
 
+#[hdk_extern]
+fn init(_: ()) -> ExternResult<InitCallbackResult> {
+    Ok(init_with_config::<EconomicResourceZomeConfig>())
+}
+
 #[hdk_extern]
 fn entry_defs(_: ()) -> ExternResult<EntryDefsCallbackResult> {
     Ok(EntryDefsCallbackResult::from(vec![
@@ -27,7 +34,9 @@ fn entry_defs(_: ()) -> ExternResult<EntryDefsCallbackResult> {
             crdt_type: CrdtType,
             required_validations: 2.into(),
             required_validation_type: RequiredValidationType::default(),
-        }
+        },
+        blob_chunk_entry_def(),
+        blob_manifest_entry_def(),
     ]))
 }
 
@@ -45,7 +54,7 @@ fn validate(validation_data: ValidateData) -> ExternResult<ValidateCallbackResul
             let record = resource_storage.entry();
             record.validate()
                 .and_then(|()| { Ok(ValidateCallbackResult::Valid) })
-                .or_else(|e| { Ok(ValidateCallbackResult::Invalid(e)) })
+                .or_else(|e| { Ok(ValidateCallbackResult::Invalid(ErrorEnvelope::new(ErrorCode::ValidationFailed, e).to_string())) })
         },
         _ => Ok(ValidateCallbackResult::Valid),
     }
@@ -57,7 +66,7 @@ fn validate(validation_data: ValidateData) -> ExternResult<ValidateCallbackResul
 fn _internal_create_inventory(params: CreationPayload) -> ExternResult<(RevisionHash, ResourceAddress, EntryData)>
 {
     Ok(handle_create_inventory_from_event(
-        RESOURCE_ENTRY_TYPE,
+        RESOURCE_ENTRY_TYPE, PROCESS_ENTRY_TYPE,
         params,
     )?)
 }
@@ -65,7 +74,7 @@ fn _internal_create_inventory(params: CreationPayload) -> ExternResult<(Revision
 #[hdk_extern]
 fn _internal_update_inventory(event: EventCreateRequest) -> ExternResult<Vec<(RevisionHash, ResourceAddress, EntryData, EntryData)>>
 {
-    Ok(handle_update_inventory_from_event(RESOURCE_ENTRY_TYPE, event)?)
+    Ok(handle_update_inventory_from_event(RESOURCE_ENTRY_TYPE, PROCESS_ENTRY_TYPE, event)?)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -76,7 +85,7 @@ struct ByAddress {
 #[hdk_extern]
 fn get_resource(ByAddress { address }: ByAddress) -> ExternResult<ResponseData> {
     Ok(handle_get_economic_resource(
-        RESOURCE_ENTRY_TYPE, EVENT_ENTRY_TYPE, PROCESS_ENTRY_TYPE,
+        RESOURCE_ENTRY_TYPE,
         address,
     )?)
 }
@@ -89,12 +98,54 @@ struct UpdateParams {
 #[hdk_extern]
 fn update_resource(UpdateParams { resource }: UpdateParams) -> ExternResult<ResponseData> {
     Ok(handle_update_economic_resource(
-        RESOURCE_ENTRY_TYPE, EVENT_ENTRY_TYPE, PROCESS_ENTRY_TYPE,
+        RESOURCE_ENTRY_TYPE,
         resource
     )?)
 }
 
 #[hdk_extern]
 fn get_all_resources(_: ()) -> ExternResult<Vec<ResponseData>> {
-    Ok(handle_get_all_economic_resources(RESOURCE_ENTRY_TYPE, EVENT_ENTRY_TYPE, PROCESS_ENTRY_TYPE)?)
+    Ok(handle_get_all_economic_resources(RESOURCE_ENTRY_TYPE)?)
+}
+
+#[hdk_extern]
+fn get_resource_as_of(AsOfRequest { address, as_of }: AsOfRequest) -> ExternResult<ResponseData> {
+    Ok(handle_get_economic_resource_as_of(
+        RESOURCE_ENTRY_TYPE, EVENT_ENTRY_TYPE, PROCESS_ENTRY_TYPE,
+        address, as_of,
+    )?)
+}
+
+#[hdk_extern]
+fn get_resources_by_specification_as_of(SpecificationAsOfRequest { conforms_to, as_of }: SpecificationAsOfRequest) -> ExternResult<Vec<ResponseData>> {
+    Ok(handle_get_economic_resources_by_specification_as_of(
+        RESOURCE_ENTRY_TYPE, EVENT_ENTRY_TYPE, PROCESS_ENTRY_TYPE,
+        conforms_to, as_of,
+    )?)
+}
+
+#[hdk_extern]
+fn reconcile_resource(ReconcileRequest { address, write_correction }: ReconcileRequest) -> ExternResult<ReconciliationReport> {
+    Ok(handle_reconcile_economic_resource(
+        RESOURCE_ENTRY_TYPE, EVENT_ENTRY_TYPE, PROCESS_ENTRY_TYPE,
+        address, write_correction,
+    )?)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TraversalParams {
+    pub address: ResourceAddress,
+    pub max_depth: Option<usize>,
+    /// maximum number of results to return
+    pub limit: Option<usize>,
+}
+
+#[hdk_extern]
+fn trace(TraversalParams { address, max_depth, limit }: TraversalParams) -> ExternResult<Vec<ProductionFlowItem>> {
+    Ok(hc_zome_rea_economic_event_lib::trace(ProductionFlowItem::EconomicResource(address), max_depth, limit)?)
+}
+
+#[hdk_extern]
+fn track(TraversalParams { address, max_depth, limit }: TraversalParams) -> ExternResult<Vec<ProductionFlowItem>> {
+    Ok(hc_zome_rea_economic_event_lib::track(ProductionFlowItem::EconomicResource(address), max_depth, limit)?)
 }
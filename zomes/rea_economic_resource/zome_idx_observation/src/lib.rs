@@ -6,6 +6,7 @@
  */
 use hdk::prelude::*;
 use hdk_records::{
+    config::init_with_config,
     index_retrieval::{
         ByAddress,
         IndexingZomeConfig,
@@ -29,14 +30,13 @@ use hc_zome_rea_resource_specification_storage_consts::{ ECONOMIC_RESOURCE_SPECI
 
 entry_defs![Path::entry_def()];
 
-// :TODO: obviate this with zome-specific configs
-#[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
-pub struct DnaConfigSlice {
-    pub economic_resource_index: IndexingZomeConfig,
+#[hdk_extern]
+fn init(_: ()) -> ExternResult<InitCallbackResult> {
+    Ok(init_with_config::<IndexingZomeConfig>())
 }
 
-fn read_index_target_zome(conf: DnaConfigSlice) -> Option<String> {
-    Some(conf.economic_resource_index.record_storage_zome)
+fn read_index_target_zome(conf: IndexingZomeConfig) -> Option<String> {
+    Some(conf.record_storage_zome)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
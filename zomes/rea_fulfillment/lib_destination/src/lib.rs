@@ -12,6 +12,7 @@
 use hdk::prelude::*;
 use hdk_records::{
     RecordAPIResult, DataIntegrityError,
+    config::ZomeConfig,
     records::{
         create_record,
         read_record_entry,
@@ -26,17 +27,27 @@ use hdk_records::{
     local_indexes::{
         query_index,
     },
+    rpc::call_idempotent_zome_method,
+    record_interface::Updateable,
 };
+use vf_flow_matching::ensure_flows_match;
 
 use hc_zome_rea_fulfillment_storage::*;
 use hc_zome_rea_fulfillment_rpc::*;
 use hc_zome_rea_fulfillment_lib::construct_response;
 use hc_zome_rea_fulfillment_storage_consts::*;
-use hc_zome_rea_economic_event_storage_consts::{EVENT_FULFILLS_LINK_TAG};
+use hc_zome_rea_economic_event_storage_consts::{EVENT_ENTRY_TYPE, EVENT_FULFILLS_LINK_TAG};
+use hc_zome_rea_economic_event_storage::{
+    EntryData as EventData,
+    EntryStorage as EventStorage,
+};
+use hc_zome_rea_commitment_rpc::{ResponseData as CommitmentResponse};
 
 pub fn handle_create_fulfillment<S>(entry_def_id: S, fulfillment: CreateRequest) -> RecordAPIResult<ResponseData>
     where S: AsRef<str>
 {
+    validate_fulfillment(&fulfillment.to_owned().into())?;
+
     let (revision_id, fulfillment_address, entry_resp): (_,_, EntryData) = create_record(&entry_def_id, fulfillment.to_owned())?;
 
     // link entries in the local DNA
@@ -64,6 +75,9 @@ pub fn handle_get_fulfillment<S>(entry_def_id: S, address: FulfillmentAddress) -
 pub fn handle_update_fulfillment<S>(entry_def_id: S, fulfillment: UpdateRequest) -> RecordAPIResult<ResponseData>
     where S: AsRef<str>
 {
+    let (_, current) = read_record_entry_by_header::<EntryData, EntryStorage, FulfillmentAddress>(fulfillment.get_revision_id())?;
+    validate_fulfillment(&current.update_with(fulfillment.to_owned()))?;
+
     let (revision_id, base_address, new_entry, prev_entry): (_, FulfillmentAddress, EntryData, EntryData) = update_record(&entry_def_id, &fulfillment.get_revision_id(), fulfillment.to_owned())?;
 
     if new_entry.fulfilled_by != prev_entry.fulfilled_by {
@@ -100,14 +114,31 @@ pub fn handle_delete_fulfillment(revision_id: RevisionHash) -> RecordAPIResult<b
     delete_record::<EntryStorage, _>(&revision_id)
 }
 
+/// Check that a replicated `Fulfillment` links a local `EconomicEvent` compatible with the remote
+/// `Commitment` it fulfills.
+///
+/// :NOTE: cumulative quantities are checked by the origin zome, which has access to all of a
+///        commitment's fulfillments.
+///
+fn validate_fulfillment(fulfillment: &EntryData) -> RecordAPIResult<()> {
+    let (_, _, event): (_, EventAddress, EventData) = read_record_entry::<EventData, EventStorage, _,_>(&EVENT_ENTRY_TYPE, fulfillment.fulfilled_by.as_ref())?;
+    let commitment: CommitmentResponse = call_idempotent_zome_method(
+        &fulfillment.fulfills,
+        &COMMITMENT_READ_REMOTE_PERMISSION,
+        ByAddress { address: fulfillment.fulfills.to_owned() },
+    )?;
+
+    ensure_flows_match(&commitment.commitment, &event)
+}
+
 /// Properties accessor for zome config.
-fn read_foreign_event_index_zome(conf: DnaConfigSliceObservation) -> Option<String> {
-    Some(conf.fulfillment.economic_event_index_zome)
+fn read_foreign_event_index_zome(conf: FulfillmentZomeConfigObservation) -> Option<String> {
+    Some(conf.economic_event_index_zome)
 }
 
 /// Properties accessor for zome config.
-fn read_foreign_index_zome(conf: DnaConfigSliceObservation) -> Option<String> {
-    Some(conf.fulfillment.index_zome)
+fn read_foreign_index_zome(conf: FulfillmentZomeConfigObservation) -> Option<String> {
+    Some(conf.index_zome)
 }
 
 const READ_FN_NAME: &str = "get_fulfillment";
@@ -117,8 +148,7 @@ pub fn generate_query_handler<S, C, F>(
     event_entry_def_id: S,
 ) -> impl FnOnce(&QueryParams) -> RecordAPIResult<Vec<ResponseData>>
     where S: AsRef<str>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: Fn(C) -> Option<String>,
 {
     move |params| {
@@ -12,10 +12,14 @@
 use hdk::prelude::*;
 use hdk_records::{
     RecordAPIResult, OtherCellResult, DataIntegrityError,
+    CrossCellError, ErrorCode,
+    MaybeUndefined,
+    config::{ ZomeConfig, zome_config },
     records::{
         create_record,
         read_record_entry,
         read_record_entry_by_header,
+        read_record_initial_entry,
         update_record,
         delete_record,
     },
@@ -24,19 +28,70 @@ use hdk_records::{
         update_foreign_index,
     },
     local_indexes::query_index,
-    rpc::call_zome_method,
+    rpc::{
+        call_zome_method,
+        call_idempotent_zome_method,
+        call_local_zome_method,
+    },
+    record_interface::{ Identifiable, Updateable },
+    outbox::{
+        Outbox,
+        outbox_entry_def,
+        read_outbox,
+        replicate,
+        flush_outbox,
+        acknowledge,
+        Acknowledgement,
+        remove_outbox_item,
+    },
+    uris::{
+        UriRoute,
+        call_uri_method,
+    },
 };
+use vf_measurement::{ QuantityValue, checked_add, checked_subtract };
+use vf_flow_matching::ensure_flows_match;
+use vf_attributes_hdk::{ DnaAddressable, UriAddressable };
 
 use hc_zome_rea_commitment_storage_consts::{COMMITMENT_FULFILLEDBY_LINK_TAG};
+use hc_zome_rea_commitment_rpc::{ResponseData as CommitmentResponse};
+use hc_zome_rea_economic_event_rpc::{ResponseData as EventResponse};
 use hc_zome_rea_fulfillment_storage_consts::*;
 use hc_zome_rea_fulfillment_storage::*;
 use hc_zome_rea_fulfillment_rpc::*;
 use hc_zome_rea_fulfillment_lib::construct_response;
 
+/// Entry types defined by the zome, to be returned from its `entry_defs()` extern.
+/// The outbox entry type is located by its position in this list.
+///
+pub fn zome_entry_defs() -> EntryDefs {
+    EntryDefs::from(vec![
+        Path::entry_def(),
+        EntryDef {
+            id: FULFILLMENT_ENTRY_TYPE.into(),
+            visibility: EntryVisibility::Public,
+            crdt_type: CrdtType,
+            required_validations: 1.into(),
+            required_validation_type: RequiredValidationType::default(),
+        },
+        outbox_entry_def(FULFILLMENT_OUTBOX_ENTRY_TYPE),
+    ])
+}
+
+/// Outbox of replication calls to the observation DNA
+fn outbox() -> RecordAPIResult<Outbox> {
+    Outbox::new(FULFILLMENT_OUTBOX_ENTRY_TYPE, &zome_entry_defs())
+}
+
 pub fn handle_create_fulfillment<S>(entry_def_id: S, fulfillment: CreateRequest) -> RecordAPIResult<ResponseData>
     where S: AsRef<str>
 {
-    let (revision_id, fulfillment_address, entry_resp): (_,_, EntryData) = create_record(&entry_def_id, fulfillment.to_owned())?;
+    validate_fulfillment(&fulfillment.to_owned().into(), None)?;
+
+    let (revision_id, fulfillment_address, entry_resp): (_,_, EntryData) = create_record(&entry_def_id, EntryData {
+        replication: Some(ReplicationStatus::Pending),
+        ..fulfillment.to_owned().into()
+    })?;
 
     // link entries in the local DNA
     let _results = create_foreign_index(
@@ -49,12 +104,12 @@ pub fn handle_create_fulfillment<S>(entry_def_id: S, fulfillment: CreateRequest)
     )?;
 
     // update in the associated foreign DNA as well
-    let _pingback: OtherCellResult<ResponseData> = call_zome_method(
-        fulfillment.get_fulfilled_by(),
-        &REPLICATE_CREATE_API_METHOD,
-        CreateParams { fulfillment: fulfillment.to_owned() },
-    );
-    // :TODO: report any error
+    let (revision_id, entry_resp) = replicate_change(
+        &entry_def_id, revision_id, entry_resp, &fulfillment_address,
+        fulfillment.get_fulfilled_by(), ReplicationOperation::Create(fulfillment.to_owned()),
+    )?;
+
+    recompute_commitment(&entry_resp.fulfills)?;
 
     construct_response(&fulfillment_address, &revision_id, &entry_resp)
 }
@@ -69,7 +124,21 @@ pub fn handle_get_fulfillment<S>(entry_def_id: S, address: FulfillmentAddress) -
 pub fn handle_update_fulfillment<S>(entry_def_id: S, fulfillment: UpdateRequest) -> RecordAPIResult<ResponseData>
     where S: AsRef<str>
 {
-    let (revision_id, base_address, new_entry, prev_entry): (_, FulfillmentAddress, EntryData, EntryData) = update_record(&entry_def_id, &fulfillment.get_revision_id(), fulfillment.to_owned())?;
+    let (identity_address, current) = read_record_entry_by_header::<EntryData, EntryStorage, FulfillmentAddress>(fulfillment.get_revision_id())?;
+    let next = current.update_with(fulfillment.to_owned());
+    validate_fulfillment(&next, Some(&current))?;
+
+    let (revision_id, base_address, new_entry, prev_entry): (_, FulfillmentAddress, EntryData, EntryData) = update_record(&entry_def_id, &fulfillment.get_revision_id(), ReplicatedUpdatePayload {
+        update: fulfillment.to_owned(),
+        replication: Some(ReplicationStatus::Pending),
+    })?;
+
+    // update fulfillment records in remote DNA (and by proxy, event indexes in remote DNA)
+    // :TODO: update to intelligently call remote DNAs if new & old target record are not in same network
+    let (revision_id, new_entry) = replicate_change(
+        &entry_def_id, revision_id, new_entry, &identity_address,
+        &current.fulfilled_by, ReplicationOperation::Update(fulfillment.to_owned()),
+    )?;
 
     // update commitment indexes in local DNA
     if new_entry.fulfills != prev_entry.fulfills {
@@ -79,19 +148,14 @@ pub fn handle_update_fulfillment<S>(entry_def_id: S, fulfillment: UpdateRequest)
             &base_address,
             read_foreign_commitment_index_zome,
             &COMMITMENT_FULFILLEDBY_INDEXING_API_METHOD,
-            vec![new_entry.fulfills.clone()].as_slice(), vec![prev_entry.fulfills].as_slice(),
+            vec![new_entry.fulfills.clone()].as_slice(), vec![prev_entry.fulfills.clone()].as_slice(),
         )?;
     }
 
-    // update fulfillment records in remote DNA (and by proxy, event indexes in remote DNA)
-    if new_entry.fulfilled_by != prev_entry.fulfilled_by {
-        let _pingback: OtherCellResult<ResponseData> = call_zome_method(
-            // :TODO: update to intelligently call remote DNAs if new & old target record are not in same network
-            &prev_entry.fulfilled_by,
-            &REPLICATE_UPDATE_API_METHOD,
-            UpdateParams { fulfillment: fulfillment.to_owned() },
-        );
-        // :TODO: report any error
+    // quantities may have changed as well as the fulfilled commitment
+    recompute_commitment(&new_entry.fulfills)?;
+    if new_entry.fulfills != prev_entry.fulfills {
+        recompute_commitment(&prev_entry.fulfills)?;
     }
 
     construct_response(&base_address, &revision_id, &new_entry)
@@ -108,28 +172,311 @@ pub fn handle_delete_fulfillment(revision_id: RevisionHash) -> RecordAPIResult<b
         &base_address,
         read_foreign_commitment_index_zome,
         &COMMITMENT_FULFILLEDBY_INDEXING_API_METHOD,
-        vec![].as_slice(), vec![entry.fulfills].as_slice(),
+        vec![].as_slice(), vec![entry.fulfills.clone()].as_slice(),
     )?;
 
+    let deleted = delete_record::<EntryStorage, _>(&revision_id)?;
+
     // update fulfillment records in remote DNA (and by proxy, event indexes in remote DNA)
-    let _pingback: OtherCellResult<ResponseData> = call_zome_method(
-        &entry.fulfilled_by,
-        &REPLICATE_DELETE_API_METHOD,
-        ByHeader { address: revision_id.to_owned() },
+    replicate(&outbox()?, &base_address, &entry.fulfilled_by, ReplicationOperation::Delete(revision_id.to_owned()), entry.replica_revision_id.as_ref())?;
+
+    // progress may no longer meet the commitment, reverting it to unfinished
+    recompute_commitment(&entry.fulfills)?;
+
+    Ok(deleted)
+}
+
+/// Retry delivery of all replication calls in the outbox which are due, marking `Fulfillment`s
+/// with no calls left undelivered as acknowledged.
+///
+pub fn handle_flush_replication_outbox<S>(entry_def_id: S) -> RecordAPIResult<OutboxFlushReport>
+    where S: AsRef<str>
+{
+    flush_outbox::<FulfillmentAddress, EventAddress, ReplicationOperation, _>(
+        &outbox()?,
+        |record, ack| acknowledge::<EntryData, EntryStorage, _,_,_>(&entry_def_id, record, ack),
+    )
+}
+
+/// Compare `Fulfillment`s against their replicas in the observation DNA, by ID, revision and field values.
+///
+/// A replica is divergent where its fields differ from the planning record, or where it has been revised
+/// since it was last written from here. Replicas remaining for fulfillments deleted in this DNA are orphaned.
+///
+/// With `repair` set, any missing or divergent replica is overwritten with the planning record, which
+/// always takes precedence, and orphaned replicas are deleted. Queued replication calls are then dropped as superseded.
+///
+pub fn handle_reconcile_replicas<S>(entry_def_id: S, request: ReconcileReplicasRequest) -> RecordAPIResult<Vec<ReplicaReport<FulfillmentAddress>>>
+    where S: AsRef<str>
+{
+    request.addresses.iter()
+        .map(|address| reconcile_replica(&entry_def_id, address, request.repair))
+        .collect()
+}
+
+fn reconcile_replica<S>(entry_def_id: &S, address: &FulfillmentAddress, repair: bool) -> RecordAPIResult<ReplicaReport<FulfillmentAddress>>
+    where S: AsRef<str>
+{
+    let (initial_revision_id, base_address, initial): (_, FulfillmentAddress, EntryData) = read_record_initial_entry::<EntryData, EntryStorage, _,_>(entry_def_id, address.as_ref())?;
+    let latest = match read_record_entry::<EntryData, EntryStorage, FulfillmentAddress,_>(entry_def_id, address.as_ref()) {
+        Ok((revision_id, _, current)) => Some((revision_id, current)),
+        Err(DataIntegrityError::EntryNotFound) => None,
+        Err(e) => return Err(e),
+    };
+
+    // replicas are created in the DNA of the original event, from the origin record's creation payload
+    let target = initial.fulfilled_by.to_owned();
+    let replica_id = FulfillmentAddress::new(
+        AsRef::<DnaHash>::as_ref(&target).to_owned(),
+        hash_entry(EntryData { replication: None, replica_revision_id: None, ..initial.to_owned() }.with_identity(None))?,
     );
-    // :TODO: report any error
+    let replica = read_replica(&target, &replica_id)?;
+
+    let mut report = ReplicaReport {
+        id: base_address.to_owned(),
+        revision_id: latest.as_ref().map(|(revision_id, _)| revision_id.to_owned()).unwrap_or(initial_revision_id),
+        replica_id: Some(replica_id),
+        replica_revision_id: replica.as_ref().map(|r| r.revision_id.to_owned()),
+        state: ReplicaState::InSync,
+        divergent_fields: vec![],
+        repaired_revision_id: None,
+    };
+
+    let (state, divergent_fields) = compare_replica(latest.as_ref().map(|(_, current)| current), replica.as_ref());
+    report.state = state;
+    report.divergent_fields = divergent_fields;
+
+    let current = match latest {
+        Some((_, current)) => current,
+        None => {
+            // the fulfillment has been deleted here, so any replica should have been deleted with it
+            if let (true, Some(replica)) = (repair, replica) {
+                let _: bool = call_zome_method(&target, &REPLICATE_DELETE_API_METHOD, ByHeader { address: replica.revision_id })?;
+                drop_queued_replication(&base_address)?;
+            }
+            return Ok(report);
+        },
+    };
+
+    if repair {
+        report.repaired_revision_id = match (&report.state, &replica) {
+            (ReplicaState::Missing, _) => Some(restore_replica(&target, &initial, &current)?),
+            (ReplicaState::Divergent, Some(replica)) => Some(overwrite_replica(&target, &replica.revision_id, &current)?),
+            _ => None,
+        };
+
+        // anything still queued for the record has been superseded once the replica matches
+        drop_queued_replication(&base_address)?;
+        acknowledge::<EntryData, EntryStorage, _,_,_>(entry_def_id, &base_address, &Acknowledgement {
+            replica_revision_id: report.repaired_revision_id.to_owned().or_else(|| report.replica_revision_id.to_owned()),
+        })?;
+    }
+
+    Ok(report)
+}
+
+/// Remove all replication calls queued for a `Fulfillment` from the outbox
+fn drop_queued_replication(address: &FulfillmentAddress) -> RecordAPIResult<()> {
+    let queued: Vec<(HeaderHash, OutboxEntry)> = read_outbox(&outbox()?)?;
+    for (header, _) in queued.iter().filter(|(_, item)| item.record == *address) {
+        remove_outbox_item(header)?;
+    }
+    Ok(())
+}
+
+/// Read the replica of a `Fulfillment`, treating only a not-found error from the remote zome as the replica not existing
+fn read_replica(target: &EventAddress, replica_id: &FulfillmentAddress) -> RecordAPIResult<Option<Response>> {
+    let resp: OtherCellResult<ResponseData> = call_idempotent_zome_method(target, &REPLICA_READ_API_METHOD, ByAddress { address: replica_id.to_owned() });
+    match resp {
+        Ok(r) => Ok(Some(r.fulfillment)),
+        Err(CrossCellError::IndexNotFound(_)) => Ok(None),
+        Err(CrossCellError::Reported(cause)) if cause.code == ErrorCode::EntryNotFound || cause.code == ErrorCode::IndexNotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Determine the state of a replica given the current revision of its origin record (`None` if deleted),
+/// listing the fields in which a divergent replica differs.
+///
+/// A replica is also divergent if it has been revised since this record last wrote to it.
+///
+fn compare_replica(current: Option<&EntryData>, replica: Option<&Response>) -> (ReplicaState, Vec<String>) {
+    match (current, replica) {
+        (None, Some(_)) => (ReplicaState::Orphaned, vec![]),
+        (None, None) => (ReplicaState::InSync, vec![]),
+        (Some(_), None) => (ReplicaState::Missing, vec![]),
+        (Some(current), Some(replica)) => {
+            let mut fields = find_divergent_fields(current, replica);
+            if current.replica_revision_id.as_ref().map_or(false, |last_written| *last_written != replica.revision_id) {
+                fields.push("revisionId".to_string());
+            }
+            if fields.is_empty() { (ReplicaState::InSync, fields) } else { (ReplicaState::Divergent, fields) }
+        },
+    }
+}
+
+fn find_divergent_fields(origin: &EntryData, replica: &Response) -> Vec<String> {
+    let mut fields = vec![];
+    if origin.fulfilled_by != replica.fulfilled_by { fields.push("fulfilledBy".to_string()); }
+    if origin.fulfills != replica.fulfills { fields.push("fulfills".to_string()); }
+    if origin.resource_quantity != replica.resource_quantity { fields.push("resourceQuantity".to_string()); }
+    if origin.effort_quantity != replica.effort_quantity { fields.push("effortQuantity".to_string()); }
+    if origin.note != replica.note { fields.push("note".to_string()); }
+    fields
+}
+
+/// Recreate a missing replica from the initial revision of a `Fulfillment`, so that it is assigned
+/// the same ID as originally, then bring it up to date with the current revision.
+///
+fn restore_replica(target: &EventAddress, initial: &EntryData, current: &EntryData) -> RecordAPIResult<RevisionHash> {
+    let created: ResponseData = call_zome_method(target, &REPLICATE_CREATE_API_METHOD, CreateParams { fulfillment: CreateRequest {
+        fulfilled_by: initial.fulfilled_by.to_owned(),
+        fulfills: initial.fulfills.to_owned(),
+        resource_quantity: initial.resource_quantity.to_owned().into(),
+        effort_quantity: initial.effort_quantity.to_owned().into(),
+        note: initial.note.to_owned().into(),
+    } })?;
+
+    if find_divergent_fields(current, &created.fulfillment).is_empty() {
+        return Ok(created.fulfillment.revision_id);
+    }
+    overwrite_replica(target, &created.fulfillment.revision_id, current)
+}
+
+/// Replace every field of a replica with the values of the origin record
+fn overwrite_replica(target: &EventAddress, replica_revision: &RevisionHash, origin: &EntryData) -> RecordAPIResult<RevisionHash> {
+    let updated: ResponseData = call_zome_method(target, &REPLICATE_UPDATE_API_METHOD, UpdateParams { fulfillment: UpdateRequest {
+        revision_id: replica_revision.to_owned(),
+        fulfilled_by: MaybeUndefined::Some(origin.fulfilled_by.to_owned()),
+        fulfills: MaybeUndefined::Some(origin.fulfills.to_owned()),
+        resource_quantity: origin.resource_quantity.to_owned().into(),
+        effort_quantity: origin.effort_quantity.to_owned().into(),
+        note: origin.note.to_owned().into(),
+    } })?;
+    Ok(updated.fulfillment.revision_id)
+}
+
+/// Mirror a change already written locally into the DNA of the fulfilling event, marking the
+/// `Fulfillment` as acknowledged if it was delivered immediately. Returns the latest revision.
+/// Updates and deletions are addressed to the replica revision last acknowledged for the record.
+///
+fn replicate_change<S>(
+    entry_def_id: &S,
+    revision_id: RevisionHash,
+    entry: EntryData,
+    address: &FulfillmentAddress,
+    target: &EventAddress,
+    operation: ReplicationOperation,
+) -> RecordAPIResult<(RevisionHash, EntryData)>
+    where S: AsRef<str>
+{
+    match replicate(&outbox()?, address, target, operation, entry.replica_revision_id.as_ref())? {
+        Some(ack) => {
+            let (revision_id, _, acknowledged, _): (_, FulfillmentAddress, EntryData, EntryData) = update_record(entry_def_id, &revision_id, ack)?;
+            Ok((revision_id, acknowledged))
+        },
+        None => Ok((revision_id, entry)),
+    }
+}
+
+/// Check that a `Fulfillment` links an `EconomicEvent` compatible with the `Commitment` it fulfills,
+/// and that it does not take the commitment's cumulative fulfilled quantity past what was committed
+/// unless the `allow_over_fulfillment` zome config flag is set.
+///
+/// `replaced` is any previous revision of the fulfillment, already counted towards the commitment's progress.
+///
+fn validate_fulfillment(fulfillment: &EntryData, replaced: Option<&EntryData>) -> RecordAPIResult<()> {
+    let commitment: CommitmentResponse = call_uri_method(
+        &fulfillment.fulfills.to_uri(), &linked_record_routes(),
+        ByAddress { address: fulfillment.fulfills.to_owned() },
+    )?;
+    let commitment = commitment.commitment;
+    let event: EventResponse = call_uri_method(
+        &fulfillment.fulfilled_by.to_uri(), &linked_record_routes(),
+        ByAddress { address: fulfillment.fulfilled_by.to_owned() },
+    )?;
+    let event = event.economic_event;
+
+    ensure_flows_match(&commitment, &event)?;
+
+    if allow_over_fulfillment() {
+        return Ok(());
+    }
+
+    // only count the previous revision if it was already counted against the same commitment
+    let replaced = replaced.filter(|r| r.fulfills == fulfillment.fulfills);
+
+    // progress is measured in effort where no resource quantity was committed, as in the commitment zome
+    match (&commitment.resource_quantity, &commitment.effort_quantity) {
+        (Some(committed), _) => ensure_within_commitment(
+            committed, &commitment.fulfilled_quantity,
+            &fulfillment.resource_quantity, replaced.and_then(|r| r.resource_quantity.as_ref()),
+        ),
+        (None, Some(committed)) => ensure_within_commitment(
+            committed, &commitment.fulfilled_quantity,
+            &fulfillment.effort_quantity, replaced.and_then(|r| r.effort_quantity.as_ref()),
+        ),
+        (None, None) => Ok(()),
+    }
+}
+
+fn ensure_within_commitment(
+    committed: &QuantityValue,
+    fulfilled: &Option<QuantityValue>,
+    added: &Option<QuantityValue>,
+    replaced: Option<&QuantityValue>,
+) -> RecordAPIResult<()> {
+    let added = match added {
+        Some(q) => q.to_owned(),
+        None => return Ok(()),
+    };
+    let mut total = fulfilled.to_owned().unwrap_or_else(|| QuantityValue::new(0.0, committed.get_unit()));
+    if let Some(q) = replaced {
+        total = checked_subtract(total, q.to_owned()).ok_or(DataIntegrityError::IncompatibleUnits)?;
+    }
+    let total = checked_add(total, added).ok_or(DataIntegrityError::IncompatibleUnits)?;
+    let excess = checked_subtract(total.to_owned(), committed.to_owned()).ok_or(DataIntegrityError::IncompatibleUnits)?;
+
+    if excess.get_numerical_value() > 0.0 {
+        return Err(DataIntegrityError::QuantityExceeded(total.get_numerical_value(), committed.get_numerical_value()));
+    }
+    Ok(())
+}
+
+/// Read the `allow_over_fulfillment` zome config flag, defaulting to `false` if unavailable
+fn allow_over_fulfillment() -> bool {
+    zome_config::<FulfillmentZomeConfigPlanning>()
+        .map(|c| c.allow_over_fulfillment)
+        .unwrap_or(false)
+}
 
-    delete_record::<EntryStorage, _>(&revision_id)
+/// Ask the commitment zome to update fulfillment progress of a `Commitment` once its
+/// `Fulfillment`s have been replicated.
+///
+fn recompute_commitment(commitment: &CommitmentAddress) -> OtherCellResult<CommitmentResponse> {
+    call_local_zome_method(
+        |conf: FulfillmentZomeConfigPlanning| { conf.commitment_zome },
+        &COMMITMENT_RECOMPUTE_API_METHOD,
+        ByAddress { address: commitment.to_owned() },
+    )
+}
+
+/// Routes for reading the records a `Fulfillment` links, wherever they are stored
+fn linked_record_routes() -> Vec<UriRoute> {
+    vec![
+        UriRoute { entry_type: CommitmentAddress::entry_type(), permission_id: COMMITMENT_READ_REMOTE_PERMISSION },
+        UriRoute { entry_type: EventAddress::entry_type(), permission_id: EVENT_READ_REMOTE_PERMISSION },
+    ]
 }
 
 /// Properties accessor for zome config.
-fn read_foreign_commitment_index_zome(conf: DnaConfigSlicePlanning) -> Option<String> {
-    Some(conf.fulfillment.commitment_index_zome)
+fn read_foreign_commitment_index_zome(conf: FulfillmentZomeConfigPlanning) -> Option<String> {
+    Some(conf.commitment_index_zome)
 }
 
 /// Properties accessor for zome config.
-fn read_foreign_index_zome(conf: DnaConfigSlicePlanning) -> Option<String> {
-    Some(conf.fulfillment.index_zome)
+fn read_foreign_index_zome(conf: FulfillmentZomeConfigPlanning) -> Option<String> {
+    Some(conf.index_zome)
 }
 
 const READ_FN_NAME: &str = "get_fulfillment";
@@ -139,8 +486,7 @@ pub fn generate_query_handler<S, C, F>(
     commitment_entry_def_id: S,
 ) -> impl FnOnce(&QueryParams) -> RecordAPIResult<Vec<ResponseData>>
     where S: AsRef<str>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: Fn(C) -> Option<String>,
 {
     move |params| {
@@ -165,3 +511,77 @@ pub fn generate_query_handler<S, C, F>(
             .collect())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dna() -> DnaHash {
+        DnaHash::from_raw_36(vec![0xdb; 36])
+    }
+
+    fn revision(byte: u8) -> RevisionHash {
+        RevisionHash(dna(), HeaderHash::from_raw_36(vec![byte; 36]))
+    }
+
+    fn origin() -> EntryData {
+        EntryData {
+            fulfilled_by: EventAddress(dna(), EntryHash::from_raw_36(vec![1; 36])),
+            fulfills: CommitmentAddress(dna(), EntryHash::from_raw_36(vec![2; 36])),
+            resource_quantity: Some(QuantityValue::new(1.0, None)),
+            effort_quantity: None,
+            note: None,
+            replication: None,
+            replica_revision_id: Some(revision(1)),
+        }
+    }
+
+    fn replica_of(origin: &EntryData) -> Response {
+        Response {
+            id: FulfillmentAddress(dna(), EntryHash::from_raw_36(vec![3; 36])),
+            revision_id: revision(1),
+            fulfilled_by: origin.fulfilled_by.to_owned(),
+            fulfills: origin.fulfills.to_owned(),
+            resource_quantity: origin.resource_quantity.to_owned(),
+            effort_quantity: origin.effort_quantity.to_owned(),
+            note: origin.note.to_owned(),
+            replication: None,
+        }
+    }
+
+    #[test]
+    fn test_matching_replica_is_in_sync() {
+        let current = origin();
+        assert_eq!(compare_replica(Some(&current), Some(&replica_of(&current))), (ReplicaState::InSync, vec![]));
+    }
+
+    #[test]
+    fn test_missing_and_orphaned_replicas() {
+        let current = origin();
+        assert_eq!(compare_replica(Some(&current), None), (ReplicaState::Missing, vec![]));
+        assert_eq!(compare_replica(None, Some(&replica_of(&current))), (ReplicaState::Orphaned, vec![]), "replica of a deleted record");
+        assert_eq!(compare_replica(None, None), (ReplicaState::InSync, vec![]), "deleted along with its replica");
+    }
+
+    #[test]
+    fn test_divergent_fields() {
+        let current = origin();
+        let replica = Response {
+            resource_quantity: Some(QuantityValue::new(2.0, None)),
+            note: Some("edited in the other DNA".to_string()),
+            ..replica_of(&current)
+        };
+
+        assert_eq!(compare_replica(Some(&current), Some(&replica)), (ReplicaState::Divergent, vec!["resourceQuantity".to_string(), "note".to_string()]));
+    }
+
+    #[test]
+    fn test_replica_revised_elsewhere_is_divergent() {
+        let current = origin();
+        let replica = Response { revision_id: revision(9), ..replica_of(&current) };
+        assert_eq!(compare_replica(Some(&current), Some(&replica)), (ReplicaState::Divergent, vec!["revisionId".to_string()]));
+
+        let never_written = EntryData { replica_revision_id: None, ..origin() };
+        assert_eq!(compare_replica(Some(&never_written), Some(&replica)).0, ReplicaState::InSync, "records replicated before revisions were tracked are compared by value only");
+    }
+}
@@ -12,6 +12,8 @@ use serde_maybe_undefined::{MaybeUndefined};
 use vf_measurement::QuantityValue;
 pub use vf_attributes_hdk::{
     RevisionHash, ByHeader, ByAddress,
+    ReplicationStatus, OutboxFlushReport,
+    ReplicaState, ReplicaReport,
     EventAddress,
     CommitmentAddress,
 };
@@ -48,6 +50,9 @@ pub struct Response {
     pub effort_quantity: Option<QuantityValue>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub note: Option<String>,
+    /// whether the record has been replicated into the observation DNA, if it needs to be
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replication: Option<ReplicationStatus>,
 }
 
 /// I/O struct to describe what is returned outside the gateway.
@@ -133,3 +138,14 @@ pub struct QueryParams {
     pub fulfills: Option<CommitmentAddress>,
     pub fulfilled_by: Option<EventAddress>,
 }
+
+//---------------- REPLICA RECONCILIATION ----------------
+
+#[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconcileReplicasRequest {
+    pub addresses: Vec<FulfillmentAddress>,
+    /// overwrite missing or divergent replicas with the origin record
+    #[serde(default)]
+    pub repair: bool,
+}
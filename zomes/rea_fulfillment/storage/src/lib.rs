@@ -9,8 +9,12 @@
 use hdk::prelude::*;
 
 use hdk_records::{
+    OtherCellResult,
+    config::ZomeConfig,
     MaybeUndefined,
     record_interface::Updateable,
+    outbox::{ OutboxItem, ReplicationCall, Replicated, Acknowledgement },
+    rpc::call_zome_method,
     generate_record_entry,
 };
 use vf_measurement::QuantityValue;
@@ -20,36 +24,36 @@ pub use vf_attributes_hdk::{
     FulfillmentAddress,
     EventAddress,
     CommitmentAddress,
+    ReplicationStatus,
 };
 
-use hc_zome_rea_fulfillment_rpc::{ CreateRequest, UpdateRequest };
+use hc_zome_rea_fulfillment_storage_consts::*;
+use hc_zome_rea_fulfillment_rpc::{ CreateRequest, UpdateRequest, CreateParams, UpdateParams, ResponseData, ByHeader };
 
 //--------------- ZOME CONFIGURATION ATTRIBUTES ----------------
 
-// :TODO: remove this, replace with reference to appropriate namespacing of zome config
-#[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
-pub struct DnaConfigSlicePlanning {
-    pub fulfillment: FulfillmentZomeConfigPlanning,
-}
-
-// :TODO: remove this, replace with reference to appropriate namespacing of zome config
-#[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
-pub struct DnaConfigSliceObservation {
-    pub fulfillment: FulfillmentZomeConfigObservation,
-}
-
 #[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct FulfillmentZomeConfigPlanning {
     pub commitment_index_zome: String,
     pub index_zome: String,
+    pub commitment_zome: Option<String>,
+    /// whether cumulative fulfilled quantities may exceed the quantity committed
+    #[serde(default)]
+    pub allow_over_fulfillment: bool,
 }
 
+impl ZomeConfig for FulfillmentZomeConfigPlanning {}
+
 #[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct FulfillmentZomeConfigObservation {
     pub economic_event_index_zome: String,
     pub index_zome: String,
 }
 
+impl ZomeConfig for FulfillmentZomeConfigObservation {}
+
 //---------------- RECORD INTERNALS & VALIDATION ----------------
 
 #[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
@@ -59,6 +63,12 @@ pub struct EntryData {
     pub resource_quantity: Option<QuantityValue>,
     pub effort_quantity: Option<QuantityValue>,
     pub note: Option<String>,
+    /// delivery state of replication into the observation DNA, where this record originates elsewhere
+    #[serde(default)]
+    pub replication: Option<ReplicationStatus>,
+    /// revision of the replica last written by this record's replication calls
+    #[serde(default)]
+    pub replica_revision_id: Option<RevisionHash>,
 }
 
 generate_record_entry!(EntryData, FulfillmentAddress, EntryStorage);
@@ -74,6 +84,8 @@ impl From<CreateRequest> for EntryData {
             resource_quantity: e.resource_quantity.into(),
             effort_quantity: e.effort_quantity.into(),
             note: e.note.into(),
+            replication: None,
+            replica_revision_id: None,
         }
     }
 }
@@ -95,6 +107,151 @@ impl Updateable<UpdateRequest> for EntryData {
             resource_quantity: if e.resource_quantity== MaybeUndefined::Undefined { self.resource_quantity.clone() } else { e.resource_quantity.clone().into() },
             effort_quantity: if e.effort_quantity== MaybeUndefined::Undefined { self.effort_quantity.clone() } else { e.effort_quantity.clone().into() },
             note: if e.note== MaybeUndefined::Undefined { self.note.clone() } else { e.note.clone().into() },
+            replication: self.replication.clone(),
+            replica_revision_id: self.replica_revision_id.clone(),
+        }
+    }
+}
+
+/// Update payload which also records the outcome of replicating the update, if any was needed
+#[derive(Debug, Clone)]
+pub struct ReplicatedUpdatePayload {
+    pub update: UpdateRequest,
+    pub replication: Option<ReplicationStatus>,
+}
+
+impl Updateable<ReplicatedUpdatePayload> for EntryData {
+    fn update_with(&self, e: ReplicatedUpdatePayload) -> EntryData {
+        let replication = e.replication.or_else(|| self.replication.clone());
+        EntryData {
+            replication,
+            ..self.update_with(e.update)
+        }
+    }
+}
+
+/// Marks the record's replication calls as delivered
+impl Updateable<Acknowledgement> for EntryData {
+    fn update_with(&self, e: Acknowledgement) -> EntryData {
+        EntryData {
+            replication: Some(ReplicationStatus::Acknowledged),
+            replica_revision_id: e.replica_revision_id.or_else(|| self.replica_revision_id.clone()),
+            ..self.clone()
+        }
+    }
+}
+
+impl Replicated for EntryData {
+    fn replication(&self) -> Option<&ReplicationStatus> {
+        self.replication.as_ref()
+    }
+
+    fn replica_revision_id(&self) -> Option<&RevisionHash> {
+        self.replica_revision_id.as_ref()
+    }
+}
+
+//---------------- REPLICATION OUTBOX ----------------
+
+/// Replication call into the observation DNA
+///
+/// :NOTE: the revisions held by `Update` and `Delete` are those of the origin record. They are
+///        replaced with the revision of the replica when the call is delivered.
+///
+#[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
+pub enum ReplicationOperation {
+    Create(CreateRequest),
+    Update(UpdateRequest),
+    Delete(RevisionHash),
+}
+
+impl ReplicationOperation {
+    /// Address the operation to the given revision of the replica, rather than of the origin record
+    pub fn addressed_to(&self, replica_revision_id: &RevisionHash) -> ReplicationOperation {
+        match self {
+            ReplicationOperation::Create(_) => self.to_owned(),
+            ReplicationOperation::Update(fulfillment) => ReplicationOperation::Update(UpdateRequest {
+                revision_id: replica_revision_id.to_owned(),
+                ..fulfillment.to_owned()
+            }),
+            ReplicationOperation::Delete(_) => ReplicationOperation::Delete(replica_revision_id.to_owned()),
+        }
+    }
+}
+
+impl ReplicationCall<EventAddress> for ReplicationOperation {
+    fn requires_replica(&self) -> bool {
+        !matches!(self, ReplicationOperation::Create(_))
+    }
+
+    fn deliver(&self, target: &EventAddress, replica_revision_id: Option<&RevisionHash>) -> OtherCellResult<Option<RevisionHash>> {
+        let operation = match replica_revision_id {
+            Some(replica_revision_id) => self.addressed_to(replica_revision_id),
+            None => self.to_owned(),
+        };
+        match operation {
+            ReplicationOperation::Create(fulfillment) => {
+                let created: ResponseData = call_zome_method(target, &REPLICATE_CREATE_API_METHOD, CreateParams { fulfillment })?;
+                Ok(Some(created.fulfillment.revision_id))
+            },
+            ReplicationOperation::Update(fulfillment) => {
+                let updated: ResponseData = call_zome_method(target, &REPLICATE_UPDATE_API_METHOD, UpdateParams { fulfillment })?;
+                Ok(Some(updated.fulfillment.revision_id))
+            },
+            ReplicationOperation::Delete(revision_id) => {
+                let _: bool = call_zome_method(target, &REPLICATE_DELETE_API_METHOD, ByHeader { address: revision_id })?;
+                Ok(None)
+            },
+        }
+    }
+}
+
+/// Undelivered replication call into the DNA of the fulfilling event
+pub type OutboxEntry = OutboxItem<FulfillmentAddress, EventAddress, ReplicationOperation>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn revision(byte: u8) -> RevisionHash {
+        RevisionHash(DnaHash::from_raw_36(vec![0xdb; 36]), HeaderHash::from_raw_36(vec![byte; 36]))
+    }
+
+    fn update(revision_id: RevisionHash) -> UpdateRequest {
+        UpdateRequest {
+            revision_id,
+            fulfilled_by: MaybeUndefined::Undefined,
+            fulfills: MaybeUndefined::Undefined,
+            resource_quantity: MaybeUndefined::Undefined,
+            effort_quantity: MaybeUndefined::Undefined,
+            note: MaybeUndefined::Some("changed".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_updates_are_delivered_to_the_replica_revision() {
+        let (origin, replica) = (revision(1), revision(2));
+        let operation = ReplicationOperation::Update(update(origin));
+
+        assert!(operation.requires_replica());
+        match operation.addressed_to(&replica) {
+            ReplicationOperation::Update(fulfillment) => {
+                assert_eq!(fulfillment.revision_id, replica);
+                assert_eq!(fulfillment.note, MaybeUndefined::Some("changed".to_string()), "updated fields are kept");
+            },
+            other => panic!("expected an update, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_deletions_are_delivered_to_the_replica_revision() {
+        let (origin, replica) = (revision(1), revision(2));
+        let operation = ReplicationOperation::Delete(origin);
+
+        assert!(operation.requires_replica());
+        match operation.addressed_to(&replica) {
+            ReplicationOperation::Delete(revision_id) => assert_eq!(revision_id, replica),
+            other => panic!("expected a deletion, got {:?}", other),
         }
     }
 }
@@ -8,6 +8,7 @@
 pub const FULFILLMENT_BASE_ENTRY_TYPE: &str = "vf_fulfillment_baseurl";
 pub const FULFILLMENT_INITIAL_ENTRY_LINK_TYPE: &str = "vf_fulfillment_entry";
 pub const FULFILLMENT_ENTRY_TYPE: &str = "vf_fulfillment";
+pub const FULFILLMENT_OUTBOX_ENTRY_TYPE: &str = "vf_fulfillment_outbox";
 pub const FULFILLMENT_FULFILLS_LINK_TYPE: &str = "vf_fulfillment_fulfills";
 pub const FULFILLMENT_FULFILLS_LINK_TAG: &str = "fulfills";
 pub const FULFILLMENT_FULFILLEDBY_LINK_TYPE: &str = "vf_fulfillment_fulfilled_by";
@@ -16,6 +17,10 @@ pub const FULFILLMENT_FULFILLEDBY_LINK_TAG: &str = "fulfilled_by";
 pub const REPLICATE_CREATE_API_METHOD: &str = "create_fulfillment";
 pub const REPLICATE_UPDATE_API_METHOD: &str = "update_fulfillment";
 pub const REPLICATE_DELETE_API_METHOD: &str = "delete_fulfillment";
+pub const REPLICA_READ_API_METHOD: &str = "read_fulfillment";
+pub const COMMITMENT_RECOMPUTE_API_METHOD: &str = "_internal_recompute_fulfillment";
+pub const COMMITMENT_READ_REMOTE_PERMISSION: &str = "read_commitment";
+pub const EVENT_READ_REMOTE_PERMISSION: &str = "read_economic_event";
 pub const COMMITMENT_FULFILLEDBY_INDEXING_API_METHOD: &str = "_internal_reindex_fulfillments";
 pub const FULFILLMENT_FULFILLS_INDEXING_API_METHOD: &str = "_internal_reindex_commitments";
 pub const EVENT_FULFILLS_INDEXING_API_METHOD: &str = "_internal_reindex_fulfillments";
@@ -6,6 +6,7 @@
  */
 use hdk::prelude::*;
 use hdk_records::{
+    config::init_with_config,
     index_retrieval::IndexingZomeConfig,
     remote_indexes::{
         RemoteEntryLinkRequest,
@@ -21,14 +22,13 @@ use hc_zome_rea_economic_event_storage_consts::{ EVENT_ENTRY_TYPE, EVENT_FULFILL
 
 entry_defs![Path::entry_def()];
 
-// :TODO: obviate this with zome-specific configs
-#[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
-pub struct DnaConfigSlice {
-    pub fulfillment_index: IndexingZomeConfig,
+#[hdk_extern]
+fn init(_: ()) -> ExternResult<InitCallbackResult> {
+    Ok(init_with_config::<IndexingZomeConfig>())
 }
 
-fn read_index_target_zome(conf: DnaConfigSlice) -> Option<String> {
-    Some(conf.fulfillment_index.record_storage_zome)
+fn read_index_target_zome(conf: IndexingZomeConfig) -> Option<String> {
+    Some(conf.record_storage_zome)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
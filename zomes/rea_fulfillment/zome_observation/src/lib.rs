@@ -10,10 +10,17 @@
  * @package Holo-REA
  */
 use hdk::prelude::*;
+use hdk_records::config::init_with_config;
 
 use hc_zome_rea_fulfillment_lib_destination::*;
 use hc_zome_rea_fulfillment_rpc::*;
 use hc_zome_rea_fulfillment_storage_consts::*;
+use hc_zome_rea_fulfillment_storage::FulfillmentZomeConfigObservation;
+
+#[hdk_extern]
+fn init(_: ()) -> ExternResult<InitCallbackResult> {
+    Ok(init_with_config::<FulfillmentZomeConfigObservation>())
+}
 
 #[hdk_extern]
 fn entry_defs(_: ()) -> ExternResult<EntryDefsCallbackResult> {
@@ -8,23 +8,21 @@
  * @package Holo-REA
  */
 use hdk::prelude::*;
+use hdk_records::config::init_with_config;
 
 use hc_zome_rea_fulfillment_lib_origin::*;
 use hc_zome_rea_fulfillment_rpc::*;
 use hc_zome_rea_fulfillment_storage_consts::*;
+use hc_zome_rea_fulfillment_storage::FulfillmentZomeConfigPlanning;
+
+#[hdk_extern]
+fn init(_: ()) -> ExternResult<InitCallbackResult> {
+    Ok(init_with_config::<FulfillmentZomeConfigPlanning>())
+}
 
 #[hdk_extern]
 fn entry_defs(_: ()) -> ExternResult<EntryDefsCallbackResult> {
-    Ok(EntryDefsCallbackResult::from(vec![
-        Path::entry_def(),
-        EntryDef {
-            id: FULFILLMENT_ENTRY_TYPE.into(),
-            visibility: EntryVisibility::Public,
-            crdt_type: CrdtType,
-            required_validations: 1.into(),
-            required_validation_type: RequiredValidationType::default(),
-        }
-    ]))
+    Ok(EntryDefsCallbackResult::Defs(zome_entry_defs()))
 }
 
 #[hdk_extern]
@@ -46,3 +44,13 @@ fn update_fulfillment(UpdateParams { fulfillment }: UpdateParams) -> ExternResul
 fn delete_fulfillment(ByHeader { address }: ByHeader) -> ExternResult<bool> {
     Ok(handle_delete_fulfillment(address)?)
 }
+
+#[hdk_extern]
+fn flush_replication_outbox(_: ()) -> ExternResult<OutboxFlushReport> {
+    Ok(handle_flush_replication_outbox(FULFILLMENT_ENTRY_TYPE)?)
+}
+
+#[hdk_extern]
+fn reconcile_replicas(request: ReconcileReplicasRequest) -> ExternResult<Vec<ReplicaReport<FulfillmentAddress>>> {
+    Ok(handle_reconcile_replicas(FULFILLMENT_ENTRY_TYPE, request)?)
+}
@@ -9,6 +9,7 @@
 use hdk::prelude::*;
 use hdk_records::{
     DataIntegrityError, RecordAPIResult,
+    config::ZomeConfig,
     MaybeUndefined,
     local_indexes::{
         query_index,
@@ -26,9 +27,12 @@ use hdk_records::{
     remote_indexes::{
         create_remote_index,
         update_remote_index,
-    }
+    },
+    integrity::ensure_deletable,
+    blobs::{ store_inline_update, read_inline_field },
 };
 
+use vf_actions::ensure_flow_action;
 use vf_attributes_hdk::{
     RevisionHash,
     SatisfactionAddress,
@@ -37,17 +41,21 @@ use vf_attributes_hdk::{
 use hc_zome_rea_intent_storage_consts::*;
 use hc_zome_rea_intent_storage::*;
 use hc_zome_rea_intent_rpc::*;
+use hc_zome_rea_process_lib_planning::reindex_planned_flows;
 use hc_zome_rea_process_storage_consts::{PROCESS_INTENT_INPUTS_LINK_TAG, PROCESS_INTENT_OUTPUTS_LINK_TAG};
 use hc_zome_rea_satisfaction_storage_consts::{SATISFACTION_SATISFIES_LINK_TAG};
 use hc_zome_rea_proposed_intent_storage_consts::{PROPOSED_INTENT_PUBLISHES_LINK_TAG};
 
 // :SHONK: needed to re-export for zome `entry_defs()` where macro-assigned defs are overridden
 pub use hdk_records::CAP_STORAGE_ENTRY_DEF_ID;
+pub use hdk_records::blobs::{ blob_chunk_entry_def, blob_manifest_entry_def };
 
 pub fn handle_create_intent<S>(entry_def_id: S, intent: CreateRequest) -> RecordAPIResult<ResponseData>
     where S: AsRef<str>,
 {
+    let intent = CreateRequest { image: store_inline_update(&intent.image)?, ..intent };
     let (header_addr, base_address, entry_resp): (_,_, EntryData) = create_record(&entry_def_id, intent.to_owned())?;
+    ensure_flow_action(&entry_resp.action, entry_resp.input_of.to_owned(), entry_resp.output_of.to_owned())?;
 
     // handle link fields
     if let CreateRequest { input_of: MaybeUndefined::Some(input_of), .. } = &intent {
@@ -69,6 +77,8 @@ pub fn handle_create_intent<S>(entry_def_id: S, intent: CreateRequest) -> Record
         )?;
     };
 
+    reindex_planned_flows(&[&entry_resp.input_of, &entry_resp.output_of])?;
+
     // return entire record structure
     construct_response(&base_address, &header_addr, &entry_resp, get_link_fields(&base_address)?)
 }
@@ -83,8 +93,10 @@ pub fn handle_get_intent<S>(entry_def_id: S, address: IntentAddress) -> RecordAP
 pub fn handle_update_intent<S>(entry_def_id: S, intent: UpdateRequest) -> RecordAPIResult<ResponseData>
     where S: AsRef<str>,
 {
+    let intent = UpdateRequest { image: store_inline_update(&intent.image)?, ..intent };
     let address = intent.get_revision_id().to_owned();
-    let (revision_id, base_address, new_entry, _prev_entry): (_, IntentAddress, EntryData, EntryData) = update_record(&entry_def_id, &address, intent.to_owned())?;
+    let (revision_id, base_address, new_entry, prev_entry): (_, IntentAddress, EntryData, EntryData) = update_record(&entry_def_id, &address, intent.to_owned())?;
+    ensure_flow_action(&new_entry.action, new_entry.input_of.to_owned(), new_entry.output_of.to_owned())?;
 
     // handle link fields
     if let UpdateRequest { input_of: MaybeUndefined::Some(input_of), .. } = &intent {
@@ -108,38 +120,77 @@ pub fn handle_update_intent<S>(entry_def_id: S, intent: UpdateRequest) -> Record
         );
     }
 
+    reindex_planned_flows(&[&new_entry.input_of, &new_entry.output_of, &prev_entry.input_of, &prev_entry.output_of])?;
+
     construct_response(&base_address, &revision_id, &new_entry, get_link_fields(&base_address)?)
 }
 
-pub fn handle_delete_intent(revision_id: RevisionHash) -> RecordAPIResult<bool>
+/// Apply a change in satisfied quantity to the `available_quantity` of an `Intent`.
+///
+/// Intents without an `available_quantity` are not tracked and are returned unchanged. Attempts
+/// to consume more than is available are rejected unless excess is allowed, in which case the
+/// excess is recorded against the intent. See `adjust_availability` & `Updateable<Availability>`.
+///
+pub fn handle_adjust_available_quantity<S>(entry_def_id: S, request: AdjustAvailableQuantityRequest) -> RecordAPIResult<ResponseData>
+    where S: AsRef<str>,
+{
+    let (revision, base_address, entry) = read_record_entry::<EntryData, EntryStorage, _,_>(&entry_def_id, request.address.as_ref())?;
+
+    let available = match &entry.available_quantity {
+        Some(q) => q.to_owned(),
+        None => return construct_response(&base_address, &revision, &entry, get_link_fields(&base_address)?),
+    };
+
+    let availability = adjust_availability(
+        available, entry.excess_quantity.to_owned(),
+        request.consumed, request.released, request.allow_excess,
+    )?;
+
+    let (revision_id, base_address, new_entry, _prev_entry): (_, IntentAddress, EntryData, EntryData) = update_record(&entry_def_id, &revision, availability)?;
+    construct_response(&base_address, &revision_id, &new_entry, get_link_fields(&base_address)?)
+}
+
+/// Indexes of other records referencing an `Intent`, which prevent its deletion
+const INBOUND_LINK_TAGS: &[&str] = &[
+    INTENT_SATISFIEDBY_LINK_TAG,
+    INTENT_PUBLISHED_IN_LINK_TAG,
+];
+
+pub fn handle_delete_intent(revision_id: RevisionHash, force: bool) -> RecordAPIResult<bool>
 {
     // load the record to ensure it is of the correct type
-    let (base_address, entry) = read_record_entry_by_header::<EntryData, EntryStorage, _>(&revision_id)?;
+    let (base_address, entry): (IntentAddress, EntryData) = read_record_entry_by_header::<EntryData, EntryStorage, _>(&revision_id)?;
+
+    ensure_deletable(&INTENT_ENTRY_TYPE, &base_address, INBOUND_LINK_TAGS, force)?;
 
     // handle link fields
-    if let Some(process_address) = entry.input_of {
+    if let Some(process_address) = &entry.input_of {
         let _results = update_remote_index(
             read_foreign_index_zome,
             &INTENT_INPUT_INDEXING_API_METHOD,
             &base_address,
             &PROCESS_INPUT_INDEXING_API_METHOD,
             vec![].as_slice(),
-            vec![process_address].as_slice(),
+            vec![process_address.to_owned()].as_slice(),
         );
     }
-    if let Some(process_address) = entry.output_of {
+    if let Some(process_address) = &entry.output_of {
         let _results = update_remote_index(
             read_foreign_index_zome,
             &INTENT_OUTPUT_INDEXING_API_METHOD,
             &base_address,
             &PROCESS_OUTPUT_INDEXING_API_METHOD,
             vec![].as_slice(),
-            vec![process_address].as_slice(),
+            vec![process_address.to_owned()].as_slice(),
         );
     }
 
     // delete entry last, as it must be present in order for links to be removed
-    delete_record::<EntryStorage, _>(&revision_id)
+    let deleted = delete_record::<EntryStorage, _>(&revision_id)?;
+
+    reindex_planned_flows(&[&entry.input_of, &entry.output_of])?;
+
+    Ok(deleted)
 }
 
 const READ_FN_NAME: &str = "get_intent";
@@ -151,8 +202,7 @@ pub fn generate_query_handler<S, C, F>(
     proposed_intent_entry_def_id: S,
 ) -> impl FnOnce(&QueryParams) -> RecordAPIResult<Vec<ResponseData>>
     where S: AsRef<str>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: Fn(C) -> Option<String>,
 {
     move |params| {
@@ -223,7 +273,7 @@ pub fn construct_response<'a>(
             revision_id: revision_id.to_owned(),
             action: e.action.to_owned(),
             note: e.note.to_owned(),
-            image: e.image.to_owned(),
+            image: read_inline_field(&e.image)?,
             input_of: e.input_of.to_owned(),
             output_of: e.output_of.to_owned(),
             provider: e.provider.to_owned(),
@@ -251,8 +301,8 @@ pub fn construct_response<'a>(
 //---------------- READ ----------------
 
 /// Properties accessor for zome config
-fn read_foreign_index_zome(conf: DnaConfigSlice) -> Option<String> {
-    Some(conf.intent.index_zome)
+fn read_foreign_index_zome(conf: IntentZomeConfig) -> Option<String> {
+    Some(conf.index_zome)
 }
 
 // @see construct_response
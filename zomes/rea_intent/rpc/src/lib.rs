@@ -12,6 +12,7 @@ use serde_maybe_undefined::{MaybeUndefined, default_false};
 use vf_attributes_hdk::RevisionHash;
 use vf_measurement::QuantityValue;
 pub use vf_attributes_hdk::{
+    DeleteRequest,
     ActionId,
     ExternalURL,
     DateTime, FixedOffset,
@@ -207,6 +208,23 @@ impl<'a> UpdateRequest {
     // :TODO: accessors for other field data
 }
 
+/// I/O struct for adjusting the `available_quantity` of an `Intent` as it is satisfied
+///
+#[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AdjustAvailableQuantityRequest {
+    pub address: IntentAddress,
+    /// quantity newly promised or delivered against the intent
+    #[serde(default)]
+    pub consumed: Option<QuantityValue>,
+    /// quantity no longer promised or delivered, eg. upon removal of a `Satisfaction`
+    #[serde(default)]
+    pub released: Option<QuantityValue>,
+    /// permit consuming more than is available, leaving the intent exhausted rather than erroring
+    #[serde(default)]
+    pub allow_excess: bool,
+}
+
 //---------------- QUERY FILTER REQUEST ----------------
 
 #[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
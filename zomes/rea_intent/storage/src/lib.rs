@@ -9,11 +9,13 @@
 use hdk::prelude::*;
 
 use hdk_records::{
+    DataIntegrityError,
+    config::ZomeConfig,
     MaybeUndefined,
     record_interface::Updateable,
     generate_record_entry,
 };
-use vf_measurement::QuantityValue;
+use vf_measurement::{ QuantityValue, checked_subtract };
 
 pub use vf_attributes_hdk::{
     RevisionHash,
@@ -37,17 +39,14 @@ pub use hdk_records::record_interface::Identified;
 
 //--------------- ZOME CONFIGURATION ATTRIBUTES ----------------
 
-// :TODO: remove this, replace with reference to appropriate namespacing of zome config
-#[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
-pub struct DnaConfigSlice {
-    pub intent: IntentZomeConfig,
-}
-
 #[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct IntentZomeConfig {
     pub index_zome: String,
 }
 
+impl ZomeConfig for IntentZomeConfig {}
+
 //---------------- RECORD INTERNALS & VALIDATION ----------------
 
 #[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
@@ -63,6 +62,10 @@ pub struct EntryData {
     pub resource_quantity: Option<QuantityValue>,
     pub effort_quantity: Option<QuantityValue>,
     pub available_quantity: Option<QuantityValue>,
+    /// quantity satisfied beyond what was available, which is repaid before any released
+    /// quantity becomes available again. Absent in entries written before it was tracked.
+    #[serde(default)]
+    pub excess_quantity: Option<QuantityValue>,
     pub has_beginning: Option<DateTime<FixedOffset>>,
     pub has_end: Option<DateTime<FixedOffset>>,
     pub has_point_in_time: Option<DateTime<FixedOffset>>,
@@ -109,6 +112,7 @@ impl From<CreateRequest> for EntryData {
             resource_quantity: e.resource_quantity.to_owned().into(),
             effort_quantity: e.effort_quantity.to_owned().into(),
             available_quantity: e.available_quantity.to_owned().into(),
+            excess_quantity: None,
             has_beginning: e.has_beginning.to_owned().into(),
             has_end: e.has_end.to_owned().into(),
             has_point_in_time: e.has_point_in_time.to_owned().into(),
@@ -138,6 +142,7 @@ impl Updateable<UpdateRequest> for EntryData {
             resource_quantity: if e.resource_quantity== MaybeUndefined::Undefined { self.resource_quantity.to_owned() } else { e.resource_quantity.to_owned().into() },
             effort_quantity: if e.effort_quantity== MaybeUndefined::Undefined { self.effort_quantity.to_owned() } else { e.effort_quantity.to_owned().into() },
             available_quantity: if e.available_quantity== MaybeUndefined::Undefined { self.available_quantity.to_owned() } else { e.available_quantity.to_owned().into() },
+            excess_quantity: self.excess_quantity.to_owned(),
             has_beginning: if e.has_beginning == MaybeUndefined::Undefined { self.has_beginning.to_owned() } else { e.has_beginning.to_owned().into() },
             has_end: if e.has_end == MaybeUndefined::Undefined { self.has_end.to_owned() } else { e.has_end.to_owned().into() },
             has_point_in_time: if e.has_point_in_time == MaybeUndefined::Undefined { self.has_point_in_time.to_owned() } else { e.has_point_in_time.to_owned().into() },
@@ -151,3 +156,134 @@ impl Updateable<UpdateRequest> for EntryData {
         }
     }
 }
+
+/// Available & excess quantities of an `Intent` after a change in the quantity satisfying it
+#[derive(Clone, Debug)]
+pub struct Availability {
+    pub available_quantity: QuantityValue,
+    pub excess_quantity: Option<QuantityValue>,
+}
+
+/// Handles changes in availability, finishing the intent once nothing remains available and
+/// reopening it if quantity is released back to an intent finished in this way
+impl Updateable<Availability> for EntryData {
+    fn update_with(&self, e: Availability) -> EntryData {
+        let was_exhausted = self.available_quantity.as_ref().map(|q| q.get_numerical_value() <= 0.0).unwrap_or(false);
+        let exhausted = e.available_quantity.get_numerical_value() <= 0.0;
+        let finished = if exhausted {
+            true
+        } else if was_exhausted {
+            false
+        } else {
+            self.finished
+        };
+
+        EntryData {
+            available_quantity: Some(e.available_quantity),
+            excess_quantity: e.excess_quantity,
+            finished,
+            ..self.to_owned()
+        }
+    }
+}
+
+/// Compute the availability of an intent once `released` is returned to it and `consumed` is taken from it.
+///
+/// Released quantity first repays any excess previously satisfied beyond what was available, so that
+/// returning it does not inflate availability. Consuming more than is available is rejected unless
+/// `allow_excess` is set, in which case the shortfall is recorded as excess.
+///
+pub fn adjust_availability(
+    available: QuantityValue,
+    excess: Option<QuantityValue>,
+    consumed: Option<QuantityValue>,
+    released: Option<QuantityValue>,
+    allow_excess: bool,
+) -> Result<Availability, DataIntegrityError> {
+    let zero = QuantityValue::new(0.0, available.get_unit());
+    let excess = excess.unwrap_or_else(|| zero.to_owned());
+
+    let (available, excess) = match released {
+        Some(q) => {
+            let unpaid = checked_subtract(excess, q).ok_or(DataIntegrityError::IncompatibleUnits)?;
+            if unpaid.get_numerical_value() < 0.0 {
+                // any surplus beyond the excess becomes available again
+                (checked_subtract(available, unpaid).ok_or(DataIntegrityError::IncompatibleUnits)?, zero.to_owned())
+            } else {
+                (available, unpaid)
+            }
+        },
+        None => (available, excess),
+    };
+
+    let (available, excess) = match consumed {
+        Some(q) => {
+            let requested = q.get_numerical_value();
+            let remaining = checked_subtract(available.to_owned(), q).ok_or(DataIntegrityError::IncompatibleUnits)?;
+            if remaining.get_numerical_value() >= 0.0 {
+                (remaining, excess)
+            } else if allow_excess {
+                (zero, checked_subtract(excess, remaining).ok_or(DataIntegrityError::IncompatibleUnits)?)
+            } else {
+                return Err(DataIntegrityError::InsufficientQuantity(requested, available.get_numerical_value()));
+            }
+        },
+        None => (available, excess),
+    };
+
+    Ok(Availability {
+        available_quantity: available,
+        excess_quantity: if excess.get_numerical_value() > 0.0 { Some(excess) } else { None },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vf_attributes_hdk::UnitId;
+
+    fn kg(qty: f64) -> QuantityValue {
+        QuantityValue::new(qty, Some(UnitId(DnaHash::from_raw_36(vec![0xdb; 36]), "kg".to_string())))
+    }
+
+    fn adjusted(available: f64, excess: Option<f64>, consumed: Option<f64>, released: Option<f64>, allow_excess: bool) -> Result<(f64, Option<f64>), DataIntegrityError> {
+        adjust_availability(kg(available), excess.map(kg), consumed.map(kg), released.map(kg), allow_excess)
+            .map(|a| (a.available_quantity.get_numerical_value(), a.excess_quantity.map(|q| q.get_numerical_value())))
+    }
+
+    #[test]
+    fn test_consumes_and_releases_available_quantity() {
+        assert_eq!(adjusted(10.0, None, Some(4.0), None, false).unwrap(), (6.0, None));
+        assert_eq!(adjusted(6.0, None, None, Some(4.0), false).unwrap(), (10.0, None));
+        assert_eq!(adjusted(10.0, None, Some(10.0), None, false).unwrap(), (0.0, None), "consuming exactly what is available is not excess");
+        assert_eq!(adjusted(6.0, None, Some(3.0), Some(1.0), false).unwrap(), (4.0, None), "released quantity is available to the same adjustment");
+    }
+
+    #[test]
+    fn test_rejects_consuming_more_than_available() {
+        match adjusted(5.0, None, Some(7.0), None, false) {
+            Err(DataIntegrityError::InsufficientQuantity(requested, available)) => assert_eq!((requested, available), (7.0, 5.0)),
+            other => panic!("expected InsufficientQuantity, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_records_excess_when_allowed() {
+        assert_eq!(adjusted(5.0, None, Some(7.0), None, true).unwrap(), (0.0, Some(2.0)));
+        assert_eq!(adjusted(0.0, Some(2.0), Some(3.0), None, true).unwrap(), (0.0, Some(5.0)), "excess accumulates");
+    }
+
+    #[test]
+    fn test_released_quantity_repays_excess_first() {
+        assert_eq!(adjusted(0.0, Some(2.0), None, Some(1.5), false).unwrap(), (0.0, Some(0.5)));
+        assert_eq!(adjusted(0.0, Some(2.0), None, Some(2.0), false).unwrap(), (0.0, None));
+        assert_eq!(adjusted(0.0, Some(2.0), None, Some(7.0), false).unwrap(), (5.0, None), "only the surplus becomes available");
+    }
+
+    #[test]
+    fn test_rejects_unit_mismatch() {
+        let litres = QuantityValue::new(1.0, Some(UnitId(DnaHash::from_raw_36(vec![0xdb; 36]), "l".to_string())));
+        assert!(matches!(adjust_availability(kg(5.0), None, Some(litres.to_owned()), None, false), Err(DataIntegrityError::IncompatibleUnits)));
+        assert!(matches!(adjust_availability(kg(5.0), None, None, Some(litres), false), Err(DataIntegrityError::IncompatibleUnits)));
+    }
+}
@@ -8,12 +8,18 @@
  * @package Holo-REA
  */
 use hdk::prelude::*;
+use hdk_records::{ ErrorEnvelope, ErrorCode, config::init_with_config };
 
 use hc_zome_rea_intent_rpc::*;
 use hc_zome_rea_intent_lib::*;
 use hc_zome_rea_intent_storage::*;
 use hc_zome_rea_intent_storage_consts::*;
 
+#[hdk_extern]
+fn init(_: ()) -> ExternResult<InitCallbackResult> {
+    Ok(init_with_config::<IntentZomeConfig>())
+}
+
 #[hdk_extern]
 fn validate(validation_data: ValidateData) -> ExternResult<ValidateCallbackResult> {
     let element = validation_data.element;
@@ -29,7 +35,7 @@ fn validate(validation_data: ValidateData) -> ExternResult<ValidateCallbackResul
             record.validate_or_fields()
                 .and_then(|()| { record.validate_action() })
                 .and_then(|()| { Ok(ValidateCallbackResult::Valid) })
-                .or_else(|e| { Ok(ValidateCallbackResult::Invalid(e)) })
+                .or_else(|e| { Ok(ValidateCallbackResult::Invalid(ErrorEnvelope::new(ErrorCode::ValidationFailed, e).to_string())) })
         },
         _ => Ok(ValidateCallbackResult::Valid),
     }
@@ -52,7 +58,9 @@ fn entry_defs(_: ()) -> ExternResult<EntryDefsCallbackResult> {
             crdt_type: CrdtType,
             required_validations: 2.into(),
             required_validation_type: RequiredValidationType::default(),
-        }
+        },
+        blob_chunk_entry_def(),
+        blob_manifest_entry_def(),
     ]))
 }
 
@@ -89,12 +97,12 @@ fn update_intent(UpdateParams { intent }: UpdateParams) -> ExternResult<Response
     Ok(handle_update_intent(INTENT_ENTRY_TYPE, intent)?)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct ByHeader {
-    pub address: RevisionHash,
+#[hdk_extern]
+fn _internal_adjust_available_quantity(request: AdjustAvailableQuantityRequest) -> ExternResult<ResponseData> {
+    Ok(handle_adjust_available_quantity(INTENT_ENTRY_TYPE, request)?)
 }
 
 #[hdk_extern]
-fn delete_intent(ByHeader { address }: ByHeader) -> ExternResult<bool> {
-    Ok(handle_delete_intent(address)?)
+fn delete_intent(DeleteRequest { address, force }: DeleteRequest) -> ExternResult<bool> {
+    Ok(handle_delete_intent(address, force)?)
 }
@@ -0,0 +1,379 @@
+/**
+ * Dependent demand explosion
+ *
+ * Walks outward from the independent demands of a `Plan`, netting each requirement against
+ * inventory held in the observation DNA and outputs already planned within the plan. Any remaining
+ * requirement is met by planning a run of the recipe producing that `ResourceSpecification`, whose
+ * inputs are then netted & expanded in turn. Requirements with neither stock nor recipe are reported
+ * as shortfalls.
+ *
+ * Consumed inputs and work draw down what is available; used and cited inputs only require that
+ * enough is available, and leave it available to later requirements.
+ *
+ * Netting against planned outputs allows explosion to be re-run on a plan without duplicating work.
+ * If planning fails part way through, all records created by the explosion are removed again.
+ *
+ * @package Holo-REA
+ */
+use std::collections::HashMap;
+use hdk::prelude::*;
+use hdk_records::{
+    RecordAPIResult, DataIntegrityError,
+    records::read_record_entry,
+    foreign_indexes::read_foreign_index,
+    rpc::call_idempotent_zome_method,
+};
+use vf_measurement::QuantityValue;
+
+use hc_zome_rea_plan_rpc::*;
+use hc_zome_rea_plan_storage_consts::*;
+use crate::{
+    read_foreign_index_zome,
+    recipe::{ TargetDna, PlanWrites, record_planned_process },
+};
+
+use hc_zome_rea_commitment_storage_consts::COMMITMENT_ENTRY_TYPE;
+use hc_zome_rea_commitment_storage::{
+    EntryData as CommitmentData,
+    EntryStorage as CommitmentStorage,
+};
+use hc_zome_rea_recipe_flow_rpc::{
+    ExpandSpecificationRequest,
+    PlannedProcess,
+    PlannedFlow,
+};
+use hc_zome_rea_economic_resource_rpc::SpecificationAsOfRequest;
+use hc_zome_rea_economic_event_rpc::ResourceResponseData;
+
+/// Number of nested recipes which may be expanded before the demand is assumed to be cyclic
+pub const MAX_DEMAND_DEPTH: usize = 32;
+
+/// Outstanding requirement for some quantity of a `ResourceSpecification` by a given time
+#[derive(Clone)]
+struct Requirement {
+    action: ActionId,
+    resource_specification: ResourceSpecificationAddress,
+    resource_quantity: QuantityValue,
+    due: DateTime<FixedOffset>,
+    depth: usize,
+}
+
+impl Requirement {
+    /// Derive the requirement placed on a `ResourceSpecification` by some planned input
+    fn from_input(flow: &PlannedFlow, due: &DateTime<FixedOffset>, depth: usize) -> Option<Self> {
+        let quantity = match flow.action.as_ref() {
+            "consume" | "use" | "cite" => &flow.resource_quantity,
+            "work" => &flow.effort_quantity,
+            _ => return None,
+        };
+        match (&flow.resource_conforms_to, quantity) {
+            (Some(resource_specification), Some(resource_quantity)) => Some(Self {
+                action: flow.action.to_owned(),
+                resource_specification: resource_specification.to_owned(),
+                resource_quantity: resource_quantity.to_owned(),
+                due: due.to_owned(),
+                depth,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Whether meeting the requirement leaves the resource unavailable to others
+    fn depletes(&self) -> bool {
+        self.action.as_ref() != "use" && self.action.as_ref() != "cite"
+    }
+}
+
+/// Quantities of each `ResourceSpecification` available to meet requirements
+#[derive(Default)]
+struct Supply {
+    /// inventory onhand as of a given time, cached per specification & time
+    onhand: HashMap<(ResourceSpecificationAddress, DateTime<FixedOffset>), f64>,
+    /// outputs planned within the plan, including those planned by this explosion
+    planned: HashMap<ResourceSpecificationAddress, f64>,
+    /// quantities drawn down by earlier requirements
+    allocated: HashMap<ResourceSpecificationAddress, f64>,
+}
+
+impl Supply {
+    fn available(&mut self, observation_dna: &DnaHash, resource_specification: &ResourceSpecificationAddress, as_of: &DateTime<FixedOffset>) -> RecordAPIResult<f64> {
+        let key = (resource_specification.to_owned(), as_of.to_owned());
+        let onhand = match self.onhand.get(&key) {
+            Some(qty) => *qty,
+            None => {
+                let qty = read_onhand_quantity(observation_dna, resource_specification, as_of)?;
+                self.onhand.insert(key, qty);
+                qty
+            },
+        };
+        let planned = self.planned.get(resource_specification).cloned().unwrap_or(0.0);
+        let allocated = self.allocated.get(resource_specification).cloned().unwrap_or(0.0);
+        Ok((onhand + planned - allocated).max(0.0))
+    }
+
+    fn plan(&mut self, resource_specification: &ResourceSpecificationAddress, quantity: f64) {
+        *self.planned.entry(resource_specification.to_owned()).or_insert(0.0) += quantity;
+    }
+
+    fn allocate(&mut self, resource_specification: &ResourceSpecificationAddress, quantity: f64) {
+        *self.allocated.entry(resource_specification.to_owned()).or_insert(0.0) += quantity;
+    }
+
+    /// Meet as much of a requirement as possible from the `available` quantity, returning the quantity met
+    fn draw(&mut self, requirement: &Requirement, available: f64) -> f64 {
+        let met = available.min(requirement.resource_quantity.get_numerical_value()).max(0.0);
+        if requirement.depletes() {
+            self.allocate(&requirement.resource_specification, met);
+        }
+        met
+    }
+}
+
+/// Explode the independent demands of a `Plan` into the `Process`es and `Commitment`s needed
+/// to satisfy them, recording everything created within the plan.
+///
+pub fn handle_explode_plan_demand(request: ExplodeDemandRequest) -> RecordAPIResult<DemandExplosionReport> {
+    let plan = request.plan.to_owned();
+    let demands: Vec<CommitmentAddress> = read_foreign_index(read_foreign_index_zome, &PLAN_INDEPENDENT_DEMANDS_READ_API_METHOD, &plan)?;
+    let commitments: Vec<CommitmentAddress> = read_foreign_index(read_foreign_index_zome, &PLAN_COMMITMENTS_READ_API_METHOD, &plan)?;
+
+    let mut supply = Supply::default();
+    let mut requirements = vec![];
+    let mut skipped_demands = vec![];
+    let mut seen = vec![];
+    for commitment in demands.iter().chain(commitments.iter()) {
+        if seen.contains(&commitment) { continue; }
+        seen.push(commitment);
+
+        let (_, _, entry): (_, CommitmentAddress, CommitmentData) = read_record_entry::<CommitmentData, CommitmentStorage, _,_>(&COMMITMENT_ENTRY_TYPE, commitment.as_ref())?;
+
+        // outputs already planned are available to meet demand
+        if let (Some(_), Some(resource_specification), Some(resource_quantity)) = (&entry.output_of, &entry.resource_conforms_to, &entry.resource_quantity) {
+            supply.plan(resource_specification, resource_quantity.get_numerical_value());
+        }
+
+        if !demands.contains(commitment) { continue; }
+        match (entry.resource_conforms_to, entry.resource_quantity, entry.due.or(entry.has_end)) {
+            (Some(resource_specification), Some(resource_quantity), Some(due)) => requirements.push(Requirement {
+                action: entry.action, resource_specification, resource_quantity, due, depth: 0,
+            }),
+            _ => skipped_demands.push(commitment.to_owned()),
+        }
+    }
+
+    let mut report = DemandExplosionReport {
+        plan: plan.to_owned(),
+        processes: vec![],
+        commitments: vec![],
+        shortfalls: vec![],
+        skipped_demands,
+    };
+    let mut writes = PlanWrites::new(&request.observation_dna);
+
+    match explode_requirements(&request, requirements, &mut supply, &mut writes, &mut report) {
+        Ok(()) => Ok(report),
+        Err(e) => Err(e.with_rollback_failures(writes.rollback())),
+    }
+}
+
+/// Net each requirement against available supply, planning production of any remainder and
+/// adding the requirements of each planned process in turn.
+///
+fn explode_requirements(
+    request: &ExplodeDemandRequest,
+    mut requirements: Vec<Requirement>,
+    supply: &mut Supply,
+    writes: &mut PlanWrites,
+    report: &mut DemandExplosionReport,
+) -> RecordAPIResult<()> {
+    let mut next = 0;
+
+    while next < requirements.len() {
+        let requirement = requirements[next].clone();
+        let Requirement { resource_specification, resource_quantity, due, depth, .. } = requirement.to_owned();
+        next += 1;
+
+        let available = supply.available(&request.observation_dna, &resource_specification, &due)?;
+        let required = resource_quantity.get_numerical_value();
+        let met = supply.draw(&requirement, available);
+
+        let net = required - met;
+        if net <= 0.0 {
+            continue;
+        }
+        let net_quantity = QuantityValue::new(net, resource_quantity.get_unit());
+
+        let planned: Option<PlannedProcess> = call_idempotent_zome_method(
+            &resource_specification,
+            &RECIPE_EXPAND_SPECIFICATION_REMOTE_PERMISSION,
+            ExpandSpecificationRequest {
+                resource_specification: resource_specification.to_owned(),
+                resource_quantity: net_quantity.to_owned(),
+                due: due.to_owned(),
+            },
+        )?;
+        let planned = match planned {
+            Some(p) => p,
+            None => {
+                report.shortfalls.push(Shortfall {
+                    action: requirement.action.to_owned(),
+                    resource_specification: resource_specification.to_owned(),
+                    required: resource_quantity.to_owned(),
+                    available: QuantityValue::new(met, resource_quantity.get_unit()),
+                    shortfall: net_quantity,
+                    due: due.to_owned(),
+                });
+                continue;
+            },
+        };
+        if depth >= MAX_DEMAND_DEPTH {
+            return Err(DataIntegrityError::MaxDepthExceeded(MAX_DEMAND_DEPTH));
+        }
+
+        let (process, commitments) = record_planned_process(
+            writes, &request.plan,
+            &request.provider, &request.receiver,
+            &planned, None,
+        )?;
+        report.processes.push(process);
+        report.commitments.extend(commitments);
+
+        supply.plan(&resource_specification, net);
+        if requirement.depletes() {
+            supply.allocate(&resource_specification, net);
+        }
+
+        // inputs must themselves be available by the time the process begins
+        requirements.extend(planned.inputs.iter()
+            .filter_map(|flow| Requirement::from_input(flow, &planned.has_beginning, depth + 1)));
+    }
+
+    Ok(())
+}
+
+/// Total onhand quantity of all `EconomicResource`s conforming to a specification, as of the given time
+///
+/// :TODO: convert between units once `QuantityValue` supports it
+///
+fn read_onhand_quantity(
+    observation_dna: &DnaHash,
+    resource_specification: &ResourceSpecificationAddress,
+    as_of: &DateTime<FixedOffset>,
+) -> RecordAPIResult<f64> {
+    let resources: Vec<ResourceResponseData> = call_idempotent_zome_method(
+        &TargetDna(observation_dna.to_owned()),
+        &RESOURCES_BY_SPECIFICATION_READ_REMOTE_PERMISSION,
+        SpecificationAsOfRequest {
+            conforms_to: resource_specification.to_owned(),
+            as_of: as_of.to_owned(),
+        },
+    )?;
+
+    Ok(resources.iter()
+        .filter_map(|r| r.economic_resource.onhand_quantity.as_ref())
+        .map(|q| q.get_numerical_value())
+        .sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hc_zome_rea_recipe_flow_rpc::RecipeFlowAddress;
+
+    fn dna() -> DnaHash {
+        DnaHash::from_raw_36(vec![0xdb; 36])
+    }
+
+    fn spec(byte: u8) -> ResourceSpecificationAddress {
+        ResourceSpecificationAddress(dna(), EntryHash::from_raw_36(vec![byte; 36]))
+    }
+
+    fn due() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2021-06-01T12:00:00+00:00").unwrap()
+    }
+
+    fn qty(value: f64) -> QuantityValue {
+        QuantityValue::new(value, None)
+    }
+
+    fn flow(action: &str, resource_quantity: Option<f64>, effort_quantity: Option<f64>) -> PlannedFlow {
+        PlannedFlow {
+            recipe_flow: RecipeFlowAddress(dna(), EntryHash::from_raw_36(vec![0xf1; 36])),
+            action: ActionId(action.to_string()),
+            resource_conforms_to: Some(spec(1)),
+            resource_quantity: resource_quantity.map(qty),
+            effort_quantity: effort_quantity.map(qty),
+            due: due(),
+        }
+    }
+
+    fn requirement(action: &str, quantity: f64) -> Requirement {
+        Requirement { action: ActionId(action.to_string()), resource_specification: spec(1), resource_quantity: qty(quantity), due: due(), depth: 0 }
+    }
+
+    /// Supply with the given onhand inventory of `spec(1)` already read as of `due()`
+    fn supply_with_onhand(onhand: f64) -> Supply {
+        let mut supply = Supply::default();
+        supply.onhand.insert((spec(1), due()), onhand);
+        supply
+    }
+
+    #[test]
+    fn test_requirements_from_inputs() {
+        let consumed = Requirement::from_input(&flow("consume", Some(3.0), Some(1.0)), &due(), 2).unwrap();
+        assert_eq!(consumed.resource_quantity, qty(3.0));
+        assert_eq!(consumed.depth, 2);
+
+        let work = Requirement::from_input(&flow("work", Some(3.0), Some(1.0)), &due(), 1).unwrap();
+        assert_eq!(work.resource_quantity, qty(1.0), "work is measured by effort");
+
+        assert!(Requirement::from_input(&flow("use", Some(1.0), None), &due(), 1).is_some());
+        assert!(Requirement::from_input(&flow("cite", Some(1.0), None), &due(), 1).is_some());
+    }
+
+    #[test]
+    fn test_inputs_without_requirements() {
+        assert!(Requirement::from_input(&flow("accept", Some(1.0), None), &due(), 1).is_none(), "only consume, use, cite & work place demands");
+        assert!(Requirement::from_input(&flow("consume", None, Some(1.0)), &due(), 1).is_none());
+        assert!(Requirement::from_input(&PlannedFlow { resource_conforms_to: None, ..flow("consume", Some(1.0), None) }, &due(), 1).is_none());
+    }
+
+    #[test]
+    fn test_depleting_requirements() {
+        assert!(requirement("consume", 1.0).depletes());
+        assert!(requirement("work", 1.0).depletes());
+        assert!(!requirement("use", 1.0).depletes());
+        assert!(!requirement("cite", 1.0).depletes());
+    }
+
+    #[test]
+    fn test_consumption_draws_down_supply() {
+        let mut supply = supply_with_onhand(5.0);
+
+        let available = supply.available(&dna(), &spec(1), &due()).unwrap();
+        assert_eq!(supply.draw(&requirement("consume", 3.0), available), 3.0);
+
+        let available = supply.available(&dna(), &spec(1), &due()).unwrap();
+        assert_eq!(available, 2.0);
+        assert_eq!(supply.draw(&requirement("consume", 3.0), available), 2.0, "only the remainder can be met");
+        assert_eq!(supply.available(&dna(), &spec(1), &due()).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_use_leaves_supply_available() {
+        let mut supply = supply_with_onhand(5.0);
+
+        assert_eq!(supply.draw(&requirement("use", 3.0), 5.0), 3.0);
+        assert_eq!(supply.available(&dna(), &spec(1), &due()).unwrap(), 5.0);
+    }
+
+    #[test]
+    fn test_planned_outputs_add_to_supply() {
+        let mut supply = supply_with_onhand(1.0);
+        supply.plan(&spec(1), 4.0);
+        assert_eq!(supply.available(&dna(), &spec(1), &due()).unwrap(), 5.0);
+
+        supply.allocate(&spec(1), 7.0);
+        assert_eq!(supply.available(&dna(), &spec(1), &due()).unwrap(), 0.0, "over-allocation never makes supply negative");
+    }
+}
@@ -0,0 +1,255 @@
+/**
+ * Holo-REA plan zome library API
+ *
+ * Contains helper methods that can be used to manipulate `Plan` data
+ * structures in either the local Holochain zome, or a separate DNA-local zome.
+ *
+ * @package Holo-REA
+ */
+use hdk::prelude::*;
+use hdk_records::{
+    RecordAPIResult,
+    records::{
+        create_record,
+        read_record_entry,
+        read_record_entry_by_header,
+        update_record,
+        delete_record,
+    },
+    foreign_indexes::{
+        read_foreign_index,
+    },
+    integrity::ensure_deletable,
+    rpc::call_idempotent_zome_method,
+};
+
+pub mod recipe;
+pub use recipe::handle_create_plan_from_recipe;
+pub mod demand;
+pub use demand::handle_explode_plan_demand;
+
+pub use hc_zome_rea_plan_storage_consts::*;
+use hc_zome_rea_plan_storage::*;
+use hc_zome_rea_plan_rpc::*;
+
+use hc_zome_rea_process_rpc::ResponseData as ProcessResponse;
+use hc_zome_rea_commitment_storage_consts::COMMITMENT_ENTRY_TYPE;
+use hc_zome_rea_commitment_storage::{
+    EntryData as CommitmentData,
+    EntryStorage as CommitmentStorage,
+};
+
+pub fn handle_create_plan<S>(entry_def_id: S, plan: CreateRequest) -> RecordAPIResult<ResponseData>
+    where S: AsRef<str>
+{
+    let (header_addr, base_address, entry_resp): (_,_, EntryData) = create_record(&entry_def_id, plan)?;
+    construct_response(&base_address, header_addr, &entry_resp, get_link_fields(&base_address)?)
+}
+
+pub fn handle_get_plan<S>(entry_def_id: S, address: PlanAddress) -> RecordAPIResult<ResponseData>
+    where S: AsRef<str>
+{
+    let (revision, base_address, entry) = read_record_entry::<EntryData, EntryStorage, _,_>(&entry_def_id, address.as_ref())?;
+    construct_response(&base_address, revision, &entry, get_link_fields(&base_address)?)
+}
+
+/// Determine whether a `Plan` is finished. Computed on request rather than upon every read,
+/// since it requires reading every `Commitment` and `Process` in the plan.
+///
+pub fn handle_get_plan_finished<S>(entry_def_id: S, address: PlanAddress) -> RecordAPIResult<bool>
+    where S: AsRef<str>
+{
+    let (_revision, base_address, _entry): (_, PlanAddress, EntryData) = read_record_entry::<EntryData, EntryStorage, _,_>(&entry_def_id, address.as_ref())?;
+    let (processes, commitments, independent_demands) = get_link_fields(&base_address)?;
+    is_finished(&processes, &commitments, &independent_demands)
+}
+
+pub fn handle_update_plan<S>(entry_def_id: S, plan: UpdateRequest) -> RecordAPIResult<ResponseData>
+    where S: AsRef<str>
+{
+    let revision_hash = plan.get_revision_id().clone();
+    let (revision_id, identity_address, entry, _prev_entry): (_,_, EntryData, EntryData) = update_record(&entry_def_id, &revision_hash, plan)?;
+    construct_response(&identity_address, revision_id, &entry, get_link_fields(&identity_address)?)
+}
+
+/// Indexes of other records referencing a `Plan`, which prevent its deletion
+const INBOUND_LINK_TAGS: &[&str] = &[
+    PLAN_PROCESSES_LINK_TAG,
+    PLAN_COMMITMENTS_LINK_TAG,
+    PLAN_INDEPENDENT_DEMANDS_LINK_TAG,
+];
+
+pub fn handle_delete_plan(address: RevisionHash, force: bool) -> RecordAPIResult<bool> {
+    let (base_address, _entry): (PlanAddress, EntryData) = read_record_entry_by_header::<EntryData, EntryStorage, _>(&address)?;
+
+    ensure_deletable(&PLAN_ENTRY_TYPE, &base_address, INBOUND_LINK_TAGS, force)?;
+
+    delete_record::<EntryStorage, _>(&address)
+}
+
+/// Create response from input DHT primitives
+fn construct_response<'a>(
+    address: &PlanAddress, revision: RevisionHash, e: &EntryData, (
+        processes,
+        commitments,
+        independent_demands,
+    ): (
+        Vec<ProcessAddress>,
+        Vec<CommitmentAddress>,
+        Vec<CommitmentAddress>,
+    ),
+) -> RecordAPIResult<ResponseData> {
+    Ok(ResponseData {
+        plan: Response {
+            id: address.to_owned(),
+            revision_id: revision.to_owned(),
+            name: e.name.to_owned(),
+            created: e.created.to_owned(),
+            due: e.due.to_owned(),
+            note: e.note.to_owned(),
+            deletable: processes.is_empty() && commitments.is_empty() && independent_demands.is_empty(),
+            processes: processes.to_owned(),
+            commitments: commitments.to_owned(),
+            independent_demands: independent_demands.to_owned(),
+        }
+    })
+}
+
+//---------------- READ ----------------
+
+/// Properties accessor for zome config
+fn read_foreign_index_zome(conf: PlanZomeConfig) -> Option<String> {
+    Some(conf.index_zome)
+}
+
+// @see construct_response
+fn get_link_fields(base_address: &PlanAddress) -> RecordAPIResult<(
+    Vec<ProcessAddress>,
+    Vec<CommitmentAddress>,
+    Vec<CommitmentAddress>,
+)> {
+    Ok((
+        read_foreign_index(read_foreign_index_zome, &PLAN_PROCESSES_READ_API_METHOD, base_address)?,
+        read_foreign_index(read_foreign_index_zome, &PLAN_COMMITMENTS_READ_API_METHOD, base_address)?,
+        read_foreign_index(read_foreign_index_zome, &PLAN_INDEPENDENT_DEMANDS_READ_API_METHOD, base_address)?,
+    ))
+}
+
+/// I/O struct for reading `Process`es from the observation DNA via zome API
+#[derive(Clone, Serialize, Deserialize, Debug)]
+struct ReadProcessRequest {
+    pub address: ProcessAddress,
+}
+
+/// A `Plan` is finished once it has some work planned within it, and every `Process`
+/// and `Commitment` in the plan has been marked as finished.
+///
+/// Any `Process` which cannot be read from the observation DNA is reported as an error,
+/// rather than being treated as unfinished.
+///
+fn is_finished(
+    processes: &[ProcessAddress],
+    commitments: &[CommitmentAddress],
+    independent_demands: &[CommitmentAddress],
+) -> RecordAPIResult<bool> {
+    check_finished(processes, commitments, independent_demands,
+        |commitment| {
+            let (_, _, entry): (_, CommitmentAddress, CommitmentData) = read_record_entry::<CommitmentData, CommitmentStorage, _,_>(&COMMITMENT_ENTRY_TYPE, commitment.as_ref())?;
+            Ok(entry.finished)
+        },
+        |process| {
+            let resp: ProcessResponse = call_idempotent_zome_method(
+                process,
+                &PROCESS_READ_REMOTE_PERMISSION,
+                ReadProcessRequest { address: process.to_owned() },
+            )?;
+            Ok(resp.process.finished)
+        },
+    )
+}
+
+fn check_finished<C, P>(
+    processes: &[ProcessAddress],
+    commitments: &[CommitmentAddress],
+    independent_demands: &[CommitmentAddress],
+    commitment_finished: C,
+    process_finished: P,
+) -> RecordAPIResult<bool>
+    where C: Fn(&CommitmentAddress) -> RecordAPIResult<bool>,
+        P: Fn(&ProcessAddress) -> RecordAPIResult<bool>,
+{
+    if processes.is_empty() && commitments.is_empty() && independent_demands.is_empty() {
+        return Ok(false);
+    }
+
+    for commitment in commitments.iter().chain(independent_demands.iter()) {
+        if !commitment_finished(commitment)? {
+            return Ok(false);
+        }
+    }
+
+    for process in processes.iter() {
+        if !process_finished(process)? {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hdk_records::DataIntegrityError;
+
+    fn dna() -> DnaHash {
+        DnaHash::from_raw_36(vec![0xdb; 36])
+    }
+
+    fn process(byte: u8) -> ProcessAddress {
+        ProcessAddress(dna(), EntryHash::from_raw_36(vec![byte; 36]))
+    }
+
+    fn commitment(byte: u8) -> CommitmentAddress {
+        CommitmentAddress(dna(), EntryHash::from_raw_36(vec![byte; 36]))
+    }
+
+    /// Everything except the listed records is finished
+    fn finished_except(unfinished: Vec<u8>) -> impl Fn(&[ProcessAddress], &[CommitmentAddress], &[CommitmentAddress]) -> RecordAPIResult<bool> {
+        move |processes, commitments, demands| {
+            let unfinished_commitments: Vec<CommitmentAddress> = unfinished.iter().map(|b| commitment(*b)).collect();
+            let unfinished_processes: Vec<ProcessAddress> = unfinished.iter().map(|b| process(*b)).collect();
+            check_finished(processes, commitments, demands,
+                |c| Ok(!unfinished_commitments.contains(c)),
+                |p| Ok(!unfinished_processes.contains(p)),
+            )
+        }
+    }
+
+    #[test]
+    fn test_empty_plan_is_unfinished() {
+        assert_eq!(finished_except(vec![])(&[], &[], &[]).unwrap(), false);
+    }
+
+    #[test]
+    fn test_plan_finished_when_all_work_finished() {
+        let check = finished_except(vec![9]);
+        assert_eq!(check(&[process(1)], &[commitment(2)], &[commitment(3)]).unwrap(), true);
+        assert_eq!(check(&[process(1)], &[], &[]).unwrap(), true, "processes alone are enough to plan work");
+        assert_eq!(check(&[], &[], &[commitment(3)]).unwrap(), true, "independent demands alone are enough to plan work");
+    }
+
+    #[test]
+    fn test_plan_unfinished_with_any_unfinished_work() {
+        let check = finished_except(vec![1]);
+        assert_eq!(check(&[process(1)], &[commitment(2)], &[]).unwrap(), false, "unfinished process");
+        assert_eq!(check(&[process(2)], &[commitment(1)], &[]).unwrap(), false, "unfinished commitment");
+        assert_eq!(check(&[process(2)], &[], &[commitment(1)]).unwrap(), false, "unfinished independent demand");
+    }
+
+    #[test]
+    fn test_unreadable_process_is_an_error() {
+        let result = check_finished(&[process(1)], &[], &[], |_| Ok(true), |_| Err(DataIntegrityError::EmptyQuery));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,238 @@
+/**
+ * Plan generation from recipes
+ *
+ * Asks the specification DNA to expand a recipe into scheduled processes and flows,
+ * then records the result as a `Plan` containing `Process`es (in the observation DNA)
+ * and `Commitment`s (in this DNA) for each of their inputs & outputs.
+ *
+ * Records are written one at a time across both DNAs, so any failure part way through
+ * is followed by deleting everything created so far.
+ *
+ * @package Holo-REA
+ */
+use hdk::prelude::*;
+use hdk_records::{
+    RecordAPIResult, DataIntegrityError, MaybeUndefined,
+    rpc::{
+        call_zome_method,
+        call_idempotent_zome_method,
+        call_local_zome_method,
+    },
+};
+use vf_attributes_hdk::Timestamp;
+
+use hc_zome_rea_plan_storage::*;
+use hc_zome_rea_plan_rpc::*;
+use hc_zome_rea_plan_storage_consts::*;
+use crate::{ handle_create_plan, handle_get_plan, handle_delete_plan };
+
+use hc_zome_rea_process_rpc::{
+    CreateRequest as ProcessCreateRequest,
+    ResponseData as ProcessResponse,
+};
+use hc_zome_rea_commitment_rpc::{
+    CreateRequest as CommitmentCreateRequest,
+    ResponseData as CommitmentResponse,
+};
+use hc_zome_rea_recipe_flow_rpc::{
+    RecipeFlowAddress,
+    ExpandRecipeRequest,
+    RecipeExpansion,
+    PlannedProcess,
+    PlannedFlow,
+};
+
+/// Adapter allowing a bare `DnaHash` to be used as the target of `call_zome_method`
+pub (crate) struct TargetDna(pub DnaHash);
+
+impl AsRef<DnaHash> for TargetDna {
+    fn as_ref(&self) -> &DnaHash {
+        &self.0
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CreateProcessParams {
+    pub process: ProcessCreateRequest,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CreateCommitmentParams {
+    pub commitment: CommitmentCreateRequest,
+}
+
+/// Properties accessor for zome config
+fn read_commitment_zome(conf: PlanZomeConfig) -> Option<String> {
+    conf.commitment_zome
+}
+
+/// `Process`es and `Commitment`s written whilst recording planned work, in the order they were created
+pub (crate) struct PlanWrites {
+    observation_dna: DnaHash,
+    processes: Vec<RevisionHash>,
+    commitments: Vec<RevisionHash>,
+}
+
+impl PlanWrites {
+    pub fn new(observation_dna: &DnaHash) -> Self {
+        Self { observation_dna: observation_dna.to_owned(), processes: vec![], commitments: vec![] }
+    }
+
+    /// Delete everything written, most recent first, returning any failures to do so
+    pub fn rollback(self) -> Vec<DataIntegrityError> {
+        let mut failures = vec![];
+        for revision_id in self.commitments.into_iter().rev() {
+            let deleted: Result<bool, _> = call_local_zome_method(
+                read_commitment_zome,
+                COMMITMENT_DELETE_API_METHOD,
+                DeleteRequest { address: revision_id, force: true },
+            );
+            if let Err(e) = deleted { failures.push(e.into()); }
+        }
+        for revision_id in self.processes.into_iter().rev() {
+            let deleted: Result<bool, _> = call_zome_method(
+                &TargetDna(self.observation_dna.to_owned()),
+                &PROCESS_DELETE_REMOTE_PERMISSION,
+                DeleteRequest { address: revision_id, force: true },
+            );
+            if let Err(e) = deleted { failures.push(e.into()); }
+        }
+        failures
+    }
+}
+
+/// Generate a `Plan` for producing `resource_quantity` of the given `RecipeResource` by `due`.
+///
+/// The output of the process delivering the requested resource is recorded as the independent
+/// demand of the plan; all other flows are recorded as ordinary commitments within it.
+///
+pub fn handle_create_plan_from_recipe<S>(entry_def_id: S, request: CreateFromRecipeRequest) -> RecordAPIResult<ResponseData>
+    where S: AsRef<str>
+{
+    let expansion: RecipeExpansion = call_idempotent_zome_method(
+        &request.recipe_resource,
+        &RECIPE_EXPAND_REMOTE_PERMISSION,
+        ExpandRecipeRequest {
+            recipe_resource: request.recipe_resource.to_owned(),
+            resource_quantity: request.resource_quantity.to_owned(),
+            due: request.due.to_owned(),
+        },
+    )?;
+
+    let plan = handle_create_plan(&entry_def_id, CreateRequest {
+        name: request.name.to_owned(),
+        created: MaybeUndefined::Undefined,
+        due: MaybeUndefined::Some(request.due.to_owned()),
+        note: request.note.to_owned(),
+    })?.plan;
+
+    let mut writes = PlanWrites::new(&request.observation_dna);
+    let recorded = expansion.processes.iter().enumerate()
+        .map(|(i, planned)| record_planned_process(
+            &mut writes, &plan.id,
+            &request.provider, &request.receiver,
+            planned, if i == 0 { Some(&planned.produces) } else { None },
+        ))
+        .collect::<RecordAPIResult<Vec<_>>>();
+
+    if let Err(e) = recorded {
+        let mut failures = writes.rollback();
+        if let Err(plan_err) = handle_delete_plan(plan.revision_id, true) {
+            failures.push(plan_err);
+        }
+        return Err(e.with_rollback_failures(failures));
+    }
+
+    handle_get_plan(&entry_def_id, plan.id)
+}
+
+/// Create a `Process` in the observation DNA for some `PlannedProcess`, along with `Commitment`s
+/// for all of its inputs & outputs. The output for `independent_demand` (if any) is recorded as an
+/// independent demand of the plan. Returns the addresses of all records created, and tracks them
+/// in `writes` as they are created.
+///
+pub (crate) fn record_planned_process(
+    writes: &mut PlanWrites,
+    plan: &PlanAddress,
+    provider: &AgentAddress,
+    receiver: &AgentAddress,
+    planned: &PlannedProcess,
+    independent_demand: Option<&RecipeFlowAddress>,
+) -> RecordAPIResult<(ProcessAddress, Vec<CommitmentAddress>)> {
+    let process: ProcessResponse = call_zome_method(
+        &TargetDna(writes.observation_dna.to_owned()),
+        &PROCESS_CREATE_REMOTE_PERMISSION,
+        CreateProcessParams { process: ProcessCreateRequest {
+            name: planned.name.to_owned(),
+            has_beginning: MaybeUndefined::Some(to_timestamp(&planned.has_beginning)),
+            has_end: MaybeUndefined::Some(to_timestamp(&planned.has_end)),
+            before: MaybeUndefined::Undefined,
+            after: MaybeUndefined::Undefined,
+            classified_as: MaybeUndefined::Undefined,
+            based_on: planned.process_conforms_to.to_owned().into(),
+            planned_within: MaybeUndefined::Some(plan.to_owned()),
+            finished: MaybeUndefined::Some(false),
+            in_scope_of: MaybeUndefined::Undefined,
+            note: planned.note.to_owned().into(),
+        } },
+    )?;
+    writes.processes.push(process.process.revision_id);
+    let process_address = process.process.id;
+
+    let mut commitments = vec![];
+    for flow in planned.inputs.iter() {
+        commitments.push(create_commitment(writes, plan, provider, receiver, flow, Some(&process_address), None, false)?);
+    }
+    for flow in planned.outputs.iter() {
+        let is_independent_demand = independent_demand == Some(&flow.recipe_flow);
+        commitments.push(create_commitment(writes, plan, provider, receiver, flow, None, Some(&process_address), is_independent_demand)?);
+    }
+
+    Ok((process_address, commitments))
+}
+
+fn create_commitment(
+    writes: &mut PlanWrites,
+    plan: &PlanAddress,
+    provider: &AgentAddress,
+    receiver: &AgentAddress,
+    flow: &PlannedFlow,
+    input_of: Option<&ProcessAddress>,
+    output_of: Option<&ProcessAddress>,
+    is_independent_demand: bool,
+) -> RecordAPIResult<CommitmentAddress> {
+    let created: CommitmentResponse = call_local_zome_method(
+        read_commitment_zome,
+        COMMITMENT_CREATE_API_METHOD,
+        CreateCommitmentParams { commitment: CommitmentCreateRequest {
+            action: flow.action.to_owned(),
+            note: MaybeUndefined::Undefined,
+            input_of: input_of.cloned().into(),
+            output_of: output_of.cloned().into(),
+            provider: provider.to_owned(),
+            receiver: receiver.to_owned(),
+            resource_inventoried_as: MaybeUndefined::Undefined,
+            resource_classified_as: MaybeUndefined::Undefined,
+            resource_conforms_to: flow.resource_conforms_to.to_owned().into(),
+            resource_quantity: flow.resource_quantity.to_owned().into(),
+            effort_quantity: flow.effort_quantity.to_owned().into(),
+            has_beginning: MaybeUndefined::Undefined,
+            has_end: MaybeUndefined::Undefined,
+            has_point_in_time: MaybeUndefined::Undefined,
+            due: MaybeUndefined::Some(flow.due.to_owned()),
+            at_location: MaybeUndefined::Undefined,
+            plan: MaybeUndefined::Some(plan.to_owned()),
+            agreed_in: MaybeUndefined::Undefined,
+            clause_of: MaybeUndefined::Undefined,
+            independent_demand_of: if is_independent_demand { MaybeUndefined::Some(plan.to_owned()) } else { MaybeUndefined::Undefined },
+            finished: MaybeUndefined::Some(false),
+            in_scope_of: MaybeUndefined::Undefined,
+        } },
+    )?;
+    writes.commitments.push(created.commitment.revision_id);
+    Ok(created.commitment.id)
+}
+
+fn to_timestamp(time: &DateTime<FixedOffset>) -> Timestamp {
+    Timestamp(time.timestamp(), time.timestamp_subsec_nanos())
+}
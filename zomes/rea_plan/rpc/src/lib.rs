@@ -0,0 +1,184 @@
+/**
+ * Holo-REA plan zome I/O data structures
+ *
+ * Required by packages wishing to interact with the zome via its standard RPC interface,
+ * and by the zome API handlers accepting these parameters.
+ *
+ * @package Holo-REA
+ */
+use holochain_serialized_bytes::prelude::*;
+
+use serde_maybe_undefined::MaybeUndefined;
+pub use vf_measurement::QuantityValue;
+pub use vf_attributes_hdk::{
+    RevisionHash, DeleteRequest,
+    PlanAddress,
+    ProcessAddress,
+    CommitmentAddress,
+    RecipeResourceAddress,
+    ResourceSpecificationAddress,
+    AgentAddress,
+    ActionId,
+    DnaHash,
+    DateTime,
+    FixedOffset,
+};
+
+//---------------- EXTERNAL RECORD STRUCTURE ----------------
+
+/// I/O struct to describe the complete record, including all managed link fields
+///
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+    pub id: PlanAddress,
+    pub revision_id: RevisionHash,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<DateTime<FixedOffset>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due: Option<DateTime<FixedOffset>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub deletable: bool,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub processes: Vec<ProcessAddress>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub commitments: Vec<CommitmentAddress>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub independent_demands: Vec<CommitmentAddress>,
+}
+
+/// I/O struct to describe what is returned outside the gateway.
+/// Responses are usually returned as named attributes in order to leave space
+/// for future additional return values.
+///
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseData {
+    pub plan: Response,
+}
+
+//---------------- CREATE REQUEST ----------------
+
+/// I/O struct to describe the complete input record, including all managed links
+///
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateRequest {
+    #[serde(default)]
+    #[serde(skip_serializing_if = "MaybeUndefined::is_undefined")]
+    pub name: MaybeUndefined<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "MaybeUndefined::is_undefined")]
+    pub created: MaybeUndefined<DateTime<FixedOffset>>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "MaybeUndefined::is_undefined")]
+    pub due: MaybeUndefined<DateTime<FixedOffset>>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "MaybeUndefined::is_undefined")]
+    pub note: MaybeUndefined<String>,
+}
+
+impl<'a> CreateRequest {
+    // :TODO: accessors for field data
+}
+
+//---------------- UPDATE REQUEST ----------------
+
+/// I/O struct to describe the complete input record, including all managed links
+///
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRequest {
+    pub revision_id: RevisionHash,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "MaybeUndefined::is_undefined")]
+    pub name: MaybeUndefined<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "MaybeUndefined::is_undefined")]
+    pub created: MaybeUndefined<DateTime<FixedOffset>>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "MaybeUndefined::is_undefined")]
+    pub due: MaybeUndefined<DateTime<FixedOffset>>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "MaybeUndefined::is_undefined")]
+    pub note: MaybeUndefined<String>,
+}
+
+impl<'a> UpdateRequest {
+    pub fn get_revision_id(&self) -> RevisionHash {
+        self.revision_id.to_owned().into()
+    }
+
+    // :TODO: accessors for other field data
+}
+
+//---------------- RECIPE EXPANSION REQUEST ----------------
+
+/// I/O struct for generating a `Plan` from the recipe which produces some `RecipeResource`
+///
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateFromRecipeRequest {
+    pub recipe_resource: RecipeResourceAddress,
+    pub resource_quantity: QuantityValue,
+    pub due: DateTime<FixedOffset>,
+    /// Observation DNA in which to create the planned `Process`es
+    pub observation_dna: DnaHash,
+    // :TODO: allow assigning agents per recipe flow
+    pub provider: AgentAddress,
+    pub receiver: AgentAddress,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "MaybeUndefined::is_undefined")]
+    pub name: MaybeUndefined<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "MaybeUndefined::is_undefined")]
+    pub note: MaybeUndefined<String>,
+}
+
+//---------------- DEMAND EXPLOSION ----------------
+
+/// I/O struct for deriving dependent demand from the independent demands of a `Plan`
+///
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplodeDemandRequest {
+    pub plan: PlanAddress,
+    /// Observation DNA holding current inventory, and in which to create the planned `Process`es
+    pub observation_dna: DnaHash,
+    // :TODO: allow assigning agents per recipe flow
+    pub provider: AgentAddress,
+    pub receiver: AgentAddress,
+}
+
+/// A requirement which could be met neither from inventory nor by any known recipe
+///
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Shortfall {
+    /// action of the flow the requirement arises from
+    pub action: ActionId,
+    pub resource_specification: ResourceSpecificationAddress,
+    pub required: QuantityValue,
+    pub available: QuantityValue,
+    pub shortfall: QuantityValue,
+    pub due: DateTime<FixedOffset>,
+}
+
+/// Outcome of a demand explosion
+///
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DemandExplosionReport {
+    pub plan: PlanAddress,
+    pub processes: Vec<ProcessAddress>,
+    pub commitments: Vec<CommitmentAddress>,
+    pub shortfalls: Vec<Shortfall>,
+    /// independent demands lacking a resource specification, quantity or due date
+    pub skipped_demands: Vec<CommitmentAddress>,
+}
@@ -0,0 +1,79 @@
+/**
+ * Holo-REA plan zome internal data structures
+ *
+ * Required by the zome itself, and for any DNA-local zomes interacting with its
+ * storage API directly.
+ *
+ * @package Holo-REA
+ */
+use hdk::prelude::*;
+
+use hdk_records::{
+    config::ZomeConfig,
+    MaybeUndefined,
+    generate_record_entry,
+    record_interface::{Updateable},
+};
+
+use vf_attributes_hdk::{
+    DateTime,
+    FixedOffset,
+};
+
+use hc_zome_rea_plan_rpc::{ CreateRequest, UpdateRequest };
+
+pub use vf_attributes_hdk::PlanAddress;
+
+// :SHONK: needed as re-export in zome logic to allow validation logic to parse entries
+pub use hdk_records::record_interface::Identified;
+
+//--------------- ZOME CONFIGURATION ATTRIBUTES ----------------
+
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct PlanZomeConfig {
+    pub index_zome: String,
+    pub commitment_zome: Option<String>,
+}
+
+impl ZomeConfig for PlanZomeConfig {}
+
+//---------------- RECORD INTERNALS & VALIDATION ----------------
+
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+pub struct EntryData {
+    pub name: Option<String>,
+    pub created: Option<DateTime<FixedOffset>>,
+    pub due: Option<DateTime<FixedOffset>>,
+    pub note: Option<String>,
+}
+
+generate_record_entry!(EntryData, PlanAddress, EntryStorage);
+
+//---------------- CREATE ----------------
+
+/// Pick relevant fields out of I/O record into underlying DHT entry
+impl From<CreateRequest> for EntryData {
+    fn from(e: CreateRequest) -> EntryData {
+        EntryData {
+            name: e.name.into(),
+            created: e.created.into(),
+            due: e.due.into(),
+            note: e.note.into(),
+        }
+    }
+}
+
+//---------------- UPDATE ----------------
+
+/// Handles update operations by merging any newly provided fields
+impl Updateable<UpdateRequest> for EntryData {
+    fn update_with(&self, e: UpdateRequest) -> EntryData {
+        EntryData {
+            name: if e.name == MaybeUndefined::Undefined { self.name.to_owned() } else { e.name.to_owned().into() },
+            created: if e.created == MaybeUndefined::Undefined { self.created.to_owned() } else { e.created.to_owned().into() },
+            due: if e.due == MaybeUndefined::Undefined { self.due.to_owned() } else { e.due.to_owned().into() },
+            note: if e.note == MaybeUndefined::Undefined { self.note.to_owned() } else { e.note.to_owned().into() },
+        }
+    }
+}
@@ -0,0 +1,26 @@
+/**
+ * Storage constants for zome entry & link type identifiers
+ *
+ * Used by modules interfacing with the underlying Holochain storage system directly.
+ *
+ * @package Holo-REA
+ */
+pub const PLAN_ENTRY_TYPE: &str = "vf_plan";
+
+pub const PLAN_PROCESSES_LINK_TAG: &str = "processes";
+pub const PLAN_COMMITMENTS_LINK_TAG: &str = "commitments";
+pub const PLAN_INDEPENDENT_DEMANDS_LINK_TAG: &str = "independent_demands";
+
+pub const PLAN_PROCESSES_READ_API_METHOD: &str = "_internal_read_plan_processes";
+pub const PLAN_COMMITMENTS_READ_API_METHOD: &str = "_internal_read_plan_commitments";
+pub const PLAN_INDEPENDENT_DEMANDS_READ_API_METHOD: &str = "_internal_read_plan_independent_demands";
+
+pub const PROCESS_READ_REMOTE_PERMISSION: &str = "read_process";
+pub const PROCESS_CREATE_REMOTE_PERMISSION: &str = "create_process";
+pub const PROCESS_DELETE_REMOTE_PERMISSION: &str = "delete_process";
+pub const RECIPE_EXPAND_REMOTE_PERMISSION: &str = "expand_recipe";
+pub const RECIPE_EXPAND_SPECIFICATION_REMOTE_PERMISSION: &str = "expand_specification_recipe";
+pub const RESOURCES_BY_SPECIFICATION_READ_REMOTE_PERMISSION: &str = "read_resources_by_specification";
+
+pub const COMMITMENT_CREATE_API_METHOD: &str = "create_commitment";
+pub const COMMITMENT_DELETE_API_METHOD: &str = "delete_commitment";
@@ -0,0 +1,89 @@
+/**
+ * Holo-REA plan zome API definition
+ *
+ * Defines the top-level zome configuration needed by Holochain's build system
+ * to bundle the app. This basically involves wiring up the helper methods from the
+ * related `_lib` module into a packaged zome WASM binary.
+ *
+ * @package Holo-REA
+ */
+use hdk::prelude::*;
+use hdk_records::config::init_with_config;
+
+use hc_zome_rea_plan_rpc::*;
+use hc_zome_rea_plan_lib::*;
+use hc_zome_rea_plan_storage::PlanZomeConfig;
+
+#[hdk_extern]
+fn init(_: ()) -> ExternResult<InitCallbackResult> {
+    Ok(init_with_config::<PlanZomeConfig>())
+}
+
+#[hdk_extern]
+fn entry_defs(_: ()) -> ExternResult<EntryDefsCallbackResult> {
+    Ok(EntryDefsCallbackResult::from(vec![
+        Path::entry_def(),
+        EntryDef {
+            id: PLAN_ENTRY_TYPE.into(),
+            visibility: EntryVisibility::Public,
+            crdt_type: CrdtType,
+            required_validations: 2.into(),
+            required_validation_type: RequiredValidationType::default(),
+        }
+    ]))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CreateParams {
+    pub plan: CreateRequest,
+}
+
+#[hdk_extern]
+fn create_plan(CreateParams { plan }: CreateParams) -> ExternResult<ResponseData> {
+    Ok(handle_create_plan(PLAN_ENTRY_TYPE, plan)?)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CreateFromRecipeParams {
+    pub plan: CreateFromRecipeRequest,
+}
+
+#[hdk_extern]
+fn create_plan_from_recipe(CreateFromRecipeParams { plan }: CreateFromRecipeParams) -> ExternResult<ResponseData> {
+    Ok(handle_create_plan_from_recipe(PLAN_ENTRY_TYPE, plan)?)
+}
+
+#[hdk_extern]
+fn explode_plan_demand(request: ExplodeDemandRequest) -> ExternResult<DemandExplosionReport> {
+    Ok(handle_explode_plan_demand(request)?)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ReadParams {
+    pub address: PlanAddress,
+}
+
+#[hdk_extern]
+fn get_plan(ReadParams { address }: ReadParams) -> ExternResult<ResponseData> {
+    Ok(handle_get_plan(PLAN_ENTRY_TYPE, address)?)
+}
+
+#[hdk_extern]
+fn get_plan_finished(ReadParams { address }: ReadParams) -> ExternResult<bool> {
+    Ok(handle_get_plan_finished(PLAN_ENTRY_TYPE, address)?)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct UpdateParams {
+    pub plan: UpdateRequest,
+}
+
+#[hdk_extern]
+fn update_plan(UpdateParams { plan }: UpdateParams) -> ExternResult<ResponseData> {
+    Ok(handle_update_plan(PLAN_ENTRY_TYPE, plan)?)
+}
+
+#[hdk_extern]
+fn delete_plan(DeleteRequest { address, force }: DeleteRequest) -> ExternResult<bool> {
+    Ok(handle_delete_plan(address, force)?)
+}
@@ -0,0 +1,85 @@
+/**
+ * Plan query indexes for planning DNA
+ *
+ * @package Holo-REA
+ * @since   2021-10-18
+ */
+use hdk::prelude::*;
+use hdk_records::{
+    config::init_with_config,
+    index_retrieval::{ ByAddress, IndexingZomeConfig },
+    remote_indexes::{
+        RemoteEntryLinkRequest,
+        RemoteEntryLinkResponse,
+        sync_remote_index,
+    },
+    local_indexes::{
+        read_index
+    },
+};
+
+use hc_zome_rea_plan_rpc::*;
+use hc_zome_rea_plan_storage_consts::*;
+use hc_zome_rea_process_storage_consts::{ PROCESS_ENTRY_TYPE, PROCESS_PLANNED_WITHIN_LINK_TAG };
+use hc_zome_rea_commitment_storage_consts::{ COMMITMENT_ENTRY_TYPE, COMMITMENT_PLAN_LINK_TAG, COMMITMENT_INDEPENDENT_DEMAND_OF_LINK_TAG };
+
+entry_defs![Path::entry_def()];
+
+#[hdk_extern]
+fn init(_: ()) -> ExternResult<InitCallbackResult> {
+    Ok(init_with_config::<IndexingZomeConfig>())
+}
+
+#[hdk_extern]
+fn _internal_read_plan_processes(ByAddress { address }: ByAddress<PlanAddress>) -> ExternResult<Vec<ProcessAddress>> {
+    Ok(read_index(&PLAN_ENTRY_TYPE, &address, &PLAN_PROCESSES_LINK_TAG)?)
+}
+
+#[hdk_extern]
+fn index_plan_processes(indexes: RemoteEntryLinkRequest<ProcessAddress, PlanAddress>) -> ExternResult<RemoteEntryLinkResponse> {
+    let RemoteEntryLinkRequest { remote_entry, target_entries, removed_entries } = indexes;
+
+    Ok(sync_remote_index(
+        &PROCESS_ENTRY_TYPE, &remote_entry,
+        &PLAN_ENTRY_TYPE,
+        target_entries.as_slice(),
+        removed_entries.as_slice(),
+        PROCESS_PLANNED_WITHIN_LINK_TAG, PLAN_PROCESSES_LINK_TAG,
+    )?)
+}
+
+#[hdk_extern]
+fn _internal_read_plan_commitments(ByAddress { address }: ByAddress<PlanAddress>) -> ExternResult<Vec<CommitmentAddress>> {
+    Ok(read_index(&PLAN_ENTRY_TYPE, &address, &PLAN_COMMITMENTS_LINK_TAG)?)
+}
+
+#[hdk_extern]
+fn _internal_reindex_commitments(indexes: RemoteEntryLinkRequest<CommitmentAddress, PlanAddress>) -> ExternResult<RemoteEntryLinkResponse> {
+    let RemoteEntryLinkRequest { remote_entry, target_entries, removed_entries } = indexes;
+
+    Ok(sync_remote_index(
+        &COMMITMENT_ENTRY_TYPE, &remote_entry,
+        &PLAN_ENTRY_TYPE,
+        target_entries.as_slice(),
+        removed_entries.as_slice(),
+        COMMITMENT_PLAN_LINK_TAG, PLAN_COMMITMENTS_LINK_TAG,
+    )?)
+}
+
+#[hdk_extern]
+fn _internal_read_plan_independent_demands(ByAddress { address }: ByAddress<PlanAddress>) -> ExternResult<Vec<CommitmentAddress>> {
+    Ok(read_index(&PLAN_ENTRY_TYPE, &address, &PLAN_INDEPENDENT_DEMANDS_LINK_TAG)?)
+}
+
+#[hdk_extern]
+fn _internal_reindex_independent_demands(indexes: RemoteEntryLinkRequest<CommitmentAddress, PlanAddress>) -> ExternResult<RemoteEntryLinkResponse> {
+    let RemoteEntryLinkRequest { remote_entry, target_entries, removed_entries } = indexes;
+
+    Ok(sync_remote_index(
+        &COMMITMENT_ENTRY_TYPE, &remote_entry,
+        &PLAN_ENTRY_TYPE,
+        target_entries.as_slice(),
+        removed_entries.as_slice(),
+        COMMITMENT_INDEPENDENT_DEMAND_OF_LINK_TAG, PLAN_INDEPENDENT_DEMANDS_LINK_TAG,
+    )?)
+}
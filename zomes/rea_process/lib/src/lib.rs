@@ -8,7 +8,8 @@
  */
 use hdk::prelude::*;
 use hdk_records::{
-    DataIntegrityError, RecordAPIResult,
+    DataIntegrityError, RecordAPIResult, OtherCellResult,
+    config::ZomeConfig,
     records::{
         create_record,
         read_record_entry,
@@ -17,11 +18,18 @@ use hdk_records::{
         delete_record,
     },
     local_indexes::{
+        read_index,
         query_index,
     },
     foreign_indexes::{
         read_foreign_index,
     },
+    remote_indexes::{
+        create_remote_index,
+        update_remote_index,
+    },
+    integrity::ensure_deletable,
+    rpc::call_idempotent_zome_method,
 };
 
 use vf_attributes_hdk::{
@@ -30,6 +38,7 @@ use vf_attributes_hdk::{
     CommitmentAddress,
     IntentAddress,
     AgentAddress,
+    FulfillmentAddress,
 };
 
 pub use hc_zome_rea_process_storage_consts::*;
@@ -38,9 +47,14 @@ pub use hc_zome_rea_commitment_storage_consts::{COMMITMENT_ENTRY_TYPE};
 pub use hc_zome_rea_intent_storage_consts::{INTENT_ENTRY_TYPE};
 use hc_zome_rea_process_storage::*;
 use hc_zome_rea_process_rpc::*;
+use hc_zome_rea_economic_event_storage::{
+    EntryData as EventData,
+    EntryStorage as EventStorage,
+};
+use hc_zome_rea_economic_resource_storage_consts::{ RESOURCE_ENTRY_TYPE, RESOURCE_AFFECTED_BY_EVENT_LINK_TAG };
 
 use hc_zome_rea_economic_event_storage_consts::{
-    EVENT_INPUT_OF_LINK_TAG, EVENT_OUTPUT_OF_LINK_TAG,
+    EVENT_INPUT_OF_LINK_TAG, EVENT_OUTPUT_OF_LINK_TAG, EVENT_FULFILLS_LINK_TAG,
 };
 use hc_zome_rea_commitment_storage_consts::{
     COMMITMENT_INPUT_OF_LINK_TAG, COMMITMENT_OUTPUT_OF_LINK_TAG,
@@ -53,6 +67,18 @@ pub fn handle_create_process<S>(entry_def_id: S, process: CreateRequest) -> Reco
     where S: AsRef<str>
 {
     let (header_addr, base_address, entry_resp): (_,_, EntryData) = create_record(&entry_def_id, process)?;
+
+    // handle link fields
+    if let Some(plan_address) = &entry_resp.planned_within {
+        let _results = create_remote_index(
+            read_foreign_index_zome,
+            &PROCESS_PLANNED_WITHIN_INDEXING_API_METHOD,
+            &base_address,
+            &PLAN_PROCESS_INDEXING_API_METHOD,
+            vec![plan_address.to_owned()].as_slice(),
+        )?;
+    };
+
     construct_response(&base_address, &header_addr, &entry_resp, get_link_fields(&base_address)?)
 }
 
@@ -67,15 +93,53 @@ pub fn handle_update_process<S>(entry_def_id: S, process: UpdateRequest) -> Reco
     where S: AsRef<str>
 {
     let address = process.get_revision_id().clone();
-    let (revision_id, identity_address, entry, _prev_entry): (_,_, EntryData, EntryData) = update_record(&entry_def_id, &address, process)?;
+    let (revision_id, identity_address, entry, prev_entry): (_,_, EntryData, EntryData) = update_record(&entry_def_id, &address, process)?;
+
+    // handle link fields
+    if entry.planned_within != prev_entry.planned_within {
+        let new_value = match &entry.planned_within { Some(plan) => vec![plan.to_owned()], None => vec![] };
+        let prev_value = match &prev_entry.planned_within { Some(plan) => vec![plan.to_owned()], None => vec![] };
+        let _results = update_remote_index(
+            read_foreign_index_zome,
+            &PROCESS_PLANNED_WITHIN_INDEXING_API_METHOD,
+            &identity_address,
+            &PLAN_PROCESS_INDEXING_API_METHOD,
+            new_value.as_slice(),
+            prev_value.as_slice(),
+        );
+    }
+
     construct_response(&identity_address, &revision_id, &entry, get_link_fields(&identity_address)?)
 }
 
-pub fn handle_delete_process<S>(_entry_def_id: S, revision_id: RevisionHash) -> RecordAPIResult<bool>
+/// Indexes of other records referencing a `Process`, which prevent its deletion
+const INBOUND_LINK_TAGS: &[&str] = &[
+    PROCESS_EVENT_INPUTS_LINK_TAG, PROCESS_EVENT_OUTPUTS_LINK_TAG,
+    PROCESS_COMMITMENT_INPUTS_LINK_TAG, PROCESS_COMMITMENT_OUTPUTS_LINK_TAG,
+    PROCESS_INTENT_INPUTS_LINK_TAG, PROCESS_INTENT_OUTPUTS_LINK_TAG,
+];
+
+pub fn handle_delete_process<S>(entry_def_id: S, revision_id: RevisionHash, force: bool) -> RecordAPIResult<bool>
+    where S: AsRef<str>
 {
     // load the record to ensure it is of the correct type
-    let (_base_address, _entry) = read_record_entry_by_header::<EntryData, EntryStorage, _>(&revision_id)?;
+    let (base_address, entry): (ProcessAddress, EntryData) = read_record_entry_by_header::<EntryData, EntryStorage, _>(&revision_id)?;
+
+    ensure_deletable(&entry_def_id, &base_address, INBOUND_LINK_TAGS, force)?;
 
+    // handle link fields
+    if let Some(plan_address) = entry.planned_within {
+        let _results = update_remote_index(
+            read_foreign_index_zome,
+            &PROCESS_PLANNED_WITHIN_INDEXING_API_METHOD,
+            &base_address,
+            &PLAN_PROCESS_INDEXING_API_METHOD,
+            vec![].as_slice(),
+            vec![plan_address].as_slice(),
+        );
+    }
+
+    // delete entry last, as it must be present in order for links to be removed
     delete_record::<EntryStorage, _>(&revision_id)
 }
 
@@ -88,8 +152,7 @@ pub fn generate_query_handler<S, C, F>(
     intent_entry_def_id: S,
 ) -> impl FnOnce(&QueryParams) -> RecordAPIResult<Vec<ResponseData>>
     where S: AsRef<str>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: Fn(C) -> Option<String>,
 {
     move |params| {
@@ -151,17 +214,13 @@ fn construct_response<'a>(
         unplanned_economic_events,
         committed_inputs, committed_outputs,
         intended_inputs, intended_outputs,
-        next_processes, previous_processes,
         working_agents,
-        trace, track
      ): (
         Vec<EventAddress>, Vec<EventAddress>,
         Vec<EventAddress>,
         Vec<CommitmentAddress>, Vec<CommitmentAddress>,
         Vec<IntentAddress>, Vec<IntentAddress>,
-        Vec<ProcessAddress>, Vec<ProcessAddress>,
         Vec<AgentAddress>,
-        Vec<EventAddress>, Vec<EventAddress>,
     ),
 ) -> RecordAPIResult<ResponseData> {
     Ok(ResponseData {
@@ -180,7 +239,9 @@ fn construct_response<'a>(
             note: e.note.to_owned(),
             in_scope_of: e.in_scope_of.to_owned(),
             finished: e.finished.to_owned(),
-            deletable: true,    // :TODO:
+            deletable: inputs.is_empty() && outputs.is_empty()
+                && committed_inputs.is_empty() && committed_outputs.is_empty()
+                && intended_inputs.is_empty() && intended_outputs.is_empty(),
 
             // link fields
             inputs: inputs.to_owned(),
@@ -190,11 +251,7 @@ fn construct_response<'a>(
             committed_outputs: committed_outputs.to_owned(),
             intended_inputs: intended_inputs.to_owned(),
             intended_outputs: intended_outputs.to_owned(),
-            next_processes: next_processes.to_owned(),
-            previous_processes: previous_processes.to_owned(),
             working_agents: working_agents.to_owned(),
-            trace: trace.to_owned(),
-            track: track.to_owned(),
         }
     })
 }
@@ -202,8 +259,133 @@ fn construct_response<'a>(
 //---------------- READ ----------------
 
 /// Properties accessor for zome config.
-fn read_foreign_index_zome(conf: DnaConfigSlice) -> Option<String> {
-    Some(conf.process.index_zome)
+fn read_foreign_index_zome(conf: ProcessZomeConfig) -> Option<String> {
+    Some(conf.index_zome)
+}
+
+/// Determine the processes which consume the outputs of (`Next`), or produce the inputs to (`Previous`) a `process`.
+///
+/// Realized flows are followed via the `EconomicEvent`s affecting each resource output from (or input to) the process.
+/// Planned flows are read from the planned flow index maintained alongside `Commitment`s and `Intent`s in the planning DNA.
+///
+pub fn handle_get_adjacent_processes(process: &ProcessAddress, direction: FlowDirection) -> RecordAPIResult<Vec<ProcessAddress>> {
+    let mut found = get_realized_adjacent_processes(process, direction)?;
+    for other_process in get_planned_adjacent_processes(process, direction)? {
+        add_adjacent(&mut found, process, other_process);
+    }
+    Ok(found)
+}
+
+/// Other processes which consumed (or produced) the same resources as a `process`
+fn get_realized_adjacent_processes(process: &ProcessAddress, direction: FlowDirection) -> RecordAPIResult<Vec<ProcessAddress>> {
+    let event_tag = match direction {
+        FlowDirection::Next => PROCESS_EVENT_OUTPUTS_LINK_TAG,
+        FlowDirection::Previous => PROCESS_EVENT_INPUTS_LINK_TAG,
+    };
+    let mut found: Vec<ProcessAddress> = vec![];
+
+    let events: Vec<EventAddress> = read_index(&PROCESS_ENTRY_TYPE, process, &event_tag)?;
+    for event_address in events.iter() {
+        let (_, _, event) = read_record_entry::<EventData, EventStorage, _,_>(&EVENT_ENTRY_TYPE, event_address.as_ref())?;
+        let resource = match &event.resource_inventoried_as {
+            Some(r) => r,
+            None => continue,
+        };
+        let affecting_events: Vec<EventAddress> = read_index(&RESOURCE_ENTRY_TYPE, resource, &RESOURCE_AFFECTED_BY_EVENT_LINK_TAG)?;
+        for other_address in affecting_events.iter() {
+            let (_, _, other) = read_record_entry::<EventData, EventStorage, _,_>(&EVENT_ENTRY_TYPE, other_address.as_ref())?;
+            if let Some(p) = flow_process(&other, direction) {
+                add_adjacent(&mut found, process, p);
+            }
+        }
+    }
+
+    Ok(found)
+}
+
+/// The process an event affecting some resource inputs the resource to (`Next`) or outputs it from (`Previous`)
+fn flow_process(event: &EventData, direction: FlowDirection) -> Option<ProcessAddress> {
+    match direction {
+        FlowDirection::Next => event.input_of.to_owned(),
+        FlowDirection::Previous => event.output_of.to_owned(),
+    }
+}
+
+/// Add `other_process` to the processes `found` adjacent to `process`, ignoring the process itself and duplicates
+fn add_adjacent(found: &mut Vec<ProcessAddress>, process: &ProcessAddress, other_process: ProcessAddress) {
+    if other_process != *process && !found.contains(&other_process) {
+        found.push(other_process);
+    }
+}
+
+/// Other processes planning to consume (or produce) the same resources as a `process`.
+///
+/// A process without planned flows in the given direction has no planned neighbours, otherwise the
+/// planning DNA is located via the process's own `Commitment`s or `Intent`s.
+///
+fn get_planned_adjacent_processes(process: &ProcessAddress, direction: FlowDirection) -> RecordAPIResult<Vec<ProcessAddress>> {
+    let (commitment_tag, intent_tag) = match direction {
+        FlowDirection::Next => (PROCESS_COMMITMENT_OUTPUTS_LINK_TAG, PROCESS_INTENT_OUTPUTS_LINK_TAG),
+        FlowDirection::Previous => (PROCESS_COMMITMENT_INPUTS_LINK_TAG, PROCESS_INTENT_INPUTS_LINK_TAG),
+    };
+    let commitments: Vec<CommitmentAddress> = read_index(&PROCESS_ENTRY_TYPE, process, &commitment_tag)?;
+    let intents: Vec<IntentAddress> = read_index(&PROCESS_ENTRY_TYPE, process, &intent_tag)?;
+
+    let request = AdjacentProcessesRequest { address: process.to_owned(), direction };
+    let resp: OtherCellResult<Vec<ProcessAddress>> = match (commitments.first(), intents.first()) {
+        (Some(commitment), _) => call_idempotent_zome_method(commitment, &PLANNED_ADJACENT_PROCESSES_READ_REMOTE_PERMISSION, request),
+        (None, Some(intent)) => call_idempotent_zome_method(intent, &PLANNED_ADJACENT_PROCESSES_READ_REMOTE_PERMISSION, request),
+        (None, None) => return Ok(vec![]),
+    };
+
+    Ok(resp?)
+}
+
+/// Distinct providers of `work` events input to a `process`
+fn get_working_agents(process: &ProcessAddress) -> RecordAPIResult<Vec<AgentAddress>> {
+    let inputs: Vec<EventAddress> = read_index(&PROCESS_ENTRY_TYPE, process, &PROCESS_EVENT_INPUTS_LINK_TAG)?;
+    let events = inputs.iter()
+        .map(|event_address| {
+            let (_, _, event) = read_record_entry::<EventData, EventStorage, _,_>(&EVENT_ENTRY_TYPE, event_address.as_ref())?;
+            Ok(event)
+        })
+        .collect::<RecordAPIResult<Vec<EventData>>>()?;
+    Ok(find_working_agents(events))
+}
+
+fn find_working_agents(input_events: Vec<EventData>) -> Vec<AgentAddress> {
+    let mut agents: Vec<AgentAddress> = vec![];
+    for event in input_events {
+        if event.action.as_ref() == "work" && !agents.contains(&event.provider) {
+            agents.push(event.provider);
+        }
+    }
+    agents
+}
+
+/// Events input to or output from a `process` which do not fulfill any `Commitment`
+fn get_unplanned_events(process: &ProcessAddress) -> RecordAPIResult<Vec<EventAddress>> {
+    let mut events: Vec<EventAddress> = read_index(&PROCESS_ENTRY_TYPE, process, &PROCESS_EVENT_INPUTS_LINK_TAG)?;
+    let mut outputs: Vec<EventAddress> = read_index(&PROCESS_ENTRY_TYPE, process, &PROCESS_EVENT_OUTPUTS_LINK_TAG)?;
+    events.append(&mut outputs);
+
+    // `fulfills` links are managed by the fulfillment index zome in this DNA
+    find_unplanned_events(events, |event_address| read_index(&EVENT_ENTRY_TYPE, event_address, &EVENT_FULFILLS_LINK_TAG))
+}
+
+fn find_unplanned_events<F>(events: Vec<EventAddress>, fulfillments_of: F) -> RecordAPIResult<Vec<EventAddress>>
+    where F: Fn(&EventAddress) -> RecordAPIResult<Vec<FulfillmentAddress>>
+{
+    let mut unplanned = vec![];
+    for event_address in events {
+        if unplanned.contains(&event_address) {
+            continue;
+        }
+        if fulfillments_of(&event_address)?.is_empty() {
+            unplanned.push(event_address);
+        }
+    }
+    Ok(unplanned)
 }
 
 // @see construct_response
@@ -215,24 +397,120 @@ fn get_link_fields(process: &ProcessAddress) -> RecordAPIResult<(
     Vec<CommitmentAddress>,
     Vec<IntentAddress>,
     Vec<IntentAddress>,
-    Vec<ProcessAddress>,
-    Vec<ProcessAddress>,
     Vec<AgentAddress>,
-    Vec<EventAddress>,
-    Vec<EventAddress>,
 )> {
     Ok((
         read_foreign_index(read_foreign_index_zome, &PROCESS_EVENT_INPUTS_READ_API_METHOD, process)?,
         read_foreign_index(read_foreign_index_zome, &PROCESS_EVENT_OUTPUTS_READ_API_METHOD, process)?,
-        vec![],  // :TODO: unplanned_economic_events
+        get_unplanned_events(process)?,
         read_foreign_index(read_foreign_index_zome, &PROCESS_COMMITMENT_INPUTS_READ_API_METHOD, process)?,
         read_foreign_index(read_foreign_index_zome, &PROCESS_COMMITMENT_OUTPUTS_READ_API_METHOD, process)?,
         read_foreign_index(read_foreign_index_zome, &PROCESS_INTENT_INPUTS_READ_API_METHOD, process)?,
         read_foreign_index(read_foreign_index_zome, &PROCESS_INTENT_OUTPUTS_READ_API_METHOD, process)?,
-        vec![], // :TODO: next_processes
-        vec![], // :TODO: previous_processes
-        vec![], // :TODO: working_agents
-        vec![], // :TODO: trace
-        vec![], // :TODO: track
+        get_working_agents(process)?,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vf_attributes_hdk::ActionId;
+
+    fn dna() -> DnaHash {
+        DnaHash::from_raw_36(vec![0xdb; 36])
+    }
+
+    fn process(byte: u8) -> ProcessAddress {
+        ProcessAddress(dna(), EntryHash::from_raw_36(vec![byte; 36]))
+    }
+
+    fn agent(byte: u8) -> AgentAddress {
+        AgentAddress(dna(), AgentPubKey::from_raw_36(vec![byte; 36]))
+    }
+
+    fn event_address(byte: u8) -> EventAddress {
+        EventAddress(dna(), EntryHash::from_raw_36(vec![byte; 36]))
+    }
+
+    fn event(input_of: Option<ProcessAddress>, output_of: Option<ProcessAddress>) -> EventData {
+        EventData {
+            action: ActionId("use".to_string()),
+            provider: agent(0xaa),
+            receiver: agent(0xaa),
+            input_of,
+            output_of,
+            resource_inventoried_as: None,
+            to_resource_inventoried_as: None,
+            resource_classified_as: None,
+            resource_conforms_to: None,
+            resource_quantity: None,
+            effort_quantity: None,
+            has_beginning: None,
+            has_end: None,
+            has_point_in_time: None,
+            at_location: None,
+            agreed_in: None,
+            realization_of: None,
+            triggered_by: None,
+            in_scope_of: None,
+            note: None,
+        }
+    }
+
+    #[test]
+    fn test_flow_process() {
+        let consumed = event(Some(process(2)), None);
+        assert_eq!(flow_process(&consumed, FlowDirection::Next), Some(process(2)));
+        assert_eq!(flow_process(&consumed, FlowDirection::Previous), None);
+
+        let produced = event(None, Some(process(3)));
+        assert_eq!(flow_process(&produced, FlowDirection::Next), None);
+        assert_eq!(flow_process(&produced, FlowDirection::Previous), Some(process(3)));
+    }
+
+    #[test]
+    fn test_add_adjacent() {
+        let mut found = vec![];
+        add_adjacent(&mut found, &process(1), process(2));
+        add_adjacent(&mut found, &process(1), process(1));
+        add_adjacent(&mut found, &process(1), process(3));
+        add_adjacent(&mut found, &process(1), process(2));
+
+        assert_eq!(found, vec![process(2), process(3)], "the process itself and duplicates are excluded");
+    }
+
+    fn work(provider: u8) -> EventData {
+        EventData { action: ActionId("work".to_string()), provider: agent(provider), ..event(Some(process(1)), None) }
+    }
+
+    #[test]
+    fn test_working_agents() {
+        let events = vec![work(1), event(Some(process(1)), None), work(2), work(1)];
+        assert_eq!(find_working_agents(events), vec![agent(1), agent(2)]);
+    }
+
+    #[test]
+    fn test_working_agents_ignore_other_actions() {
+        let used = EventData { provider: agent(3), ..event(Some(process(1)), None) };
+        assert!(find_working_agents(vec![used]).is_empty());
+    }
+
+    #[test]
+    fn test_unplanned_events() {
+        let fulfilled = |e: &EventAddress| Ok(if *e == event_address(2) {
+            vec![FulfillmentAddress(dna(), EntryHash::from_raw_36(vec![0xf0; 36]))]
+        } else {
+            vec![]
+        });
+        let events = vec![event_address(1), event_address(2), event_address(3), event_address(1)];
+
+        assert_eq!(find_unplanned_events(events, fulfilled).unwrap(), vec![event_address(1), event_address(3)]);
+    }
+
+    #[test]
+    fn test_unplanned_events_propagate_errors() {
+        let failing = |_: &EventAddress| Err(DataIntegrityError::EmptyQuery);
+        assert!(find_unplanned_events(vec![event_address(1)], failing).is_err());
+        assert!(find_unplanned_events(vec![], failing).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,220 @@
+/**
+ * Holo-REA 'process' planned flow index
+ *
+ * Maintains an index, in the planning DNA, of the resources each `Process` plans to consume
+ * and produce via its `Commitment`s and `Intent`s. Inventoried resources are keyed by their
+ * identity, otherwise flows are keyed by `ResourceSpecification`.
+ *
+ * The index for a process is recomputed whenever one of its commitments or intents is written,
+ * allowing the processes adjacent to it in a plan to be found without scanning all processes.
+ *
+ * @package Holo-REA
+ */
+use hdk::prelude::*;
+use hdk_records::{
+    RecordAPIResult,
+    local_indexes::{
+        read_index,
+        update_index,
+    },
+    records::read_record_entry,
+};
+
+use vf_attributes_hdk::{
+    ProcessAddress,
+    CommitmentAddress,
+    IntentAddress,
+    ResourceAddress,
+    ResourceSpecificationAddress,
+    DnaAddressable,
+};
+
+use hc_zome_rea_process_storage_consts::*;
+use hc_zome_rea_process_rpc::FlowDirection;
+use hc_zome_rea_commitment_storage_consts::COMMITMENT_ENTRY_TYPE;
+use hc_zome_rea_commitment_storage::{
+    EntryData as CommitmentData,
+    EntryStorage as CommitmentStorage,
+};
+use hc_zome_rea_intent_storage_consts::INTENT_ENTRY_TYPE;
+use hc_zome_rea_intent_storage::{
+    EntryData as IntentData,
+    EntryStorage as IntentStorage,
+};
+use hc_zome_rea_economic_resource_storage_consts::RESOURCE_ENTRY_TYPE;
+use hc_zome_rea_resource_specification_storage_consts::ECONOMIC_RESOURCE_SPECIFICATION_ENTRY_TYPE;
+
+/// Resources planned to flow into or out of a process
+#[derive(Default)]
+struct PlannedFlowKeys {
+    resources: Vec<ResourceAddress>,
+    specifications: Vec<ResourceSpecificationAddress>,
+}
+
+impl PlannedFlowKeys {
+    fn add(&mut self, inventoried_as: Option<ResourceAddress>, conforms_to: Option<ResourceSpecificationAddress>) {
+        match (inventoried_as, conforms_to) {
+            (Some(resource), _) => push_unique(&mut self.resources, resource),
+            (None, Some(spec)) => push_unique(&mut self.specifications, spec),
+            _ => (),
+        }
+    }
+}
+
+/// Recompute the planned flow index for each of the given processes from their current
+/// `Commitment`s and `Intent`s.
+///
+/// Must be called after the process's commitment & intent indexes have been updated.
+///
+pub fn reindex_planned_flows(processes: &[&Option<ProcessAddress>]) -> RecordAPIResult<()> {
+    let mut done: Vec<&ProcessAddress> = vec![];
+    for process in processes.iter().filter_map(|p| p.as_ref()) {
+        if done.contains(&process) {
+            continue;
+        }
+        reindex_process(process)?;
+        done.push(process);
+    }
+    Ok(())
+}
+
+fn reindex_process(process: &ProcessAddress) -> RecordAPIResult<()> {
+    let inputs = read_planned_flow_keys(process, FlowDirection::Previous)?;
+    let outputs = read_planned_flow_keys(process, FlowDirection::Next)?;
+
+    sync_keys(process, &RESOURCE_ENTRY_TYPE, &PROCESS_PLANNED_INPUT_RESOURCES_LINK_TAG, &PLANNED_FLOW_CONSUMERS_LINK_TAG, &inputs.resources)?;
+    sync_keys(process, &ECONOMIC_RESOURCE_SPECIFICATION_ENTRY_TYPE, &PROCESS_PLANNED_INPUT_SPECIFICATIONS_LINK_TAG, &PLANNED_FLOW_CONSUMERS_LINK_TAG, &inputs.specifications)?;
+    sync_keys(process, &RESOURCE_ENTRY_TYPE, &PROCESS_PLANNED_OUTPUT_RESOURCES_LINK_TAG, &PLANNED_FLOW_PRODUCERS_LINK_TAG, &outputs.resources)?;
+    sync_keys(process, &ECONOMIC_RESOURCE_SPECIFICATION_ENTRY_TYPE, &PROCESS_PLANNED_OUTPUT_SPECIFICATIONS_LINK_TAG, &PLANNED_FLOW_PRODUCERS_LINK_TAG, &outputs.specifications)?;
+
+    Ok(())
+}
+
+/// Determine the resources a process plans to consume (`Previous`) or produce (`Next`)
+///
+/// :NOTE: flows are checked against the process they reference, since commitment & intent
+/// updates do not currently remove indexes to previously referenced processes.
+///
+fn read_planned_flow_keys(process: &ProcessAddress, direction: FlowDirection) -> RecordAPIResult<PlannedFlowKeys> {
+    let (commitment_tag, intent_tag) = match direction {
+        FlowDirection::Previous => (PROCESS_COMMITMENT_INPUTS_LINK_TAG, PROCESS_INTENT_INPUTS_LINK_TAG),
+        FlowDirection::Next => (PROCESS_COMMITMENT_OUTPUTS_LINK_TAG, PROCESS_INTENT_OUTPUTS_LINK_TAG),
+    };
+    let mut keys = PlannedFlowKeys::default();
+
+    let commitments: Vec<CommitmentAddress> = read_index(&PROCESS_ENTRY_TYPE, process, &commitment_tag)?;
+    for commitment_address in commitments.iter() {
+        let (_, _, commitment) = read_record_entry::<CommitmentData, CommitmentStorage, CommitmentAddress, _>(&COMMITMENT_ENTRY_TYPE, commitment_address.as_ref())?;
+        if is_planned_flow_of(process, direction, &commitment.input_of, &commitment.output_of) {
+            keys.add(commitment.resource_inventoried_as, commitment.resource_conforms_to);
+        }
+    }
+
+    let intents: Vec<IntentAddress> = read_index(&PROCESS_ENTRY_TYPE, process, &intent_tag)?;
+    for intent_address in intents.iter() {
+        let (_, _, intent) = read_record_entry::<IntentData, IntentStorage, IntentAddress, _>(&INTENT_ENTRY_TYPE, intent_address.as_ref())?;
+        if is_planned_flow_of(process, direction, &intent.input_of, &intent.output_of) {
+            keys.add(intent.resource_inventoried_as, intent.resource_conforms_to);
+        }
+    }
+
+    Ok(keys)
+}
+
+/// Whether a commitment or intent with the given `input_of` & `output_of` is an input to (`Previous`) or output of (`Next`) a `process`
+fn is_planned_flow_of(process: &ProcessAddress, direction: FlowDirection, input_of: &Option<ProcessAddress>, output_of: &Option<ProcessAddress>) -> bool {
+    match direction {
+        FlowDirection::Previous => input_of.as_ref() == Some(process),
+        FlowDirection::Next => output_of.as_ref() == Some(process),
+    }
+}
+
+/// Replace the set of flow keys indexed for a process with `keys`
+fn sync_keys<B>(
+    process: &ProcessAddress,
+    key_entry_type: &&str,
+    link_tag: &&str,
+    link_tag_reciprocal: &&str,
+    keys: &[B],
+) -> RecordAPIResult<()>
+    where B: DnaAddressable<EntryHash>,
+{
+    let existing: Vec<B> = read_index(&PROCESS_ENTRY_TYPE, process, link_tag)?;
+    let results = update_index(&PROCESS_ENTRY_TYPE, process, key_entry_type, link_tag, link_tag_reciprocal, keys, existing.as_slice())?;
+    results.into_iter().collect::<RecordAPIResult<Vec<HeaderHash>>>()?;
+    Ok(())
+}
+
+/// Read the processes which consume the planned outputs of (`Next`), or produce the planned inputs to (`Previous`) a `process`
+///
+pub fn read_adjacent_planned_processes(process: &ProcessAddress, direction: FlowDirection) -> RecordAPIResult<Vec<ProcessAddress>> {
+    let (resources_tag, specifications_tag, adjacent_tag) = match direction {
+        FlowDirection::Next => (PROCESS_PLANNED_OUTPUT_RESOURCES_LINK_TAG, PROCESS_PLANNED_OUTPUT_SPECIFICATIONS_LINK_TAG, PLANNED_FLOW_CONSUMERS_LINK_TAG),
+        FlowDirection::Previous => (PROCESS_PLANNED_INPUT_RESOURCES_LINK_TAG, PROCESS_PLANNED_INPUT_SPECIFICATIONS_LINK_TAG, PLANNED_FLOW_PRODUCERS_LINK_TAG),
+    };
+    let mut found: Vec<ProcessAddress> = vec![];
+
+    let resources: Vec<ResourceAddress> = read_index(&PROCESS_ENTRY_TYPE, process, &resources_tag)?;
+    for resource in resources.iter() {
+        let adjacent: Vec<ProcessAddress> = read_index(&RESOURCE_ENTRY_TYPE, resource, &adjacent_tag)?;
+        adjacent.into_iter().filter(|p| p != process).for_each(|p| push_unique(&mut found, p));
+    }
+
+    let specifications: Vec<ResourceSpecificationAddress> = read_index(&PROCESS_ENTRY_TYPE, process, &specifications_tag)?;
+    for specification in specifications.iter() {
+        let adjacent: Vec<ProcessAddress> = read_index(&ECONOMIC_RESOURCE_SPECIFICATION_ENTRY_TYPE, specification, &adjacent_tag)?;
+        adjacent.into_iter().filter(|p| p != process).for_each(|p| push_unique(&mut found, p));
+    }
+
+    Ok(found)
+}
+
+fn push_unique<T: PartialEq>(list: &mut Vec<T>, item: T) {
+    if !list.contains(&item) {
+        list.push(item);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dna() -> DnaHash {
+        DnaHash::from_raw_36(vec![0xdb; 36])
+    }
+
+    fn process(byte: u8) -> ProcessAddress {
+        ProcessAddress(dna(), EntryHash::from_raw_36(vec![byte; 36]))
+    }
+
+    fn resource(byte: u8) -> ResourceAddress {
+        ResourceAddress(dna(), EntryHash::from_raw_36(vec![byte; 36]))
+    }
+
+    fn spec(byte: u8) -> ResourceSpecificationAddress {
+        ResourceSpecificationAddress(dna(), EntryHash::from_raw_36(vec![byte; 36]))
+    }
+
+    #[test]
+    fn test_planned_flow_keys_prefer_resource_identity() {
+        let mut keys = PlannedFlowKeys::default();
+        keys.add(Some(resource(1)), Some(spec(1)));
+        keys.add(None, Some(spec(2)));
+        keys.add(None, None);
+        keys.add(Some(resource(1)), None);
+        keys.add(None, Some(spec(2)));
+
+        assert_eq!(keys.resources, vec![resource(1)], "inventoried flows are keyed by resource only");
+        assert_eq!(keys.specifications, vec![spec(2)]);
+    }
+
+    #[test]
+    fn test_is_planned_flow_of() {
+        let (p, other) = (process(1), process(2));
+
+        assert!(is_planned_flow_of(&p, FlowDirection::Previous, &Some(p.clone()), &None));
+        assert!(!is_planned_flow_of(&p, FlowDirection::Next, &Some(p.clone()), &None));
+        assert!(is_planned_flow_of(&p, FlowDirection::Next, &None, &Some(p.clone())));
+        assert!(!is_planned_flow_of(&p, FlowDirection::Next, &None, &Some(other)), "flows since moved to another process are ignored");
+    }
+}
@@ -8,7 +8,7 @@ use serde_maybe_undefined::{
     default_false,
 };
 pub use vf_attributes_hdk::{
-    RevisionHash,
+    RevisionHash, DeleteRequest,
     ProcessAddress,
     Timestamp,
     ExternalURL,
@@ -74,19 +74,7 @@ pub struct Response {
     pub intended_outputs: Vec<IntentAddress>,
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
-    pub next_processes: Vec<ProcessAddress>,
-    #[serde(default)]
-    #[serde(skip_serializing_if = "Vec::is_empty")]
-    pub previous_processes: Vec<ProcessAddress>,
-    #[serde(default)]
-    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub working_agents: Vec<AgentAddress>,
-    #[serde(default)]
-    #[serde(skip_serializing_if = "Vec::is_empty")]
-    pub trace: Vec<EventAddress>,
-    #[serde(default)]
-    #[serde(skip_serializing_if = "Vec::is_empty")]
-    pub track: Vec<EventAddress>,
 }
 
 /// I/O struct to describe what is returned outside the gateway
@@ -182,3 +170,23 @@ pub struct QueryParams {
     pub intended_outputs: Option<IntentAddress>,
     pub working_agents: Option<AgentAddress>,
 }
+
+//---------------- FLOW QUERIES ----------------
+
+/// Direction in which to follow resource flows between processes
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum FlowDirection {
+    /// processes which consume the outputs of a process
+    Next,
+    /// processes which produce the inputs to a process
+    Previous,
+}
+
+/// Request for the processes adjacent to a `Process` in a given direction
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AdjacentProcessesRequest {
+    pub address: ProcessAddress,
+    pub direction: FlowDirection,
+}
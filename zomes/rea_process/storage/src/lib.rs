@@ -9,6 +9,7 @@
 use hdk::prelude::*;
 
 use hdk_records::{
+    config::ZomeConfig,
     MaybeUndefined,
     generate_record_entry,
     record_interface::Updateable,
@@ -26,17 +27,14 @@ use hc_zome_rea_process_rpc::{ CreateRequest, UpdateRequest };
 
 //--------------- ZOME CONFIGURATION ATTRIBUTES ----------------
 
-// :TODO: remove this, replace with reference to appropriate namespacing of zome config
-#[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
-pub struct DnaConfigSlice {
-    pub process: ProcessZomeConfig,
-}
-
 #[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct ProcessZomeConfig {
     pub index_zome: String,
 }
 
+impl ZomeConfig for ProcessZomeConfig {}
+
 //---------------- RECORD INTERNALS & VALIDATION ----------------
 
 #[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
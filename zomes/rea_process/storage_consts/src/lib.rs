@@ -12,6 +12,15 @@ pub const PROCESS_COMMITMENT_INPUTS_LINK_TAG: &str = "committed_inputs";
 pub const PROCESS_COMMITMENT_OUTPUTS_LINK_TAG: &str = "committed_outputs";
 pub const PROCESS_INTENT_INPUTS_LINK_TAG: &str = "intended_inputs";
 pub const PROCESS_INTENT_OUTPUTS_LINK_TAG: &str = "intended_outputs";
+pub const PROCESS_PLANNED_WITHIN_LINK_TAG: &str = "planned_within";
+
+// planned flow index, maintained in the planning DNA
+pub const PROCESS_PLANNED_INPUT_RESOURCES_LINK_TAG: &str = "planned_input_resources";
+pub const PROCESS_PLANNED_OUTPUT_RESOURCES_LINK_TAG: &str = "planned_output_resources";
+pub const PROCESS_PLANNED_INPUT_SPECIFICATIONS_LINK_TAG: &str = "planned_input_specifications";
+pub const PROCESS_PLANNED_OUTPUT_SPECIFICATIONS_LINK_TAG: &str = "planned_output_specifications";
+pub const PLANNED_FLOW_CONSUMERS_LINK_TAG: &str = "planned_consumers";
+pub const PLANNED_FLOW_PRODUCERS_LINK_TAG: &str = "planned_producers";
 
 pub const PROCESS_EVENT_INPUTS_READ_API_METHOD: &str = "_internal_read_process_inputs";
 pub const PROCESS_EVENT_OUTPUTS_READ_API_METHOD: &str = "_internal_read_process_outputs";
@@ -19,3 +28,8 @@ pub const PROCESS_COMMITMENT_INPUTS_READ_API_METHOD: &str = "_internal_read_proc
 pub const PROCESS_COMMITMENT_OUTPUTS_READ_API_METHOD: &str = "_internal_read_process_committed_outputs";
 pub const PROCESS_INTENT_INPUTS_READ_API_METHOD: &str = "_internal_read_process_intended_inputs";
 pub const PROCESS_INTENT_OUTPUTS_READ_API_METHOD: &str = "_internal_read_process_intended_outputs";
+
+pub const PROCESS_PLANNED_WITHIN_INDEXING_API_METHOD: &str = "_internal_reindex_plans";
+pub const PLAN_PROCESS_INDEXING_API_METHOD: &str = "index_plan_processes";
+
+pub const PLANNED_ADJACENT_PROCESSES_READ_REMOTE_PERMISSION: &str = "read_adjacent_planned_processes";
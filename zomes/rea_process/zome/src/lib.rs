@@ -8,10 +8,18 @@
  * @package Holo-REA
  */
 use hdk::prelude::*;
+use hdk_records::config::init_with_config;
 
 use hc_zome_rea_process_storage_consts::*;
 use hc_zome_rea_process_lib::*;
 use hc_zome_rea_process_rpc::*;
+use hc_zome_rea_process_storage::ProcessZomeConfig;
+use hc_zome_rea_economic_event_rpc::ProductionFlowItem;
+
+#[hdk_extern]
+fn init(_: ()) -> ExternResult<InitCallbackResult> {
+    Ok(init_with_config::<ProcessZomeConfig>())
+}
 
 #[hdk_extern]
 fn entry_defs(_: ()) -> ExternResult<EntryDefsCallbackResult> {
@@ -47,6 +55,16 @@ fn get_process(ReadParams { address }: ReadParams) -> ExternResult<ResponseData>
     Ok(handle_get_process(PROCESS_ENTRY_TYPE, address)?)
 }
 
+#[hdk_extern]
+fn get_next_processes(ReadParams { address }: ReadParams) -> ExternResult<Vec<ProcessAddress>> {
+    Ok(handle_get_adjacent_processes(&address, FlowDirection::Next)?)
+}
+
+#[hdk_extern]
+fn get_previous_processes(ReadParams { address }: ReadParams) -> ExternResult<Vec<ProcessAddress>> {
+    Ok(handle_get_adjacent_processes(&address, FlowDirection::Previous)?)
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct UpdateParams {
     pub process: UpdateRequest,
@@ -57,12 +75,25 @@ fn update_process(UpdateParams { process }: UpdateParams) -> ExternResult<Respon
     Ok(handle_update_process(PROCESS_ENTRY_TYPE, process)?)
 }
 
+#[hdk_extern]
+fn delete_process(DeleteRequest { address, force }: DeleteRequest) -> ExternResult<bool> {
+    Ok(handle_delete_process(PROCESS_ENTRY_TYPE, address, force)?)
+}
+
 #[derive(Debug, Serialize, Deserialize)]
-struct DeleteParams {
-    pub address: RevisionHash,
+struct TraversalParams {
+    pub address: ProcessAddress,
+    pub max_depth: Option<usize>,
+    /// maximum number of results to return
+    pub limit: Option<usize>,
+}
+
+#[hdk_extern]
+fn trace(TraversalParams { address, max_depth, limit }: TraversalParams) -> ExternResult<Vec<ProductionFlowItem>> {
+    Ok(hc_zome_rea_economic_event_lib::trace(ProductionFlowItem::Process(address), max_depth, limit)?)
 }
 
 #[hdk_extern]
-fn delete_process(DeleteParams { address }: DeleteParams) -> ExternResult<bool> {
-    Ok(handle_delete_process(PROCESS_ENTRY_TYPE, address)?)
+fn track(TraversalParams { address, max_depth, limit }: TraversalParams) -> ExternResult<Vec<ProductionFlowItem>> {
+    Ok(hc_zome_rea_economic_event_lib::track(ProductionFlowItem::Process(address), max_depth, limit)?)
 }
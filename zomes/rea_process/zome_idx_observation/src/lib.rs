@@ -6,6 +6,7 @@
  */
 use hdk::prelude::*;
 use hdk_records::{
+    config::init_with_config,
     index_retrieval::{ ByAddress, IndexingZomeConfig },
     remote_indexes::{
         RemoteEntryLinkRequest,
@@ -23,17 +24,17 @@ use hc_zome_rea_process_storage_consts::*;
 use hc_zome_rea_economic_event_storage_consts::{ EVENT_ENTRY_TYPE, EVENT_INPUT_OF_LINK_TAG, EVENT_OUTPUT_OF_LINK_TAG };
 use hc_zome_rea_commitment_storage_consts::{ COMMITMENT_ENTRY_TYPE, COMMITMENT_INPUT_OF_LINK_TAG, COMMITMENT_OUTPUT_OF_LINK_TAG };
 use hc_zome_rea_intent_storage_consts::{ INTENT_ENTRY_TYPE, INTENT_INPUT_OF_LINK_TAG, INTENT_OUTPUT_OF_LINK_TAG };
+use hc_zome_rea_plan_storage_consts::{ PLAN_ENTRY_TYPE, PLAN_PROCESSES_LINK_TAG };
 
 entry_defs![Path::entry_def()];
 
-// :TODO: obviate this with zome-specific configs
-#[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
-pub struct DnaConfigSlice {
-    pub process_index: IndexingZomeConfig,
+#[hdk_extern]
+fn init(_: ()) -> ExternResult<InitCallbackResult> {
+    Ok(init_with_config::<IndexingZomeConfig>())
 }
 
-fn read_index_target_zome(conf: DnaConfigSlice) -> Option<String> {
-    Some(conf.process_index.record_storage_zome)
+fn read_index_target_zome(conf: IndexingZomeConfig) -> Option<String> {
+    Some(conf.record_storage_zome)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -161,3 +162,16 @@ fn index_output_intents(indexes: RemoteEntryLinkRequest<IntentAddress, ProcessAd
         &INTENT_OUTPUT_OF_LINK_TAG, &PROCESS_INTENT_OUTPUTS_LINK_TAG,
     )?)
 }
+
+#[hdk_extern]
+fn _internal_reindex_plans(indexes: RemoteEntryLinkRequest<PlanAddress, ProcessAddress>) -> ExternResult<RemoteEntryLinkResponse> {
+    let RemoteEntryLinkRequest { remote_entry, target_entries, removed_entries } = indexes;
+
+    Ok(sync_remote_index(
+        &PLAN_ENTRY_TYPE, &remote_entry,
+        &PROCESS_ENTRY_TYPE,
+        target_entries.as_slice(),
+        removed_entries.as_slice(),
+        PLAN_PROCESSES_LINK_TAG, PROCESS_PLANNED_WITHIN_LINK_TAG,
+    )?)
+}
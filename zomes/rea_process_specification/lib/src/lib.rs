@@ -12,10 +12,13 @@ use hdk_records::{
         create_record,
         read_record_entry,
         update_record,
+        read_record_entry_by_header,
         delete_record,
     },
+    integrity::ensure_deletable,
 };
 
+pub use hc_zome_rea_process_specification_storage_consts::*;
 use hc_zome_rea_process_specification_storage::*;
 use hc_zome_rea_process_specification_rpc::*;
 
@@ -42,8 +45,17 @@ pub fn handle_update_process_specification<S>(entry_def_id: S, process_specifica
     Ok(construct_response(&base_address, &revision_id, &new_entry))
 }
 
-pub fn handle_delete_process_specification(revision_id: RevisionHash) -> RecordAPIResult<bool>
+/// Indexes of other records referencing a `ProcessSpecification`, which prevent its deletion
+const INBOUND_LINK_TAGS: &[&str] = &[
+    PROCESS_SPECIFICATION_RECIPE_PROCESSES_LINK_TAG,
+];
+
+pub fn handle_delete_process_specification(revision_id: RevisionHash, force: bool) -> RecordAPIResult<bool>
 {
+    let (base_address, _entry): (ProcessSpecificationAddress, EntryData) = read_record_entry_by_header::<EntryData, EntryStorage, _>(&revision_id)?;
+
+    ensure_deletable(&PROCESS_SPECIFICATION_ENTRY_TYPE, &base_address, INBOUND_LINK_TAGS, force)?;
+
     delete_record::<EntryStorage, _>(&revision_id)
 }
 
@@ -8,3 +8,4 @@
 pub const PROCESS_SPECIFICATION_ENTRY_TYPE: &str = "vf_process_specification";
 pub const PROCESS_SPECIFICATION_BASE_ENTRY_TYPE: &str = "vf_process_specification_baseurl";
 pub const PROCESS_SPECIFICATION_INITIAL_ENTRY_LINK_TYPE: &str = "vf_process_specification_entry";
+pub const PROCESS_SPECIFICATION_RECIPE_PROCESSES_LINK_TAG: &str = "recipe_processes";
@@ -10,14 +10,17 @@ use hdk::prelude::*;
 
 use hdk_records::{
     RecordAPIResult, DataIntegrityError,
+    config::ZomeConfig,
     foreign_indexes::read_foreign_index,
     local_indexes::query_index,
     records::{
         create_record,
         delete_record,
         read_record_entry,
+        read_record_entry_by_header,
         update_record,
     },
+    integrity::ensure_deletable,
 };
 
 use hc_zome_rea_proposal_rpc::*;
@@ -48,7 +51,17 @@ pub fn handle_update_proposal<S>(entry_def_id: S, proposal: UpdateRequest) -> Re
     Ok(construct_response(&base_address, &revision_id, &new_entry, get_link_fields(&base_address)?))
 }
 
-pub fn handle_delete_proposal(address: RevisionHash) -> RecordAPIResult<bool> {
+/// Indexes of other records referencing a `Proposal`, which prevent its deletion
+const INBOUND_LINK_TAGS: &[&str] = &[
+    PROPOSAL_PUBLISHES_LINK_TAG,
+    PROPOSAL_PUBLISHED_TO_LINK_TAG,
+];
+
+pub fn handle_delete_proposal(address: RevisionHash, force: bool) -> RecordAPIResult<bool> {
+    let (base_address, _entry): (ProposalAddress, EntryData) = read_record_entry_by_header::<EntryData, EntryStorage, _>(&address)?;
+
+    ensure_deletable(&PROPOSAL_ENTRY_TYPE, &base_address, INBOUND_LINK_TAGS, force)?;
+
     delete_record::<EntryStorage,_>(&address)
 }
 
@@ -60,8 +73,7 @@ pub fn generate_query_handler<S, C, F>(
     proposed_to_entry_def_id: S,
 ) -> impl FnOnce(&QueryParams) -> RecordAPIResult<Vec<ResponseData>>
     where S: AsRef<str>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: Fn(C) -> Option<String>,
 {
     move |params| {
@@ -127,8 +139,8 @@ fn construct_response<'a>(
 }
 
 /// Properties accessor for zome config
-fn read_foreign_index_zome(conf: DnaConfigSlice) -> Option<String> {
-    Some(conf.proposal.index_zome)
+fn read_foreign_index_zome(conf: ProposalZomeConfig) -> Option<String> {
+    Some(conf.index_zome)
 }
 
 fn get_link_fields<'a>(
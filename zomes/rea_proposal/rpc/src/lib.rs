@@ -9,7 +9,7 @@
 use holochain_serialized_bytes::prelude::*;
 use serde_maybe_undefined::MaybeUndefined;
 pub use vf_attributes_hdk::{
-    RevisionHash, ByAddress, ByHeader,
+    RevisionHash, ByAddress, ByHeader, DeleteRequest,
     ProposalAddress, ProposedIntentAddress, ProposedToAddress, Timestamp,
 };
 
@@ -9,6 +9,7 @@
 use hdk::prelude::*;
 
 use hdk_records::{
+    config::ZomeConfig,
     record_interface::Updateable, MaybeUndefined,
     generate_record_entry,
 };
@@ -19,17 +20,14 @@ use hc_zome_rea_proposal_rpc::{CreateRequest, UpdateRequest};
 
 //--------------- ZOME CONFIGURATION ATTRIBUTES ----------------
 
-// :TODO: remove this, replace with reference to appropriate namespacing of zome config
-#[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
-pub struct DnaConfigSlice {
-    pub proposal: ProposalZomeConfig,
-}
-
 #[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct ProposalZomeConfig {
     pub index_zome: String,
 }
 
+impl ZomeConfig for ProposalZomeConfig {}
+
 //---------------- RECORD INTERNALS & VALIDATION ----------------
 
 #[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
@@ -8,10 +8,17 @@
  * @package Holo-REA
  */
 use hdk::prelude::*;
+use hdk_records::config::init_with_config;
 
 use hc_zome_rea_proposal_lib::*;
 use hc_zome_rea_proposal_rpc::*;
 use hc_zome_rea_proposal_storage_consts::*;
+use hc_zome_rea_proposal_storage::ProposalZomeConfig;
+
+#[hdk_extern]
+fn init(_: ()) -> ExternResult<InitCallbackResult> {
+    Ok(init_with_config::<ProposalZomeConfig>())
+}
 
 #[hdk_extern]
 fn entry_defs(_: ()) -> ExternResult<EntryDefsCallbackResult> {
@@ -43,6 +50,6 @@ fn update_proposal(UpdateParams { proposal }: UpdateParams) -> ExternResult<Resp
 }
 
 #[hdk_extern]
-fn delete_proposal(ByHeader { address }: ByHeader) -> ExternResult<bool> {
-    Ok(handle_delete_proposal(address)?)
+fn delete_proposal(DeleteRequest { address, force }: DeleteRequest) -> ExternResult<bool> {
+    Ok(handle_delete_proposal(address, force)?)
 }
@@ -10,6 +10,7 @@ use hdk::prelude::*;
 
 use hdk_records::{
     RecordAPIResult, DataIntegrityError,
+    config::ZomeConfig,
     records::{
         create_record,
         delete_record,
@@ -106,8 +107,7 @@ pub fn generate_query_handler<S, C, F>(
     proposal_entry_def_id: S,
 ) -> impl FnOnce(&QueryParams) -> RecordAPIResult<Vec<ResponseData>>
     where S: AsRef<str>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: Fn(C) -> Option<String>,
 {
     move |params| {
@@ -149,11 +149,11 @@ fn construct_response<'a>(address: &ProposedIntentAddress, revision_id: &Revisio
 }
 
 /// Properties accessor for zome config.
-fn read_foreign_index_zome(conf: DnaConfigSlice) -> Option<String> {
-    Some(conf.proposed_intent.index_zome)
+fn read_foreign_index_zome(conf: ProposedIntentZomeConfig) -> Option<String> {
+    Some(conf.index_zome)
 }
 
 /// Properties accessor for zome config.
-fn read_foreign_proposal_index_zome(conf: DnaConfigSlice) -> Option<String> {
-    Some(conf.proposed_intent.proposal_index_zome)
+fn read_foreign_proposal_index_zome(conf: ProposedIntentZomeConfig) -> Option<String> {
+    Some(conf.proposal_index_zome)
 }
@@ -8,10 +8,17 @@
 * @package Holo-REA
 */
 use hdk::prelude::*;
+use hdk_records::config::init_with_config;
 
 use hc_zome_rea_proposed_intent_lib::*;
 use hc_zome_rea_proposed_intent_rpc::*;
 use hc_zome_rea_proposed_intent_storage_consts::*;
+use hc_zome_rea_proposed_intent_storage::ProposedIntentZomeConfig;
+
+#[hdk_extern]
+fn init(_: ()) -> ExternResult<InitCallbackResult> {
+    Ok(init_with_config::<ProposedIntentZomeConfig>())
+}
 
 #[hdk_extern]
 fn entry_defs(_: ()) -> ExternResult<EntryDefsCallbackResult> {
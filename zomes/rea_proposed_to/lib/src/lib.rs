@@ -10,6 +10,7 @@ use hdk::prelude::*;
 
 use hdk_records::{
     RecordAPIResult, DataIntegrityError,
+    config::ZomeConfig,
     records::{
         create_record,
         delete_record,
@@ -78,8 +79,7 @@ pub fn generate_query_handler<S, C, F>(
     proposal_entry_def_id: S,
 ) -> impl FnOnce(&QueryParams) -> RecordAPIResult<Vec<ResponseData>>
     where S: AsRef<str>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: Fn(C) -> Option<String>,
 {
     move |params| {
@@ -117,11 +117,11 @@ fn construct_response<'a>(address: &ProposedToAddress, revision_id: &RevisionHas
 }
 
 /// Properties accessor for zome config.
-fn read_foreign_index_zome(conf: DnaConfigSlice) -> Option<String> {
-    Some(conf.proposed_to.index_zome)
+fn read_foreign_index_zome(conf: ProposedToZomeConfig) -> Option<String> {
+    Some(conf.index_zome)
 }
 
 /// Properties accessor for zome config.
-fn read_foreign_proposal_index_zome(conf: DnaConfigSlice) -> Option<String> {
-    Some(conf.proposed_to.proposal_index_zome)
+fn read_foreign_proposal_index_zome(conf: ProposedToZomeConfig) -> Option<String> {
+    Some(conf.proposal_index_zome)
 }
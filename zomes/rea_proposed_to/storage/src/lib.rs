@@ -9,6 +9,7 @@
 use hdk::prelude::*;
 
 use hdk_records::{
+    config::ZomeConfig,
     generate_record_entry,
 };
 
@@ -18,18 +19,15 @@ use hc_zome_rea_proposed_to_rpc::CreateRequest;
 
 //--------------- ZOME CONFIGURATION ATTRIBUTES ----------------
 
-// :TODO: remove this, replace with reference to appropriate namespacing of zome config
-#[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
-pub struct DnaConfigSlice {
-    pub proposed_to: ProposedToZomeConfig,
-}
-
 #[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct ProposedToZomeConfig {
     pub proposal_index_zome: String,
     pub index_zome: String,
 }
 
+impl ZomeConfig for ProposedToZomeConfig {}
+
 //---------------- RECORD INTERNALS & VALIDATION ----------------
 
 #[derive(Serialize, Deserialize, SerializedBytes, Debug, PartialEq, Clone)]
@@ -0,0 +1,356 @@
+/**
+ * Recipe expansion algorithm for generating plans from `RecipeFlow`s
+ *
+ * Starting from a demand for some quantity of a `RecipeResource`, finds the `RecipeProcess`
+ * which outputs it and scales that process' flows to match. Any consumed inputs become
+ * demands in turn, due at the time the consuming process must begin. Processes are
+ * back-scheduled from the due date using each `RecipeProcess.has_duration`.
+ *
+ * @package Holo-REA
+ */
+use chrono::Duration as TimeDelta;
+use hdk_records::{
+    RecordAPIResult, DataIntegrityError,
+    local_indexes::read_index,
+    records::read_record_entry,
+};
+use vf_measurement::{ multiply, Duration };
+
+use hc_zome_rea_recipe_flow_storage_consts::RECIPE_FLOW_ENTRY_TYPE;
+use hc_zome_rea_recipe_flow_storage::{ EntryData, EntryStorage };
+use hc_zome_rea_recipe_flow_rpc::*;
+use hc_zome_rea_recipe_process_storage_consts::{
+    RECIPE_PROCESS_ENTRY_TYPE,
+    RECIPE_PROCESS_INPUTS_LINK_TAG, RECIPE_PROCESS_OUTPUTS_LINK_TAG,
+};
+use hc_zome_rea_recipe_process_storage::{
+    EntryData as RecipeProcessData,
+    EntryStorage as RecipeProcessStorage,
+};
+use hc_zome_rea_recipe_resource_storage_consts::{
+    RECIPE_RESOURCE_ENTRY_TYPE,
+    RECIPE_RESOURCE_FLOWS_LINK_TAG,
+};
+use hc_zome_rea_recipe_resource_storage::{
+    EntryData as RecipeResourceData,
+    EntryStorage as RecipeResourceStorage,
+};
+use hc_zome_rea_resource_specification_storage_consts::{
+    ECONOMIC_RESOURCE_SPECIFICATION_ENTRY_TYPE,
+    RESOURCE_SPECIFICATION_RECIPE_RESOURCES_LINK_TAG,
+};
+
+/// Number of nested recipes which may be expanded before the recipe is assumed to be cyclic
+pub const MAX_RECIPE_DEPTH: usize = 32;
+
+/// Outstanding requirement for some quantity of a `RecipeResource` by a given time
+struct Demand {
+    recipe_resource: RecipeResourceAddress,
+    resource_quantity: QuantityValue,
+    due: DateTime<FixedOffset>,
+    depth: usize,
+}
+
+/// Expand the recipe producing `recipe_resource` into the set of processes needed to
+/// deliver `resource_quantity` of it by `due`.
+///
+/// Resources which no `RecipeProcess` outputs are treated as raw inputs and not expanded further.
+///
+pub fn expand_recipe(
+    recipe_resource: &RecipeResourceAddress,
+    resource_quantity: &QuantityValue,
+    due: &DateTime<FixedOffset>,
+) -> RecordAPIResult<RecipeExpansion> {
+    expand_demands(recipe_resource, resource_quantity, due, plan_process)
+}
+
+/// Breadth-first expansion of a demand and all the demands for consumed inputs it generates,
+/// planning a process to satisfy each via `plan`.
+///
+fn expand_demands<F>(
+    recipe_resource: &RecipeResourceAddress,
+    resource_quantity: &QuantityValue,
+    due: &DateTime<FixedOffset>,
+    plan: F,
+) -> RecordAPIResult<RecipeExpansion>
+    where F: Fn(&RecipeResourceAddress, &QuantityValue, &DateTime<FixedOffset>) -> RecordAPIResult<Option<(PlannedProcess, Vec<(RecipeResourceAddress, QuantityValue)>)>>
+{
+    let mut processes = vec![];
+    let mut demands = vec![Demand {
+        recipe_resource: recipe_resource.to_owned(),
+        resource_quantity: resource_quantity.to_owned(),
+        due: due.to_owned(),
+        depth: 0,
+    }];
+    let mut next = 0;
+
+    while next < demands.len() {
+        let depth = demands[next].depth;
+        let planned = plan(&demands[next].recipe_resource, &demands[next].resource_quantity, &demands[next].due)?;
+        next += 1;
+
+        let (process, consumed) = match planned {
+            Some(p) => p,
+            None => continue,
+        };
+        if depth >= MAX_RECIPE_DEPTH {
+            return Err(DataIntegrityError::MaxDepthExceeded(MAX_RECIPE_DEPTH));
+        }
+
+        // consumed inputs must themselves be produced in time for this process to begin
+        for (recipe_resource, resource_quantity) in consumed {
+            demands.push(Demand {
+                recipe_resource,
+                resource_quantity,
+                due: process.has_beginning,
+                depth: depth + 1,
+            });
+        }
+        processes.push(process);
+    }
+
+    Ok(RecipeExpansion { processes })
+}
+
+/// Plan a single run of the recipe producing some `ResourceSpecification`, without expanding
+/// any of its inputs. Used by planning algorithms which need to inspect each level of a recipe
+/// before deciding whether to expand the next.
+///
+/// Returns `None` if no `RecipeResource` conforming to the specification has a recipe.
+///
+pub fn expand_specification_recipe(
+    resource_specification: &ResourceSpecificationAddress,
+    resource_quantity: &QuantityValue,
+    due: &DateTime<FixedOffset>,
+) -> RecordAPIResult<Option<PlannedProcess>> {
+    let recipe_resources: Vec<RecipeResourceAddress> = read_index(&ECONOMIC_RESOURCE_SPECIFICATION_ENTRY_TYPE, resource_specification, &RESOURCE_SPECIFICATION_RECIPE_RESOURCES_LINK_TAG)?;
+
+    for recipe_resource in recipe_resources.iter() {
+        if let Some((process, _)) = plan_process(recipe_resource, resource_quantity, due)? {
+            return Ok(Some(process));
+        }
+    }
+    Ok(None)
+}
+
+/// Scale and schedule the `RecipeProcess` producing `recipe_resource` so that it delivers
+/// `resource_quantity` by `due`. Also returns the quantities of each `RecipeResource` the
+/// process consumes.
+///
+fn plan_process(
+    recipe_resource: &RecipeResourceAddress,
+    resource_quantity: &QuantityValue,
+    due: &DateTime<FixedOffset>,
+) -> RecordAPIResult<Option<(PlannedProcess, Vec<(RecipeResourceAddress, QuantityValue)>)>> {
+    let (producing_flow_address, producing_flow, recipe_process) = match find_producing_flow(recipe_resource)? {
+        Some(found) => found,
+        None => return Ok(None),
+    };
+    let scale = scale_factor(resource_quantity, &producing_flow.resource_quantity)?;
+
+    let (_, _, process): (_, RecipeProcessAddress, RecipeProcessData) = read_record_entry::<RecipeProcessData, RecipeProcessStorage, _,_>(&RECIPE_PROCESS_ENTRY_TYPE, recipe_process.as_ref())?;
+    let has_end = due.to_owned();
+    let has_beginning = back_schedule(&has_end, &process.has_duration);
+
+    let outputs = plan_flows(&recipe_process, &RECIPE_PROCESS_OUTPUTS_LINK_TAG, scale, &has_end)?;
+    let inputs = plan_flows(&recipe_process, &RECIPE_PROCESS_INPUTS_LINK_TAG, scale, &has_beginning)?;
+
+    let consumed = inputs.iter()
+        .filter(|(flow, _)| flow.action.as_ref() == "consume")
+        .filter_map(|(flow, planned)| {
+            planned.resource_quantity.to_owned().map(|q| (flow.recipe_flow_resource.to_owned(), q))
+        })
+        .collect();
+
+    Ok(Some((PlannedProcess {
+        recipe_process,
+        name: process.name,
+        process_conforms_to: process.process_conforms_to,
+        has_beginning,
+        has_end,
+        note: process.note,
+        inputs: inputs.into_iter().map(|(_, planned)| planned).collect(),
+        outputs: outputs.into_iter().map(|(_, planned)| planned).collect(),
+        produces: producing_flow_address,
+    }, consumed)))
+}
+
+/// Time at which a process taking `duration` must begin in order to end at `has_end`.
+/// Processes without a duration are taken to be instantaneous.
+///
+fn back_schedule(has_end: &DateTime<FixedOffset>, duration: &Option<Duration>) -> DateTime<FixedOffset> {
+    match duration {
+        Some(duration) => *has_end - TimeDelta::seconds(duration.as_seconds()),
+        None => *has_end,
+    }
+}
+
+/// Locate the first `RecipeFlow` outputting the given `RecipeResource` from a `RecipeProcess`
+fn find_producing_flow(recipe_resource: &RecipeResourceAddress) -> RecordAPIResult<Option<(RecipeFlowAddress, EntryData, RecipeProcessAddress)>> {
+    let flows: Vec<RecipeFlowAddress> = read_index(&RECIPE_RESOURCE_ENTRY_TYPE, recipe_resource, &RECIPE_RESOURCE_FLOWS_LINK_TAG)?;
+
+    for flow_address in flows.iter() {
+        let flow = read_flow(flow_address)?;
+        if let Some(recipe_process) = flow.recipe_output_of.to_owned() {
+            return Ok(Some((flow_address.to_owned(), flow, recipe_process)));
+        }
+    }
+    Ok(None)
+}
+
+/// Scale and schedule all flows of a `RecipeProcess` linked via `link_tag`
+fn plan_flows(
+    recipe_process: &RecipeProcessAddress,
+    link_tag: &str,
+    scale: f64,
+    due: &DateTime<FixedOffset>,
+) -> RecordAPIResult<Vec<(EntryData, PlannedFlow)>> {
+    let flows: Vec<RecipeFlowAddress> = read_index(&RECIPE_PROCESS_ENTRY_TYPE, recipe_process, link_tag)?;
+
+    flows.iter()
+        .map(|flow_address| {
+            let flow = read_flow(flow_address)?;
+            let (_, _, resource): (_, RecipeResourceAddress, RecipeResourceData) = read_record_entry::<RecipeResourceData, RecipeResourceStorage, _,_>(&RECIPE_RESOURCE_ENTRY_TYPE, flow.recipe_flow_resource.as_ref())?;
+            let planned = PlannedFlow {
+                recipe_flow: flow_address.to_owned(),
+                action: flow.action.to_owned(),
+                resource_conforms_to: resource.resource_conforms_to,
+                resource_quantity: flow.resource_quantity.to_owned().map(|q| multiply(q, scale)),
+                effort_quantity: flow.effort_quantity.to_owned().map(|q| multiply(q, scale)),
+                due: due.to_owned(),
+            };
+            Ok((flow, planned))
+        })
+        .collect()
+}
+
+fn read_flow(address: &RecipeFlowAddress) -> RecordAPIResult<EntryData> {
+    let (_, _, flow): (_, RecipeFlowAddress, EntryData) = read_record_entry::<EntryData, EntryStorage, _,_>(&RECIPE_FLOW_ENTRY_TYPE, address.as_ref())?;
+    Ok(flow)
+}
+
+/// Ratio of the demanded quantity to the quantity a single run of the recipe produces.
+/// Recipes without an output quantity are taken to produce one unit per run.
+///
+/// :TODO: convert between units once `QuantityValue` supports it
+///
+fn scale_factor(demanded: &QuantityValue, produced: &Option<QuantityValue>) -> RecordAPIResult<f64> {
+    match produced {
+        Some(q) if q.get_unit() != demanded.get_unit() => Err(DataIntegrityError::IncompatibleUnits),
+        Some(q) if q.get_numerical_value() != 0.0 => Ok(demanded.get_numerical_value() / q.get_numerical_value()),
+        _ => Ok(demanded.get_numerical_value()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use hdk_records::{ DnaHash, EntryHash };
+    use vf_measurement::TimeUnit;
+
+    fn dna() -> DnaHash {
+        DnaHash::from_raw_36(vec![0xdb; 36])
+    }
+
+    fn recipe_resource(byte: u8) -> RecipeResourceAddress {
+        RecipeResourceAddress(dna(), EntryHash::from_raw_36(vec![byte; 36]))
+    }
+
+    fn time(rfc3339: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap()
+    }
+
+    fn qty(value: f64) -> QuantityValue {
+        QuantityValue::new(value, None)
+    }
+
+    fn planned(byte: u8, has_beginning: DateTime<FixedOffset>, has_end: DateTime<FixedOffset>) -> PlannedProcess {
+        PlannedProcess {
+            recipe_process: RecipeProcessAddress(dna(), EntryHash::from_raw_36(vec![byte; 36])),
+            name: format!("process {}", byte),
+            process_conforms_to: None,
+            has_beginning,
+            has_end,
+            note: None,
+            inputs: vec![],
+            outputs: vec![],
+            produces: RecipeFlowAddress(dna(), EntryHash::from_raw_36(vec![byte; 36])),
+        }
+    }
+
+    #[test]
+    fn test_scale_factor() {
+        assert_eq!(scale_factor(&qty(6.0), &Some(qty(2.0))).unwrap(), 3.0);
+        assert_eq!(scale_factor(&qty(6.0), &None).unwrap(), 6.0, "recipes without an output quantity produce one unit per run");
+        assert_eq!(scale_factor(&qty(6.0), &Some(qty(0.0))).unwrap(), 6.0);
+    }
+
+    #[test]
+    fn test_scale_factor_requires_matching_units() {
+        let kg = QuantityValue::new(1.0, Some(vf_attributes_hdk::UnitId(dna(), "kg".to_string())));
+        assert!(matches!(scale_factor(&qty(6.0), &Some(kg)), Err(DataIntegrityError::IncompatibleUnits)));
+    }
+
+    #[test]
+    fn test_back_schedule() {
+        let due = time("2021-06-01T12:00:00+00:00");
+        assert_eq!(back_schedule(&due, &Some(Duration::new(2.0, TimeUnit::Hour))), time("2021-06-01T10:00:00+00:00"));
+        assert_eq!(back_schedule(&due, &Some(Duration::new(1.5, TimeUnit::Day))), time("2021-05-31T00:00:00+00:00"));
+        assert_eq!(back_schedule(&due, &None), due, "processes without a duration are instantaneous");
+    }
+
+    /// Fake recipe: resource 1 takes an hour to make from twice as much of resource 2,
+    /// which takes 30 minutes to make from the same amount of resource 3, which is a raw input.
+    fn plan_chain(requested: &RefCell<Vec<(RecipeResourceAddress, f64, DateTime<FixedOffset>)>>)
+        -> impl Fn(&RecipeResourceAddress, &QuantityValue, &DateTime<FixedOffset>) -> RecordAPIResult<Option<(PlannedProcess, Vec<(RecipeResourceAddress, QuantityValue)>)>> + '_
+    {
+        move |resource, quantity, due| {
+            requested.borrow_mut().push((resource.to_owned(), quantity.get_numerical_value(), due.to_owned()));
+            let q = quantity.get_numerical_value();
+            Ok(if *resource == recipe_resource(1) {
+                let begins = back_schedule(due, &Some(Duration::new(1.0, TimeUnit::Hour)));
+                Some((planned(1, begins, *due), vec![(recipe_resource(2), qty(q * 2.0))]))
+            } else if *resource == recipe_resource(2) {
+                let begins = back_schedule(due, &Some(Duration::new(30.0, TimeUnit::Minute)));
+                Some((planned(2, begins, *due), vec![(recipe_resource(3), qty(q))]))
+            } else {
+                None
+            })
+        }
+    }
+
+    #[test]
+    fn test_expansion_back_schedules_inputs() {
+        let requested = RefCell::new(vec![]);
+        let expansion = expand_demands(&recipe_resource(1), &qty(5.0), &time("2021-06-01T12:00:00+00:00"), plan_chain(&requested)).unwrap();
+
+        let schedule: Vec<(DateTime<FixedOffset>, DateTime<FixedOffset>)> = expansion.processes.iter().map(|p| (p.has_beginning, p.has_end)).collect();
+        assert_eq!(schedule, vec![
+            (time("2021-06-01T11:00:00+00:00"), time("2021-06-01T12:00:00+00:00")),
+            (time("2021-06-01T10:30:00+00:00"), time("2021-06-01T11:00:00+00:00")),
+        ], "inputs are due when the consuming process begins");
+
+        assert_eq!(requested.into_inner(), vec![
+            (recipe_resource(1), 5.0, time("2021-06-01T12:00:00+00:00")),
+            (recipe_resource(2), 10.0, time("2021-06-01T11:00:00+00:00")),
+            (recipe_resource(3), 10.0, time("2021-06-01T10:30:00+00:00")),
+        ]);
+    }
+
+    #[test]
+    fn test_expansion_of_raw_resource() {
+        let requested = RefCell::new(vec![]);
+        let expansion = expand_demands(&recipe_resource(3), &qty(1.0), &time("2021-06-01T12:00:00+00:00"), plan_chain(&requested)).unwrap();
+        assert!(expansion.processes.is_empty());
+    }
+
+    #[test]
+    fn test_cyclic_recipe_is_rejected() {
+        let cyclic = |resource: &RecipeResourceAddress, quantity: &QuantityValue, due: &DateTime<FixedOffset>| {
+            Ok(Some((planned(1, *due, *due), vec![(resource.to_owned(), quantity.to_owned())])))
+        };
+        let result = expand_demands(&recipe_resource(1), &qty(1.0), &time("2021-06-01T12:00:00+00:00"), cyclic);
+        assert!(matches!(result, Err(DataIntegrityError::MaxDepthExceeded(MAX_RECIPE_DEPTH))));
+    }
+}
@@ -0,0 +1,170 @@
+/**
+ * Holo-REA recipe flow zome library API
+ *
+ * Contains helper methods that can be used to manipulate `RecipeFlow` data
+ * structures in either the local Holochain zome, or a separate DNA-local zome.
+ *
+ * `RecipeFlow`s, `RecipeProcess`es and `RecipeResource`s all live in the specification
+ * DNA, so the indexes between them are managed directly rather than via index zomes.
+ *
+ * @package Holo-REA
+ */
+use hdk_records::{
+    RecordAPIResult,
+    records::{
+        create_record,
+        read_record_entry,
+        read_record_entry_by_header,
+        update_record,
+        delete_record,
+    },
+    local_indexes::{
+        create_index,
+        update_index,
+    },
+};
+
+pub mod expansion;
+pub use expansion::{ expand_recipe, expand_specification_recipe, MAX_RECIPE_DEPTH };
+
+pub use hc_zome_rea_recipe_flow_storage_consts::*;
+use hc_zome_rea_recipe_flow_storage::*;
+use hc_zome_rea_recipe_flow_rpc::*;
+use hc_zome_rea_recipe_process_storage_consts::{
+    RECIPE_PROCESS_ENTRY_TYPE,
+    RECIPE_PROCESS_INPUTS_LINK_TAG, RECIPE_PROCESS_OUTPUTS_LINK_TAG,
+};
+use hc_zome_rea_recipe_resource_storage_consts::{
+    RECIPE_RESOURCE_ENTRY_TYPE,
+    RECIPE_RESOURCE_FLOWS_LINK_TAG,
+};
+
+pub fn handle_create_recipe_flow<S>(entry_def_id: S, recipe_flow: CreateRequest) -> RecordAPIResult<ResponseData>
+    where S: AsRef<str>,
+{
+    let (revision_id, base_address, entry_resp): (_,_, EntryData) = create_record(&entry_def_id, recipe_flow)?;
+
+    // handle link fields
+    create_index(
+        &RECIPE_FLOW_ENTRY_TYPE, &base_address,
+        &RECIPE_RESOURCE_ENTRY_TYPE, &entry_resp.recipe_flow_resource,
+        RECIPE_FLOW_RESOURCE_LINK_TAG, RECIPE_RESOURCE_FLOWS_LINK_TAG,
+    )?;
+    if let Some(recipe_process) = &entry_resp.recipe_input_of {
+        create_index(
+            &RECIPE_FLOW_ENTRY_TYPE, &base_address,
+            &RECIPE_PROCESS_ENTRY_TYPE, recipe_process,
+            RECIPE_FLOW_INPUT_OF_LINK_TAG, RECIPE_PROCESS_INPUTS_LINK_TAG,
+        )?;
+    }
+    if let Some(recipe_process) = &entry_resp.recipe_output_of {
+        create_index(
+            &RECIPE_FLOW_ENTRY_TYPE, &base_address,
+            &RECIPE_PROCESS_ENTRY_TYPE, recipe_process,
+            RECIPE_FLOW_OUTPUT_OF_LINK_TAG, RECIPE_PROCESS_OUTPUTS_LINK_TAG,
+        )?;
+    }
+
+    Ok(construct_response(&base_address, &revision_id, &entry_resp))
+}
+
+pub fn handle_get_recipe_flow<S>(entry_def_id: S, address: RecipeFlowAddress) -> RecordAPIResult<ResponseData>
+    where S: AsRef<str>,
+{
+    let (revision, base_address, entry) = read_record_entry::<EntryData, EntryStorage, _,_>(&entry_def_id, address.as_ref())?;
+    Ok(construct_response(&base_address, &revision, &entry))
+}
+
+pub fn handle_update_recipe_flow<S>(entry_def_id: S, recipe_flow: UpdateRequest) -> RecordAPIResult<ResponseData>
+    where S: AsRef<str>,
+{
+    let old_revision = recipe_flow.get_revision_id();
+    let (revision_id, base_address, new_entry, prev_entry): (_, RecipeFlowAddress, EntryData, EntryData) = update_record(&entry_def_id, old_revision, recipe_flow.to_owned())?;
+
+    // handle link fields
+    if new_entry.recipe_flow_resource != prev_entry.recipe_flow_resource {
+        update_index(
+            &RECIPE_FLOW_ENTRY_TYPE, &base_address,
+            &RECIPE_RESOURCE_ENTRY_TYPE,
+            RECIPE_FLOW_RESOURCE_LINK_TAG, RECIPE_RESOURCE_FLOWS_LINK_TAG,
+            vec![new_entry.recipe_flow_resource.to_owned()].as_slice(),
+            vec![prev_entry.recipe_flow_resource.to_owned()].as_slice(),
+        )?;
+    }
+    if new_entry.recipe_input_of != prev_entry.recipe_input_of {
+        update_index(
+            &RECIPE_FLOW_ENTRY_TYPE, &base_address,
+            &RECIPE_PROCESS_ENTRY_TYPE,
+            RECIPE_FLOW_INPUT_OF_LINK_TAG, RECIPE_PROCESS_INPUTS_LINK_TAG,
+            new_entry.recipe_input_of.iter().cloned().collect::<Vec<_>>().as_slice(),
+            prev_entry.recipe_input_of.iter().cloned().collect::<Vec<_>>().as_slice(),
+        )?;
+    }
+    if new_entry.recipe_output_of != prev_entry.recipe_output_of {
+        update_index(
+            &RECIPE_FLOW_ENTRY_TYPE, &base_address,
+            &RECIPE_PROCESS_ENTRY_TYPE,
+            RECIPE_FLOW_OUTPUT_OF_LINK_TAG, RECIPE_PROCESS_OUTPUTS_LINK_TAG,
+            new_entry.recipe_output_of.iter().cloned().collect::<Vec<_>>().as_slice(),
+            prev_entry.recipe_output_of.iter().cloned().collect::<Vec<_>>().as_slice(),
+        )?;
+    }
+
+    Ok(construct_response(&base_address, &revision_id, &new_entry))
+}
+
+pub fn handle_delete_recipe_flow(revision_id: RevisionHash) -> RecordAPIResult<bool>
+{
+    let (base_address, entry): (RecipeFlowAddress, EntryData) = read_record_entry_by_header::<EntryData, EntryStorage, _>(&revision_id)?;
+
+    // handle link fields
+    update_index(
+        &RECIPE_FLOW_ENTRY_TYPE, &base_address,
+        &RECIPE_RESOURCE_ENTRY_TYPE,
+        RECIPE_FLOW_RESOURCE_LINK_TAG, RECIPE_RESOURCE_FLOWS_LINK_TAG,
+        vec![].as_slice(),
+        vec![entry.recipe_flow_resource].as_slice(),
+    )?;
+    if let Some(recipe_process) = entry.recipe_input_of {
+        update_index(
+            &RECIPE_FLOW_ENTRY_TYPE, &base_address,
+            &RECIPE_PROCESS_ENTRY_TYPE,
+            RECIPE_FLOW_INPUT_OF_LINK_TAG, RECIPE_PROCESS_INPUTS_LINK_TAG,
+            vec![].as_slice(),
+            vec![recipe_process].as_slice(),
+        )?;
+    }
+    if let Some(recipe_process) = entry.recipe_output_of {
+        update_index(
+            &RECIPE_FLOW_ENTRY_TYPE, &base_address,
+            &RECIPE_PROCESS_ENTRY_TYPE,
+            RECIPE_FLOW_OUTPUT_OF_LINK_TAG, RECIPE_PROCESS_OUTPUTS_LINK_TAG,
+            vec![].as_slice(),
+            vec![recipe_process].as_slice(),
+        )?;
+    }
+
+    // delete entry last, as it must be present in order for links to be removed
+    delete_record::<EntryStorage, _>(&revision_id)
+}
+
+/// Create response from input DHT primitives
+fn construct_response<'a>(
+    address: &RecipeFlowAddress,
+    revision_id: &RevisionHash,
+    e: &EntryData,
+) -> ResponseData {
+    ResponseData {
+        recipe_flow: Response {
+            id: address.to_owned(),
+            revision_id: revision_id.to_owned(),
+            action: e.action.to_owned(),
+            recipe_flow_resource: e.recipe_flow_resource.to_owned(),
+            recipe_input_of: e.recipe_input_of.to_owned(),
+            recipe_output_of: e.recipe_output_of.to_owned(),
+            resource_quantity: e.resource_quantity.to_owned(),
+            effort_quantity: e.effort_quantity.to_owned(),
+            note: e.note.to_owned(),
+        }
+    }
+}
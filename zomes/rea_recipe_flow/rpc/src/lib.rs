@@ -0,0 +1,189 @@
+/**
+ * Holo-REA recipe flow zome I/O data structures
+ *
+ * Required by packages wishing to interact with the zome via its standard RPC interface,
+ * and by the zome API handlers accepting these parameters.
+ *
+ * @package Holo-REA
+ */
+use holochain_serialized_bytes::prelude::*;
+use serde_maybe_undefined::MaybeUndefined;
+pub use vf_measurement::QuantityValue;
+pub use vf_attributes_hdk::{
+    RevisionHash, ByAddress, ByHeader,
+    RecipeFlowAddress,
+    RecipeResourceAddress,
+    RecipeProcessAddress,
+    ResourceSpecificationAddress,
+    ProcessSpecificationAddress,
+    ActionId,
+    DateTime, FixedOffset,
+};
+
+// toplevel I/O structs for WASM API
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateParams {
+    pub recipe_flow: CreateRequest,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateParams {
+    pub recipe_flow: UpdateRequest,
+}
+
+//---------------- EXTERNAL RECORD STRUCTURE ----------------
+
+/// I/O struct to describe the complete record, including all managed link fields
+///
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+    pub id: RecipeFlowAddress,
+    pub revision_id: RevisionHash,
+    pub action: ActionId,
+    pub recipe_flow_resource: RecipeResourceAddress,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipe_input_of: Option<RecipeProcessAddress>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipe_output_of: Option<RecipeProcessAddress>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_quantity: Option<QuantityValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effort_quantity: Option<QuantityValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// I/O struct to describe what is returned outside the gateway.
+/// Responses are usually returned as named attributes in order to leave space
+/// for future additional return values.
+///
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseData {
+    pub recipe_flow: Response,
+}
+
+//---------------- CREATE REQUEST ----------------
+
+/// I/O struct to describe the complete input record, including all managed links
+///
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateRequest {
+    pub action: ActionId,
+    pub recipe_flow_resource: RecipeResourceAddress,
+    #[serde(default)]
+    pub recipe_input_of: MaybeUndefined<RecipeProcessAddress>,
+    #[serde(default)]
+    pub recipe_output_of: MaybeUndefined<RecipeProcessAddress>,
+    #[serde(default)]
+    pub resource_quantity: MaybeUndefined<QuantityValue>,
+    #[serde(default)]
+    pub effort_quantity: MaybeUndefined<QuantityValue>,
+    #[serde(default)]
+    pub note: MaybeUndefined<String>,
+}
+
+impl<'a> CreateRequest {
+    // :TODO: accessors for field data
+}
+
+//---------------- UPDATE REQUEST ----------------
+
+/// I/O struct to describe the complete input record, including all managed links
+///
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRequest {
+    pub revision_id: RevisionHash,
+    #[serde(default)]
+    pub action: MaybeUndefined<ActionId>,
+    #[serde(default)]
+    pub recipe_flow_resource: MaybeUndefined<RecipeResourceAddress>,
+    #[serde(default)]
+    pub recipe_input_of: MaybeUndefined<RecipeProcessAddress>,
+    #[serde(default)]
+    pub recipe_output_of: MaybeUndefined<RecipeProcessAddress>,
+    #[serde(default)]
+    pub resource_quantity: MaybeUndefined<QuantityValue>,
+    #[serde(default)]
+    pub effort_quantity: MaybeUndefined<QuantityValue>,
+    #[serde(default)]
+    pub note: MaybeUndefined<String>,
+}
+
+impl<'a> UpdateRequest {
+    pub fn get_revision_id(&'a self) -> &RevisionHash {
+        &self.revision_id
+    }
+
+    // :TODO: accessors for other field data
+}
+
+//---------------- RECIPE EXPANSION ----------------
+
+/// I/O struct for requesting the work needed to produce some quantity of a `RecipeResource`
+///
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpandRecipeRequest {
+    pub recipe_resource: RecipeResourceAddress,
+    pub resource_quantity: QuantityValue,
+    pub due: DateTime<FixedOffset>,
+}
+
+/// I/O struct for requesting a single level of the recipe producing some `ResourceSpecification`
+///
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpandSpecificationRequest {
+    pub resource_specification: ResourceSpecificationAddress,
+    pub resource_quantity: QuantityValue,
+    pub due: DateTime<FixedOffset>,
+}
+
+/// A `RecipeFlow` scaled to the requested output quantity and scheduled against its `RecipeProcess`
+///
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedFlow {
+    pub recipe_flow: RecipeFlowAddress,
+    pub action: ActionId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_conforms_to: Option<ResourceSpecificationAddress>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_quantity: Option<QuantityValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effort_quantity: Option<QuantityValue>,
+    pub due: DateTime<FixedOffset>,
+}
+
+/// A `RecipeProcess` scheduled to complete in time for the demand it satisfies
+///
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedProcess {
+    pub recipe_process: RecipeProcessAddress,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process_conforms_to: Option<ProcessSpecificationAddress>,
+    pub has_beginning: DateTime<FixedOffset>,
+    pub has_end: DateTime<FixedOffset>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub inputs: Vec<PlannedFlow>,
+    pub outputs: Vec<PlannedFlow>,
+    /// the output `RecipeFlow` delivering the demanded resource
+    pub produces: RecipeFlowAddress,
+}
+
+/// Result of expanding a recipe. The first `PlannedProcess` is the one producing the
+/// requested `RecipeResource`; the rest are ordered by their distance from it.
+///
+#[derive(Clone, Serialize, Deserialize, SerializedBytes, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RecipeExpansion {
+    pub processes: Vec<PlannedProcess>,
+}
@@ -0,0 +1,72 @@
+/**
+ * Holo-REA recipe flow zome internal data structures
+ *
+ * Required by the zome itself, and for any DNA-local zomes interacting with its
+ * storage API directly.
+ *
+ * @package Holo-REA
+ */
+use hdk::prelude::*;
+
+use hdk_records::{
+    record_interface::Updateable,
+    generate_record_entry,
+};
+
+use vf_attributes_hdk::{
+    ActionId,
+    RecipeResourceAddress,
+    RecipeProcessAddress,
+};
+use vf_measurement::QuantityValue;
+
+use hc_zome_rea_recipe_flow_rpc::{CreateRequest, RecipeFlowAddress, UpdateRequest};
+
+//---------------- RECORD INTERNALS & VALIDATION ----------------
+
+#[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
+pub struct EntryData {
+    pub action: ActionId,
+    pub recipe_flow_resource: RecipeResourceAddress,
+    pub recipe_input_of: Option<RecipeProcessAddress>,
+    pub recipe_output_of: Option<RecipeProcessAddress>,
+    pub resource_quantity: Option<QuantityValue>,
+    pub effort_quantity: Option<QuantityValue>,
+    pub note: Option<String>,
+}
+
+generate_record_entry!(EntryData, RecipeFlowAddress, EntryStorage);
+
+//---------------- CREATE ----------------
+
+/// Pick relevant fields out of I/O record into underlying DHT entry
+impl From<CreateRequest> for EntryData {
+    fn from(e: CreateRequest) -> EntryData {
+        EntryData {
+            action: e.action,
+            recipe_flow_resource: e.recipe_flow_resource,
+            recipe_input_of: e.recipe_input_of.into(),
+            recipe_output_of: e.recipe_output_of.into(),
+            resource_quantity: e.resource_quantity.into(),
+            effort_quantity: e.effort_quantity.into(),
+            note: e.note.into(),
+        }
+    }
+}
+
+//---------------- UPDATE ----------------
+
+/// Handles update operations by merging any newly provided fields
+impl Updateable<UpdateRequest> for EntryData {
+    fn update_with(&self, e: UpdateRequest) -> EntryData {
+        EntryData {
+            action: if !e.action.is_some() { self.action.to_owned() } else { e.action.to_owned().unwrap() },
+            recipe_flow_resource: if !e.recipe_flow_resource.is_some() { self.recipe_flow_resource.to_owned() } else { e.recipe_flow_resource.to_owned().unwrap() },
+            recipe_input_of: if e.recipe_input_of.is_undefined() { self.recipe_input_of.to_owned() } else { e.recipe_input_of.to_owned().into() },
+            recipe_output_of: if e.recipe_output_of.is_undefined() { self.recipe_output_of.to_owned() } else { e.recipe_output_of.to_owned().into() },
+            resource_quantity: if e.resource_quantity.is_undefined() { self.resource_quantity.to_owned() } else { e.resource_quantity.to_owned().into() },
+            effort_quantity: if e.effort_quantity.is_undefined() { self.effort_quantity.to_owned() } else { e.effort_quantity.to_owned().into() },
+            note: if e.note.is_undefined() { self.note.to_owned() } else { e.note.to_owned().into() },
+        }
+    }
+}
@@ -0,0 +1,11 @@
+/**
+ * Storage constants for zome entry & link type identifiers
+ *
+ * Used by modules interfacing with the underlying Holochain storage system directly.
+ *
+ * @package Holo-REA
+ */
+pub const RECIPE_FLOW_ENTRY_TYPE: &str = "vf_recipe_flow";
+pub const RECIPE_FLOW_RESOURCE_LINK_TAG: &str = "recipe_flow_resource";
+pub const RECIPE_FLOW_INPUT_OF_LINK_TAG: &str = "recipe_input_of";
+pub const RECIPE_FLOW_OUTPUT_OF_LINK_TAG: &str = "recipe_output_of";
@@ -0,0 +1,57 @@
+/**
+ * Holo-REA recipe flow zome API definition
+ *
+ * Defines the top-level zome configuration needed by Holochain's build system
+ * to bundle the app. This basically involves wiring up the helper methods from the
+ * related `_lib` module into a packaged zome WASM binary.
+ *
+ * @package Holo-REA
+ */
+use hdk::prelude::*;
+
+use hc_zome_rea_recipe_flow_rpc::*;
+use hc_zome_rea_recipe_flow_lib::*;
+
+#[hdk_extern]
+fn entry_defs(_: ()) -> ExternResult<EntryDefsCallbackResult> {
+    Ok(EntryDefsCallbackResult::from(vec![
+        Path::entry_def(),
+        EntryDef {
+            id: RECIPE_FLOW_ENTRY_TYPE.into(),
+            visibility: EntryVisibility::Public,
+            crdt_type: CrdtType,
+            required_validations: 2.into(),
+            required_validation_type: RequiredValidationType::default(),
+        }
+    ]))
+}
+
+#[hdk_extern]
+fn create_recipe_flow(CreateParams { recipe_flow }: CreateParams) -> ExternResult<ResponseData> {
+    Ok(handle_create_recipe_flow(RECIPE_FLOW_ENTRY_TYPE, recipe_flow)?)
+}
+
+#[hdk_extern]
+fn get_recipe_flow(ByAddress { address }: ByAddress<RecipeFlowAddress>) -> ExternResult<ResponseData> {
+    Ok(handle_get_recipe_flow(RECIPE_FLOW_ENTRY_TYPE, address)?)
+}
+
+#[hdk_extern]
+fn update_recipe_flow(UpdateParams { recipe_flow }: UpdateParams) -> ExternResult<ResponseData> {
+    Ok(handle_update_recipe_flow(RECIPE_FLOW_ENTRY_TYPE, recipe_flow)?)
+}
+
+#[hdk_extern]
+fn delete_recipe_flow(ByHeader { address }: ByHeader) -> ExternResult<bool> {
+    Ok(handle_delete_recipe_flow(address)?)
+}
+
+#[hdk_extern]
+fn expand_recipe(ExpandRecipeRequest { recipe_resource, resource_quantity, due }: ExpandRecipeRequest) -> ExternResult<RecipeExpansion> {
+    Ok(hc_zome_rea_recipe_flow_lib::expand_recipe(&recipe_resource, &resource_quantity, &due)?)
+}
+
+#[hdk_extern]
+fn expand_specification_recipe(ExpandSpecificationRequest { resource_specification, resource_quantity, due }: ExpandSpecificationRequest) -> ExternResult<Option<PlannedProcess>> {
+    Ok(hc_zome_rea_recipe_flow_lib::expand_specification_recipe(&resource_specification, &resource_quantity, &due)?)
+}
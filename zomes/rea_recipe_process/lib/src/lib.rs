@@ -0,0 +1,149 @@
+/**
+ * Holo-REA recipe process zome library API
+ *
+ * Contains helper methods that can be used to manipulate `RecipeProcess` data
+ * structures in either the local Holochain zome, or a separate DNA-local zome.
+ *
+ * @package Holo-REA
+ */
+use hdk_records::{
+    RecordAPIResult,
+    records::{
+        create_record,
+        read_record_entry,
+        read_record_entry_by_header,
+        update_record,
+        delete_record,
+    },
+    local_indexes::{
+        read_index,
+        create_index,
+        update_index,
+    },
+    integrity::ensure_deletable,
+    blobs::{ store_inline_update, read_inline_field },
+};
+
+pub use hc_zome_rea_recipe_process_storage_consts::*;
+pub use hdk_records::blobs::{ blob_chunk_entry_def, blob_manifest_entry_def };
+use hc_zome_rea_recipe_process_storage::*;
+use hc_zome_rea_recipe_process_rpc::*;
+use hc_zome_rea_process_specification_storage_consts::{
+    PROCESS_SPECIFICATION_ENTRY_TYPE,
+    PROCESS_SPECIFICATION_RECIPE_PROCESSES_LINK_TAG,
+};
+
+pub fn handle_create_recipe_process<S>(entry_def_id: S, recipe_process: CreateRequest) -> RecordAPIResult<ResponseData>
+    where S: AsRef<str>,
+{
+    let recipe_process = CreateRequest { image: store_inline_update(&recipe_process.image)?, ..recipe_process };
+    let (revision_id, base_address, entry_resp): (_,_, EntryData) = create_record(&entry_def_id, recipe_process)?;
+
+    // handle link fields
+    if let Some(process_specification) = &entry_resp.process_conforms_to {
+        create_index(
+            &RECIPE_PROCESS_ENTRY_TYPE, &base_address,
+            &PROCESS_SPECIFICATION_ENTRY_TYPE, process_specification,
+            RECIPE_PROCESS_CONFORMS_TO_LINK_TAG, PROCESS_SPECIFICATION_RECIPE_PROCESSES_LINK_TAG,
+        )?;
+    }
+
+    construct_response(&base_address, &revision_id, &entry_resp, get_link_fields(&base_address)?)
+}
+
+pub fn handle_get_recipe_process<S>(entry_def_id: S, address: RecipeProcessAddress) -> RecordAPIResult<ResponseData>
+    where S: AsRef<str>,
+{
+    let (revision, base_address, entry) = read_record_entry::<EntryData, EntryStorage, _,_>(&entry_def_id, address.as_ref())?;
+    construct_response(&base_address, &revision, &entry, get_link_fields(&base_address)?)
+}
+
+pub fn handle_update_recipe_process<S>(entry_def_id: S, recipe_process: UpdateRequest) -> RecordAPIResult<ResponseData>
+    where S: AsRef<str>,
+{
+    let recipe_process = UpdateRequest { image: store_inline_update(&recipe_process.image)?, ..recipe_process };
+    let old_revision = recipe_process.get_revision_id();
+    let (revision_id, base_address, new_entry, prev_entry): (_, RecipeProcessAddress, EntryData, EntryData) = update_record(&entry_def_id, old_revision, recipe_process.to_owned())?;
+
+    // handle link fields
+    if new_entry.process_conforms_to != prev_entry.process_conforms_to {
+        update_index(
+            &RECIPE_PROCESS_ENTRY_TYPE, &base_address,
+            &PROCESS_SPECIFICATION_ENTRY_TYPE,
+            RECIPE_PROCESS_CONFORMS_TO_LINK_TAG, PROCESS_SPECIFICATION_RECIPE_PROCESSES_LINK_TAG,
+            new_entry.process_conforms_to.iter().cloned().collect::<Vec<_>>().as_slice(),
+            prev_entry.process_conforms_to.iter().cloned().collect::<Vec<_>>().as_slice(),
+        )?;
+    }
+    construct_response(&base_address, &revision_id, &new_entry, get_link_fields(&base_address)?)
+}
+
+/// Indexes of other records referencing a `RecipeProcess`, which prevent its deletion
+const INBOUND_LINK_TAGS: &[&str] = &[
+    RECIPE_PROCESS_INPUTS_LINK_TAG,
+    RECIPE_PROCESS_OUTPUTS_LINK_TAG,
+];
+
+pub fn handle_delete_recipe_process(revision_id: RevisionHash, force: bool) -> RecordAPIResult<bool>
+{
+    let (base_address, entry): (RecipeProcessAddress, EntryData) = read_record_entry_by_header::<EntryData, EntryStorage, _>(&revision_id)?;
+
+    ensure_deletable(&RECIPE_PROCESS_ENTRY_TYPE, &base_address, INBOUND_LINK_TAGS, force)?;
+
+    // handle link fields
+    if let Some(process_specification) = entry.process_conforms_to {
+        update_index(
+            &RECIPE_PROCESS_ENTRY_TYPE, &base_address,
+            &PROCESS_SPECIFICATION_ENTRY_TYPE,
+            RECIPE_PROCESS_CONFORMS_TO_LINK_TAG, PROCESS_SPECIFICATION_RECIPE_PROCESSES_LINK_TAG,
+            vec![].as_slice(),
+            vec![process_specification].as_slice(),
+        )?;
+    }
+
+    // delete entry last, as it must be present in order for links to be removed
+    delete_record::<EntryStorage, _>(&revision_id)
+}
+
+/// Create response from input DHT primitives
+fn construct_response<'a>(
+    address: &RecipeProcessAddress,
+    revision_id: &RevisionHash,
+    e: &EntryData,
+    (
+        recipe_inputs,
+        recipe_outputs,
+    ): (
+        Vec<RecipeFlowAddress>,
+        Vec<RecipeFlowAddress>,
+    )
+) -> RecordAPIResult<ResponseData> {
+    Ok(ResponseData {
+        recipe_process: Response {
+            // entry fields
+            id: address.to_owned(),
+            revision_id: revision_id.to_owned(),
+            name: e.name.to_owned(),
+            process_conforms_to: e.process_conforms_to.to_owned(),
+            process_classified_as: e.process_classified_as.to_owned(),
+            has_duration: e.has_duration.to_owned(),
+            image: read_inline_field(&e.image)?,
+            note: e.note.to_owned(),
+
+            // link fields
+            recipe_inputs,
+            recipe_outputs,
+        }
+    })
+}
+
+// @see construct_response
+fn get_link_fields(address: &RecipeProcessAddress) -> RecordAPIResult<(
+    Vec<RecipeFlowAddress>,
+    Vec<RecipeFlowAddress>,
+)> {
+    Ok((
+        read_index(&RECIPE_PROCESS_ENTRY_TYPE, address, &RECIPE_PROCESS_INPUTS_LINK_TAG)?,
+        read_index(&RECIPE_PROCESS_ENTRY_TYPE, address, &RECIPE_PROCESS_OUTPUTS_LINK_TAG)?,
+    ))
+}
@@ -0,0 +1,11 @@
+/**
+ * Storage constants for zome entry & link type identifiers
+ *
+ * Used by modules interfacing with the underlying Holochain storage system directly.
+ *
+ * @package Holo-REA
+ */
+pub const RECIPE_PROCESS_ENTRY_TYPE: &str = "vf_recipe_process";
+pub const RECIPE_PROCESS_INPUTS_LINK_TAG: &str = "recipe_inputs";
+pub const RECIPE_PROCESS_OUTPUTS_LINK_TAG: &str = "recipe_outputs";
+pub const RECIPE_PROCESS_CONFORMS_TO_LINK_TAG: &str = "process_conforms_to";
@@ -0,0 +1,145 @@
+/**
+ * Holo-REA recipe resource zome library API
+ *
+ * Contains helper methods that can be used to manipulate `RecipeResource` data
+ * structures in either the local Holochain zome, or a separate DNA-local zome.
+ *
+ * @package Holo-REA
+ */
+use hdk_records::{
+    RecordAPIResult,
+    records::{
+        create_record,
+        read_record_entry,
+        read_record_entry_by_header,
+        update_record,
+        delete_record,
+    },
+    local_indexes::{
+        read_index,
+        create_index,
+        update_index,
+    },
+    integrity::ensure_deletable,
+    blobs::{ store_inline_update, read_inline_field },
+};
+
+pub use hc_zome_rea_recipe_resource_storage_consts::*;
+pub use hdk_records::blobs::{ blob_chunk_entry_def, blob_manifest_entry_def };
+use hc_zome_rea_recipe_resource_storage::*;
+use hc_zome_rea_recipe_resource_rpc::*;
+use hc_zome_rea_resource_specification_storage_consts::{
+    ECONOMIC_RESOURCE_SPECIFICATION_ENTRY_TYPE,
+    RESOURCE_SPECIFICATION_RECIPE_RESOURCES_LINK_TAG,
+};
+
+pub fn handle_create_recipe_resource<S>(entry_def_id: S, recipe_resource: CreateRequest) -> RecordAPIResult<ResponseData>
+    where S: AsRef<str>,
+{
+    let recipe_resource = CreateRequest { image: store_inline_update(&recipe_resource.image)?, ..recipe_resource };
+    let (revision_id, base_address, entry_resp): (_,_, EntryData) = create_record(&entry_def_id, recipe_resource)?;
+
+    // handle link fields
+    if let Some(resource_specification) = &entry_resp.resource_conforms_to {
+        create_index(
+            &RECIPE_RESOURCE_ENTRY_TYPE, &base_address,
+            &ECONOMIC_RESOURCE_SPECIFICATION_ENTRY_TYPE, resource_specification,
+            RECIPE_RESOURCE_CONFORMS_TO_LINK_TAG, RESOURCE_SPECIFICATION_RECIPE_RESOURCES_LINK_TAG,
+        )?;
+    }
+
+    construct_response(&base_address, &revision_id, &entry_resp, get_link_fields(&base_address)?)
+}
+
+pub fn handle_get_recipe_resource<S>(entry_def_id: S, address: RecipeResourceAddress) -> RecordAPIResult<ResponseData>
+    where S: AsRef<str>,
+{
+    let (revision, base_address, entry) = read_record_entry::<EntryData, EntryStorage, _,_>(&entry_def_id, address.as_ref())?;
+    construct_response(&base_address, &revision, &entry, get_link_fields(&base_address)?)
+}
+
+pub fn handle_update_recipe_resource<S>(entry_def_id: S, recipe_resource: UpdateRequest) -> RecordAPIResult<ResponseData>
+    where S: AsRef<str>,
+{
+    let recipe_resource = UpdateRequest { image: store_inline_update(&recipe_resource.image)?, ..recipe_resource };
+    let old_revision = recipe_resource.get_revision_id();
+    let (revision_id, base_address, new_entry, prev_entry): (_, RecipeResourceAddress, EntryData, EntryData) = update_record(&entry_def_id, old_revision, recipe_resource.to_owned())?;
+
+    // handle link fields
+    if new_entry.resource_conforms_to != prev_entry.resource_conforms_to {
+        update_index(
+            &RECIPE_RESOURCE_ENTRY_TYPE, &base_address,
+            &ECONOMIC_RESOURCE_SPECIFICATION_ENTRY_TYPE,
+            RECIPE_RESOURCE_CONFORMS_TO_LINK_TAG, RESOURCE_SPECIFICATION_RECIPE_RESOURCES_LINK_TAG,
+            new_entry.resource_conforms_to.iter().cloned().collect::<Vec<_>>().as_slice(),
+            prev_entry.resource_conforms_to.iter().cloned().collect::<Vec<_>>().as_slice(),
+        )?;
+    }
+    construct_response(&base_address, &revision_id, &new_entry, get_link_fields(&base_address)?)
+}
+
+/// Indexes of other records referencing a `RecipeResource`, which prevent its deletion
+const INBOUND_LINK_TAGS: &[&str] = &[
+    RECIPE_RESOURCE_FLOWS_LINK_TAG,
+];
+
+pub fn handle_delete_recipe_resource(revision_id: RevisionHash, force: bool) -> RecordAPIResult<bool>
+{
+    let (base_address, entry): (RecipeResourceAddress, EntryData) = read_record_entry_by_header::<EntryData, EntryStorage, _>(&revision_id)?;
+
+    ensure_deletable(&RECIPE_RESOURCE_ENTRY_TYPE, &base_address, INBOUND_LINK_TAGS, force)?;
+
+    // handle link fields
+    if let Some(resource_specification) = entry.resource_conforms_to {
+        update_index(
+            &RECIPE_RESOURCE_ENTRY_TYPE, &base_address,
+            &ECONOMIC_RESOURCE_SPECIFICATION_ENTRY_TYPE,
+            RECIPE_RESOURCE_CONFORMS_TO_LINK_TAG, RESOURCE_SPECIFICATION_RECIPE_RESOURCES_LINK_TAG,
+            vec![].as_slice(),
+            vec![resource_specification].as_slice(),
+        )?;
+    }
+
+    // delete entry last, as it must be present in order for links to be removed
+    delete_record::<EntryStorage, _>(&revision_id)
+}
+
+/// Create response from input DHT primitives
+fn construct_response<'a>(
+    address: &RecipeResourceAddress,
+    revision_id: &RevisionHash,
+    e: &EntryData,
+    (
+        recipe_flows,
+    ): (
+        Vec<RecipeFlowAddress>,
+    )
+) -> RecordAPIResult<ResponseData> {
+    Ok(ResponseData {
+        recipe_resource: Response {
+            // entry fields
+            id: address.to_owned(),
+            revision_id: revision_id.to_owned(),
+            name: e.name.to_owned(),
+            resource_conforms_to: e.resource_conforms_to.to_owned(),
+            resource_classified_as: e.resource_classified_as.to_owned(),
+            unit_of_resource: e.unit_of_resource.to_owned(),
+            unit_of_effort: e.unit_of_effort.to_owned(),
+            substitutable: e.substitutable,
+            image: read_inline_field(&e.image)?,
+            note: e.note.to_owned(),
+
+            // link fields
+            recipe_flows,
+        }
+    })
+}
+
+// @see construct_response
+fn get_link_fields(address: &RecipeResourceAddress) -> RecordAPIResult<(
+    Vec<RecipeFlowAddress>,
+)> {
+    Ok((
+        read_index(&RECIPE_RESOURCE_ENTRY_TYPE, address, &RECIPE_RESOURCE_FLOWS_LINK_TAG)?,
+    ))
+}
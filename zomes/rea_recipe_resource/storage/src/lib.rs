@@ -0,0 +1,74 @@
+/**
+ * Holo-REA recipe resource zome internal data structures
+ *
+ * Required by the zome itself, and for any DNA-local zomes interacting with its
+ * storage API directly.
+ *
+ * @package Holo-REA
+ */
+use hdk::prelude::*;
+
+use hdk_records::{
+    record_interface::Updateable,
+    generate_record_entry,
+};
+
+use vf_attributes_hdk::{
+    ResourceSpecificationAddress,
+    ExternalURL,
+    UnitId,
+};
+
+use hc_zome_rea_recipe_resource_rpc::{CreateRequest, RecipeResourceAddress, UpdateRequest};
+
+//---------------- RECORD INTERNALS & VALIDATION ----------------
+
+#[derive(Serialize, Deserialize, Debug, SerializedBytes, Default, Clone)]
+pub struct EntryData {
+    pub name: String,
+    pub resource_conforms_to: Option<ResourceSpecificationAddress>,
+    pub resource_classified_as: Option<Vec<ExternalURL>>,
+    pub unit_of_resource: Option<UnitId>,
+    pub unit_of_effort: Option<UnitId>,
+    pub substitutable: bool,
+    pub image: Option<ExternalURL>,
+    pub note: Option<String>,
+}
+
+generate_record_entry!(EntryData, RecipeResourceAddress, EntryStorage);
+
+//---------------- CREATE ----------------
+
+/// Pick relevant fields out of I/O record into underlying DHT entry
+impl From<CreateRequest> for EntryData {
+    fn from(e: CreateRequest) -> EntryData {
+        EntryData {
+            name: e.name.into(),
+            resource_conforms_to: e.resource_conforms_to.into(),
+            resource_classified_as: e.resource_classified_as.into(),
+            unit_of_resource: e.unit_of_resource.into(),
+            unit_of_effort: e.unit_of_effort.into(),
+            substitutable: e.substitutable.to_option().unwrap_or(false),
+            image: e.image.into(),
+            note: e.note.into(),
+        }
+    }
+}
+
+//---------------- UPDATE ----------------
+
+/// Handles update operations by merging any newly provided fields
+impl Updateable<UpdateRequest> for EntryData {
+    fn update_with(&self, e: UpdateRequest) -> EntryData {
+        EntryData {
+            name: if !e.name.is_some() { self.name.to_owned() } else { e.name.to_owned().unwrap() },
+            resource_conforms_to: if e.resource_conforms_to.is_undefined() { self.resource_conforms_to.to_owned() } else { e.resource_conforms_to.to_owned().into() },
+            resource_classified_as: if e.resource_classified_as.is_undefined() { self.resource_classified_as.to_owned() } else { e.resource_classified_as.to_owned().into() },
+            unit_of_resource: if e.unit_of_resource.is_undefined() { self.unit_of_resource.to_owned() } else { e.unit_of_resource.to_owned().into() },
+            unit_of_effort: if e.unit_of_effort.is_undefined() { self.unit_of_effort.to_owned() } else { e.unit_of_effort.to_owned().into() },
+            substitutable: if !e.substitutable.is_some() { self.substitutable } else { e.substitutable.to_owned().unwrap() },
+            image: if e.image.is_undefined() { self.image.to_owned() } else { e.image.to_owned().into() },
+            note: if e.note.is_undefined() { self.note.to_owned() } else { e.note.to_owned().into() },
+        }
+    }
+}
@@ -0,0 +1,10 @@
+/**
+ * Storage constants for zome entry & link type identifiers
+ *
+ * Used by modules interfacing with the underlying Holochain storage system directly.
+ *
+ * @package Holo-REA
+ */
+pub const RECIPE_RESOURCE_ENTRY_TYPE: &str = "vf_recipe_resource";
+pub const RECIPE_RESOURCE_FLOWS_LINK_TAG: &str = "recipe_flows";
+pub const RECIPE_RESOURCE_CONFORMS_TO_LINK_TAG: &str = "resource_conforms_to";
@@ -0,0 +1,49 @@
+/**
+ * Holo-REA recipe resource zome API definition
+ *
+ * Defines the top-level zome configuration needed by Holochain's build system
+ * to bundle the app. This basically involves wiring up the helper methods from the
+ * related `_lib` module into a packaged zome WASM binary.
+ *
+ * @package Holo-REA
+ */
+use hdk::prelude::*;
+
+use hc_zome_rea_recipe_resource_rpc::*;
+use hc_zome_rea_recipe_resource_lib::*;
+
+#[hdk_extern]
+fn entry_defs(_: ()) -> ExternResult<EntryDefsCallbackResult> {
+    Ok(EntryDefsCallbackResult::from(vec![
+        Path::entry_def(),
+        EntryDef {
+            id: RECIPE_RESOURCE_ENTRY_TYPE.into(),
+            visibility: EntryVisibility::Public,
+            crdt_type: CrdtType,
+            required_validations: 2.into(),
+            required_validation_type: RequiredValidationType::default(),
+        },
+        blob_chunk_entry_def(),
+        blob_manifest_entry_def(),
+    ]))
+}
+
+#[hdk_extern]
+fn create_recipe_resource(CreateParams { recipe_resource }: CreateParams) -> ExternResult<ResponseData> {
+    Ok(handle_create_recipe_resource(RECIPE_RESOURCE_ENTRY_TYPE, recipe_resource)?)
+}
+
+#[hdk_extern]
+fn get_recipe_resource(ByAddress { address }: ByAddress<RecipeResourceAddress>) -> ExternResult<ResponseData> {
+    Ok(handle_get_recipe_resource(RECIPE_RESOURCE_ENTRY_TYPE, address)?)
+}
+
+#[hdk_extern]
+fn update_recipe_resource(UpdateParams { recipe_resource }: UpdateParams) -> ExternResult<ResponseData> {
+    Ok(handle_update_recipe_resource(RECIPE_RESOURCE_ENTRY_TYPE, recipe_resource)?)
+}
+
+#[hdk_extern]
+fn delete_recipe_resource(DeleteRequest { address, force }: DeleteRequest) -> ExternResult<bool> {
+    Ok(handle_delete_recipe_resource(address, force)?)
+}
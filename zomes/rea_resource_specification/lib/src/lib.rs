@@ -11,43 +11,65 @@ use hdk_records::{
     records::{
         create_record,
         read_record_entry,
+        read_record_entry_by_header,
         update_record,
         delete_record,
     },
+    integrity::ensure_deletable,
+    blobs::{ store_inline_update, read_inline_field },
 };
 
+pub use hdk_records::blobs::{ blob_chunk_entry_def, blob_manifest_entry_def };
+
 use vf_attributes_hdk::{
     ResourceAddress,
 };
 
+use hc_zome_rea_resource_specification_storage_consts::{
+    ECONOMIC_RESOURCE_SPECIFICATION_ENTRY_TYPE,
+    RESOURCE_SPECIFICATION_CONFORMING_RESOURCE_LINK_TAG,
+    RESOURCE_SPECIFICATION_RECIPE_RESOURCES_LINK_TAG,
+};
 use hc_zome_rea_resource_specification_storage::*;
 use hc_zome_rea_resource_specification_rpc::*;
 
 pub fn handle_create_resource_specification<S>(entry_def_id: S, resource_specification: CreateRequest) -> RecordAPIResult<ResponseData>
     where S: AsRef<str>,
 {
+    let resource_specification = CreateRequest { image: store_inline_update(&resource_specification.image)?, ..resource_specification };
     let (revision_id, base_address, entry_resp): (_,_, EntryData) = create_record(&entry_def_id, resource_specification)?;
 
-    Ok(construct_response(&base_address, &revision_id, &entry_resp, get_link_fields(&base_address)?))
+    construct_response(&base_address, &revision_id, &entry_resp, get_link_fields(&base_address)?)
 }
 
 pub fn handle_get_resource_specification<S>(entry_def_id: S, address: ResourceSpecificationAddress) -> RecordAPIResult<ResponseData>
     where S: AsRef<str>,
 {
     let (revision, base_address, entry) = read_record_entry::<EntryData, EntryStorage, _,_>(&entry_def_id, address.as_ref())?;
-    Ok(construct_response(&address, &revision, &entry, get_link_fields(&base_address)?))
+    construct_response(&address, &revision, &entry, get_link_fields(&base_address)?)
 }
 
 pub fn handle_update_resource_specification<S>(entry_def_id: S, resource_specification: UpdateRequest) -> RecordAPIResult<ResponseData>
     where S: AsRef<str>,
 {
+    let resource_specification = UpdateRequest { image: store_inline_update(&resource_specification.image)?, ..resource_specification };
     let old_revision = resource_specification.get_revision_id();
     let (revision_id, base_address, new_entry, _prev_entry): (_, ResourceSpecificationAddress, EntryData, EntryData) = update_record(&entry_def_id, old_revision, resource_specification.to_owned())?;
-    Ok(construct_response(&base_address, &revision_id, &new_entry, get_link_fields(&base_address)?))
+    construct_response(&base_address, &revision_id, &new_entry, get_link_fields(&base_address)?)
 }
 
-pub fn handle_delete_resource_specification(revision_id: RevisionHash) -> RecordAPIResult<bool>
+/// Indexes of other records referencing a `ResourceSpecification`, which prevent its deletion
+const INBOUND_LINK_TAGS: &[&str] = &[
+    RESOURCE_SPECIFICATION_CONFORMING_RESOURCE_LINK_TAG,
+    RESOURCE_SPECIFICATION_RECIPE_RESOURCES_LINK_TAG,
+];
+
+pub fn handle_delete_resource_specification(revision_id: RevisionHash, force: bool) -> RecordAPIResult<bool>
 {
+    let (base_address, _entry): (ResourceSpecificationAddress, EntryData) = read_record_entry_by_header::<EntryData, EntryStorage, _>(&revision_id)?;
+
+    ensure_deletable(&ECONOMIC_RESOURCE_SPECIFICATION_ENTRY_TYPE, &base_address, INBOUND_LINK_TAGS, force)?;
+
     delete_record::<EntryStorage, _>(&revision_id)
 }
 
@@ -62,20 +84,20 @@ fn construct_response<'a>(
     ): (
         Vec<ResourceAddress>,
     )
-) -> ResponseData {
-    ResponseData {
+) -> RecordAPIResult<ResponseData> {
+    Ok(ResponseData {
         resource_specification: Response {
             // entry fields
             id: address.to_owned(),
             revision_id: revision_id.to_owned(),
             name: e.name.to_owned(),
-            image: e.image.to_owned(),
+            image: read_inline_field(&e.image)?,
             note: e.note.to_owned(),
             default_unit_of_effort: e.default_unit_of_effort.to_owned(),
 
             // conforming_resources: conforming_resources.map(Cow::into_owned),
         }
-    }
+    })
 }
 
 // @see construct_response
@@ -23,7 +23,9 @@ fn entry_defs(_: ()) -> ExternResult<EntryDefsCallbackResult> {
             crdt_type: CrdtType,
             required_validations: 2.into(),
             required_validation_type: RequiredValidationType::default(),
-        }
+        },
+        blob_chunk_entry_def(),
+        blob_manifest_entry_def(),
     ]))
 }
 
@@ -43,6 +45,6 @@ fn update_resource_specification(UpdateParams { resource_specification }: Update
 }
 
 #[hdk_extern]
-fn delete_resource_specification(ByHeader { address }: ByHeader) -> ExternResult<bool> {
-    Ok(handle_delete_resource_specification(address)?)
+fn delete_resource_specification(DeleteRequest { address, force }: DeleteRequest) -> ExternResult<bool> {
+    Ok(handle_delete_resource_specification(address, force)?)
 }
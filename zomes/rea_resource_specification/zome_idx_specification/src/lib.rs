@@ -0,0 +1,53 @@
+/**
+ * Resource specification query indexes for specification DNA
+ *
+ * Tracks the `EconomicResource`s conforming to each `ResourceSpecification`, so that
+ * specifications cannot be deleted whilst resources in the observation DNA reference them.
+ *
+ * @package Holo-REA
+ */
+use hdk::prelude::*;
+use hdk_records::{
+    config::init_with_config,
+    index_retrieval::{ ByAddress, IndexingZomeConfig },
+    remote_indexes::{
+        RemoteEntryLinkRequest,
+        RemoteEntryLinkResponse,
+        sync_remote_index,
+    },
+    local_indexes::{
+        read_index,
+    },
+};
+use vf_attributes_hdk::{
+    ResourceAddress,
+    ResourceSpecificationAddress,
+};
+
+use hc_zome_rea_resource_specification_storage_consts::*;
+use hc_zome_rea_economic_resource_storage_consts::{ RESOURCE_ENTRY_TYPE, RESOURCE_CONFORMS_TO_LINK_TAG };
+
+entry_defs![Path::entry_def()];
+
+#[hdk_extern]
+fn init(_: ()) -> ExternResult<InitCallbackResult> {
+    Ok(init_with_config::<IndexingZomeConfig>())
+}
+
+#[hdk_extern]
+fn _internal_read_resource_specification_resources(ByAddress { address }: ByAddress<ResourceSpecificationAddress>) -> ExternResult<Vec<ResourceAddress>> {
+    Ok(read_index(&ECONOMIC_RESOURCE_SPECIFICATION_ENTRY_TYPE, &address, &RESOURCE_SPECIFICATION_CONFORMING_RESOURCE_LINK_TAG)?)
+}
+
+#[hdk_extern]
+fn index_resource_specification_resources(indexes: RemoteEntryLinkRequest<ResourceAddress, ResourceSpecificationAddress>) -> ExternResult<RemoteEntryLinkResponse> {
+    let RemoteEntryLinkRequest { remote_entry, target_entries, removed_entries } = indexes;
+
+    Ok(sync_remote_index(
+        &RESOURCE_ENTRY_TYPE, &remote_entry,
+        &ECONOMIC_RESOURCE_SPECIFICATION_ENTRY_TYPE,
+        target_entries.as_slice(),
+        removed_entries.as_slice(),
+        &RESOURCE_CONFORMS_TO_LINK_TAG, &RESOURCE_SPECIFICATION_CONFORMING_RESOURCE_LINK_TAG,
+    )?)
+}
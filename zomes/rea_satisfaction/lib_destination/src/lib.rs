@@ -12,6 +12,8 @@
 use hdk::prelude::*;
 use hdk_records::{
     RecordAPIResult, DataIntegrityError,
+    MaybeUndefined,
+    config::ZomeConfig,
     records::{
         create_record,
         read_record_entry,
@@ -22,13 +24,22 @@ use hdk_records::{
     local_indexes::{
         query_index,
     },
-    foreign_indexes::{
-        create_foreign_index,
-        update_foreign_index,
+    rpc::call_idempotent_zome_method,
+    uris::{
+        UriIndexRoute,
+        update_uri_index,
     },
+    record_interface::Updateable,
 };
+use vf_attributes_hdk::UriAddressable;
+use vf_flow_matching::ensure_flows_match;
 
-use hc_zome_rea_economic_event_storage_consts::{EVENT_SATISFIES_LINK_TAG};
+use hc_zome_rea_economic_event_storage_consts::{EVENT_ENTRY_TYPE, EVENT_SATISFIES_LINK_TAG};
+use hc_zome_rea_economic_event_storage::{
+    EntryData as EventData,
+    EntryStorage as EventStorage,
+};
+use hc_zome_rea_intent_rpc::{ResponseData as IntentResponse};
 use hc_zome_rea_satisfaction_storage_consts::*;
 use hc_zome_rea_satisfaction_storage::*;
 use hc_zome_rea_satisfaction_rpc::*;
@@ -37,16 +48,21 @@ use hc_zome_rea_satisfaction_lib::construct_response;
 pub fn handle_create_satisfaction<S>(entry_def_id: S, satisfaction: CreateRequest) -> RecordAPIResult<ResponseData>
     where S: AsRef<str>
 {
+    let satisfaction = CreateRequest {
+        satisfied_by: resolve_satisfied_by(satisfaction.get_satisfied_by(), EventAddress::entry_type())?,
+        ..satisfaction
+    };
+    validate_satisfaction(&satisfaction.to_owned().into())?;
+
     let (revision_id, satisfaction_address, entry_resp): (_,_, EntryData) = create_record(&entry_def_id, satisfaction.to_owned())?;
 
     // link entries in the local DNA
-    let _results = create_foreign_index(
+    update_uri_index(
         read_foreign_index_zome,
         &SATISFACTION_SATISFIEDBY_INDEXING_API_METHOD,
         &satisfaction_address,
-        read_foreign_event_index_zome,
-        &EVENT_INDEXING_API_METHOD,
-        satisfaction.get_satisfied_by(),
+        &satisfied_by_index_routes(),
+        &[satisfaction.get_satisfied_by().uri()], &[],
     )?;
 
     // :TODO: figure out if necessary/desirable to do bidirectional bridging between observation and other planning DNAs
@@ -58,22 +74,34 @@ pub fn handle_get_satisfaction<S>(entry_def_id: S, address: SatisfactionAddress)
     where S: AsRef<str>
 {
     let (revision, base_address, entry) = read_record_entry::<EntryData, EntryStorage, _,_>(&entry_def_id, address.as_ref())?;
-    construct_response(&base_address, &revision, &entry)
+    construct_response(&base_address, &revision, &entry.resolve_legacy(EventAddress::entry_type())?)
 }
 
 pub fn handle_update_satisfaction<S>(entry_def_id: S, satisfaction: UpdateRequest) -> RecordAPIResult<ResponseData>
     where S: AsRef<str>
 {
-    let (revision_id, base_address, new_entry, prev_entry): (_, SatisfactionAddress, EntryData, EntryData) = update_record(&entry_def_id, &satisfaction.get_revision_id(), satisfaction.to_owned())?;
-
-    if new_entry.satisfied_by != prev_entry.satisfied_by {
-        let _results = update_foreign_index(
-        read_foreign_index_zome,
+    let (_, current) = read_record_entry_by_header::<EntryData, EntryStorage, SatisfactionAddress>(satisfaction.get_revision_id())?;
+    let current = current.resolve_legacy(EventAddress::entry_type())?;
+
+    // always write `satisfied_by`, so that identifiers read in the legacy encoding are stored in their resolved form
+    let satisfaction = UpdateRequest {
+        satisfied_by: MaybeUndefined::Some(match &satisfaction.satisfied_by {
+            MaybeUndefined::Some(satisfied_by) => resolve_satisfied_by(satisfied_by, EventAddress::entry_type())?,
+            _ => current.satisfied_by.to_owned(),
+        }),
+        ..satisfaction
+    };
+    validate_satisfaction(&current.update_with(satisfaction.to_owned()))?;
+
+    let (revision_id, base_address, new_entry, _prev_entry): (_, SatisfactionAddress, EntryData, EntryData) = update_record(&entry_def_id, &satisfaction.get_revision_id(), satisfaction.to_owned())?;
+
+    if new_entry.satisfied_by != current.satisfied_by {
+        update_uri_index(
+            read_foreign_index_zome,
             &SATISFACTION_SATISFIEDBY_INDEXING_API_METHOD,
             &base_address,
-            read_foreign_event_index_zome,
-            &EVENT_INDEXING_API_METHOD,
-            vec![new_entry.satisfied_by.clone()].as_slice(), vec![prev_entry.satisfied_by].as_slice(),
+            &satisfied_by_index_routes(),
+            &[new_entry.satisfied_by.uri()], &[current.satisfied_by.uri()],
         )?;
     }
 
@@ -84,28 +112,57 @@ pub fn handle_delete_satisfaction(revision_id: RevisionHash) -> RecordAPIResult<
 {
     // read any referencing indexes
     let (base_address, entry) = read_record_entry_by_header::<EntryData, EntryStorage, _>(&revision_id)?;
+    let entry = entry.resolve_legacy(EventAddress::entry_type())?;
 
     // handle link fields
-    let _results = update_foreign_index(
+    update_uri_index(
         read_foreign_index_zome,
         &SATISFACTION_SATISFIEDBY_INDEXING_API_METHOD,
         &base_address,
-        read_foreign_event_index_zome,
-        &EVENT_INDEXING_API_METHOD,
-        vec![].as_slice(), vec![entry.satisfied_by].as_slice(),
+        &satisfied_by_index_routes(),
+        &[], &[entry.satisfied_by.uri()],
     )?;
 
     delete_record::<EntryStorage, _>(&revision_id)
 }
 
+/// Check that a replicated `Satisfaction` links a local `EconomicEvent` compatible with the remote
+/// `Intent` it satisfies.
+///
+/// :NOTE: cumulative quantities are checked by the origin zome, which manages the intent's `available_quantity`.
+///
+fn validate_satisfaction(satisfaction: &EntryData) -> RecordAPIResult<()> {
+    // only satisfactions of events are replicated into this DNA
+    let event_address = satisfaction.satisfied_by.as_event().ok_or(DataIntegrityError::EntryWrongType)?;
+    let (_, _, event): (_, EventAddress, EventData) = read_record_entry::<EventData, EventStorage, _,_>(&EVENT_ENTRY_TYPE, event_address.as_ref())?;
+    let intent: IntentResponse = call_idempotent_zome_method(
+        &satisfaction.satisfies,
+        &INTENT_READ_REMOTE_PERMISSION,
+        ByAddress { address: satisfaction.satisfies.to_owned() },
+    )?;
+
+    ensure_flows_match(&intent.intent, &event)
+}
+
+/// Routes for indexing the records a replicated `Satisfaction` is satisfied by
+fn satisfied_by_index_routes() -> Vec<UriIndexRoute<SatisfactionZomeConfigObservation>> {
+    vec![
+        UriIndexRoute {
+            entry_type: EventAddress::entry_type(),
+            index_zome: read_foreign_event_index_zome,
+            index_fn_name: EVENT_INDEXING_API_METHOD,
+        },
+    ]
+}
+
 /// Properties accessor for zome config.
-fn read_foreign_index_zome(conf: DnaConfigSliceObservation) -> Option<String> {
-    Some(conf.satisfaction.index_zome)
+fn read_foreign_index_zome(conf: SatisfactionZomeConfigObservation) -> Option<String> {
+    Some(conf.index_zome)
 }
 
 /// Properties accessor for zome config.
-fn read_foreign_event_index_zome(conf: DnaConfigSliceObservation) -> Option<String> {
-    Some(conf.satisfaction.economic_event_index_zome)
+fn read_foreign_event_index_zome(conf: SatisfactionZomeConfigObservation) -> Option<String> {
+    Some(conf.economic_event_index_zome)
 }
 
 const READ_FN_NAME: &str = "get_satisfaction";
@@ -115,8 +172,7 @@ pub fn generate_query_handler<S, C, F>(
     event_entry_def_id: S,
 ) -> impl FnOnce(&QueryParams) -> RecordAPIResult<Vec<ResponseData>>
     where S: AsRef<str>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: Fn(C) -> Option<String>,
 {
     move |params| {
@@ -124,9 +180,13 @@ pub fn generate_query_handler<S, C, F>(
 
         match &params.satisfied_by {
             Some(satisfied_by) => {
+                // only satisfactions of events are replicated into this DNA
+                let satisfied_by = resolve_satisfied_by(satisfied_by, EventAddress::entry_type())?;
+                let event = satisfied_by.as_event()
+                    .ok_or_else(|| DataIntegrityError::UnresolvableUri(satisfied_by.uri().to_string()))?;
                 entries_result = query_index::<ResponseData, SatisfactionAddress, C,F,_,_,_,_>(
                     &event_entry_def_id,
-                    satisfied_by, EVENT_SATISFIES_LINK_TAG,
+                    &event, EVENT_SATISFIES_LINK_TAG,
                     &foreign_zome_name_from_config, &READ_FN_NAME,
                 );
             },
@@ -12,15 +12,20 @@
 use hdk::prelude::*;
 use hdk_records::{
     RecordAPIResult, OtherCellResult, DataIntegrityError,
+    CrossCellError, ErrorCode,
+    MaybeUndefined,
+    config::{ ZomeConfig, zome_config },
     records::{
         create_record,
         read_record_entry,
         read_record_entry_by_header,
+        read_record_initial_entry,
         update_record,
         delete_record,
     },
     rpc::{
         call_zome_method,
+        call_idempotent_zome_method,
         call_local_zome_method,
     },
     local_indexes::{
@@ -30,78 +35,202 @@ use hdk_records::{
         create_foreign_index,
         update_foreign_index,
     },
+    record_interface::{ Identifiable, Updateable },
+    outbox::{
+        Outbox,
+        outbox_entry_def,
+        read_outbox,
+        replicate,
+        flush_outbox,
+        acknowledge,
+        Acknowledgement,
+        remove_outbox_item,
+    },
+    uris::{
+        UriRoute,
+        UriIndexRoute,
+        call_uri_method,
+        update_uri_index,
+    },
 };
 
+use vf_measurement::QuantityValue;
+use vf_flow_matching::ensure_flows_match;
+use vf_attributes_hdk::{ DnaAddressable, UriAddressable };
 use hc_zome_rea_intent_storage_consts::{INTENT_SATISFIEDBY_LINK_TAG};
+use hc_zome_rea_intent_rpc::{
+    AdjustAvailableQuantityRequest,
+    ResponseData as IntentResponse,
+};
 use hc_zome_rea_commitment_storage_consts::{COMMITMENT_SATISFIES_LINK_TAG};
 use hc_zome_rea_commitment_rpc::{ResponseData as CommitmentResponse};
+use hc_zome_rea_economic_event_rpc::{ResponseData as EventResponse};
 use hc_zome_rea_satisfaction_storage_consts::*;
 use hc_zome_rea_satisfaction_storage::*;
 use hc_zome_rea_satisfaction_rpc::*;
 use hc_zome_rea_satisfaction_lib::construct_response;
 
+/// Entry types defined by the zome, to be returned from its `entry_defs()` extern.
+/// The outbox entry type is located by its position in this list.
+///
+pub fn zome_entry_defs() -> EntryDefs {
+    EntryDefs::from(vec![
+        Path::entry_def(),
+        EntryDef {
+            id: SATISFACTION_ENTRY_TYPE.into(),
+            visibility: EntryVisibility::Public,
+            crdt_type: CrdtType,
+            required_validations: 1.into(),
+            required_validation_type: RequiredValidationType::default(),
+        },
+        outbox_entry_def(SATISFACTION_OUTBOX_ENTRY_TYPE),
+    ])
+}
+
+/// Outbox of replication calls to the observation DNA
+fn outbox() -> RecordAPIResult<Outbox> {
+    Outbox::new(SATISFACTION_OUTBOX_ENTRY_TYPE, &zome_entry_defs())
+}
+
 pub fn handle_create_satisfaction<S>(entry_def_id: S, satisfaction: CreateRequest) -> RecordAPIResult<ResponseData>
     where S: AsRef<str>
 {
-    let (revision_id, satisfaction_address, entry_resp): (_,_, EntryData) = create_record(&entry_def_id, satisfaction.to_owned())?;
+    let satisfaction = CreateRequest {
+        satisfied_by: resolve_satisfied_by(satisfaction.get_satisfied_by(), CommitmentAddress::entry_type())?,
+        ..satisfaction
+    };
+    let event_or_commitment = satisfaction.get_satisfied_by();
+    let remote = is_replicated(event_or_commitment);
+
+    validate_satisfaction(&satisfaction.to_owned().into())?;
+
+    // reserve quantity from the intent first, so that over-commitment is rejected before anything is written
+    let reservation = [IntentAdjustment::new(satisfaction.get_satisfies(), satisfaction.resource_quantity.to_owned().to_option(), None)];
+    reserve_intent_quantities(&reservation)?;
+
+    let created: RecordAPIResult<(_,_, EntryData)> = create_record(&entry_def_id, EntryData {
+        replication: if remote { Some(ReplicationStatus::Pending) } else { None },
+        ..satisfaction.to_owned().into()
+    });
+    let (revision_id, satisfaction_address, entry_resp) = created
+        .map_err(|e| e.with_rollback_failures(undo_intent_adjustments(&reservation)))?;
+
+    // remove the record again if it cannot be linked, so that no reservation is left without a satisfaction to release it
+    if let Err(e) = index_created_satisfaction(&satisfaction_address, &satisfaction) {
+        let mut failures: Vec<DataIntegrityError> = delete_record::<EntryStorage, _>(&revision_id).err().into_iter().collect();
+        failures.extend(undo_intent_adjustments(&reservation));
+        return Err(e.with_rollback_failures(failures));
+    }
+
+    // events live in a remote DNA, which must be pinged to replicate the satisfaction there
+    let (revision_id, entry_resp) = if remote {
+        replicate_change(
+            &entry_def_id, revision_id, entry_resp, &satisfaction_address,
+            event_or_commitment, ReplicationOperation::Create(satisfaction.to_owned()),
+        )?
+    } else {
+        (revision_id, entry_resp)
+    };
+
+    construct_response(&satisfaction_address, &revision_id, &entry_resp)
+}
+
+/// Link a newly created `Satisfaction` to the records it relates
+///
+/// :TODO: indexes written before a failure are not removed, and will reference a deleted record
+///
+fn index_created_satisfaction(
+    satisfaction_address: &SatisfactionAddress,
+    satisfaction: &CreateRequest,
+) -> RecordAPIResult<()> {
 
     // link entries in the local DNA
     let _results1 = create_foreign_index(
         read_foreign_index_zome,
         &SATISFACTION_SATISFIES_INDEXING_API_METHOD,
-        &satisfaction_address,
+        satisfaction_address,
         read_foreign_intent_index_zome,
         &INTENT_INDEXING_API_METHOD,
         satisfaction.get_satisfies(),
     )?;
 
     // link entries which may be local or remote
-    // :TODO: Should not have to do this-
-    //        One option is that linking to a nonexistent entry should autocreate the base.
-    //        This would also make it safe to create things out of order at the expense of validation of external data.
-    //        (Alternative: every link has to get a successful pingback from the destination object with its trait signature intact.)
-    //        Could also probably just run both ops and return OK if one succeeds; overhead would be similar to checking.
-    // :TODO: use of URIs and a Holochain protocol resolver would also make this type of logic entirely unnecessary
-    // :TODO: actually this will probably fail under the new model since may not have updated values if accessing via WASM
-    let event_or_commitment = satisfaction.get_satisfied_by();
-    let satisfying_commitment = is_satisfiedby_commitment(event_or_commitment);
-
-    match satisfying_commitment {
-        // links to local commitment, create link index pair
-        Ok(_) => {
-            let _results2 = create_foreign_index(
-                read_foreign_index_zome,
-                &SATISFACTION_SATISFIEDBY_INDEXING_API_METHOD,
-                &satisfaction_address,
-                read_foreign_commitment_index_zome,
-                &COMMITMENT_INDEXING_API_METHOD,
-                event_or_commitment,
-            )?;
-        },
-        // links to remote event, ping associated foreign DNA
-        _ => {
-            let _pingback: OtherCellResult<ResponseData> = call_zome_method(
-                event_or_commitment,
-                &REPLICATE_CREATE_API_METHOD,
-                FwdCreateRequest { satisfaction: satisfaction.to_owned() }
-            );
-        },
-    };
+    update_uri_index(
+        read_foreign_index_zome,
+        &SATISFACTION_SATISFIEDBY_INDEXING_API_METHOD,
+        satisfaction_address,
+        &satisfied_by_index_routes(),
+        &[satisfaction.get_satisfied_by().uri()], &[],
+    )?;
 
-    construct_response(&satisfaction_address, &revision_id, &entry_resp)
+    Ok(())
 }
 
 pub fn handle_get_satisfaction<S>(entry_def_id: S, address: SatisfactionAddress) -> RecordAPIResult<ResponseData>
     where S: AsRef<str>
 {
     let (revision, base_address, entry) = read_record_entry::<EntryData, EntryStorage, _,_>(&entry_def_id, address.as_ref())?;
-    construct_response(&base_address, &revision, &entry)
+    construct_response(&base_address, &revision, &entry.resolve_legacy(CommitmentAddress::entry_type())?)
 }
 
 pub fn handle_update_satisfaction<S>(entry_def_id: S, satisfaction: UpdateRequest) -> RecordAPIResult<ResponseData>
     where S: AsRef<str>
 {
-    let (revision_id, base_address, new_entry, prev_entry): (_, SatisfactionAddress, EntryData, EntryData) = update_record(&entry_def_id, &satisfaction.get_revision_id(), satisfaction.to_owned())?;
+    let (identity_address, current) = read_record_entry_by_header::<EntryData, EntryStorage, SatisfactionAddress>(satisfaction.get_revision_id())?;
+    let current = current.resolve_legacy(CommitmentAddress::entry_type())?;
+
+    // always write `satisfied_by`, so that identifiers read in the legacy encoding are stored in their resolved form
+    let satisfaction = UpdateRequest {
+        satisfied_by: MaybeUndefined::Some(match &satisfaction.satisfied_by {
+            MaybeUndefined::Some(satisfied_by) => resolve_satisfied_by(satisfied_by, CommitmentAddress::entry_type())?,
+            _ => current.satisfied_by.to_owned(),
+        }),
+        ..satisfaction
+    };
+    let next = current.update_with(satisfaction.to_owned());
+    validate_satisfaction(&next)?;
+
+    let next_intent = satisfaction.satisfies.to_owned().to_option().unwrap_or_else(|| current.satisfies.to_owned());
+    let next_quantity = if satisfaction.resource_quantity.is_undefined() {
+        current.resource_quantity.to_owned()
+    } else {
+        satisfaction.resource_quantity.to_owned().to_option()
+    };
+
+    // move reserved quantity between intents, consuming from the new one first so that rejection leaves both untouched
+    let adjustments = if next_intent == current.satisfies {
+        if next_quantity != current.resource_quantity {
+            vec![IntentAdjustment::new(&next_intent, next_quantity, current.resource_quantity.to_owned())]
+        } else {
+            vec![]
+        }
+    } else {
+        vec![
+            IntentAdjustment::new(&next_intent, next_quantity, None),
+            IntentAdjustment::new(&current.satisfies, None, current.resource_quantity.to_owned()),
+        ]
+    };
+    reserve_intent_quantities(&adjustments)?;
+
+    // satisfactions of remote events are replicated into the DNA of the previously satisfying event
+    let remote = is_replicated(&current.satisfied_by);
+    let updated: RecordAPIResult<(_, SatisfactionAddress, EntryData, EntryData)> = update_record(&entry_def_id, satisfaction.get_revision_id(), ReplicatedUpdatePayload {
+        update: satisfaction.to_owned(),
+        replication: if remote { Some(ReplicationStatus::Pending) } else { None },
+    });
+    let (revision_id, base_address, new_entry, prev_entry) = updated
+        .map_err(|e| e.with_rollback_failures(undo_intent_adjustments(&adjustments)))?;
+
+    // update satisfaction records in remote DNA (and by proxy, indexes held there)
+    // :TODO: update to intelligently call remote DNAs if new & old target record are not in same network
+    let (revision_id, new_entry) = if remote {
+        replicate_change(
+            &entry_def_id, revision_id, new_entry, &identity_address,
+            &current.satisfied_by, ReplicationOperation::Update(satisfaction.to_owned()),
+        )?
+    } else {
+        (revision_id, new_entry)
+    };
 
     // update intent indexes in local DNA
     if new_entry.satisfies != prev_entry.satisfies {
@@ -116,25 +245,14 @@ pub fn handle_update_satisfaction<S>(entry_def_id: S, satisfaction: UpdateReques
     }
 
     // update commitment / event indexes in local and/or remote DNA
-    if new_entry.satisfied_by != prev_entry.satisfied_by {
-        let _results = update_foreign_index(
+    if new_entry.satisfied_by != current.satisfied_by {
+        update_uri_index(
             read_foreign_index_zome,
             &SATISFACTION_SATISFIEDBY_INDEXING_API_METHOD,
             &base_address,
-            read_foreign_commitment_index_zome,
-            &COMMITMENT_INDEXING_API_METHOD,
-            vec![new_entry.satisfied_by.clone()].as_slice(), vec![prev_entry.satisfied_by.clone()].as_slice(),
+            &satisfied_by_index_routes(),
+            &[new_entry.satisfied_by.uri()], &[current.satisfied_by.uri()],
         )?;
-
-        // update satisfaction records in remote DNA (and by proxy, indexes held there)
-        let _pingback: OtherCellResult<ResponseData> = call_zome_method(
-            // :TODO: update to intelligently call remote DNAs if new & old target record are not in same network
-            &prev_entry.satisfied_by,
-            &REPLICATE_UPDATE_API_METHOD,
-            UpdateParams { satisfaction: satisfaction.clone() },
-        );
-
-        // :TODO: ensure exactly 1 operation succeeded
     }
 
     construct_response(&base_address, &revision_id, &new_entry)
@@ -143,6 +261,9 @@ pub fn handle_update_satisfaction<S>(entry_def_id: S, satisfaction: UpdateReques
 pub fn handle_delete_satisfaction(revision_id: RevisionHash) -> RecordAPIResult<bool>
 {
     let (base_address, entry) = read_record_entry_by_header::<EntryData, EntryStorage, _>(&revision_id)?;
+    let entry = entry.resolve_legacy(CommitmentAddress::entry_type())?;
+
+    let release = [IntentAdjustment::new(&entry.satisfies, None, entry.resource_quantity.to_owned())];
 
     // update intent indexes in local DNA
     let _results = update_foreign_index(
@@ -154,56 +275,363 @@ pub fn handle_delete_satisfaction(revision_id: RevisionHash) -> RecordAPIResult<
         vec![].as_slice(), vec![entry.satisfies].as_slice(),
     )?;
 
-    // :TODO: implement URI resolving logic so as to not have to make this check
     let event_or_commitment = entry.satisfied_by.clone();
-    let satisfying_commitment = is_satisfiedby_commitment(&event_or_commitment);
-
-    match satisfying_commitment {
-        // links to local commitment, create link index pair
-        Ok(_) => {
-            let _results2 = update_foreign_index(
-                read_foreign_index_zome,
-                &SATISFACTION_SATISFIEDBY_INDEXING_API_METHOD,
-                &base_address,
-                read_foreign_commitment_index_zome,
-                &COMMITMENT_INDEXING_API_METHOD,
-                vec![].as_slice(), vec![entry.satisfied_by].as_slice(),
-            )?;
-        },
+    let remote = is_replicated(&event_or_commitment);
+
+    update_uri_index(
+        read_foreign_index_zome,
+        &SATISFACTION_SATISFIEDBY_INDEXING_API_METHOD,
+        &base_address,
+        &satisfied_by_index_routes(),
+        &[], &[event_or_commitment.uri()],
+    )?;
+
+    // return reserved quantity to the intent, reclaiming it if the satisfaction cannot be removed
+    reserve_intent_quantities(&release)?;
+    let deleted = delete_record::<EntryStorage, _>(&revision_id)
+        .map_err(|e| e.with_rollback_failures(undo_intent_adjustments(&release)))?;
+
+    if remote {
         // links to remote event, ping associated foreign DNA to replicate deletion there
-        _ => {
-            let _pingback: OtherCellResult<ResponseData> = call_zome_method(
-                &event_or_commitment,
-                &REPLICATE_DELETE_API_METHOD,
-                ByHeader { address: revision_id.to_owned() },
-            );
+        replicate(&outbox()?, &base_address, &event_or_commitment, ReplicationOperation::Delete(revision_id.to_owned()), entry.replica_revision_id.as_ref())?;
+    }
+
+    Ok(deleted)
+}
+
+/// Retry delivery of all replication calls in the outbox which are due, marking `Satisfaction`s
+/// with no calls left undelivered as acknowledged.
+///
+pub fn handle_flush_replication_outbox<S>(entry_def_id: S) -> RecordAPIResult<OutboxFlushReport>
+    where S: AsRef<str>
+{
+    flush_outbox::<SatisfactionAddress, EventOrCommitmentAddress, ReplicationOperation, _>(
+        &outbox()?,
+        |record, ack| acknowledge::<EntryData, EntryStorage, _,_,_>(&entry_def_id, record, ack),
+    )
+}
+
+/// Compare `Satisfaction`s against their replicas in the observation DNA, by ID, revision and field values.
+/// Satisfactions of local `Commitment`s are not replicated, and are reported as such.
+///
+/// A replica is divergent where its fields differ from the planning record, or where it has been revised
+/// since it was last written from here. Replicas remaining for satisfactions deleted in this DNA are orphaned.
+///
+/// With `repair` set, any missing or divergent replica is overwritten with the planning record, which
+/// always takes precedence, and orphaned replicas are deleted. Queued replication calls are then dropped as superseded.
+///
+pub fn handle_reconcile_replicas<S>(entry_def_id: S, request: ReconcileReplicasRequest) -> RecordAPIResult<Vec<ReplicaReport<SatisfactionAddress>>>
+    where S: AsRef<str>
+{
+    request.addresses.iter()
+        .map(|address| reconcile_replica(&entry_def_id, address, request.repair))
+        .collect()
+}
+
+fn reconcile_replica<S>(entry_def_id: &S, address: &SatisfactionAddress, repair: bool) -> RecordAPIResult<ReplicaReport<SatisfactionAddress>>
+    where S: AsRef<str>
+{
+    let (initial_revision_id, base_address, initial): (_, SatisfactionAddress, EntryData) = read_record_initial_entry::<EntryData, EntryStorage, _,_>(entry_def_id, address.as_ref())?;
+    let initial = initial.resolve_legacy(CommitmentAddress::entry_type())?;
+    let latest = match read_record_entry::<EntryData, EntryStorage, SatisfactionAddress,_>(entry_def_id, address.as_ref()) {
+        Ok((revision_id, _, current)) => Some((revision_id, current.resolve_legacy(CommitmentAddress::entry_type())?)),
+        Err(DataIntegrityError::EntryNotFound) => None,
+        Err(e) => return Err(e),
+    };
+
+    let mut report = ReplicaReport {
+        id: base_address.to_owned(),
+        revision_id: latest.as_ref().map(|(revision_id, _)| revision_id.to_owned()).unwrap_or(initial_revision_id),
+        replica_id: None,
+        replica_revision_id: None,
+        state: ReplicaState::NotReplicated,
+        divergent_fields: vec![],
+        repaired_revision_id: None,
+    };
+
+    // replicas are only created for satisfactions initially linking an event
+    let target = initial.satisfied_by.to_owned();
+    if !is_replicated(&target) {
+        return Ok(report);
+    }
+
+    // replicas are created in the DNA of the original event, from the origin record's creation payload
+    let replica_id = SatisfactionAddress::new(
+        AsRef::<DnaHash>::as_ref(&target).to_owned(),
+        hash_entry(EntryData { replication: None, replica_revision_id: None, ..initial.to_owned() }.with_identity(None))?,
+    );
+    let replica = read_replica(&target, &replica_id)?;
+    report.replica_id = Some(replica_id);
+    report.replica_revision_id = replica.as_ref().map(|r| r.revision_id.to_owned());
+
+    let (state, divergent_fields) = compare_replica(latest.as_ref().map(|(_, current)| current), replica.as_ref());
+    report.state = state;
+    report.divergent_fields = divergent_fields;
+
+    let current = match latest {
+        Some((_, current)) => current,
+        None => {
+            // the satisfaction has been deleted here, so any replica should have been deleted with it
+            if let (true, Some(replica)) = (repair, replica) {
+                let _: bool = call_zome_method(&target, &REPLICATE_DELETE_API_METHOD, ByHeader { address: replica.revision_id })?;
+                drop_queued_replication(&base_address)?;
+            }
+            return Ok(report);
         },
     };
 
-    delete_record::<EntryStorage, _>(&revision_id)
+    if repair {
+        report.repaired_revision_id = match (&report.state, &replica) {
+            (ReplicaState::Missing, _) => Some(restore_replica(&target, &initial, &current)?),
+            (ReplicaState::Divergent, Some(replica)) => Some(overwrite_replica(&target, &replica.revision_id, &current)?),
+            _ => None,
+        };
+
+        // anything still queued for the record has been superseded once the replica matches
+        drop_queued_replication(&base_address)?;
+        acknowledge::<EntryData, EntryStorage, _,_,_>(entry_def_id, &base_address, &Acknowledgement {
+            replica_revision_id: report.repaired_revision_id.to_owned().or_else(|| report.replica_revision_id.to_owned()),
+        })?;
+    }
+
+    Ok(report)
 }
 
-fn is_satisfiedby_commitment(event_or_commitment: &EventOrCommitmentAddress) -> OtherCellResult<CommitmentResponse> {
-    call_local_zome_method(
-        |conf: DnaConfigSlicePlanning| { conf.satisfaction.commitment_zome },
-        &CHECK_COMMITMENT_API_METHOD,
-        CheckCommitmentRequest { address: event_or_commitment.to_owned().into() },
-    )
+/// Remove all replication calls queued for a `Satisfaction` from the outbox
+fn drop_queued_replication(address: &SatisfactionAddress) -> RecordAPIResult<()> {
+    let queued: Vec<(HeaderHash, OutboxEntry)> = read_outbox(&outbox()?)?;
+    for (header, _) in queued.iter().filter(|(_, item)| item.record == *address) {
+        remove_outbox_item(header)?;
+    }
+    Ok(())
+}
+
+/// Read the replica of a `Satisfaction`, treating only a not-found error from the remote zome as the replica not existing
+fn read_replica(target: &EventOrCommitmentAddress, replica_id: &SatisfactionAddress) -> RecordAPIResult<Option<Response>> {
+    let resp: OtherCellResult<ResponseData> = call_idempotent_zome_method(target, &REPLICA_READ_API_METHOD, ByAddress { address: replica_id.to_owned() });
+    match resp {
+        Ok(r) => Ok(Some(r.satisfaction)),
+        Err(CrossCellError::IndexNotFound(_)) => Ok(None),
+        Err(CrossCellError::Reported(cause)) if cause.code == ErrorCode::EntryNotFound || cause.code == ErrorCode::IndexNotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Determine the state of a replica given the current revision of its origin record (`None` if deleted),
+/// listing the fields in which a divergent replica differs.
+///
+/// A replica is also divergent if it has been revised since this record last wrote to it.
+///
+fn compare_replica(current: Option<&EntryData>, replica: Option<&Response>) -> (ReplicaState, Vec<String>) {
+    match (current, replica) {
+        (None, Some(_)) => (ReplicaState::Orphaned, vec![]),
+        (None, None) => (ReplicaState::InSync, vec![]),
+        (Some(_), None) => (ReplicaState::Missing, vec![]),
+        (Some(current), Some(replica)) => {
+            let mut fields = find_divergent_fields(current, replica);
+            if current.replica_revision_id.as_ref().map_or(false, |last_written| *last_written != replica.revision_id) {
+                fields.push("revisionId".to_string());
+            }
+            if fields.is_empty() { (ReplicaState::InSync, fields) } else { (ReplicaState::Divergent, fields) }
+        },
+    }
+}
+
+fn find_divergent_fields(origin: &EntryData, replica: &Response) -> Vec<String> {
+    let mut fields = vec![];
+    if origin.satisfied_by != replica.satisfied_by { fields.push("satisfiedBy".to_string()); }
+    if origin.satisfies != replica.satisfies { fields.push("satisfies".to_string()); }
+    if origin.resource_quantity != replica.resource_quantity { fields.push("resourceQuantity".to_string()); }
+    if origin.effort_quantity != replica.effort_quantity { fields.push("effortQuantity".to_string()); }
+    if origin.note != replica.note { fields.push("note".to_string()); }
+    fields
+}
+
+/// Recreate a missing replica from the initial revision of a `Satisfaction`, so that it is assigned
+/// the same ID as originally, then bring it up to date with the current revision.
+///
+fn restore_replica(target: &EventOrCommitmentAddress, initial: &EntryData, current: &EntryData) -> RecordAPIResult<RevisionHash> {
+    let created: ResponseData = call_zome_method(target, &REPLICATE_CREATE_API_METHOD, FwdCreateRequest { satisfaction: CreateRequest {
+        satisfied_by: initial.satisfied_by.to_owned(),
+        satisfies: initial.satisfies.to_owned(),
+        resource_quantity: initial.resource_quantity.to_owned().into(),
+        effort_quantity: initial.effort_quantity.to_owned().into(),
+        note: initial.note.to_owned().into(),
+    } })?;
+
+    if find_divergent_fields(current, &created.satisfaction).is_empty() {
+        return Ok(created.satisfaction.revision_id);
+    }
+    overwrite_replica(target, &created.satisfaction.revision_id, current)
+}
+
+/// Replace every field of a replica with the values of the origin record
+fn overwrite_replica(target: &EventOrCommitmentAddress, replica_revision: &RevisionHash, origin: &EntryData) -> RecordAPIResult<RevisionHash> {
+    let updated: ResponseData = call_zome_method(target, &REPLICATE_UPDATE_API_METHOD, UpdateParams { satisfaction: UpdateRequest {
+        revision_id: replica_revision.to_owned(),
+        satisfied_by: MaybeUndefined::Some(origin.satisfied_by.to_owned()),
+        satisfies: MaybeUndefined::Some(origin.satisfies.to_owned()),
+        resource_quantity: origin.resource_quantity.to_owned().into(),
+        effort_quantity: origin.effort_quantity.to_owned().into(),
+        note: origin.note.to_owned().into(),
+    } })?;
+    Ok(updated.satisfaction.revision_id)
+}
+
+/// Mirror a change already written locally into the DNA of the satisfying event, marking the
+/// `Satisfaction` as acknowledged if it was delivered immediately. Returns the latest revision.
+/// Updates and deletions are addressed to the replica revision last acknowledged for the record.
+///
+fn replicate_change<S>(
+    entry_def_id: &S,
+    revision_id: RevisionHash,
+    entry: EntryData,
+    address: &SatisfactionAddress,
+    target: &EventOrCommitmentAddress,
+    operation: ReplicationOperation,
+) -> RecordAPIResult<(RevisionHash, EntryData)>
+    where S: AsRef<str>
+{
+    match replicate(&outbox()?, address, target, operation, entry.replica_revision_id.as_ref())? {
+        Some(ack) => {
+            let (revision_id, _, acknowledged, _): (_, SatisfactionAddress, EntryData, EntryData) = update_record(entry_def_id, &revision_id, ack)?;
+            Ok((revision_id, acknowledged))
+        },
+        None => Ok((revision_id, entry)),
+    }
+}
+
+/// A change in the quantity of an `Intent` reserved by a `Satisfaction`
+struct IntentAdjustment {
+    intent: IntentAddress,
+    consumed: Option<QuantityValue>,
+    released: Option<QuantityValue>,
+}
+
+impl IntentAdjustment {
+    fn new(intent: &IntentAddress, consumed: Option<QuantityValue>, released: Option<QuantityValue>) -> Self {
+        Self { intent: intent.to_owned(), consumed, released }
+    }
+
+    /// The adjustment which undoes this one
+    fn reversed(&self) -> Self {
+        Self::new(&self.intent, self.released.to_owned(), self.consumed.to_owned())
+    }
+}
+
+/// Apply `adjustments` to the `available_quantity` of their intents in order.
+/// If any is rejected, those already applied are undone before returning the error.
+///
+fn reserve_intent_quantities(adjustments: &[IntentAdjustment]) -> RecordAPIResult<()> {
+    let allow_excess = allow_over_satisfaction()?;
+    for (applied, adjustment) in adjustments.iter().enumerate() {
+        if let Err(e) = adjust_intent_quantity(adjustment, allow_excess) {
+            return Err(e.with_rollback_failures(undo_intent_adjustments(&adjustments[..applied])));
+        }
+    }
+    Ok(())
+}
+
+/// Undo previously applied `adjustments`, returning any errors encountered in doing so.
+///
+/// Excess is always allowed here, since other satisfactions may have consumed released quantity in the meantime.
+///
+fn undo_intent_adjustments(adjustments: &[IntentAdjustment]) -> Vec<DataIntegrityError> {
+    adjustments.iter().rev()
+        .filter_map(|a| adjust_intent_quantity(&a.reversed(), true).err())
+        .collect()
+}
+
+/// Update the `available_quantity` of an `Intent` as satisfied quantities change
+fn adjust_intent_quantity(adjustment: &IntentAdjustment, allow_excess: bool) -> RecordAPIResult<()> {
+    if adjustment.consumed.is_none() && adjustment.released.is_none() {
+        return Ok(());
+    }
+    let _intent: IntentResponse = call_local_zome_method(
+        |conf: SatisfactionZomeConfigPlanning| { conf.intent_zome },
+        &INTENT_ADJUST_QUANTITY_API_METHOD,
+        AdjustAvailableQuantityRequest {
+            address: adjustment.intent.to_owned(),
+            consumed: adjustment.consumed.to_owned(),
+            released: adjustment.released.to_owned(),
+            allow_excess,
+        },
+    )?;
+    Ok(())
+}
+
+/// Check that a `Satisfaction` links an `EconomicEvent` or `Commitment` compatible with the `Intent`
+/// it satisfies. Cumulative quantities are limited by the intent's `available_quantity`, which is
+/// maintained by `adjust_intent_quantity`.
+///
+fn validate_satisfaction(satisfaction: &EntryData) -> RecordAPIResult<()> {
+    let intent: IntentResponse = call_local_zome_method(
+        |conf: SatisfactionZomeConfigPlanning| { conf.intent_zome },
+        &INTENT_READ_API_METHOD,
+        ByAddress { address: satisfaction.satisfies.to_owned() },
+    )?;
+    let intent = intent.intent;
+
+    let satisfied_by = satisfaction.satisfied_by.uri();
+    match satisfaction.satisfied_by.as_commitment() {
+        Some(address) => {
+            let commitment: CommitmentResponse = call_uri_method(satisfied_by, &satisfied_by_routes(), ByAddress { address })?;
+            ensure_flows_match(&intent, &commitment.commitment)
+        },
+        None => {
+            let event: EventResponse = call_uri_method(
+                satisfied_by, &satisfied_by_routes(),
+                ByAddress::<EventAddress> { address: satisfied_by.to_address() },
+            )?;
+            ensure_flows_match(&intent, &event.economic_event)
+        },
+    }
+}
+
+/// Read the `allow_over_satisfaction` zome config flag
+fn allow_over_satisfaction() -> RecordAPIResult<bool> {
+    Ok(zome_config::<SatisfactionZomeConfigPlanning>()?.allow_over_satisfaction)
+}
+
+/// Satisfactions of events are replicated into the observation DNA storing the event
+fn is_replicated(event_or_commitment: &EventOrCommitmentAddress) -> bool {
+    event_or_commitment.as_event().is_some()
+}
+
+/// Routes for reading the records a `Satisfaction` may be satisfied by
+fn satisfied_by_routes() -> Vec<UriRoute> {
+    vec![
+        UriRoute { entry_type: CommitmentAddress::entry_type(), permission_id: COMMITMENT_READ_REMOTE_PERMISSION },
+        UriRoute { entry_type: EventAddress::entry_type(), permission_id: EVENT_READ_REMOTE_PERMISSION },
+    ]
+}
+
+/// Routes for indexing the records a `Satisfaction` may be satisfied by.
+/// Events are indexed in the observation DNA, when the satisfaction is replicated there.
+///
+fn satisfied_by_index_routes() -> Vec<UriIndexRoute<SatisfactionZomeConfigPlanning>> {
+    vec![
+        UriIndexRoute {
+            entry_type: CommitmentAddress::entry_type(),
+            index_zome: read_foreign_commitment_index_zome,
+            index_fn_name: COMMITMENT_INDEXING_API_METHOD,
+        },
+    ]
 }
 
 /// Properties accessor for zome config.
-fn read_foreign_index_zome(conf: DnaConfigSlicePlanning) -> Option<String> {
-    Some(conf.satisfaction.index_zome)
+fn read_foreign_index_zome(conf: SatisfactionZomeConfigPlanning) -> Option<String> {
+    Some(conf.index_zome)
 }
 
 /// Properties accessor for zome config.
-fn read_foreign_intent_index_zome(conf: DnaConfigSlicePlanning) -> Option<String> {
-    Some(conf.satisfaction.intent_index_zome)
+fn read_foreign_intent_index_zome(conf: SatisfactionZomeConfigPlanning) -> Option<String> {
+    Some(conf.intent_index_zome)
 }
 
 /// Properties accessor for zome config.
-fn read_foreign_commitment_index_zome(conf: DnaConfigSlicePlanning) -> Option<String> {
-    Some(conf.satisfaction.commitment_index_zome)
+fn read_foreign_commitment_index_zome(conf: SatisfactionZomeConfigPlanning) -> Option<String> {
+    Some(conf.commitment_index_zome)
 }
 
 const READ_FN_NAME: &str = "get_satisfaction";
@@ -214,8 +642,7 @@ pub fn generate_query_handler<S, C, F>(
     commitment_entry_def_id: S,
 ) -> impl FnOnce(&QueryParams) -> RecordAPIResult<Vec<ResponseData>>
     where S: AsRef<str>,
-        C: std::fmt::Debug,
-        SerializedBytes: TryInto<C, Error = SerializedBytesError>,
+        C: ZomeConfig,
         F: Fn(C) -> Option<String>,
 {
     move |params| {
@@ -234,9 +661,13 @@ pub fn generate_query_handler<S, C, F>(
         };
         match &params.satisfied_by {
             Some(satisfied_by) => {
+                // satisfactions are indexed by commitment here, and by event in the observation DNA
+                let satisfied_by = resolve_satisfied_by(satisfied_by, CommitmentAddress::entry_type())?;
+                let commitment = satisfied_by.as_commitment()
+                    .ok_or_else(|| DataIntegrityError::UnresolvableUri(satisfied_by.uri().to_string()))?;
                 entries_result = query_index::<ResponseData, SatisfactionAddress, C,F,_,_,_,_>(
                     &commitment_entry_def_id,
-                    satisfied_by, COMMITMENT_SATISFIES_LINK_TAG,
+                    &commitment, COMMITMENT_SATISFIES_LINK_TAG,
                     &foreign_zome_name_from_config, &READ_FN_NAME,
                 );
             },
@@ -250,3 +681,77 @@ pub fn generate_query_handler<S, C, F>(
             .collect())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dna() -> DnaHash {
+        DnaHash::from_raw_36(vec![0xdb; 36])
+    }
+
+    fn revision(byte: u8) -> RevisionHash {
+        RevisionHash(dna(), HeaderHash::from_raw_36(vec![byte; 36]))
+    }
+
+    fn origin() -> EntryData {
+        EntryData {
+            satisfied_by: EventAddress(dna(), EntryHash::from_raw_36(vec![1; 36])).into(),
+            satisfies: IntentAddress(dna(), EntryHash::from_raw_36(vec![2; 36])),
+            resource_quantity: Some(QuantityValue::new(1.0, None)),
+            effort_quantity: None,
+            note: None,
+            replication: None,
+            replica_revision_id: Some(revision(1)),
+        }
+    }
+
+    fn replica_of(origin: &EntryData) -> Response {
+        Response {
+            id: SatisfactionAddress(dna(), EntryHash::from_raw_36(vec![3; 36])),
+            revision_id: revision(1),
+            satisfied_by: origin.satisfied_by.to_owned(),
+            satisfies: origin.satisfies.to_owned(),
+            resource_quantity: origin.resource_quantity.to_owned(),
+            effort_quantity: origin.effort_quantity.to_owned(),
+            note: origin.note.to_owned(),
+            replication: None,
+        }
+    }
+
+    #[test]
+    fn test_matching_replica_is_in_sync() {
+        let current = origin();
+        assert_eq!(compare_replica(Some(&current), Some(&replica_of(&current))), (ReplicaState::InSync, vec![]));
+    }
+
+    #[test]
+    fn test_missing_and_orphaned_replicas() {
+        let current = origin();
+        assert_eq!(compare_replica(Some(&current), None), (ReplicaState::Missing, vec![]));
+        assert_eq!(compare_replica(None, Some(&replica_of(&current))), (ReplicaState::Orphaned, vec![]), "replica of a deleted record");
+        assert_eq!(compare_replica(None, None), (ReplicaState::InSync, vec![]), "deleted along with its replica");
+    }
+
+    #[test]
+    fn test_divergent_fields() {
+        let current = origin();
+        let replica = Response {
+            resource_quantity: Some(QuantityValue::new(2.0, None)),
+            note: Some("edited in the other DNA".to_string()),
+            ..replica_of(&current)
+        };
+
+        assert_eq!(compare_replica(Some(&current), Some(&replica)), (ReplicaState::Divergent, vec!["resourceQuantity".to_string(), "note".to_string()]));
+    }
+
+    #[test]
+    fn test_replica_revised_elsewhere_is_divergent() {
+        let current = origin();
+        let replica = Response { revision_id: revision(9), ..replica_of(&current) };
+        assert_eq!(compare_replica(Some(&current), Some(&replica)), (ReplicaState::Divergent, vec!["revisionId".to_string()]));
+
+        let never_written = EntryData { replica_revision_id: None, ..origin() };
+        assert_eq!(compare_replica(Some(&never_written), Some(&replica)).0, ReplicaState::InSync, "records replicated before revisions were tracked are compared by value only");
+    }
+}
@@ -12,6 +12,8 @@ use serde_maybe_undefined::{MaybeUndefined};
 use vf_measurement::QuantityValue;
 pub use vf_attributes_hdk::{
     RevisionHash, ByHeader, ByAddress,
+    ReplicationStatus, OutboxFlushReport,
+    ReplicaState, ReplicaReport,
     SatisfactionAddress,
     EventOrCommitmentAddress,
     EventAddress,
@@ -48,6 +50,9 @@ pub struct Response {
     pub effort_quantity: Option<QuantityValue>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub note: Option<String>,
+    /// whether the record has been replicated into the observation DNA, if it needs to be
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replication: Option<ReplicationStatus>,
 }
 
 /// I/O struct to describe what is returned outside the gateway.
@@ -99,13 +104,6 @@ pub struct FwdCreateRequest {
     pub satisfaction: CreateRequest,
 }
 
-/// I/O struct for forwarding records to other DNAs via zome API
-#[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct CheckCommitmentRequest {
-    pub address: CommitmentAddress,
-}
-
 //---------------- UPDATE REQUEST ----------------
 
 /// I/O struct to describe the complete input record, including all managed links
@@ -145,5 +143,16 @@ impl<'a> UpdateRequest {
 #[serde(rename_all = "camelCase")]
 pub struct QueryParams {
     pub satisfies: Option<IntentAddress>,
-    pub satisfied_by: Option<CommitmentAddress>,
+    pub satisfied_by: Option<EventOrCommitmentAddress>,
+}
+
+//---------------- REPLICA RECONCILIATION ----------------
+
+#[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconcileReplicasRequest {
+    pub addresses: Vec<SatisfactionAddress>,
+    /// overwrite missing or divergent replicas with the origin record
+    #[serde(default)]
+    pub repair: bool,
 }
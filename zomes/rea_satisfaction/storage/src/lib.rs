@@ -9,48 +9,53 @@
 use hdk::prelude::*;
 
 use hdk_records::{
+    RecordAPIResult, OtherCellResult,
+    config::ZomeConfig,
     MaybeUndefined,
     record_interface::Updateable,
+    outbox::{ OutboxItem, ReplicationCall, Replicated, Acknowledgement },
+    rpc::call_zome_method,
     generate_record_entry,
 };
 use vf_measurement::QuantityValue;
 
 pub use vf_attributes_hdk::{
+    RevisionHash,
     SatisfactionAddress,
     EventOrCommitmentAddress,
     IntentAddress,
+    ReplicationStatus,
 };
 
-use hc_zome_rea_satisfaction_rpc::{ CreateRequest, UpdateRequest };
+use hc_zome_rea_satisfaction_storage_consts::*;
+use hc_zome_rea_satisfaction_rpc::{ CreateRequest, UpdateRequest, FwdCreateRequest, UpdateParams, ResponseData, ByHeader };
 
 //--------------- ZOME CONFIGURATION ATTRIBUTES ----------------
 
-// :TODO: remove this, replace with reference to appropriate namespacing of zome config
-#[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
-pub struct DnaConfigSliceObservation {
-    pub satisfaction: SatisfactionZomeConfigObservation,
-}
-
 #[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct SatisfactionZomeConfigObservation {
     pub index_zome: String,
     pub economic_event_index_zome: String,
 }
 
-// :TODO: remove this, replace with reference to appropriate namespacing of zome config
-#[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
-pub struct DnaConfigSlicePlanning {
-    pub satisfaction: SatisfactionZomeConfigPlanning,
-}
+impl ZomeConfig for SatisfactionZomeConfigObservation {}
 
 #[derive(Clone, Serialize, Deserialize, SerializedBytes, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct SatisfactionZomeConfigPlanning {
     pub index_zome: String,
     pub commitment_zome: Option<String>, // :TODO: deprecate this, now we have DnaHash-capable IDs we don't need to query related zome to check relevance
     pub commitment_index_zome: String,
     pub intent_index_zome: String,
+    pub intent_zome: Option<String>,
+    /// whether cumulative satisfied quantities may exceed the quantity available from an intent
+    #[serde(default)]
+    pub allow_over_satisfaction: bool,
 }
 
+impl ZomeConfig for SatisfactionZomeConfigPlanning {}
+
 //---------------- RECORD INTERNALS & VALIDATION ----------------
 
 #[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
@@ -60,6 +65,12 @@ pub struct EntryData {
     pub resource_quantity: Option<QuantityValue>,
     pub effort_quantity: Option<QuantityValue>,
     pub note: Option<String>,
+    /// delivery state of replication into the observation DNA, where this record originates elsewhere
+    #[serde(default)]
+    pub replication: Option<ReplicationStatus>,
+    /// revision of the replica last written by this record's replication calls
+    #[serde(default)]
+    pub replica_revision_id: Option<RevisionHash>,
 }
 
 generate_record_entry!(EntryData, SatisfactionAddress, EntryStorage);
@@ -75,6 +86,8 @@ impl From<CreateRequest> for EntryData {
             resource_quantity: e.resource_quantity.into(),
             effort_quantity: e.effort_quantity.into(),
             note: e.note.into(),
+            replication: None,
+            replica_revision_id: None,
         }
     }
 }
@@ -96,6 +109,173 @@ impl Updateable<UpdateRequest> for EntryData {
             resource_quantity: if e.resource_quantity== MaybeUndefined::Undefined { self.resource_quantity.clone() } else { e.resource_quantity.clone().into() },
             effort_quantity: if e.effort_quantity== MaybeUndefined::Undefined { self.effort_quantity.clone() } else { e.effort_quantity.clone().into() },
             note: if e.note== MaybeUndefined::Undefined { self.note.clone() } else { e.note.clone().into() },
+            replication: self.replication.clone(),
+            replica_revision_id: self.replica_revision_id.clone(),
+        }
+    }
+}
+
+/// Update payload which also records the outcome of replicating the update, if any was needed
+#[derive(Debug, Clone)]
+pub struct ReplicatedUpdatePayload {
+    pub update: UpdateRequest,
+    pub replication: Option<ReplicationStatus>,
+}
+
+impl Updateable<ReplicatedUpdatePayload> for EntryData {
+    fn update_with(&self, e: ReplicatedUpdatePayload) -> EntryData {
+        let replication = e.replication.or_else(|| self.replication.clone());
+        EntryData {
+            replication,
+            ..self.update_with(e.update)
+        }
+    }
+}
+
+/// Marks the record's replication calls as delivered
+impl Updateable<Acknowledgement> for EntryData {
+    fn update_with(&self, e: Acknowledgement) -> EntryData {
+        EntryData {
+            replication: Some(ReplicationStatus::Acknowledged),
+            replica_revision_id: e.replica_revision_id.or_else(|| self.replica_revision_id.clone()),
+            ..self.clone()
+        }
+    }
+}
+
+impl Replicated for EntryData {
+    fn replication(&self) -> Option<&ReplicationStatus> {
+        self.replication.as_ref()
+    }
+
+    fn replica_revision_id(&self) -> Option<&RevisionHash> {
+        self.replica_revision_id.as_ref()
+    }
+}
+
+//---------------- LEGACY IDENTIFIERS ----------------
+
+/// Type a `satisfied_by` identifier read in the legacy encoding, relative to the DNA of the calling
+/// zome, whose own records are of `local_entry_type` (@see `EventOrCommitmentAddress::resolve_legacy`).
+///
+pub fn resolve_satisfied_by(address: &EventOrCommitmentAddress, local_entry_type: &str) -> RecordAPIResult<EventOrCommitmentAddress> {
+    if !address.is_legacy() {
+        return Ok(address.to_owned());
+    }
+    Ok(address.resolve_legacy(&zome_info()?.dna_hash, local_entry_type))
+}
+
+impl EntryData {
+    /// Type the `satisfied_by` identifier of an entry written in the legacy encoding
+    pub fn resolve_legacy(self, local_entry_type: &str) -> RecordAPIResult<EntryData> {
+        Ok(EntryData {
+            satisfied_by: resolve_satisfied_by(&self.satisfied_by, local_entry_type)?,
+            ..self
+        })
+    }
+}
+
+//---------------- REPLICATION OUTBOX ----------------
+
+/// Replication call into the observation DNA
+///
+/// :NOTE: the revisions held by `Update` and `Delete` are those of the origin record. They are
+///        replaced with the revision of the replica when the call is delivered.
+///
+#[derive(Serialize, Deserialize, Debug, SerializedBytes, Clone)]
+pub enum ReplicationOperation {
+    Create(CreateRequest),
+    Update(UpdateRequest),
+    Delete(RevisionHash),
+}
+
+impl ReplicationOperation {
+    /// Address the operation to the given revision of the replica, rather than of the origin record
+    pub fn addressed_to(&self, replica_revision_id: &RevisionHash) -> ReplicationOperation {
+        match self {
+            ReplicationOperation::Create(_) => self.to_owned(),
+            ReplicationOperation::Update(satisfaction) => ReplicationOperation::Update(UpdateRequest {
+                revision_id: replica_revision_id.to_owned(),
+                ..satisfaction.to_owned()
+            }),
+            ReplicationOperation::Delete(_) => ReplicationOperation::Delete(replica_revision_id.to_owned()),
+        }
+    }
+}
+
+impl ReplicationCall<EventOrCommitmentAddress> for ReplicationOperation {
+    fn requires_replica(&self) -> bool {
+        !matches!(self, ReplicationOperation::Create(_))
+    }
+
+    fn deliver(&self, target: &EventOrCommitmentAddress, replica_revision_id: Option<&RevisionHash>) -> OtherCellResult<Option<RevisionHash>> {
+        let operation = match replica_revision_id {
+            Some(replica_revision_id) => self.addressed_to(replica_revision_id),
+            None => self.to_owned(),
+        };
+        match operation {
+            ReplicationOperation::Create(satisfaction) => {
+                let created: ResponseData = call_zome_method(target, &REPLICATE_CREATE_API_METHOD, FwdCreateRequest { satisfaction })?;
+                Ok(Some(created.satisfaction.revision_id))
+            },
+            ReplicationOperation::Update(satisfaction) => {
+                let updated: ResponseData = call_zome_method(target, &REPLICATE_UPDATE_API_METHOD, UpdateParams { satisfaction })?;
+                Ok(Some(updated.satisfaction.revision_id))
+            },
+            ReplicationOperation::Delete(revision_id) => {
+                let _: bool = call_zome_method(target, &REPLICATE_DELETE_API_METHOD, ByHeader { address: revision_id })?;
+                Ok(None)
+            },
+        }
+    }
+}
+
+/// Undelivered replication call into the DNA of the satisfying event
+pub type OutboxEntry = OutboxItem<SatisfactionAddress, EventOrCommitmentAddress, ReplicationOperation>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn revision(byte: u8) -> RevisionHash {
+        RevisionHash(DnaHash::from_raw_36(vec![0xdb; 36]), HeaderHash::from_raw_36(vec![byte; 36]))
+    }
+
+    fn update(revision_id: RevisionHash) -> UpdateRequest {
+        UpdateRequest {
+            revision_id,
+            satisfied_by: MaybeUndefined::Undefined,
+            satisfies: MaybeUndefined::Undefined,
+            resource_quantity: MaybeUndefined::Undefined,
+            effort_quantity: MaybeUndefined::Undefined,
+            note: MaybeUndefined::Some("changed".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_updates_are_delivered_to_the_replica_revision() {
+        let (origin, replica) = (revision(1), revision(2));
+        let operation = ReplicationOperation::Update(update(origin));
+
+        assert!(operation.requires_replica());
+        match operation.addressed_to(&replica) {
+            ReplicationOperation::Update(satisfaction) => {
+                assert_eq!(satisfaction.revision_id, replica);
+                assert_eq!(satisfaction.note, MaybeUndefined::Some("changed".to_string()), "updated fields are kept");
+            },
+            other => panic!("expected an update, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_deletions_are_delivered_to_the_replica_revision() {
+        let (origin, replica) = (revision(1), revision(2));
+        let operation = ReplicationOperation::Delete(origin);
+
+        assert!(operation.requires_replica());
+        match operation.addressed_to(&replica) {
+            ReplicationOperation::Delete(revision_id) => assert_eq!(revision_id, replica),
+            other => panic!("expected a deletion, got {:?}", other),
         }
     }
 }
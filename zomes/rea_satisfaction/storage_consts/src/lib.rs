@@ -8,6 +8,7 @@
 pub const SATISFACTION_BASE_ENTRY_TYPE: &str = "vf_satisfaction_baseurl";
 pub const SATISFACTION_INITIAL_ENTRY_LINK_TYPE: &str = "vf_satisfaction_entry";
 pub const SATISFACTION_ENTRY_TYPE: &str = "vf_satisfaction";
+pub const SATISFACTION_OUTBOX_ENTRY_TYPE: &str = "vf_satisfaction_outbox";
 pub const SATISFACTION_SATISFIES_LINK_TYPE: &str = "vf_satisfaction_satisfies";
 pub const SATISFACTION_SATISFIES_LINK_TAG: &str = "satisfies";
 pub const SATISFACTION_SATISFIEDBY_LINK_TYPE: &str = "vf_satisfaction_satisfied_by";
@@ -16,7 +17,12 @@ pub const SATISFACTION_SATISFIEDBY_LINK_TAG: &str = "satisfied_by";
 pub const REPLICATE_CREATE_API_METHOD: &str = "create_satisfaction";
 pub const REPLICATE_UPDATE_API_METHOD: &str = "update_satisfaction";
 pub const REPLICATE_DELETE_API_METHOD: &str = "delete_satisfaction";
-pub const CHECK_COMMITMENT_API_METHOD: &str = "get_commitment";
+pub const REPLICA_READ_API_METHOD: &str = "read_satisfaction";
+pub const COMMITMENT_READ_REMOTE_PERMISSION: &str = "read_commitment";
+pub const INTENT_ADJUST_QUANTITY_API_METHOD: &str = "_internal_adjust_available_quantity";
+pub const INTENT_READ_API_METHOD: &str = "get_intent";
+pub const INTENT_READ_REMOTE_PERMISSION: &str = "read_intent";
+pub const EVENT_READ_REMOTE_PERMISSION: &str = "read_economic_event";
 pub const INTENT_INDEXING_API_METHOD: &str = "_internal_reindex_satisfactions";
 pub const COMMITMENT_INDEXING_API_METHOD: &str = "_internal_reindex_satisfactions";
 pub const EVENT_INDEXING_API_METHOD: &str = "_internal_reindex_satisfactions";
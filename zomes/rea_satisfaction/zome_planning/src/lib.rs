@@ -8,23 +8,21 @@
  * @package Holo-REA
  */
 use hdk::prelude::*;
+use hdk_records::config::init_with_config;
 
 use hc_zome_rea_satisfaction_lib_origin::*;
 use hc_zome_rea_satisfaction_rpc::*;
 use hc_zome_rea_satisfaction_storage_consts::*;
+use hc_zome_rea_satisfaction_storage::SatisfactionZomeConfigPlanning;
+
+#[hdk_extern]
+fn init(_: ()) -> ExternResult<InitCallbackResult> {
+    Ok(init_with_config::<SatisfactionZomeConfigPlanning>())
+}
 
 #[hdk_extern]
 fn entry_defs(_: ()) -> ExternResult<EntryDefsCallbackResult> {
-    Ok(EntryDefsCallbackResult::from(vec![
-        Path::entry_def(),
-        EntryDef {
-            id: SATISFACTION_ENTRY_TYPE.into(),
-            visibility: EntryVisibility::Public,
-            crdt_type: CrdtType,
-            required_validations: 1.into(),
-            required_validation_type: RequiredValidationType::default(),
-        }
-    ]))
+    Ok(EntryDefsCallbackResult::Defs(zome_entry_defs()))
 }
 
 #[hdk_extern]
@@ -46,3 +44,13 @@ fn update_satisfaction(UpdateParams { satisfaction }: UpdateParams) -> ExternRes
 fn delete_satisfaction(ByHeader { address }: ByHeader) -> ExternResult<bool> {
     Ok(handle_delete_satisfaction(address)?)
 }
+
+#[hdk_extern]
+fn flush_replication_outbox(_: ()) -> ExternResult<OutboxFlushReport> {
+    Ok(handle_flush_replication_outbox(SATISFACTION_ENTRY_TYPE)?)
+}
+
+#[hdk_extern]
+fn reconcile_replicas(request: ReconcileReplicasRequest) -> ExternResult<Vec<ReplicaReport<SatisfactionAddress>>> {
+    Ok(handle_reconcile_replicas(SATISFACTION_ENTRY_TYPE, request)?)
+}
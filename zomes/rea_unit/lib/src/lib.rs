@@ -47,6 +47,9 @@ pub fn handle_update_unit<S>(entry_def_id: S, unit: UpdateRequest) -> RecordAPIR
     Ok(construct_response(&new_id, &new_revision, &new_entry))
 }
 
+/// :TODO: units are referenced by ID from `QuantityValue`s in every DNA, which are not indexed;
+///        deletion cannot be checked against them until they are.
+///
 pub fn handle_delete_unit(revision_id: RevisionHash) -> RecordAPIResult<bool> {
     delete_anchored_record::<EntryData, RevisionHash>(&revision_id)
 }